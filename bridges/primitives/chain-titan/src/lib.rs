@@ -42,10 +42,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: sp_version::create_runtime_str!("titan"),
 	impl_name: sp_version::create_runtime_str!("parity-titan-v1.6"),
 	authoring_version: 0,
-	spec_version: 9100,
+	spec_version: 9104,
 	impl_version: 0,
 	apis: sp_version::create_apis_vec![[]],
-	transaction_version: 0,
+	transaction_version: 1,
 };
 
 // NOTE: This needs to be kept up to date with the titan runtime found in the diamond repo.
@@ -111,6 +111,28 @@ pub struct RunCmd {
 	/// commonly `127.0.0.1:6831`.
 	#[structopt(long)]
 	pub jaeger_agent: Option<std::net::SocketAddr>,
+
+	/// Add the endpoint of an OpenTelemetry collector to send the jaeger spans to.
+	///
+	/// Must be a valid URL, commonly `http://127.0.0.1:4317` for gRPC
+	/// or `http://127.0.0.1:4318` for HTTP.
+	#[structopt(long)]
+	pub jaeger_otlp_endpoint: Option<String>,
+
+	/// The protocol of the OpenTelemetry collector, either `grpc` or `http`.
+	#[structopt(long, default_value = "grpc", requires = "jaeger-otlp-endpoint")]
+	pub jaeger_otlp_protocol: String,
+
+	/// Append the jaeger spans as newline-delimited JSON to the given file.
+	#[structopt(long, parse(from_os_str))]
+	pub jaeger_file: Option<std::path::PathBuf>,
+
+	/// Only export a ratio of the jaeger traces annotated with a candidate stage.
+	///
+	/// Must be of format `stage=ratio`, i.e. `availability-distribution=0.1`.
+	/// Can be passed multiple times.
+	#[structopt(long = "jaeger-sample-stage", number_of_values(1))]
+	pub jaeger_sample_stages: Vec<String>,
 }
 
 #[allow(missing_docs)]
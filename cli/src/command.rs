@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with diamond.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::cli::{Cli, Subcommand};
+use crate::cli::{Cli, RunCmd, Subcommand};
 use futures::future::TryFutureExt;
 use log::info;
 use sc_cli::{Role, RuntimeVersion, SubstrateCli};
@@ -223,6 +223,37 @@ pub fn run_node(cli: Cli, overseer_gen: impl service::OverseerGen) -> Result<()>
 	run_node_inner(cli, overseer_gen)
 }
 
+/// Collect the jaeger exporters and sampling ratios passed on the command line.
+///
+/// Returns `None` if no exporter was configured.
+fn jaeger_config(run: &RunCmd) -> Result<Option<service::jaeger::JaegerConfigBuilder>> {
+	let mut builder = service::jaeger::JaegerConfig::builder();
+
+	if let Some(agent) = run.jaeger_agent {
+		builder = builder.agent(agent);
+	}
+	if let Some(endpoint) = &run.jaeger_otlp_endpoint {
+		let protocol =
+			run.jaeger_otlp_protocol.parse().map_err(|e| Error::Other(format!("{}", e)))?;
+		builder = builder.otlp(endpoint.clone(), protocol);
+	}
+	if let Some(path) = &run.jaeger_file {
+		builder = builder.file(path.clone());
+	}
+	for sample in &run.jaeger_sample_stages {
+		let (stage, ratio) = sample
+			.split_once('=')
+			.ok_or_else(|| Error::Other(format!("Expected `stage=ratio`, got {:?}", sample)))?;
+		let stage = stage.parse().map_err(|e| Error::Other(format!("{}", e)))?;
+		let ratio = ratio
+			.parse::<f64>()
+			.map_err(|e| Error::Other(format!("Invalid sampling ratio {:?}: {}", ratio, e)))?;
+		builder = builder.sample_stage(stage, ratio);
+	}
+
+	Ok(if builder.has_exporters() { Some(builder) } else { None })
+}
+
 fn run_node_inner(cli: Cli, overseer_gen: impl service::OverseerGen) -> Result<()> {
 	let runner = cli.create_runner(&cli.run.base).map_err(Error::from)?;
 	let chain_spec = &runner.config().chain_spec;
@@ -243,7 +274,7 @@ fn run_node_inner(cli: Cli, overseer_gen: impl service::OverseerGen) -> Result<(
 		info!("----------------------------");
 	}
 
-	let jaeger_agent = cli.run.jaeger_agent;
+	let jaeger_config = jaeger_config(&cli.run)?;
 
 	runner.run_node_until_exit(move |config| async move {
 		let role = config.role.clone();
@@ -255,7 +286,7 @@ fn run_node_inner(cli: Cli, overseer_gen: impl service::OverseerGen) -> Result<(
 				service::IsCollator::No,
 				grandpa_pause,
 				cli.run.no_beefy,
-				jaeger_agent,
+				jaeger_config,
 				None,
 				overseer_gen,
 			)
@@ -1821,6 +1821,12 @@ fn advance_approval_state(
 
 			metrics.on_candidate_approved(status.tranche_now as _);
 
+			jaeger::lifecycle::note_stage(
+				candidate_hash,
+				Some(candidate_entry.candidate_receipt().descriptor.para_id),
+				jaeger::lifecycle::LifecycleStage::Approved,
+			);
+
 			if is_block_approved && !was_block_approved {
 				metrics.on_block_approved(status.tranche_now as _);
 				actions.push(Action::NoteApprovedInChainSelection(block_hash));
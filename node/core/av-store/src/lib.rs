@@ -42,6 +42,7 @@ use diamond_primitives::v1::{
 };
 use diamond_subsystem::{
 	errors::{ChainApiError, RuntimeApiError},
+	jaeger::lifecycle::{self, LifecycleStage},
 	messages::{AvailabilityStoreMessage, ChainApiMessage},
 	overseer, ActiveLeavesUpdate, FromOverseer, OverseerSignal, SpawnedSubsystem, SubsystemContext,
 	SubsystemError,
@@ -709,6 +710,12 @@ fn note_block_backed(
 
 	tracing::debug!(target: LOG_TARGET, ?candidate_hash, "Candidate backed");
 
+	lifecycle::note_stage(
+		candidate_hash,
+		Some(candidate.descriptor.para_id),
+		LifecycleStage::Backed,
+	);
+
 	if load_meta(db, config, &candidate_hash)?.is_none() {
 		let meta = CandidateMeta {
 			state: State::Unavailable(now.into()),
@@ -750,6 +757,12 @@ fn note_block_included(
 
 			tracing::debug!(target: LOG_TARGET, ?candidate_hash, "Candidate included");
 
+			lifecycle::note_stage(
+				candidate_hash,
+				Some(candidate.descriptor.para_id),
+				LifecycleStage::Included,
+			);
+
 			meta.state = match meta.state {
 				State::Unavailable(at) => {
 					let at_d: Duration = at.into();
@@ -959,6 +972,8 @@ fn update_blocks_at_finalized_height(
 
 			meta.state = State::Finalized(now.into());
 
+			lifecycle::note_stage(candidate_hash, None, LifecycleStage::Finalized);
+
 			// Write the meta and a pruning record.
 			write_meta(db_transaction, &subsystem.config, &candidate_hash, &meta);
 			write_pruning_key(
@@ -1136,6 +1151,9 @@ fn store_chunk(
 	);
 
 	db.write(tx)?;
+
+	lifecycle::note_stage(candidate_hash, None, LifecycleStage::Available);
+
 	Ok(true)
 }
 
@@ -1196,6 +1214,8 @@ fn store_available_data(
 
 	tracing::debug!(target: LOG_TARGET, ?candidate_hash, "Stored data and chunks");
 
+	lifecycle::note_stage(candidate_hash, None, LifecycleStage::Available);
+
 	Ok(())
 }
 
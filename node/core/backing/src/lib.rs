@@ -804,6 +804,14 @@ impl CandidateBackingJob {
 
 		let summary = self.table.import_statement(&self.table_context, stmt);
 
+		if let Statement::Seconded(receipt) = statement.payload() {
+			jaeger::lifecycle::note_stage(
+				candidate_hash,
+				Some(receipt.descriptor.para_id),
+				jaeger::lifecycle::LifecycleStage::Seconded,
+			);
+		}
+
 		let unbacked_span = if let Some(attested) = summary
 			.as_ref()
 			.and_then(|s| self.table.attested_candidate(&s.candidate, &self.table_context))
@@ -822,6 +830,12 @@ impl CandidateBackingJob {
 						"Candidate backed",
 					);
 
+					jaeger::lifecycle::note_stage(
+						candidate_hash,
+						Some(backed.candidate.descriptor.para_id),
+						jaeger::lifecycle::LifecycleStage::Backed,
+					);
+
 					let message = ProvisionerMessage::ProvisionableData(
 						self.parent,
 						ProvisionableData::BackedCandidate(backed.receipt()),
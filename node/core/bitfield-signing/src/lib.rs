@@ -15,6 +15,9 @@
 // along with diamond.  If not, see <http://www.gnu.org/licenses/>.
 
 //! The bitfield signing subsystem produces `SignedAvailabilityBitfield`s once per block.
+//!
+//! If further chunks become available afterwards, an updated bitfield gets signed and distributed
+//! for the same block.
 
 #![deny(unused_crate_dependencies)]
 #![warn(missing_docs)]
@@ -51,6 +54,12 @@ mod tests;
 
 /// Delay between starting a bitfield signing job and its attempting to create a bitfield.
 const JOB_DELAY: Duration = Duration::from_millis(1500);
+/// Delay between signing a bitfield and checking whether further chunks became available.
+const RECHECK_DELAY: Duration = Duration::from_millis(1000);
+/// How often we check for late chunks after signing the first bitfield.
+///
+/// Together with `JOB_DELAY` this covers the time until the next block is expected.
+const MAX_RECHECKS: usize = 4;
 const LOG_TARGET: &str = "parachain::bitfield-signing";
 
 /// Each `BitfieldSigningJob` prepares a signed bitfield for a single relay parent.
@@ -81,12 +90,18 @@ pub enum Error {
 
 /// If there is a candidate pending availability, query the Availability Store
 /// for whether we have the availability chunk for our validator index.
+///
+/// Cores already known to be available are not queried again.
 async fn get_core_availability(
 	core: &CoreState,
+	known_available: bool,
 	validator_idx: ValidatorIndex,
 	sender: &Mutex<&mut impl SubsystemSender>,
 	span: &jaeger::Span,
 ) -> Result<bool, Error> {
+	if known_available {
+		return Ok(true)
+	}
 	if let &CoreState::Occupied(ref core) = core {
 		let _span = span.child("query-chunk-availability");
 
@@ -148,6 +163,19 @@ async fn construct_availability_bitfield(
 	span: &jaeger::Span,
 	validator_idx: ValidatorIndex,
 	sender: &mut impl SubsystemSender,
+) -> Result<AvailabilityBitfield, Error> {
+	update_availability_bitfield(relay_parent, span, validator_idx, None, sender).await
+}
+
+/// Like `construct_availability_bitfield`, but only cores not set in `previous` get queried.
+///
+/// The result is therefore always a superset of `previous`.
+async fn update_availability_bitfield(
+	relay_parent: Hash,
+	span: &jaeger::Span,
+	validator_idx: ValidatorIndex,
+	previous: Option<&AvailabilityBitfield>,
+	sender: &mut impl SubsystemSender,
 ) -> Result<AvailabilityBitfield, Error> {
 	// get the set of availability cores from the runtime
 	let availability_cores = {
@@ -164,11 +192,11 @@ async fn construct_availability_bitfield(
 
 	// Handle all cores concurrently
 	// `try_join_all` returns all results in the same order as the input futures.
-	let results = future::try_join_all(
-		availability_cores
-			.iter()
-			.map(|core| get_core_availability(core, validator_idx, &sender, span)),
-	)
+	let results = future::try_join_all(availability_cores.iter().enumerate().map(|(i, core)| {
+		let known_available =
+			previous.and_then(|p| p.0.get(i).as_deref().copied()).unwrap_or(false);
+		get_core_availability(core, known_available, validator_idx, &sender, span)
+	}))
 	.await?;
 
 	tracing::debug!(
@@ -264,12 +292,12 @@ impl JobTrait for BitfieldSigningJob {
 
 			// this timer does not appear at the head of the function because we don't want to include
 			// JOB_DELAY each time.
-			let _timer = metrics.time_run();
+			let timer = metrics.time_run();
 
 			drop(_span);
 			let span_availability = span.child("availability");
 
-			let bitfield = match construct_availability_bitfield(
+			let mut bitfield = match construct_availability_bitfield(
 				relay_parent,
 				&span_availability,
 				validator.index(),
@@ -287,34 +315,65 @@ impl JobTrait for BitfieldSigningJob {
 			};
 
 			drop(span_availability);
-			let _span = span.child("signing");
 
-			let signed_bitfield = match validator
-				.sign(keystore.clone(), bitfield)
-				.await
-				.map_err(|e| Error::Keystore(e))?
-			{
-				Some(b) => b,
-				None => {
-					tracing::error!(
-						target: LOG_TARGET,
-						"Key was found at construction, but while signing it could not be found.",
-					);
-					return Ok(())
-				},
-			};
+			sign_and_distribute(
+				relay_parent,
+				bitfield.clone(),
+				&validator,
+				&keystore,
+				&metrics,
+				&span,
+				&mut sender,
+			)
+			.await?;
 
-			metrics.on_bitfield_signed();
+			drop(timer);
 
-			drop(_span);
-			let _span = span.child("gossip");
+			// Chunks arriving late should still make it into a block: Re-sign whenever our
+			// bitfield improved. The job gets dropped once the relay parent is no longer a leaf.
+			for _ in 0..MAX_RECHECKS {
+				Delay::new(RECHECK_DELAY).await?;
 
-			sender
-				.send_message(BitfieldDistributionMessage::DistributeBitfield(
+				let span_recheck = span.child("recheck-availability");
+				let updated = match update_availability_bitfield(
+					relay_parent,
+					&span_recheck,
+					validator.index(),
+					Some(&bitfield),
+					sender.subsystem_sender(),
+				)
+				.await
+				{
+					Err(Error::Runtime(runtime_err)) => {
+						tracing::warn!(target: LOG_TARGET, err = ?runtime_err, "Encountered a runtime API error");
+						return Ok(())
+					},
+					Err(err) => return Err(err),
+					Ok(updated) => updated,
+				};
+				drop(span_recheck);
+
+				if updated == bitfield {
+					continue
+				}
+
+				tracing::debug!(
+					target: LOG_TARGET,
+					?relay_parent,
+					"Further chunks became available, signing updated bitfield",
+				);
+				bitfield = updated;
+				sign_and_distribute(
 					relay_parent,
-					signed_bitfield,
-				))
-				.await;
+					bitfield.clone(),
+					&validator,
+					&keystore,
+					&metrics,
+					&span,
+					&mut sender,
+				)
+				.await?;
+			}
 
 			Ok(())
 		}
@@ -322,5 +381,47 @@ impl JobTrait for BitfieldSigningJob {
 	}
 }
 
+/// Sign the given bitfield and hand it over to bitfield distribution.
+async fn sign_and_distribute<S: SubsystemSender>(
+	relay_parent: Hash,
+	bitfield: AvailabilityBitfield,
+	validator: &Validator,
+	keystore: &SyncCryptoStorePtr,
+	metrics: &Metrics,
+	span: &PerLeafSpan,
+	sender: &mut JobSender<S>,
+) -> Result<(), Error> {
+	let _span = span.child("signing");
+
+	let signed_bitfield = match validator
+		.sign(keystore.clone(), bitfield)
+		.await
+		.map_err(|e| Error::Keystore(e))?
+	{
+		Some(b) => b,
+		None => {
+			tracing::error!(
+				target: LOG_TARGET,
+				"Key was found at construction, but while signing it could not be found.",
+			);
+			return Ok(())
+		},
+	};
+
+	metrics.on_bitfield_signed();
+
+	drop(_span);
+	let _span = span.child("gossip");
+
+	sender
+		.send_message(BitfieldDistributionMessage::DistributeBitfield(
+			relay_parent,
+			signed_bitfield,
+		))
+		.await;
+
+	Ok(())
+}
+
 /// `BitfieldSigningSubsystem` manages a number of bitfield signing jobs.
 pub type BitfieldSigningSubsystem<Spawner> = JobSubsystem<BitfieldSigningJob, Spawner>;
@@ -82,3 +82,58 @@ fn construct_availability_bitfield_works() {
 		}
 	});
 }
+
+#[test]
+fn update_availability_bitfield_only_queries_missing_chunks() {
+	block_on(async move {
+		let relay_parent = Hash::default();
+		let validator_index = ValidatorIndex(1u32);
+
+		let hash_a = CandidateHash(Hash::repeat_byte(1));
+		let hash_b = CandidateHash(Hash::repeat_byte(2));
+		let previous = AvailabilityBitfield(FromIterator::from_iter(vec![false, true, false]));
+
+		let (mut sender, mut receiver) = diamond_node_subsystem_test_helpers::sender_receiver();
+		let future = update_availability_bitfield(
+			relay_parent,
+			&jaeger::Span::Disabled,
+			validator_index,
+			Some(&previous),
+			&mut sender,
+		)
+		.fuse();
+		pin_mut!(future);
+
+		loop {
+			futures::select! {
+				m = receiver.next() => match m.unwrap() {
+					AllMessages::RuntimeApi(
+						RuntimeApiMessage::Request(rp, RuntimeApiRequest::AvailabilityCores(tx)),
+					) => {
+						assert_eq!(relay_parent, rp);
+						tx.send(Ok(vec![CoreState::Free, occupied_core(1, hash_a), occupied_core(2, hash_b)])).unwrap();
+					}
+					AllMessages::AvailabilityStore(
+						AvailabilityStoreMessage::QueryChunkAvailability(c_hash, vidx, tx),
+					) => {
+						assert_eq!(validator_index, vidx);
+						// Chunk for `hash_a` is known to be available already.
+						assert_eq!(c_hash, hash_b);
+
+						tx.send(true).unwrap();
+					},
+					o => panic!("Unknown message: {:?}", o),
+				},
+				r = future => match r {
+					Ok(r) => {
+						assert!(!r.0.get(0).unwrap());
+						assert!(r.0.get(1).unwrap());
+						assert!(r.0.get(2).unwrap());
+						break
+					},
+					Err(e) => panic!("Failed: {:?}", e),
+				},
+			}
+		}
+	});
+}
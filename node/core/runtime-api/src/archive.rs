@@ -0,0 +1,79 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A persistent archive of every `SessionInfo` seen by the node.
+//!
+//! The runtime prunes session info after a while, but late dispute checks and auditing tools may
+//! still need the validator sets of old sessions.
+
+use diamond_primitives::v1::{SessionIndex, SessionInfo};
+use kvdb::{DBTransaction, KeyValueDB};
+use parity_scale_codec::{Decode, Encode};
+use std::sync::Arc;
+
+use crate::LOG_TARGET;
+
+/// A persistent store of session info, keyed by session index.
+#[derive(Clone)]
+pub struct SessionInfoArchive {
+	db: Arc<dyn KeyValueDB>,
+	col_data: u32,
+}
+
+impl SessionInfoArchive {
+	/// Create a new archive backed by the given column of the database.
+	pub fn new(db: Arc<dyn KeyValueDB>, col_data: u32) -> Self {
+		SessionInfoArchive { db, col_data }
+	}
+
+	/// Persist the info of the given session, unless it is archived already.
+	///
+	/// The session info of a session never changes, so an archived entry is never overwritten.
+	pub fn store(&self, index: SessionIndex, info: &SessionInfo) {
+		let key = index.to_be_bytes();
+		match self.db.has_key(self.col_data, &key) {
+			Ok(true) => return,
+			Ok(false) => {},
+			Err(err) => {
+				tracing::warn!(target: LOG_TARGET, ?err, index, "Failed to query session info archive");
+				return
+			},
+		}
+
+		let mut tx = DBTransaction::new();
+		tx.put_vec(self.col_data, &key, info.encode());
+		if let Err(err) = self.db.write(tx) {
+			tracing::warn!(target: LOG_TARGET, ?err, index, "Failed to archive session info");
+		}
+	}
+
+	/// Load the archived info of the given session, if any.
+	pub fn get(&self, index: SessionIndex) -> Option<SessionInfo> {
+		match self.db.get(self.col_data, &index.to_be_bytes()) {
+			Ok(raw) => raw.and_then(|raw| match SessionInfo::decode(&mut &raw[..]) {
+				Ok(info) => Some(info),
+				Err(err) => {
+					tracing::warn!(target: LOG_TARGET, ?err, index, "Corrupted session info archive");
+					None
+				},
+			}),
+			Err(err) => {
+				tracing::warn!(target: LOG_TARGET, ?err, index, "Failed to read session info archive");
+				None
+			},
+		}
+	}
+}
@@ -22,7 +22,7 @@ use sp_consensus_babe::Epoch;
 
 use diamond_primitives::v1::{
 	AuthorityDiscoveryId, BlockNumber, CandidateCommitments, CandidateEvent,
-	CommittedCandidateReceipt, CoreState, GroupRotationInfo, Hash, Id as ParaId,
+	CommittedCandidateReceipt, CoreState, GroupRotationInfo, Hash, HrmpParaState, Id as ParaId,
 	InboundDownwardMessage, InboundHrmpMessage, OccupiedCoreAssumption, PersistedValidationData,
 	SessionIndex, SessionInfo, ValidationCode, ValidationCodeHash, ValidatorId, ValidatorIndex,
 };
@@ -40,6 +40,7 @@ const CANDIDATE_EVENTS_CACHE_SIZE: usize = 64 * 1024;
 const SESSION_INFO_CACHE_SIZE: usize = 64 * 1024;
 const DMQ_CONTENTS_CACHE_SIZE: usize = 64 * 1024;
 const INBOUND_HRMP_CHANNELS_CACHE_SIZE: usize = 64 * 1024;
+const HRMP_STATE_CACHE_SIZE: usize = 64 * 1024;
 const CURRENT_BABE_EPOCH_CACHE_SIZE: usize = 64 * 1024;
 
 struct ResidentSizeOf<T>(T);
@@ -97,6 +98,7 @@ pub(crate) struct RequestResultCache {
 		(Hash, ParaId),
 		ResidentSizeOf<BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>>>,
 	>,
+	hrmp_state: MemoryLruCache<(Hash, ParaId), ResidentSizeOf<HrmpParaState<BlockNumber>>>,
 	current_babe_epoch: MemoryLruCache<Hash, DoesNotAllocate<Epoch>>,
 }
 
@@ -119,6 +121,7 @@ impl Default for RequestResultCache {
 			session_info: MemoryLruCache::new(SESSION_INFO_CACHE_SIZE),
 			dmq_contents: MemoryLruCache::new(DMQ_CONTENTS_CACHE_SIZE),
 			inbound_hrmp_channels_contents: MemoryLruCache::new(INBOUND_HRMP_CHANNELS_CACHE_SIZE),
+			hrmp_state: MemoryLruCache::new(HRMP_STATE_CACHE_SIZE),
 			current_babe_epoch: MemoryLruCache::new(CURRENT_BABE_EPOCH_CACHE_SIZE),
 		}
 	}
@@ -313,6 +316,21 @@ impl RequestResultCache {
 		self.inbound_hrmp_channels_contents.insert(key, ResidentSizeOf(value));
 	}
 
+	pub(crate) fn hrmp_state(
+		&mut self,
+		key: (Hash, ParaId),
+	) -> Option<&HrmpParaState<BlockNumber>> {
+		self.hrmp_state.get(&key).map(|v| &v.0)
+	}
+
+	pub(crate) fn cache_hrmp_state(
+		&mut self,
+		key: (Hash, ParaId),
+		value: HrmpParaState<BlockNumber>,
+	) {
+		self.hrmp_state.insert(key, ResidentSizeOf(value));
+	}
+
 	pub(crate) fn current_babe_epoch(&mut self, relay_parent: &Hash) -> Option<&Epoch> {
 		self.current_babe_epoch.get(relay_parent).map(|v| &v.0)
 	}
@@ -341,5 +359,6 @@ pub(crate) enum RequestResult {
 		ParaId,
 		BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>>,
 	),
+	HrmpState(Hash, ParaId, HrmpParaState<BlockNumber>),
 	CurrentBabeEpoch(Hash, Epoch),
 }
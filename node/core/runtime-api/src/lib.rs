@@ -23,7 +23,7 @@
 #![warn(missing_docs)]
 
 use diamond_node_subsystem_util::metrics::{self, prometheus};
-use diamond_primitives::v1::{Block, BlockId, Hash, ParachainHost};
+use diamond_primitives::v1::{Block, BlockId, Hash, ParachainHost, HRMP_STATE_API_VERSION};
 use diamond_subsystem::{
 	errors::RuntimeApiError,
 	messages::{RuntimeApiMessage, RuntimeApiRequest as Request},
@@ -31,7 +31,7 @@ use diamond_subsystem::{
 	SubsystemResult,
 };
 
-use sp_api::ProvideRuntimeApi;
+use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_authority_discovery::AuthorityDiscoveryApi;
 use sp_consensus_babe::BabeApi;
 use sp_core::traits::SpawnNamed;
@@ -40,8 +40,11 @@ use cache::{RequestResult, RequestResultCache};
 use futures::{channel::oneshot, prelude::*, select, stream::FuturesUnordered};
 use std::{collections::VecDeque, pin::Pin, sync::Arc};
 
+mod archive;
 mod cache;
 
+pub use archive::SessionInfoArchive;
+
 #[cfg(test)]
 mod tests;
 
@@ -67,6 +70,8 @@ pub struct RuntimeApiSubsystem<Client> {
 	active_requests: FuturesUnordered<oneshot::Receiver<Option<RequestResult>>>,
 	/// Requests results cache
 	requests_cache: RequestResultCache,
+	/// The persistent archive of every session info seen, if any.
+	session_info_archive: Option<SessionInfoArchive>,
 }
 
 impl<Client> RuntimeApiSubsystem<Client> {
@@ -83,8 +88,16 @@ impl<Client> RuntimeApiSubsystem<Client> {
 			waiting_requests: Default::default(),
 			active_requests: Default::default(),
 			requests_cache: RequestResultCache::default(),
+			session_info_archive: None,
 		}
 	}
+
+	/// Archive every session info fetched from the runtime in the given archive, and serve
+	/// [`RuntimeApiMessage::SessionInfoArchive`] queries from it.
+	pub fn with_session_info_archive(mut self, archive: SessionInfoArchive) -> Self {
+		self.session_info_archive = Some(archive);
+		self
+	}
 }
 
 impl<Client, Context> overseer::Subsystem<Context, SubsystemError> for RuntimeApiSubsystem<Client>
@@ -141,6 +154,8 @@ where
 			InboundHrmpChannelsContents(relay_parent, para_id, contents) => self
 				.requests_cache
 				.cache_inbound_hrmp_channel_contents((relay_parent, para_id), contents),
+			HrmpState(relay_parent, para_id, state) =>
+				self.requests_cache.cache_hrmp_state((relay_parent, para_id), state),
 			CurrentBabeEpoch(relay_parent, epoch) =>
 				self.requests_cache.cache_current_babe_epoch(relay_parent, epoch),
 		}
@@ -207,6 +222,8 @@ where
 			Request::InboundHrmpChannelsContents(id, sender) =>
 				query!(inbound_hrmp_channels_contents(id), sender)
 					.map(|sender| Request::InboundHrmpChannelsContents(id, sender)),
+			Request::HrmpState(id, sender) =>
+				query!(hrmp_state(id), sender).map(|sender| Request::HrmpState(id, sender)),
 			Request::CurrentBabeEpoch(sender) =>
 				query!(current_babe_epoch(), sender).map(|sender| Request::CurrentBabeEpoch(sender)),
 		}
@@ -218,6 +235,7 @@ where
 	fn spawn_request(&mut self, relay_parent: Hash, request: Request) {
 		let client = self.client.clone();
 		let metrics = self.metrics.clone();
+		let session_info_archive = self.session_info_archive.clone();
 		let (sender, receiver) = oneshot::channel();
 
 		let request = match self.query_cache(relay_parent.clone(), request) {
@@ -226,7 +244,13 @@ where
 		};
 
 		let request = async move {
-			let result = make_runtime_api_request(client, metrics, relay_parent, request);
+			let result = make_runtime_api_request(
+				client,
+				metrics,
+				session_info_archive,
+				relay_parent,
+				request,
+			);
 			let _ = sender.send(result);
 		}
 		.boxed();
@@ -286,6 +310,13 @@ where
 					RuntimeApiMessage::Request(relay_parent, request) => {
 						subsystem.spawn_request(relay_parent, request);
 					},
+					RuntimeApiMessage::SessionInfoArchive(index, sender) => {
+						let info = subsystem
+							.session_info_archive
+							.as_ref()
+							.and_then(|archive| archive.get(index));
+						let _ = sender.send(info);
+					},
 				}
 			},
 			_ = subsystem.poll_requests().fuse() => {},
@@ -296,6 +327,7 @@ where
 fn make_runtime_api_request<Client>(
 	client: Arc<Client>,
 	metrics: Metrics,
+	session_info_archive: Option<SessionInfoArchive>,
 	relay_parent: Hash,
 	request: Request,
 ) -> Option<RequestResult>
@@ -337,10 +369,52 @@ where
 		Request::CandidatePendingAvailability(para, sender) =>
 			query!(CandidatePendingAvailability, candidate_pending_availability(para), sender),
 		Request::CandidateEvents(sender) => query!(CandidateEvents, candidate_events(), sender),
-		Request::SessionInfo(index, sender) => query!(SessionInfo, session_info(index), sender),
+		Request::SessionInfo(index, sender) => {
+			let res = client
+				.runtime_api()
+				.session_info(&BlockId::Hash(relay_parent), index)
+				.map_err(|e| RuntimeApiError::from(format!("{:?}", e)));
+			metrics.on_request(res.is_ok());
+
+			// archive the session info before handing it out, so that it can be queried from
+			// the archive by the time the requester has it.
+			if let (Some(archive), Ok(Some(info))) = (&session_info_archive, &res) {
+				archive.store(index, info);
+			}
+			let _ = sender.send(res.clone());
+
+			res.ok().map(|res| RequestResult::SessionInfo(relay_parent, index, res))
+		},
 		Request::DmqContents(id, sender) => query!(DmqContents, dmq_contents(id), sender),
 		Request::InboundHrmpChannelsContents(id, sender) =>
 			query!(InboundHrmpChannelsContents, inbound_hrmp_channels_contents(id), sender),
+		Request::HrmpState(id, sender) => {
+			// Runtimes implementing earlier versions of the API don't know about the call.
+			let supported = client
+				.runtime_api()
+				.has_api_with::<dyn ParachainHost<Block>, _>(&BlockId::Hash(relay_parent), |v| {
+					v >= HRMP_STATE_API_VERSION
+				})
+				.map_err(|e| RuntimeApiError::from(format!("{:?}", e)))
+				.and_then(|supported| {
+					if supported {
+						Ok(())
+					} else {
+						Err(RuntimeApiError::from(format!(
+							"`hrmp_state` requires version {} of the `ParachainHost` API",
+							HRMP_STATE_API_VERSION,
+						)))
+					}
+				});
+			match supported {
+				Ok(()) => query!(HrmpState, hrmp_state(id), sender),
+				Err(e) => {
+					metrics.on_request(false);
+					let _ = sender.send(Err(e));
+					None
+				},
+			}
+		},
 		Request::CurrentBabeEpoch(sender) => query!(CurrentBabeEpoch, current_epoch(), sender),
 	}
 }
@@ -21,9 +21,9 @@ use diamond_node_primitives::{BabeAllowedSlots, BabeEpoch, BabeEpochConfiguratio
 use diamond_node_subsystem_test_helpers as test_helpers;
 use diamond_primitives::v1::{
 	AuthorityDiscoveryId, CandidateEvent, CommittedCandidateReceipt, CoreState, GroupRotationInfo,
-	Id as ParaId, InboundDownwardMessage, InboundHrmpMessage, OccupiedCoreAssumption,
-	PersistedValidationData, SessionIndex, SessionInfo, ValidationCode, ValidationCodeHash,
-	ValidatorId, ValidatorIndex,
+	HrmpChannelState, HrmpParaState, Id as ParaId, InboundDownwardMessage, InboundHrmpMessage,
+	OccupiedCoreAssumption, PersistedValidationData, SessionIndex, SessionInfo, ValidationCode,
+	ValidationCodeHash, ValidatorId, ValidatorIndex,
 };
 use sp_core::testing::TaskExecutor;
 use std::{
@@ -48,6 +48,7 @@ struct MockRuntimeApi {
 	candidate_events: Vec<CandidateEvent>,
 	dmq_contents: HashMap<ParaId, Vec<InboundDownwardMessage>>,
 	hrmp_channels: HashMap<ParaId, BTreeMap<ParaId, Vec<InboundHrmpMessage>>>,
+	hrmp_state: HashMap<ParaId, HrmpParaState>,
 	babe_epoch: Option<BabeEpoch>,
 }
 
@@ -149,6 +150,10 @@ sp_api::mock_impl_runtime_apis! {
 		) -> Option<ValidationCode> {
 			self.validation_code_by_hash.get(&hash).map(|c| c.clone())
 		}
+
+		fn hrmp_state(&self, para_id: ParaId) -> HrmpParaState {
+			self.hrmp_state.get(&para_id).cloned().unwrap_or_default()
+		}
 	}
 
 	impl BabeApi<Block> for MockRuntimeApi {
@@ -446,6 +451,62 @@ fn requests_session_info() {
 	futures::executor::block_on(future::join(subsystem_task, test_task));
 }
 
+#[test]
+fn session_info_is_archived_and_served_from_archive() {
+	let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
+	let mut runtime_api = MockRuntimeApi::default();
+	let session_index = 1;
+	runtime_api.session_info.insert(session_index, Default::default());
+	let runtime_api = Arc::new(runtime_api);
+	let spawner = sp_core::testing::TaskExecutor::new();
+
+	let relay_parent = [1; 32].into();
+
+	let archive = SessionInfoArchive::new(Arc::new(kvdb_memorydb::create(1)), 0);
+	let subsystem = RuntimeApiSubsystem::new(runtime_api.clone(), Metrics(None), spawner)
+		.with_session_info_archive(archive.clone());
+	let subsystem_task = run(ctx, subsystem).map(|x| x.unwrap());
+	let test_task = async move {
+		let (tx, rx) = oneshot::channel();
+
+		ctx_handle
+			.send(FromOverseer::Communication {
+				msg: RuntimeApiMessage::SessionInfoArchive(session_index, tx),
+			})
+			.await;
+
+		assert_eq!(rx.await.unwrap(), None);
+
+		let (tx, rx) = oneshot::channel();
+
+		ctx_handle
+			.send(FromOverseer::Communication {
+				msg: RuntimeApiMessage::Request(
+					relay_parent,
+					Request::SessionInfo(session_index, tx),
+				),
+			})
+			.await;
+
+		assert_eq!(rx.await.unwrap().unwrap(), Some(Default::default()));
+		assert_eq!(archive.get(session_index), Some(Default::default()));
+
+		let (tx, rx) = oneshot::channel();
+
+		ctx_handle
+			.send(FromOverseer::Communication {
+				msg: RuntimeApiMessage::SessionInfoArchive(session_index, tx),
+			})
+			.await;
+
+		assert_eq!(rx.await.unwrap(), Some(Default::default()));
+
+		ctx_handle.send(FromOverseer::Signal(OverseerSignal::Conclude)).await;
+	};
+
+	futures::executor::block_on(future::join(subsystem_task, test_task));
+}
+
 #[test]
 fn requests_validation_code() {
 	let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
@@ -671,6 +732,61 @@ fn requests_inbound_hrmp_channels_contents() {
 	futures::executor::block_on(future::join(subsystem_task, test_task));
 }
 
+#[test]
+fn requests_hrmp_state() {
+	let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
+
+	let relay_parent = [1; 32].into();
+	let para_a = 99.into();
+	let para_b = 66.into();
+	let spawner = sp_core::testing::TaskExecutor::new();
+
+	let para_b_state = HrmpParaState {
+		inbound: vec![HrmpChannelState {
+			sender: para_a,
+			recipient: para_b,
+			max_capacity: 8,
+			max_total_size: 1024,
+			max_message_size: 256,
+			msg_count: 2,
+			total_size: 96,
+			mqc_head: Some([2; 32].into()),
+			message_sizes: vec![32, 64],
+		}],
+		watermark: Some(1),
+		..Default::default()
+	};
+
+	let runtime_api = Arc::new({
+		let mut runtime_api = MockRuntimeApi::default();
+		runtime_api.hrmp_state.insert(para_b, para_b_state.clone());
+		runtime_api
+	});
+
+	let subsystem = RuntimeApiSubsystem::new(runtime_api.clone(), Metrics(None), spawner);
+	let subsystem_task = run(ctx, subsystem).map(|x| x.unwrap());
+	let test_task = async move {
+		let (tx, rx) = oneshot::channel();
+		ctx_handle
+			.send(FromOverseer::Communication {
+				msg: RuntimeApiMessage::Request(relay_parent, Request::HrmpState(para_a, tx)),
+			})
+			.await;
+		assert_eq!(rx.await.unwrap().unwrap(), HrmpParaState::default());
+
+		let (tx, rx) = oneshot::channel();
+		ctx_handle
+			.send(FromOverseer::Communication {
+				msg: RuntimeApiMessage::Request(relay_parent, Request::HrmpState(para_b, tx)),
+			})
+			.await;
+		assert_eq!(rx.await.unwrap().unwrap(), para_b_state);
+
+		ctx_handle.send(FromOverseer::Signal(OverseerSignal::Conclude)).await;
+	};
+	futures::executor::block_on(future::join(subsystem_task, test_task));
+}
+
 #[test]
 fn requests_validation_code_by_hash() {
 	let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
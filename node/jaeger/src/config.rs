@@ -16,24 +16,109 @@
 
 //! diamond Jaeger configuration.
 
+use crate::{errors::JaegerError, spans::Stage};
+
+use std::{collections::HashMap, net::SocketAddr, path::PathBuf, str::FromStr};
+
+/// The wire protocol used to talk to an OpenTelemetry collector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtlpProtocol {
+	/// OTLP over gRPC, usually served on port `4317`.
+	Grpc,
+	/// OTLP over HTTP with binary protobuf payloads, usually served on port `4318`.
+	HttpProtobuf,
+}
+
+impl FromStr for OtlpProtocol {
+	type Err = JaegerError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"grpc" => Ok(Self::Grpc),
+			"http" | "http/protobuf" => Ok(Self::HttpProtobuf),
+			other => Err(JaegerError::UnknownOtlpProtocol(other.to_owned())),
+		}
+	}
+}
+
+/// A destination for the collected spans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JaegerExporter {
+	/// Send compact thrift batches via UDP to a jaeger agent.
+	Agent(SocketAddr),
+	/// Send spans to an OpenTelemetry collector.
+	Otlp {
+		/// The collector endpoint, i.e. `http://127.0.0.1:4317`.
+		endpoint: String,
+		/// The protocol spoken by the collector at `endpoint`.
+		protocol: OtlpProtocol,
+	},
+	/// Append spans as newline-delimited JSON to a file.
+	File(PathBuf),
+}
+
+/// Per [`Stage`] sampling ratios.
+///
+/// Spans that are annotated with a stage are only exported if their trace
+/// falls within the configured ratio. The decision is derived from the trace
+/// identifier only, so all nodes sample the same candidates.
+/// Spans without a stage annotation and stages without a configured ratio are
+/// always exported.
+#[derive(Debug, Clone, Default)]
+pub struct StageSampling {
+	ratios: HashMap<u8, f64>,
+}
+
+impl StageSampling {
+	/// Set the ratio of traces to export for the given stage.
+	///
+	/// The ratio is clamped to `0.0..=1.0`.
+	pub fn set(&mut self, stage: Stage, ratio: f64) {
+		self.ratios.insert(stage as u8, ratio.clamp(0.0, 1.0));
+	}
+
+	/// Whether there are any sampling ratios configured at all.
+	pub fn is_empty(&self) -> bool {
+		self.ratios.is_empty()
+	}
+
+	/// Check if a span with the given raw stage value of a trace should be exported.
+	pub(crate) fn is_sampled(&self, stage: u8, trace_id: u128) -> bool {
+		let ratio = match self.ratios.get(&stage) {
+			Some(ratio) => *ratio,
+			None => return true,
+		};
+		// Trace identifiers are derived from hashes, so the lower bits are
+		// uniformly distributed.
+		let position = (trace_id as u64) as f64 / u64::MAX as f64;
+		position < ratio
+	}
+}
+
 /// Configuration for the jaeger tracing.
 #[derive(Clone)]
 pub struct JaegerConfig {
 	pub(crate) node_name: String,
-	pub(crate) agent_addr: std::net::SocketAddr,
+	pub(crate) exporters: Vec<JaegerExporter>,
+	pub(crate) sampling: StageSampling,
 }
 
 impl std::default::Default for JaegerConfig {
 	fn default() -> Self {
 		Self {
 			node_name: "unknown_".to_owned(),
-			agent_addr: "127.0.0.1:6831"
-				.parse()
-				.expect(r#"Static "127.0.0.1:6831" is a valid socket address string. qed"#),
+			exporters: vec![JaegerExporter::Agent(default_agent_addr())],
+			sampling: StageSampling::default(),
 		}
 	}
 }
 
+fn default_agent_addr() -> SocketAddr {
+	"127.0.0.1:6831"
+		.parse()
+		.expect(r#"Static "127.0.0.1:6831" is a valid socket address string. qed"#)
+}
+
 impl JaegerConfig {
 	/// Use the builder pattern to construct a configuration.
 	pub fn builder() -> JaegerConfigBuilder {
@@ -42,9 +127,14 @@ impl JaegerConfig {
 }
 
 /// Jaeger configuration builder.
+///
+/// If no exporter is configured explicitly, spans are sent to a jaeger agent
+/// on `127.0.0.1:6831`.
 #[derive(Default)]
 pub struct JaegerConfigBuilder {
-	inner: JaegerConfig,
+	node_name: Option<String>,
+	exporters: Vec<JaegerExporter>,
+	sampling: StageSampling,
 }
 
 impl JaegerConfigBuilder {
@@ -53,21 +143,62 @@ impl JaegerConfigBuilder {
 	where
 		S: AsRef<str>,
 	{
-		self.inner.node_name = name.as_ref().to_owned();
+		self.node_name = Some(name.as_ref().to_owned());
 		self
 	}
 
 	/// Set the agent address to send the collected spans to.
+	///
+	/// Replaces any previously configured agent.
 	pub fn agent<U>(mut self, addr: U) -> Self
 	where
 		U: Into<std::net::SocketAddr>,
 	{
-		self.inner.agent_addr = addr.into();
+		self.exporters.retain(|exporter| !matches!(exporter, JaegerExporter::Agent(_)));
+		self.exporters.push(JaegerExporter::Agent(addr.into()));
+		self
+	}
+
+	/// Additionally send the collected spans to an OpenTelemetry collector.
+	pub fn otlp<S>(mut self, endpoint: S, protocol: OtlpProtocol) -> Self
+	where
+		S: Into<String>,
+	{
+		self.exporters
+			.push(JaegerExporter::Otlp { endpoint: endpoint.into(), protocol });
 		self
 	}
 
+	/// Additionally append the collected spans as newline-delimited JSON to `path`.
+	pub fn file<P>(mut self, path: P) -> Self
+	where
+		P: Into<PathBuf>,
+	{
+		self.exporters.push(JaegerExporter::File(path.into()));
+		self
+	}
+
+	/// Only export the given ratio of traces for spans annotated with `stage`.
+	pub fn sample_stage(mut self, stage: Stage, ratio: f64) -> Self {
+		self.sampling.set(stage, ratio);
+		self
+	}
+
+	/// Whether any exporter was configured explicitly.
+	pub fn has_exporters(&self) -> bool {
+		!self.exporters.is_empty()
+	}
+
 	/// Construct the configuration.
 	pub fn build(self) -> JaegerConfig {
-		self.inner
+		let mut cfg = JaegerConfig::default();
+		if let Some(node_name) = self.node_name {
+			cfg.node_name = node_name;
+		}
+		if !self.exporters.is_empty() {
+			cfg.exporters = self.exporters;
+		}
+		cfg.sampling = self.sampling;
+		cfg
 	}
 }
@@ -25,4 +25,10 @@ pub enum JaegerError {
 
 	#[error("Missing jaeger configuration")]
 	MissingConfiguration,
+
+	#[error("Unknown OTLP protocol {0:?}, expected `grpc` or `http`")]
+	UnknownOtlpProtocol(String),
+
+	#[error("Unknown candidate stage {0:?}")]
+	UnknownStage(String),
 }
@@ -0,0 +1,478 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Compact thrift encoding of the jaeger agent `emitBatch` call.
+//!
+//! [`mick_jaeger`] only hands out the encoded datagrams destined for the
+//! agent, so this re-implements the small subset of the jaeger thrift
+//! definitions needed to inspect and re-encode them.
+
+use thrift::protocol::{
+	TCompactInputProtocol, TCompactOutputProtocol, TFieldIdentifier, TInputProtocol,
+	TListIdentifier, TMessageIdentifier, TMessageType, TOutputProtocol, TStructIdentifier, TType,
+};
+
+/// A batch of spans, as emitted by a single process.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Batch {
+	pub process: Process,
+	pub spans: Vec<Span>,
+}
+
+/// The process that emitted a batch.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Process {
+	pub service_name: String,
+	pub tags: Vec<Tag>,
+}
+
+/// A single finished span.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Span {
+	pub trace_id_low: i64,
+	pub trace_id_high: i64,
+	pub span_id: i64,
+	pub parent_span_id: i64,
+	pub operation_name: String,
+	pub references: Vec<SpanRef>,
+	pub flags: i32,
+	/// Start time in microseconds since the unix epoch.
+	pub start_time: i64,
+	/// Duration in microseconds.
+	pub duration: i64,
+	pub tags: Vec<Tag>,
+	pub logs: Vec<Log>,
+}
+
+impl Span {
+	/// The 128 bit trace identifier.
+	pub fn trace_id(&self) -> u128 {
+		((self.trace_id_high as u64 as u128) << 64) | (self.trace_id_low as u64 as u128)
+	}
+
+	/// Find the value of the tag with the given key.
+	pub fn tag(&self, key: &str) -> Option<&TagValue> {
+		self.tags.iter().find(|tag| tag.key == key).map(|tag| &tag.value)
+	}
+}
+
+/// A reference from one span to another.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SpanRef {
+	/// `0` for `child-of`, `1` for `follows-from`.
+	pub ref_type: i32,
+	pub trace_id_low: i64,
+	pub trace_id_high: i64,
+	pub span_id: i64,
+}
+
+/// A timestamped set of fields attached to a span.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Log {
+	/// Timestamp in microseconds since the unix epoch.
+	pub timestamp: i64,
+	pub fields: Vec<Tag>,
+}
+
+/// A key value pair.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Tag {
+	pub key: String,
+	pub value: TagValue,
+}
+
+/// The typed value of a [`Tag`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TagValue {
+	String(String),
+	Double(f64),
+	Bool(bool),
+	Long(i64),
+	Binary(Vec<u8>),
+}
+
+/// Decode an `emitBatch` datagram as produced by [`mick_jaeger::TracesOut::next`].
+pub(crate) fn decode_batch(mut bytes: &[u8]) -> thrift::Result<Batch> {
+	let mut i = TCompactInputProtocol::new(&mut bytes);
+	i.read_message_begin()?;
+	let mut batch = None;
+	read_struct(&mut i, |i, id, ty| match (id, ty) {
+		(1, TType::Struct) => {
+			batch = Some(read_batch(i)?);
+			Ok(true)
+		},
+		_ => Ok(false),
+	})?;
+	i.read_message_end()?;
+	batch.ok_or_else(|| missing_field("batch"))
+}
+
+/// Encode `batch` as an `emitBatch` datagram for the jaeger agent.
+pub(crate) fn encode_batch(batch: &Batch) -> thrift::Result<Vec<u8>> {
+	let mut buf = Vec::with_capacity(512);
+	{
+		let mut o = TCompactOutputProtocol::new(&mut buf);
+		o.write_message_begin(&TMessageIdentifier::new("emitBatch", TMessageType::OneWay, 0))?;
+		o.write_struct_begin(&TStructIdentifier::new("Agent_emitBatch_args"))?;
+		write_field(&mut o, "batch", TType::Struct, 1, |o| write_batch(o, batch))?;
+		o.write_field_stop()?;
+		o.write_struct_end()?;
+		o.write_message_end()?;
+		o.flush()?;
+	}
+	Ok(buf)
+}
+
+fn missing_field(name: &str) -> thrift::Error {
+	thrift::Error::Protocol(thrift::ProtocolError::new(
+		thrift::ProtocolErrorKind::InvalidData,
+		format!("missing required field {}", name),
+	))
+}
+
+/// Read a struct, calling `read_field` for every field.
+///
+/// `read_field` returns `false` for fields it does not know, which are skipped.
+fn read_struct<P, F>(i: &mut P, mut read_field: F) -> thrift::Result<()>
+where
+	P: TInputProtocol,
+	F: FnMut(&mut P, i16, TType) -> thrift::Result<bool>,
+{
+	i.read_struct_begin()?;
+	loop {
+		let field = i.read_field_begin()?;
+		if field.field_type == TType::Stop {
+			break
+		}
+		if !read_field(i, field.id.unwrap_or_default(), field.field_type)? {
+			i.skip(field.field_type)?;
+		}
+		i.read_field_end()?;
+	}
+	i.read_struct_end()
+}
+
+fn read_list<P, T, F>(i: &mut P, mut read_element: F) -> thrift::Result<Vec<T>>
+where
+	P: TInputProtocol,
+	F: FnMut(&mut P) -> thrift::Result<T>,
+{
+	let ident = i.read_list_begin()?;
+	// Do not trust the announced size for the allocation.
+	let mut elements = Vec::with_capacity((ident.size.max(0) as usize).min(64));
+	for _ in 0..ident.size {
+		elements.push(read_element(i)?);
+	}
+	i.read_list_end()?;
+	Ok(elements)
+}
+
+fn read_batch(i: &mut impl TInputProtocol) -> thrift::Result<Batch> {
+	let mut batch = Batch::default();
+	read_struct(i, |i, id, ty| {
+		match (id, ty) {
+			(1, TType::Struct) => batch.process = read_process(i)?,
+			(2, TType::List) => batch.spans = read_list(i, read_span)?,
+			_ => return Ok(false),
+		}
+		Ok(true)
+	})?;
+	Ok(batch)
+}
+
+fn read_process(i: &mut impl TInputProtocol) -> thrift::Result<Process> {
+	let mut process = Process::default();
+	read_struct(i, |i, id, ty| {
+		match (id, ty) {
+			(1, TType::String) => process.service_name = i.read_string()?,
+			(2, TType::List) => process.tags = read_list(i, read_tag)?,
+			_ => return Ok(false),
+		}
+		Ok(true)
+	})?;
+	Ok(process)
+}
+
+fn read_span(i: &mut impl TInputProtocol) -> thrift::Result<Span> {
+	let mut span = Span::default();
+	read_struct(i, |i, id, ty| {
+		match (id, ty) {
+			(1, TType::I64) => span.trace_id_low = i.read_i64()?,
+			(2, TType::I64) => span.trace_id_high = i.read_i64()?,
+			(3, TType::I64) => span.span_id = i.read_i64()?,
+			(4, TType::I64) => span.parent_span_id = i.read_i64()?,
+			(5, TType::String) => span.operation_name = i.read_string()?,
+			(6, TType::List) => span.references = read_list(i, read_span_ref)?,
+			(7, TType::I32) => span.flags = i.read_i32()?,
+			(8, TType::I64) => span.start_time = i.read_i64()?,
+			(9, TType::I64) => span.duration = i.read_i64()?,
+			(10, TType::List) => span.tags = read_list(i, read_tag)?,
+			(11, TType::List) => span.logs = read_list(i, read_log)?,
+			_ => return Ok(false),
+		}
+		Ok(true)
+	})?;
+	Ok(span)
+}
+
+fn read_span_ref(i: &mut impl TInputProtocol) -> thrift::Result<SpanRef> {
+	let mut span_ref = SpanRef::default();
+	read_struct(i, |i, id, ty| {
+		match (id, ty) {
+			(1, TType::I32) => span_ref.ref_type = i.read_i32()?,
+			(2, TType::I64) => span_ref.trace_id_low = i.read_i64()?,
+			(3, TType::I64) => span_ref.trace_id_high = i.read_i64()?,
+			(4, TType::I64) => span_ref.span_id = i.read_i64()?,
+			_ => return Ok(false),
+		}
+		Ok(true)
+	})?;
+	Ok(span_ref)
+}
+
+fn read_log(i: &mut impl TInputProtocol) -> thrift::Result<Log> {
+	let mut log = Log::default();
+	read_struct(i, |i, id, ty| {
+		match (id, ty) {
+			(1, TType::I64) => log.timestamp = i.read_i64()?,
+			(2, TType::List) => log.fields = read_list(i, read_tag)?,
+			_ => return Ok(false),
+		}
+		Ok(true)
+	})?;
+	Ok(log)
+}
+
+fn read_tag(i: &mut impl TInputProtocol) -> thrift::Result<Tag> {
+	let mut key = None;
+	let mut v_type = None;
+	let (mut v_str, mut v_double, mut v_bool, mut v_long, mut v_binary) =
+		(None, None, None, None, None);
+	read_struct(i, |i, id, ty| {
+		match (id, ty) {
+			(1, TType::String) => key = Some(i.read_string()?),
+			(2, TType::I32) => v_type = Some(i.read_i32()?),
+			(3, TType::String) => v_str = Some(i.read_string()?),
+			(4, TType::Double) => v_double = Some(i.read_double()?),
+			(5, TType::Bool) => v_bool = Some(i.read_bool()?),
+			(6, TType::I64) => v_long = Some(i.read_i64()?),
+			(7, TType::String) => v_binary = Some(i.read_bytes()?),
+			_ => return Ok(false),
+		}
+		Ok(true)
+	})?;
+
+	let key = key.ok_or_else(|| missing_field("key"))?;
+	let value = match v_type.ok_or_else(|| missing_field("vType"))? {
+		0 => v_str.map(TagValue::String),
+		1 => v_double.map(TagValue::Double),
+		2 => v_bool.map(TagValue::Bool),
+		3 => v_long.map(TagValue::Long),
+		4 => v_binary.map(TagValue::Binary),
+		_ => None,
+	}
+	.ok_or_else(|| missing_field("tag value"))?;
+
+	Ok(Tag { key, value })
+}
+
+fn write_field<P, F>(o: &mut P, name: &str, ty: TType, id: i16, write: F) -> thrift::Result<()>
+where
+	P: TOutputProtocol,
+	F: FnOnce(&mut P) -> thrift::Result<()>,
+{
+	o.write_field_begin(&TFieldIdentifier::new(name, ty, id))?;
+	write(o)?;
+	o.write_field_end()
+}
+
+fn write_list<P, T, F>(o: &mut P, ty: TType, elements: &[T], mut write: F) -> thrift::Result<()>
+where
+	P: TOutputProtocol,
+	F: FnMut(&mut P, &T) -> thrift::Result<()>,
+{
+	o.write_list_begin(&TListIdentifier::new(ty, elements.len() as i32))?;
+	for element in elements {
+		write(o, element)?;
+	}
+	o.write_list_end()
+}
+
+fn write_batch<P: TOutputProtocol>(o: &mut P, batch: &Batch) -> thrift::Result<()> {
+	o.write_struct_begin(&TStructIdentifier::new("Batch"))?;
+	write_field(o, "process", TType::Struct, 1, |o| write_process(o, &batch.process))?;
+	write_field(o, "spans", TType::List, 2, |o| {
+		write_list(o, TType::Struct, &batch.spans, write_span)
+	})?;
+	o.write_field_stop()?;
+	o.write_struct_end()
+}
+
+fn write_process<P: TOutputProtocol>(o: &mut P, process: &Process) -> thrift::Result<()> {
+	o.write_struct_begin(&TStructIdentifier::new("Process"))?;
+	write_field(o, "serviceName", TType::String, 1, |o| o.write_string(&process.service_name))?;
+	write_field(o, "tags", TType::List, 2, |o| {
+		write_list(o, TType::Struct, &process.tags, write_tag)
+	})?;
+	o.write_field_stop()?;
+	o.write_struct_end()
+}
+
+fn write_span<P: TOutputProtocol>(o: &mut P, span: &Span) -> thrift::Result<()> {
+	o.write_struct_begin(&TStructIdentifier::new("Span"))?;
+	write_field(o, "traceIdLow", TType::I64, 1, |o| o.write_i64(span.trace_id_low))?;
+	write_field(o, "traceIdHigh", TType::I64, 2, |o| o.write_i64(span.trace_id_high))?;
+	write_field(o, "spanId", TType::I64, 3, |o| o.write_i64(span.span_id))?;
+	write_field(o, "parentSpanId", TType::I64, 4, |o| o.write_i64(span.parent_span_id))?;
+	write_field(o, "operationName", TType::String, 5, |o| o.write_string(&span.operation_name))?;
+	if !span.references.is_empty() {
+		write_field(o, "references", TType::List, 6, |o| {
+			write_list(o, TType::Struct, &span.references, write_span_ref)
+		})?;
+	}
+	write_field(o, "flags", TType::I32, 7, |o| o.write_i32(span.flags))?;
+	write_field(o, "startTime", TType::I64, 8, |o| o.write_i64(span.start_time))?;
+	write_field(o, "duration", TType::I64, 9, |o| o.write_i64(span.duration))?;
+	write_field(o, "tags", TType::List, 10, |o| {
+		write_list(o, TType::Struct, &span.tags, write_tag)
+	})?;
+	if !span.logs.is_empty() {
+		write_field(o, "logs", TType::List, 11, |o| {
+			write_list(o, TType::Struct, &span.logs, write_log)
+		})?;
+	}
+	o.write_field_stop()?;
+	o.write_struct_end()
+}
+
+fn write_span_ref<P: TOutputProtocol>(o: &mut P, span_ref: &SpanRef) -> thrift::Result<()> {
+	o.write_struct_begin(&TStructIdentifier::new("SpanRef"))?;
+	write_field(o, "refType", TType::I32, 1, |o| o.write_i32(span_ref.ref_type))?;
+	write_field(o, "traceIdLow", TType::I64, 2, |o| o.write_i64(span_ref.trace_id_low))?;
+	write_field(o, "traceIdHigh", TType::I64, 3, |o| o.write_i64(span_ref.trace_id_high))?;
+	write_field(o, "spanId", TType::I64, 4, |o| o.write_i64(span_ref.span_id))?;
+	o.write_field_stop()?;
+	o.write_struct_end()
+}
+
+fn write_log<P: TOutputProtocol>(o: &mut P, log: &Log) -> thrift::Result<()> {
+	o.write_struct_begin(&TStructIdentifier::new("Log"))?;
+	write_field(o, "timestamp", TType::I64, 1, |o| o.write_i64(log.timestamp))?;
+	write_field(o, "fields", TType::List, 2, |o| {
+		write_list(o, TType::Struct, &log.fields, write_tag)
+	})?;
+	o.write_field_stop()?;
+	o.write_struct_end()
+}
+
+fn write_tag<P: TOutputProtocol>(o: &mut P, tag: &Tag) -> thrift::Result<()> {
+	o.write_struct_begin(&TStructIdentifier::new("Tag"))?;
+	write_field(o, "key", TType::String, 1, |o| o.write_string(&tag.key))?;
+	match &tag.value {
+		TagValue::String(v) => {
+			write_field(o, "vType", TType::I32, 2, |o| o.write_i32(0))?;
+			write_field(o, "vStr", TType::String, 3, |o| o.write_string(v))?;
+		},
+		TagValue::Double(v) => {
+			write_field(o, "vType", TType::I32, 2, |o| o.write_i32(1))?;
+			write_field(o, "vDouble", TType::Double, 4, |o| o.write_double(*v))?;
+		},
+		TagValue::Bool(v) => {
+			write_field(o, "vType", TType::I32, 2, |o| o.write_i32(2))?;
+			write_field(o, "vBool", TType::Bool, 5, |o| o.write_bool(*v))?;
+		},
+		TagValue::Long(v) => {
+			write_field(o, "vType", TType::I32, 2, |o| o.write_i32(3))?;
+			write_field(o, "vLong", TType::I64, 6, |o| o.write_i64(*v))?;
+		},
+		TagValue::Binary(v) => {
+			write_field(o, "vType", TType::I32, 2, |o| o.write_i32(4))?;
+			write_field(o, "vBinary", TType::String, 7, |o| o.write_bytes(v))?;
+		},
+	}
+	o.write_field_stop()?;
+	o.write_struct_end()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn tag(key: &str, value: TagValue) -> Tag {
+		Tag { key: key.to_owned(), value }
+	}
+
+	#[test]
+	fn batch_roundtrip() {
+		let batch = Batch {
+			process: Process {
+				service_name: "diamond-alice".to_owned(),
+				tags: vec![tag("hostname", TagValue::String("localhost".to_owned()))],
+			},
+			spans: vec![Span {
+				trace_id_low: -7,
+				trace_id_high: 42,
+				span_id: 1337,
+				parent_span_id: 0,
+				operation_name: "candidate-backing".to_owned(),
+				references: vec![SpanRef {
+					ref_type: 1,
+					trace_id_low: 1,
+					trace_id_high: 2,
+					span_id: 3,
+				}],
+				flags: 0,
+				start_time: 1_600_000_000_000_000,
+				duration: 250,
+				tags: vec![
+					tag("candidate-stage", TagValue::String("2".to_owned())),
+					tag("para-id", TagValue::Long(100)),
+					tag("valid", TagValue::Bool(true)),
+					tag("ratio", TagValue::Double(0.5)),
+					tag("raw", TagValue::Binary(vec![0xde, 0xad])),
+				],
+				logs: vec![Log {
+					timestamp: 1_600_000_000_000_100,
+					fields: vec![tag("event", TagValue::String("seconded".to_owned()))],
+				}],
+			}],
+		};
+
+		let encoded = encode_batch(&batch).unwrap();
+		assert_eq!(decode_batch(&encoded).unwrap(), batch);
+	}
+
+	#[test]
+	fn decodes_mick_jaeger_output() {
+		let (traces_in, mut traces_out) =
+			mick_jaeger::init(mick_jaeger::Config { service_name: "diamond-test".to_owned() });
+
+		let trace_id =
+			std::num::NonZeroU128::new(0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10).unwrap();
+		let mut span = traces_in.span(trace_id, "root");
+		span.add_string_tag("candidate-stage", "5");
+		drop(span);
+
+		let encoded = async_std::task::block_on(traces_out.next());
+		let batch = decode_batch(&encoded).unwrap();
+
+		assert_eq!(batch.process.service_name, "diamond-test");
+		assert_eq!(batch.spans.len(), 1);
+		assert_eq!(batch.spans[0].trace_id(), trace_id.get());
+		assert_eq!(batch.spans[0].operation_name, "root");
+		assert_eq!(batch.spans[0].tag("candidate-stage"), Some(&TagValue::String("5".to_owned())),);
+	}
+}
@@ -0,0 +1,95 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Export of spans as newline-delimited JSON.
+
+use super::codec::{self, TagValue};
+
+use async_std::{fs, io::WriteExt};
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+fn tags_to_json(tags: &[codec::Tag]) -> Value {
+	let tags = tags
+		.iter()
+		.map(|tag| {
+			let value = match &tag.value {
+				TagValue::String(v) => json!(v),
+				TagValue::Bool(v) => json!(v),
+				TagValue::Long(v) => json!(v),
+				TagValue::Double(v) => json!(v),
+				TagValue::Binary(v) =>
+					json!(v.iter().map(|b| format!("{:02x}", b)).collect::<String>()),
+			};
+			(tag.key.clone(), value)
+		})
+		.collect::<Map<_, _>>();
+	Value::Object(tags)
+}
+
+fn span_to_json(service_name: &str, span: &codec::Span) -> Value {
+	json!({
+		"traceId": format!("{:032x}", span.trace_id()),
+		"spanId": format!("{:016x}", span.span_id as u64),
+		"parentSpanId": format!("{:016x}", span.parent_span_id as u64),
+		"serviceName": service_name,
+		"operationName": span.operation_name,
+		"startTime": span.start_time,
+		"duration": span.duration,
+		"tags": tags_to_json(&span.tags),
+		"logs": span.logs.iter().map(|log| json!({
+			"timestamp": log.timestamp,
+			"fields": tags_to_json(&log.fields),
+		})).collect::<Vec<_>>(),
+		"references": span.references.iter().map(|reference| json!({
+			"refType": if reference.ref_type == 1 { "follows-from" } else { "child-of" },
+			"traceId": format!(
+				"{:016x}{:016x}",
+				reference.trace_id_high as u64,
+				reference.trace_id_low as u64,
+			),
+			"spanId": format!("{:016x}", reference.span_id as u64),
+		})).collect::<Vec<_>>(),
+	})
+}
+
+/// Appends spans to a file, one JSON object per line.
+///
+/// Times are given in microseconds.
+pub(crate) struct FileSink {
+	file: fs::File,
+}
+
+impl FileSink {
+	pub(crate) async fn open(path: &Path) -> std::io::Result<Self> {
+		let file = fs::OpenOptions::new().create(true).append(true).open(path).await?;
+		Ok(Self { file })
+	}
+
+	pub(crate) async fn export(&mut self, batch: &codec::Batch) {
+		let mut buf = Vec::new();
+		for span in &batch.spans {
+			// Serializing a `Value` cannot fail.
+			let _ =
+				serde_json::to_writer(&mut buf, &span_to_json(&batch.process.service_name, span));
+			buf.push(b'\n');
+		}
+
+		if let Err(e) = self.file.write_all(&buf).await {
+			log::debug!(target: "jaeger", "Span file write error: {}", e);
+		}
+	}
+}
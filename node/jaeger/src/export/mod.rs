@@ -0,0 +1,199 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Forwarding of the collected spans to the configured exporters.
+
+mod codec;
+mod file;
+mod otlp;
+
+use crate::config::{JaegerExporter, StageSampling};
+
+use std::net::SocketAddr;
+
+/// The tag used to annotate spans with their [`Stage`](crate::Stage).
+pub(crate) const STAGE_TAG: &str = "candidate-stage";
+
+enum Sink {
+	Agent { socket: async_std::net::UdpSocket, addr: SocketAddr },
+	Otlp(otlp::OtlpSink),
+	File(file::FileSink),
+}
+
+impl Sink {
+	async fn connect(exporter: JaegerExporter) -> Option<Self> {
+		match exporter {
+			JaegerExporter::Agent(addr) => match async_std::net::UdpSocket::bind("0.0.0.0:0").await
+			{
+				Ok(socket) => Some(Sink::Agent { socket, addr }),
+				Err(e) => {
+					log::warn!(target: "jaeger", "UDP socket open error: {}", e);
+					None
+				},
+			},
+			JaegerExporter::Otlp { endpoint, protocol } =>
+				match otlp::OtlpSink::new(&endpoint, protocol) {
+					Ok(sink) => Some(Sink::Otlp(sink)),
+					Err(e) => {
+						log::warn!(target: "jaeger", "Invalid OTLP endpoint {:?}: {}", endpoint, e);
+						None
+					},
+				},
+			JaegerExporter::File(path) => match file::FileSink::open(&path).await {
+				Ok(sink) => Some(Sink::File(sink)),
+				Err(e) => {
+					log::warn!(target: "jaeger", "Span file {} open error: {}", path.display(), e);
+					None
+				},
+			},
+		}
+	}
+
+	fn needs_decoding(&self) -> bool {
+		!matches!(self, Sink::Agent { .. })
+	}
+}
+
+/// Remove all spans from `batch` that are not sampled.
+///
+/// Returns `true` if any span was removed.
+fn apply_sampling(sampling: &StageSampling, batch: &mut codec::Batch) -> bool {
+	let before = batch.spans.len();
+	batch.spans.retain(|span| {
+		let stage = match span.tag(STAGE_TAG) {
+			Some(codec::TagValue::String(stage)) => stage.parse::<u8>().ok(),
+			_ => None,
+		};
+		match stage {
+			Some(stage) => sampling.is_sampled(stage, span.trace_id()),
+			None => true,
+		}
+	});
+	batch.spans.len() != before
+}
+
+/// Pull encoded span batches out of `traces_out` and forward them to all `exporters`.
+pub(crate) async fn run(
+	mut traces_out: mick_jaeger::TracesOut,
+	exporters: Vec<JaegerExporter>,
+	sampling: StageSampling,
+) {
+	let mut sinks = Vec::with_capacity(exporters.len());
+	for exporter in exporters {
+		sinks.extend(Sink::connect(exporter).await);
+	}
+	if sinks.is_empty() {
+		return
+	}
+
+	let needs_decoding = !sampling.is_empty() || sinks.iter().any(Sink::needs_decoding);
+
+	loop {
+		let raw = traces_out.next().await;
+
+		let (batch, raw) = if needs_decoding {
+			let mut batch = match codec::decode_batch(&raw) {
+				Ok(batch) => batch,
+				Err(e) => {
+					log::debug!(target: "jaeger", "Failed to decode span batch: {}", e);
+					continue
+				},
+			};
+			if apply_sampling(&sampling, &mut batch) {
+				if batch.spans.is_empty() {
+					continue
+				}
+				match codec::encode_batch(&batch) {
+					Ok(raw) => (Some(batch), raw),
+					Err(e) => {
+						log::debug!(target: "jaeger", "Failed to encode span batch: {}", e);
+						continue
+					},
+				}
+			} else {
+				(Some(batch), raw)
+			}
+		} else {
+			(None, raw)
+		};
+
+		for sink in sinks.iter_mut() {
+			match (sink, batch.as_ref()) {
+				(Sink::Agent { socket, addr }, _) => {
+					// UDP sending errors happen only either if the API is misused or in case of missing privilege.
+					if let Err(e) = socket.send_to(&raw, *addr).await {
+						log::debug!(target: "jaeger", "UDP send error: {}", e);
+					}
+				},
+				(Sink::Otlp(sink), Some(batch)) => sink.export(batch).await,
+				(Sink::File(sink), Some(batch)) => sink.export(batch).await,
+				(_, None) => {},
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{codec::*, *};
+	use crate::Stage;
+
+	fn span_with_stage(trace_id_low: i64, stage: Option<Stage>) -> Span {
+		Span {
+			trace_id_low,
+			tags: stage
+				.map(|stage| Tag {
+					key: STAGE_TAG.to_owned(),
+					value: TagValue::String((stage as u8).to_string()),
+				})
+				.into_iter()
+				.collect(),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn sampling_only_affects_configured_stages() {
+		let mut sampling = StageSampling::default();
+		sampling.set(Stage::CandidateBacking, 0.0);
+
+		let mut batch = Batch {
+			process: Default::default(),
+			spans: vec![
+				span_with_stage(1, Some(Stage::CandidateBacking)),
+				span_with_stage(2, Some(Stage::ApprovalChecking)),
+				span_with_stage(3, None),
+			],
+		};
+
+		assert!(apply_sampling(&sampling, &mut batch));
+		assert_eq!(batch.spans.len(), 2);
+		assert!(batch.spans.iter().all(|span| span.trace_id_low != 1));
+	}
+
+	#[test]
+	fn sampling_is_deterministic_per_trace() {
+		let mut sampling = StageSampling::default();
+		sampling.set(Stage::AvailabilityDistribution, 0.5);
+
+		let stage = Stage::AvailabilityDistribution as u8;
+		let low = u64::MAX as u128 / 4;
+		let high = u64::MAX as u128 / 4 * 3;
+		assert!(sampling.is_sampled(stage, low));
+		assert!(sampling.is_sampled(stage, low | (42 << 64)));
+		assert!(!sampling.is_sampled(stage, high));
+	}
+}
@@ -0,0 +1,288 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Export of spans to an OpenTelemetry collector.
+//!
+//! Only the subset of the `opentelemetry.proto.trace.v1` definitions needed
+//! to export spans is defined here.
+
+use super::codec::{self, TagValue};
+use crate::config::OtlpProtocol;
+
+use prost::Message;
+
+/// Path of the gRPC trace export method.
+const GRPC_EXPORT_PATH: &str = "/opentelemetry.proto.collector.trace.v1.TraceService/Export";
+
+/// Path of the HTTP trace export endpoint.
+const HTTP_EXPORT_PATH: &str = "/v1/traces";
+
+/// Name reported as the instrumentation library of all spans.
+const INSTRUMENTATION_LIBRARY: &str = "diamond-node-jaeger";
+
+#[derive(Clone, PartialEq, Message)]
+struct ExportTraceServiceRequest {
+	#[prost(message, repeated, tag = "1")]
+	resource_spans: Vec<ResourceSpans>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ResourceSpans {
+	#[prost(message, optional, tag = "1")]
+	resource: Option<Resource>,
+	#[prost(message, repeated, tag = "2")]
+	instrumentation_library_spans: Vec<InstrumentationLibrarySpans>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Resource {
+	#[prost(message, repeated, tag = "1")]
+	attributes: Vec<KeyValue>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct InstrumentationLibrarySpans {
+	#[prost(message, optional, tag = "1")]
+	instrumentation_library: Option<InstrumentationLibrary>,
+	#[prost(message, repeated, tag = "2")]
+	spans: Vec<Span>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct InstrumentationLibrary {
+	#[prost(string, tag = "1")]
+	name: String,
+	#[prost(string, tag = "2")]
+	version: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Span {
+	#[prost(bytes = "vec", tag = "1")]
+	trace_id: Vec<u8>,
+	#[prost(bytes = "vec", tag = "2")]
+	span_id: Vec<u8>,
+	#[prost(bytes = "vec", tag = "4")]
+	parent_span_id: Vec<u8>,
+	#[prost(string, tag = "5")]
+	name: String,
+	#[prost(fixed64, tag = "7")]
+	start_time_unix_nano: u64,
+	#[prost(fixed64, tag = "8")]
+	end_time_unix_nano: u64,
+	#[prost(message, repeated, tag = "9")]
+	attributes: Vec<KeyValue>,
+	#[prost(message, repeated, tag = "11")]
+	events: Vec<Event>,
+	#[prost(message, repeated, tag = "13")]
+	links: Vec<Link>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Event {
+	#[prost(fixed64, tag = "1")]
+	time_unix_nano: u64,
+	#[prost(string, tag = "2")]
+	name: String,
+	#[prost(message, repeated, tag = "3")]
+	attributes: Vec<KeyValue>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Link {
+	#[prost(bytes = "vec", tag = "1")]
+	trace_id: Vec<u8>,
+	#[prost(bytes = "vec", tag = "2")]
+	span_id: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct KeyValue {
+	#[prost(string, tag = "1")]
+	key: String,
+	#[prost(message, optional, tag = "2")]
+	value: Option<AnyValue>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct AnyValue {
+	#[prost(oneof = "any_value::Value", tags = "1, 2, 3, 4, 7")]
+	value: Option<any_value::Value>,
+}
+
+mod any_value {
+	#[derive(Clone, PartialEq, prost::Oneof)]
+	pub(super) enum Value {
+		#[prost(string, tag = "1")]
+		String(String),
+		#[prost(bool, tag = "2")]
+		Bool(bool),
+		#[prost(int64, tag = "3")]
+		Int(i64),
+		#[prost(double, tag = "4")]
+		Double(f64),
+		#[prost(bytes, tag = "7")]
+		Bytes(Vec<u8>),
+	}
+}
+
+fn key_value(tag: &codec::Tag) -> KeyValue {
+	let value = match &tag.value {
+		TagValue::String(v) => any_value::Value::String(v.clone()),
+		TagValue::Bool(v) => any_value::Value::Bool(*v),
+		TagValue::Long(v) => any_value::Value::Int(*v),
+		TagValue::Double(v) => any_value::Value::Double(*v),
+		TagValue::Binary(v) => any_value::Value::Bytes(v.clone()),
+	};
+	KeyValue { key: tag.key.clone(), value: Some(AnyValue { value: Some(value) }) }
+}
+
+fn span_id_bytes(span_id: i64) -> Vec<u8> {
+	if span_id == 0 {
+		Vec::new()
+	} else {
+		(span_id as u64).to_be_bytes().to_vec()
+	}
+}
+
+fn trace_id_bytes(high: i64, low: i64) -> Vec<u8> {
+	let mut bytes = Vec::with_capacity(16);
+	bytes.extend_from_slice(&high.to_be_bytes());
+	bytes.extend_from_slice(&low.to_be_bytes());
+	bytes
+}
+
+fn micros_to_nanos(micros: i64) -> u64 {
+	(micros.max(0) as u64).saturating_mul(1_000)
+}
+
+fn convert_span(span: &codec::Span) -> Span {
+	Span {
+		trace_id: trace_id_bytes(span.trace_id_high, span.trace_id_low),
+		span_id: span_id_bytes(span.span_id),
+		parent_span_id: span_id_bytes(span.parent_span_id),
+		name: span.operation_name.clone(),
+		start_time_unix_nano: micros_to_nanos(span.start_time),
+		end_time_unix_nano: micros_to_nanos(span.start_time.saturating_add(span.duration)),
+		attributes: span.tags.iter().map(key_value).collect(),
+		events: span
+			.logs
+			.iter()
+			.map(|log| Event {
+				time_unix_nano: micros_to_nanos(log.timestamp),
+				name: "log".to_owned(),
+				attributes: log.fields.iter().map(key_value).collect(),
+			})
+			.collect(),
+		links: span
+			.references
+			.iter()
+			.map(|reference| Link {
+				trace_id: trace_id_bytes(reference.trace_id_high, reference.trace_id_low),
+				span_id: span_id_bytes(reference.span_id),
+			})
+			.collect(),
+	}
+}
+
+fn export_request(batch: &codec::Batch) -> ExportTraceServiceRequest {
+	let service_name = codec::Tag {
+		key: "service.name".to_owned(),
+		value: TagValue::String(batch.process.service_name.clone()),
+	};
+	let resource = Resource {
+		attributes: std::iter::once(&service_name)
+			.chain(batch.process.tags.iter())
+			.map(key_value)
+			.collect(),
+	};
+
+	ExportTraceServiceRequest {
+		resource_spans: vec![ResourceSpans {
+			resource: Some(resource),
+			instrumentation_library_spans: vec![InstrumentationLibrarySpans {
+				instrumentation_library: Some(InstrumentationLibrary {
+					name: INSTRUMENTATION_LIBRARY.to_owned(),
+					version: env!("CARGO_PKG_VERSION").to_owned(),
+				}),
+				spans: batch.spans.iter().map(convert_span).collect(),
+			}],
+		}],
+	}
+}
+
+/// Sends span batches to an OpenTelemetry collector.
+pub(crate) struct OtlpSink {
+	uri: hyper::Uri,
+	protocol: OtlpProtocol,
+	client: hyper::Client<hyper::client::HttpConnector>,
+}
+
+impl OtlpSink {
+	pub(crate) fn new(endpoint: &str, protocol: OtlpProtocol) -> Result<Self, hyper::http::Error> {
+		let path = match protocol {
+			OtlpProtocol::Grpc => GRPC_EXPORT_PATH,
+			OtlpProtocol::HttpProtobuf => HTTP_EXPORT_PATH,
+		};
+		let uri = format!("{}{}", endpoint.trim_end_matches('/'), path)
+			.parse::<hyper::Uri>()
+			.map_err(hyper::http::Error::from)?;
+		let client =
+			hyper::Client::builder().http2_only(protocol == OtlpProtocol::Grpc).build_http();
+
+		Ok(Self { uri, protocol, client })
+	}
+
+	pub(crate) async fn export(&self, batch: &codec::Batch) {
+		let payload = export_request(batch).encode_to_vec();
+
+		let request = match self.protocol {
+			OtlpProtocol::Grpc => {
+				// Length prefixed message, uncompressed.
+				let mut body = Vec::with_capacity(payload.len() + 5);
+				body.push(0);
+				body.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+				body.extend_from_slice(&payload);
+
+				hyper::Request::post(self.uri.clone())
+					.header(hyper::header::CONTENT_TYPE, "application/grpc")
+					.header(hyper::header::TE, "trailers")
+					.body(hyper::Body::from(body))
+			},
+			OtlpProtocol::HttpProtobuf => hyper::Request::post(self.uri.clone())
+				.header(hyper::header::CONTENT_TYPE, "application/x-protobuf")
+				.body(hyper::Body::from(payload)),
+		};
+
+		let request = match request {
+			Ok(request) => request,
+			Err(e) => {
+				log::debug!(target: "jaeger", "Failed to build OTLP request: {}", e);
+				return
+			},
+		};
+
+		match self.client.request(request).await {
+			Ok(response) if !response.status().is_success() => {
+				log::debug!(target: "jaeger", "OTLP collector rejected spans: {}", response.status());
+			},
+			Ok(_) => {},
+			Err(e) => {
+				log::debug!(target: "jaeger", "OTLP send error: {}", e);
+			},
+		}
+	}
+}
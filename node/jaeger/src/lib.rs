@@ -48,10 +48,13 @@
 
 mod config;
 mod errors;
+#[cfg(not(target_os = "unknown"))]
+mod export;
+pub mod lifecycle;
 mod spans;
 
 pub use self::{
-	config::{JaegerConfig, JaegerConfigBuilder},
+	config::{JaegerConfig, JaegerConfigBuilder, JaegerExporter, OtlpProtocol, StageSampling},
 	errors::JaegerError,
 	spans::{PerLeafSpan, Span, Stage},
 };
@@ -86,13 +89,13 @@ impl Jaeger {
 		Jaeger::Prep(cfg)
 	}
 
-	/// Spawn the background task in order to send the tracing information out via UDP
+	/// Spawn the background task in order to send the tracing information to the configured exporters.
 	#[cfg(target_os = "unknown")]
 	pub fn launch<S: SpawnNamed>(self, _spawner: S) -> result::Result<(), JaegerError> {
 		Ok(())
 	}
 
-	/// Spawn the background task in order to send the tracing information out via UDP
+	/// Spawn the background task in order to send the tracing information to the configured exporters.
 	#[cfg(not(target_os = "unknown"))]
 	pub fn launch<S: SpawnNamed>(self, spawner: S) -> result::Result<(), JaegerError> {
 		let cfg = match self {
@@ -101,31 +104,16 @@ impl Jaeger {
 			Self::None => Err(JaegerError::MissingConfiguration),
 		}?;
 
-		let jaeger_agent = cfg.agent_addr;
+		log::info!("🐹 Collecting jaeger spans for {:?}", &cfg.exporters);
 
-		log::info!("🐹 Collecting jaeger spans for {:?}", &jaeger_agent);
-
-		let (traces_in, mut traces_out) = mick_jaeger::init(mick_jaeger::Config {
+		let (traces_in, traces_out) = mick_jaeger::init(mick_jaeger::Config {
 			service_name: format!("diamond-{}", cfg.node_name),
 		});
 
-		// Spawn a background task that pulls span information and sends them on the network.
+		// Spawn a background task that pulls span information and hands them to the exporters.
 		spawner.spawn(
 			"jaeger-collector",
-			Box::pin(async move {
-				match async_std::net::UdpSocket::bind("0.0.0.0:0").await {
-					Ok(udp_socket) => loop {
-						let buf = traces_out.next().await;
-						// UDP sending errors happen only either if the API is misused or in case of missing privilege.
-						if let Err(e) = udp_socket.send_to(&buf, jaeger_agent).await {
-							log::debug!(target: "jaeger", "UDP send error: {}", e);
-						}
-					},
-					Err(e) => {
-						log::warn!(target: "jaeger", "UDP socket open error: {}", e);
-					},
-				}
-			}),
+			Box::pin(export::run(traces_out, cfg.exporters, cfg.sampling)),
 		);
 
 		*INSTANCE.write() = Self::Launched { traces_in };
@@ -0,0 +1,295 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracking of candidates through the inclusion pipeline.
+//!
+//! Spans are keyed per leaf, so the journey of a single candidate is spread
+//! over many traces. Subsystems additionally note when a candidate reaches one
+//! of the [`LifecycleStage`]s here, which allows to look up the whole lifecycle
+//! of a candidate by its hash and to gather latency statistics per stage.
+//!
+//! ```rust
+//! # use diamond_primitives::v1::{CandidateHash, Id as ParaId};
+//! use diamond_node_jaeger::lifecycle::{self, LifecycleStage};
+//!
+//! let candidate = CandidateHash::default();
+//!
+//! lifecycle::note_stage(candidate, Some(ParaId::from(100)), LifecycleStage::Seconded);
+//! lifecycle::note_stage(candidate, None, LifecycleStage::Backed);
+//!
+//! let tracked = lifecycle::candidate_lifecycle(&candidate).unwrap();
+//! assert_eq!(tracked.stages.len(), 2);
+//! ```
+
+use diamond_primitives::v1::{CandidateHash, Id as ParaId};
+use parking_lot::Mutex;
+
+use std::{
+	collections::{HashMap, VecDeque},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The maximum number of candidates for which the lifecycle is retained.
+const MAX_TRACKED_CANDIDATES: usize = 4096;
+
+/// Upper bounds of the latency histogram buckets, in milliseconds.
+pub const LATENCY_BUCKETS_MS: [u64; 12] =
+	[50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000, 60_000, 120_000, 300_000];
+
+lazy_static::lazy_static! {
+	static ref LIFECYCLES: Mutex<Lifecycles> = Mutex::new(Lifecycles::new(MAX_TRACKED_CANDIDATES));
+}
+
+/// The stages of a candidate on its way to finality, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LifecycleStage {
+	/// A `Seconded` statement for the candidate was imported.
+	Seconded,
+	/// The candidate gathered enough validity votes of its backing group,
+	/// or was backed in a relay chain block.
+	Backed,
+	/// This node stored its chunk or the full available data of the candidate.
+	Available,
+	/// The candidate was included in a relay chain block, after becoming available.
+	Included,
+	/// The candidate was approved under a relay chain block.
+	Approved,
+	/// A relay chain block that included the candidate was finalized.
+	Finalized,
+}
+
+impl LifecycleStage {
+	/// All stages, in order.
+	pub const ALL: [LifecycleStage; 6] = [
+		LifecycleStage::Seconded,
+		LifecycleStage::Backed,
+		LifecycleStage::Available,
+		LifecycleStage::Included,
+		LifecycleStage::Approved,
+		LifecycleStage::Finalized,
+	];
+
+	/// A human readable name of the stage.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			LifecycleStage::Seconded => "seconded",
+			LifecycleStage::Backed => "backed",
+			LifecycleStage::Available => "available",
+			LifecycleStage::Included => "included",
+			LifecycleStage::Approved => "approved",
+			LifecycleStage::Finalized => "finalized",
+		}
+	}
+}
+
+/// The lifecycle of a single candidate as observed by this node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandidateLifecycle {
+	/// The para the candidate belongs to, if known.
+	pub para_id: Option<ParaId>,
+	/// The reached stages with the time they were first observed, in order.
+	pub stages: Vec<(LifecycleStage, SystemTime)>,
+}
+
+impl CandidateLifecycle {
+	/// The time the given stage was first observed.
+	pub fn reached(&self, stage: LifecycleStage) -> Option<SystemTime> {
+		self.stages.iter().find(|(s, _)| *s == stage).map(|(_, at)| *at)
+	}
+}
+
+/// A histogram of the latencies between a stage and the preceding observed stage.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+	/// Number of observations per bucket of [`LATENCY_BUCKETS_MS`],
+	/// with an additional last bucket for everything above.
+	pub buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+	/// The total number of observations.
+	pub count: u64,
+	/// The sum of all observed latencies in milliseconds.
+	pub sum_ms: u64,
+}
+
+impl LatencyHistogram {
+	fn observe(&mut self, latency: Duration) {
+		let ms = latency.as_millis() as u64;
+		let bucket = LATENCY_BUCKETS_MS
+			.iter()
+			.position(|bound| ms <= *bound)
+			.unwrap_or(LATENCY_BUCKETS_MS.len());
+		self.buckets[bucket] += 1;
+		self.count += 1;
+		self.sum_ms = self.sum_ms.saturating_add(ms);
+	}
+}
+
+struct Lifecycles {
+	capacity: usize,
+	candidates: HashMap<CandidateHash, CandidateLifecycle>,
+	/// Candidates in order of first observation, used for eviction.
+	order: VecDeque<CandidateHash>,
+	latencies: HashMap<LifecycleStage, LatencyHistogram>,
+}
+
+impl Lifecycles {
+	fn new(capacity: usize) -> Self {
+		Self {
+			capacity,
+			candidates: HashMap::new(),
+			order: VecDeque::new(),
+			latencies: HashMap::new(),
+		}
+	}
+
+	fn note(
+		&mut self,
+		candidate_hash: CandidateHash,
+		para_id: Option<ParaId>,
+		stage: LifecycleStage,
+		now: SystemTime,
+	) {
+		if !self.candidates.contains_key(&candidate_hash) {
+			if self.order.len() >= self.capacity {
+				if let Some(evicted) = self.order.pop_front() {
+					self.candidates.remove(&evicted);
+				}
+			}
+			self.order.push_back(candidate_hash);
+		}
+
+		let lifecycle = self
+			.candidates
+			.entry(candidate_hash)
+			.or_insert_with(|| CandidateLifecycle { para_id: None, stages: Vec::new() });
+		if lifecycle.para_id.is_none() {
+			lifecycle.para_id = para_id;
+		}
+
+		let pos = match lifecycle.stages.binary_search_by_key(&stage, |(s, _)| *s) {
+			// Only the first observation of a stage is of interest.
+			Ok(_) => return,
+			Err(pos) => pos,
+		};
+
+		// Stages are not necessarily observed in order, i.e. a candidate
+		// might be included in a block before we see it backed ourselves.
+		if let Some((_, previous)) = pos.checked_sub(1).and_then(|p| lifecycle.stages.get(p)) {
+			let latency = now.duration_since(*previous).unwrap_or_default();
+			self.latencies.entry(stage).or_default().observe(latency);
+		}
+
+		lifecycle.stages.insert(pos, (stage, now));
+	}
+}
+
+/// Note that the candidate reached the given stage.
+///
+/// Only the first observation of each stage of a candidate is recorded.
+/// The para can be omitted at stages where it is not at hand.
+pub fn note_stage(candidate_hash: CandidateHash, para_id: Option<ParaId>, stage: LifecycleStage) {
+	LIFECYCLES.lock().note(candidate_hash, para_id, stage, SystemTime::now());
+}
+
+/// Look up the lifecycle of a candidate.
+///
+/// Returns `None` if the candidate was never observed or was evicted already.
+pub fn candidate_lifecycle(candidate_hash: &CandidateHash) -> Option<CandidateLifecycle> {
+	LIFECYCLES.lock().candidates.get(candidate_hash).cloned()
+}
+
+/// The latency histograms of all stages that were observed at least once.
+///
+/// The latency of a stage is measured from the preceding stage that was
+/// observed for the same candidate.
+pub fn stage_latencies() -> Vec<(LifecycleStage, LatencyHistogram)> {
+	let lifecycles = LIFECYCLES.lock();
+	LifecycleStage::ALL
+		.iter()
+		.filter_map(|stage| lifecycles.latencies.get(stage).map(|h| (*stage, h.clone())))
+		.collect()
+}
+
+/// Convert a point in time to milliseconds since the unix epoch.
+pub fn unix_millis(at: SystemTime) -> u64 {
+	at.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn candidate(n: u8) -> CandidateHash {
+		CandidateHash([n; 32].into())
+	}
+
+	#[test]
+	fn records_first_observation_and_latency() {
+		let mut lifecycles = Lifecycles::new(16);
+		let start = UNIX_EPOCH + Duration::from_secs(1_000);
+		let para = Some(ParaId::from(100));
+
+		lifecycles.note(candidate(1), para, LifecycleStage::Seconded, start);
+		lifecycles.note(
+			candidate(1),
+			para,
+			LifecycleStage::Backed,
+			start + Duration::from_millis(300),
+		);
+		lifecycles.note(candidate(1), para, LifecycleStage::Backed, start + Duration::from_secs(5));
+
+		let lifecycle = &lifecycles.candidates[&candidate(1)];
+		assert_eq!(lifecycle.para_id, para);
+		assert_eq!(
+			lifecycle.reached(LifecycleStage::Backed),
+			Some(start + Duration::from_millis(300)),
+		);
+
+		let backed = &lifecycles.latencies[&LifecycleStage::Backed];
+		assert_eq!(backed.count, 1);
+		assert_eq!(backed.sum_ms, 300);
+		assert_eq!(backed.buckets[3], 1);
+		assert!(!lifecycles.latencies.contains_key(&LifecycleStage::Seconded));
+	}
+
+	#[test]
+	fn out_of_order_stages_are_sorted() {
+		let mut lifecycles = Lifecycles::new(16);
+		let start = UNIX_EPOCH + Duration::from_secs(1_000);
+		let para = Some(ParaId::from(100));
+
+		lifecycles.note(candidate(1), para, LifecycleStage::Included, start);
+		lifecycles.note(candidate(1), para, LifecycleStage::Seconded, start);
+
+		let stages: Vec<_> =
+			lifecycles.candidates[&candidate(1)].stages.iter().map(|(s, _)| *s).collect();
+		assert_eq!(stages, vec![LifecycleStage::Seconded, LifecycleStage::Included]);
+	}
+
+	#[test]
+	fn evicts_oldest_candidates() {
+		let mut lifecycles = Lifecycles::new(2);
+		let now = UNIX_EPOCH;
+		let para = Some(ParaId::from(100));
+
+		for n in 0..3 {
+			lifecycles.note(candidate(n), para, LifecycleStage::Seconded, now);
+		}
+
+		assert!(!lifecycles.candidates.contains_key(&candidate(0)));
+		assert!(lifecycles.candidates.contains_key(&candidate(1)));
+		assert!(lifecycles.candidates.contains_key(&candidate(2)));
+	}
+}
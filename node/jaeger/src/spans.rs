@@ -90,9 +90,9 @@ use diamond_primitives::v1::{
 };
 use sc_network::PeerId;
 
-use std::{fmt, sync::Arc};
+use std::{fmt, str::FromStr, sync::Arc};
 
-use super::INSTANCE;
+use super::{JaegerError, INSTANCE};
 
 /// A special "per leaf span".
 ///
@@ -158,6 +158,23 @@ pub enum Stage {
 	// see [issue](https://github.com/paritytech/diamond/issues/2389)
 }
 
+impl FromStr for Stage {
+	type Err = JaegerError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s {
+			"candidate-backing" => Self::CandidateBacking,
+			"statement-distribution" => Self::StatementDistribution,
+			"pov-distribution" => Self::PoVDistribution,
+			"availability-distribution" => Self::AvailabilityDistribution,
+			"availability-recovery" => Self::AvailabilityRecovery,
+			"bitfield-distribution" => Self::BitfieldDistribution,
+			"approval-checking" => Self::ApprovalChecking,
+			other => return Err(JaegerError::UnknownStage(other.to_owned())),
+		})
+	}
+}
+
 /// A wrapper type for a span.
 ///
 /// Handles running with and without jaeger.
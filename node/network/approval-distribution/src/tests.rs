@@ -17,7 +17,7 @@
 use super::*;
 use assert_matches::assert_matches;
 use futures::{executor, future, Future};
-use diamond_node_network_protocol::{view, ObservedRole};
+use diamond_node_network_protocol::{peer_set::VALIDATION_PROTOCOL_V2, view, ObservedRole};
 use diamond_node_primitives::approval::{
 	AssignmentCertKind, VRFOutput, VRFProof, RELAY_VRF_MODULO_CONTEXT,
 };
@@ -105,6 +105,7 @@ async fn setup_peer_with_view(
 		ApprovalDistributionMessage::NetworkBridgeUpdateV1(NetworkBridgeEvent::PeerConnected(
 			peer_id.clone(),
 			ObservedRole::Full,
+			VALIDATION_PROTOCOL_V2,
 			None,
 		)),
 	)
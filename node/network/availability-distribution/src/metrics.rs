@@ -22,6 +22,8 @@ use diamond_node_subsystem_util::{
 	},
 };
 
+use crate::requester::{PeerResponse, PeerScore};
+
 /// Label for success counters.
 pub const SUCCEEDED: &'static str = "succeeded";
 
@@ -59,6 +61,20 @@ struct MetricsInner {
 	/// Number of times our first set of validators did not provide the needed chunk and we had to
 	/// query further validators.
 	retries: Counter<U64>,
+
+	/// Number of requests sent to a further validator, because the first one was slow to respond.
+	speculative_requests: Counter<U64>,
+
+	/// Number of chunk requests sent to validators, by outcome.
+	peer_requests: CounterVec<U64>,
+
+	/// Smoothed latency of the validators which served us a chunk, as scored after each of their
+	/// responses.
+	peer_latency: prometheus::Histogram,
+
+	/// Smoothed failure rate of the validators we requested chunks from, as scored after each of
+	/// their responses.
+	peer_failure_rate: prometheus::Histogram,
 }
 
 impl Metrics {
@@ -94,6 +110,27 @@ impl Metrics {
 			metrics.retries.inc()
 		}
 	}
+
+	/// Increment speculative request counter.
+	pub fn on_speculative_request(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.speculative_requests.inc()
+		}
+	}
+
+	/// Record the outcome of a single chunk request and the resulting score of the validator.
+	///
+	/// Validators are not used as labels, as their number is unbounded over time.
+	pub(crate) fn on_peer_response(&self, response: &PeerResponse, score: &PeerScore) {
+		if let Some(metrics) = &self.0 {
+			let label = if response.success { SUCCEEDED } else { FAILED };
+			metrics.peer_requests.with_label_values(&[label]).inc();
+			if let Some(latency) = score.latency {
+				metrics.peer_latency.observe(latency.as_secs_f64());
+			}
+			metrics.peer_failure_rate.observe(score.failure_rate);
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -136,6 +173,40 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			speculative_requests: prometheus::register(
+				Counter::new(
+					"parachain_fetch_speculative_requests_total",
+					"Number of times a backer was slow and we asked another one in parallel.",
+				)?,
+				registry,
+			)?,
+			peer_requests: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"parachain_fetch_peer_requests_total",
+						"Number of chunk requests sent to validators, by outcome.",
+					),
+					&["success"]
+				)?,
+				registry,
+			)?,
+			peer_latency: prometheus::register(
+				prometheus::Histogram::with_opts(prometheus::HistogramOpts::new(
+					"parachain_fetch_peer_latency_seconds",
+					"Smoothed latency of validators serving chunks, after each response.",
+				))?,
+				registry,
+			)?,
+			peer_failure_rate: prometheus::register(
+				prometheus::Histogram::with_opts(
+					prometheus::HistogramOpts::new(
+						"parachain_fetch_peer_failure_rate",
+						"Smoothed failure rate of validators asked for chunks, after each response.",
+					)
+					.buckets(vec![0.01, 0.05, 0.1, 0.25, 0.5, 0.75, 1.0]),
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
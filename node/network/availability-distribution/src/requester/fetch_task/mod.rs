@@ -14,13 +14,18 @@
 // You should have received a copy of the GNU General Public License
 // along with diamond.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::HashSet;
+use std::{
+	collections::HashSet,
+	time::{Duration, Instant},
+};
 
 use futures::{
 	channel::{mpsc, oneshot},
-	future::select,
-	FutureExt, SinkExt,
+	future::{select, BoxFuture, Fuse},
+	stream::FuturesUnordered,
+	FutureExt, SinkExt, StreamExt,
 };
+use futures_timer::Delay;
 
 use diamond_erasure_coding::branch_hash;
 use diamond_node_network_protocol::request_response::{
@@ -41,13 +46,22 @@ use diamond_subsystem::{
 use crate::{
 	error::{Fatal, Result},
 	metrics::{Metrics, FAILED, SUCCEEDED},
-	requester::session_cache::{BadValidators, SessionInfo},
+	requester::{
+		peer_stats::{PeerResponse, PeerStats},
+		session_cache::{BadValidators, SessionInfo},
+	},
 	LOG_TARGET,
 };
 
 #[cfg(test)]
 mod tests;
 
+/// How many requests for the same chunk we have in flight at most.
+///
+/// If a validator does not respond in time, we speculatively ask the next one, while still
+/// waiting for the first response.
+const MAX_PARALLEL_REQUESTS: usize = 2;
+
 /// Configuration for a `FetchTask`
 ///
 /// This exists to separate preparation of a `FetchTask` from actual starting it, which is
@@ -94,6 +108,9 @@ pub enum FromFetchTask {
 
 	/// We were not able to fetch the desired chunk for the given `CandidateHash`.
 	Failed(CandidateHash),
+
+	/// Outcome of the individual requests the task sent, for updating `PeerStats`.
+	PeerResponses(Vec<PeerResponse>),
 }
 
 /// Information a running task needs.
@@ -111,6 +128,9 @@ struct RunningTask {
 	/// This vector gets drained during execution of the task (it will be empty afterwards).
 	group: Vec<AuthorityDiscoveryId>,
 
+	/// How long to wait for a response, before speculatively asking the next validator.
+	speculative_delay: Duration,
+
 	/// The request to send.
 	request: ChunkFetchingRequest,
 
@@ -140,6 +160,7 @@ impl FetchTaskConfig {
 		sender: mpsc::Sender<FromFetchTask>,
 		metrics: Metrics,
 		session_info: &SessionInfo,
+		peer_stats: &PeerStats,
 	) -> Self {
 		let live_in = vec![leaf].into_iter().collect();
 
@@ -151,12 +172,18 @@ impl FetchTaskConfig {
 		let span = jaeger::Span::new(core.candidate_hash, "availability-distribution")
 			.with_stage(jaeger::Stage::AvailabilityDistribution);
 
+		let mut group = session_info.validator_groups.get(core.group_responsible.0 as usize)
+			.expect("The responsible group of a candidate should be available in the corresponding session. qed.")
+			.clone();
+		// Try fast and reliable validators first:
+		peer_stats.order_group(&mut group);
+		let speculative_delay = peer_stats.speculative_delay(group.last());
+
 		let prepared_running = RunningTask {
 			session_index: session_info.session_index,
 			group_index: core.group_responsible,
-			group: session_info.validator_groups.get(core.group_responsible.0 as usize)
-				.expect("The responsible group of a candidate should be available in the corresponding session. qed.")
-				.clone(),
+			group,
+			speculative_delay,
 			request: ChunkFetchingRequest {
 				candidate_hash: core.candidate_hash,
 				index: session_info.our_index,
@@ -246,9 +273,11 @@ impl RunningTask {
 
 	/// Fetch and store chunk.
 	///
-	/// Try validators in backing group in order.
+	/// Try validators in backing group in order. If a validator is slow to respond, the next one
+	/// gets asked in parallel and whichever delivers a valid chunk first wins.
 	async fn run_inner(mut self) {
 		let mut bad_validators = Vec::new();
+		let mut responses = Vec::new();
 		let mut succeeded = false;
 		let mut count: u32 = 0;
 		let mut _span = self
@@ -256,57 +285,83 @@ impl RunningTask {
 			.child("fetch-task")
 			.with_chunk_index(self.request.index.0)
 			.with_relay_parent(self.relay_parent);
-		// Try validators in reverse order:
-		while let Some(validator) = self.group.pop() {
-			let _try_span = _span.child("try");
-			// Report retries:
-			if count > 0 {
-				self.metrics.on_retry();
+		let mut pending = FuturesUnordered::new();
+		loop {
+			if pending.is_empty() {
+				// Try validators in reverse order:
+				let validator = match self.group.pop() {
+					Some(validator) => validator,
+					None => break,
+				};
+				if self.launch_request(validator, &_span, &mut count, &mut pending).await.is_err() {
+					return
+				}
 			}
-			count += 1;
 
-			// Send request:
-			let resp = match self.do_request(&validator).await {
-				Ok(resp) => resp,
-				Err(TaskError::ShuttingDown) => {
-					tracing::info!(
-						target: LOG_TARGET,
-						"Node seems to be shutting down, canceling fetch task"
-					);
-					self.metrics.on_fetch(FAILED);
-					return
-				},
-				Err(TaskError::PeerError) => {
-					bad_validators.push(validator);
-					continue
-				},
+			let mut speculative = self.speculative_timer(pending.len());
+			let (validator, latency, resp) = loop {
+				let next = futures::select! {
+					response = pending.select_next_some() => Ok(response),
+					_ = speculative => Err(()),
+				};
+				match next {
+					Ok(response) => break response,
+					Err(()) =>
+						if let Some(validator) = self.group.pop() {
+							tracing::debug!(
+								target: LOG_TARGET,
+								validator = ?validator,
+								"Chunk request is slow, asking next validator in parallel"
+							);
+							self.metrics.on_speculative_request();
+							let launched = self
+								.launch_request(validator, &_span, &mut count, &mut pending)
+								.await;
+							if launched.is_err() {
+								return
+							}
+						},
+				}
+				speculative = self.speculative_timer(pending.len());
 			};
+
 			let chunk = match resp {
-				ChunkFetchingResponse::Chunk(resp) => resp.recombine_into_chunk(&self.request),
-				ChunkFetchingResponse::NoSuchChunk => {
+				Ok(ChunkFetchingResponse::Chunk(resp)) =>
+					Some(resp.recombine_into_chunk(&self.request)),
+				Ok(ChunkFetchingResponse::NoSuchChunk) => {
 					tracing::debug!(
 						target: LOG_TARGET,
 						validator = ?validator,
 						"Validator did not have our chunk"
 					);
-					bad_validators.push(validator);
-					continue
+					None
 				},
+				Err(TaskError::PeerError) | Err(TaskError::ShuttingDown) => None,
 			};
 
 			// Data genuine?
-			if !self.validate_chunk(&validator, &chunk) {
-				bad_validators.push(validator);
-				continue
-			}
+			let chunk = match chunk {
+				Some(chunk) if self.validate_chunk(&validator, &chunk) => chunk,
+				_ => {
+					responses.push(PeerResponse {
+						validator: validator.clone(),
+						latency,
+						success: false,
+					});
+					bad_validators.push(validator);
+					continue
+				},
+			};
 
 			// Ok, let's store it and be happy:
+			responses.push(PeerResponse { validator, latency, success: true });
 			self.store_chunk(chunk).await;
 			succeeded = true;
 			_span.add_string_tag("success", "true");
 			break
 		}
 		_span.add_int_tag("tries", count as _);
+		self.report_responses(responses).await;
 		if succeeded {
 			self.metrics.on_fetch(SUCCEEDED);
 			self.conclude(bad_validators).await;
@@ -316,11 +371,62 @@ impl RunningTask {
 		}
 	}
 
-	/// Do request and return response, if successful.
+	/// Timer for launching a speculative request.
+	///
+	/// Never fires, if there is no validator left to ask or we are already waiting for enough
+	/// responses.
+	fn speculative_timer(&self, in_flight: usize) -> Fuse<Delay> {
+		if self.group.is_empty() || in_flight >= MAX_PARALLEL_REQUESTS {
+			Fuse::terminated()
+		} else {
+			Delay::new(self.speculative_delay).fuse()
+		}
+	}
+
+	/// Send a request to the given validator and add the future for its response to `pending`.
+	///
+	/// Fails only if we are shutting down, in which case the task is done.
+	async fn launch_request(
+		&mut self,
+		validator: AuthorityDiscoveryId,
+		span: &jaeger::Span,
+		count: &mut u32,
+		pending: &mut FuturesUnordered<BoxFuture<'static, RequestOutcome>>,
+	) -> std::result::Result<(), TaskError> {
+		// Report retries:
+		if *count > 0 {
+			self.metrics.on_retry();
+		}
+		*count += 1;
+
+		let try_span = span.child("try");
+		match self.do_request(validator).await {
+			Ok(response) => {
+				pending.push(
+					async move {
+						let _try_span = try_span;
+						response.await
+					}
+					.boxed(),
+				);
+				Ok(())
+			},
+			Err(err) => {
+				tracing::info!(
+					target: LOG_TARGET,
+					"Node seems to be shutting down, canceling fetch task"
+				);
+				self.metrics.on_fetch(FAILED);
+				Err(err)
+			},
+		}
+	}
+
+	/// Send request and return a future resolving to the response.
 	async fn do_request(
 		&mut self,
-		validator: &AuthorityDiscoveryId,
-	) -> std::result::Result<ChunkFetchingResponse, TaskError> {
+		validator: AuthorityDiscoveryId,
+	) -> std::result::Result<BoxFuture<'static, RequestOutcome>, TaskError> {
 		let (full_request, response_recv) =
 			OutgoingRequest::new(Recipient::Authority(validator.clone()), self.request);
 		let requests = Requests::ChunkFetching(full_request);
@@ -332,33 +438,12 @@ impl RunningTask {
 			.await
 			.map_err(|_| TaskError::ShuttingDown)?;
 
-		match response_recv.await {
-			Ok(resp) => Ok(resp),
-			Err(RequestError::InvalidResponse(err)) => {
-				tracing::warn!(
-					target: LOG_TARGET,
-					origin= ?validator,
-					err= ?err,
-					"Peer sent us invalid erasure chunk data"
-				);
-				Err(TaskError::PeerError)
-			},
-			Err(RequestError::NetworkError(err)) => {
-				tracing::warn!(
-					target: LOG_TARGET,
-					origin= ?validator,
-					err= ?err,
-					"Some network error occurred when fetching erasure chunk"
-				);
-				Err(TaskError::PeerError)
-			},
-			Err(RequestError::Canceled(oneshot::Canceled)) => {
-				tracing::warn!(target: LOG_TARGET,
-							   origin= ?validator,
-							   "Erasure chunk request got canceled");
-				Err(TaskError::PeerError)
-			},
+		let started = Instant::now();
+		Ok(async move {
+			let resp = check_response(&validator, response_recv.await);
+			(validator, started.elapsed(), resp)
 		}
+		.boxed())
 	}
 
 	fn validate_chunk(&self, validator: &AuthorityDiscoveryId, chunk: &ErasureChunk) -> bool {
@@ -432,4 +517,52 @@ impl RunningTask {
 			tracing::warn!(target: LOG_TARGET, ?err, "Sending `Failed` message for task failed");
 		}
 	}
+
+	/// Report outcome of our requests, so future tasks can prefer well performing validators.
+	async fn report_responses(&mut self, responses: Vec<PeerResponse>) {
+		if responses.is_empty() {
+			return
+		}
+		if let Err(err) = self.sender.send(FromFetchTask::PeerResponses(responses)).await {
+			tracing::warn!(target: LOG_TARGET, ?err, "Sending peer responses failed");
+		}
+	}
+}
+
+/// Response to a request, together with the validator it came from and how long it took.
+type RequestOutcome =
+	(AuthorityDiscoveryId, Duration, std::result::Result<ChunkFetchingResponse, TaskError>);
+
+/// Turn request errors into a `TaskError::PeerError`, logging them appropriately.
+fn check_response(
+	validator: &AuthorityDiscoveryId,
+	resp: std::result::Result<ChunkFetchingResponse, RequestError>,
+) -> std::result::Result<ChunkFetchingResponse, TaskError> {
+	match resp {
+		Ok(resp) => Ok(resp),
+		Err(RequestError::InvalidResponse(err)) => {
+			tracing::warn!(
+				target: LOG_TARGET,
+				origin= ?validator,
+				err= ?err,
+				"Peer sent us invalid erasure chunk data"
+			);
+			Err(TaskError::PeerError)
+		},
+		Err(RequestError::NetworkError(err)) => {
+			tracing::warn!(
+				target: LOG_TARGET,
+				origin= ?validator,
+				err= ?err,
+				"Some network error occurred when fetching erasure chunk"
+			);
+			Err(TaskError::PeerError)
+		},
+		Err(RequestError::Canceled(oneshot::Canceled)) => {
+			tracing::warn!(target: LOG_TARGET,
+						   origin= ?validator,
+						   "Erasure chunk request got canceled");
+			Err(TaskError::PeerError)
+		},
+	}
 }
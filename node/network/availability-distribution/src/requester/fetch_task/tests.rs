@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with diamond.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, convert::TryFrom};
+use std::{collections::HashMap, convert::TryFrom, time::Duration};
 
 use parity_scale_codec::Encode;
 
@@ -185,6 +185,79 @@ fn task_stores_valid_chunk_if_there_is_one() {
 	test.run(task, rx);
 }
 
+/// A slow validator should not stall the task, the next one gets asked in parallel.
+#[test]
+fn task_asks_next_validator_if_first_is_slow() {
+	sp_tracing::try_init_simple();
+	let (mut task, rx) = get_test_running_task();
+	let pov = PoV { block_data: BlockData(vec![45, 46, 47]) };
+	let (root_hash, chunk) = get_valid_chunk_data(pov);
+	task.erasure_root = root_hash;
+	task.request.index = chunk.index;
+	task.speculative_delay = Duration::from_millis(10);
+
+	// Alice gets asked first, but never answers:
+	let alice: AuthorityDiscoveryId = Sr25519Keyring::Alice.public().into();
+	let bob: AuthorityDiscoveryId = Sr25519Keyring::Bob.public().into();
+	task.group = vec![bob.clone(), alice.clone()];
+
+	let mut rx = rx.fuse();
+	let task = task.run_inner().fuse();
+	futures::pin_mut!(task);
+	executor::block_on(async {
+		let mut unanswered = Vec::new();
+		let mut stored = false;
+		let mut responses = None;
+		loop {
+			let msg = select!(
+				from_task = rx.next() => match from_task {
+					Some(msg) => msg,
+					None => break,
+				},
+				() = task => break,
+			);
+			match msg {
+				FromFetchTask::Message(AllMessages::NetworkBridge(
+					NetworkBridgeMessage::SendRequests(reqs, IfDisconnected::TryConnect),
+				)) =>
+					for req in reqs {
+						let req = match req {
+							Requests::ChunkFetching(req) => req,
+							_ => panic!("Unexpected request"),
+						};
+						if req.peer == Recipient::Authority(alice.clone()) {
+							unanswered.push(req);
+							continue
+						}
+						let response = ChunkFetchingResponse::Chunk(v1::ChunkResponse {
+							chunk: chunk.chunk.clone(),
+							proof: chunk.proof.clone(),
+						});
+						req.pending_response
+							.send(Ok(response.encode()))
+							.expect("Sending response should succeed");
+					},
+				FromFetchTask::Message(AllMessages::AvailabilityStore(
+					AvailabilityStoreMessage::StoreChunk { tx, .. },
+				)) => {
+					stored = true;
+					tx.send(Ok(())).expect("Answering fetching task should work");
+				},
+				FromFetchTask::Message(_) => panic!("Unexpected message"),
+				FromFetchTask::PeerResponses(r) => responses = Some(r),
+				FromFetchTask::Concluded(_) => break,
+				FromFetchTask::Failed(_) => panic!("Fetching should have succeeded"),
+			}
+		}
+		assert_eq!(unanswered.len(), 1);
+		assert!(stored);
+		let responses = responses.expect("Responses get reported before concluding");
+		assert_eq!(responses.len(), 1);
+		assert_eq!(responses[0].validator, bob);
+		assert!(responses[0].success);
+	});
+}
+
 struct TestRun {
 	/// Response to deliver for a given validator index.
 	/// None means, answer with `NetworkError`.
@@ -215,6 +288,7 @@ impl TestRun {
 				match msg {
 					FromFetchTask::Concluded(_) => break,
 					FromFetchTask::Failed(_) => break,
+					FromFetchTask::PeerResponses(_) => {},
 					FromFetchTask::Message(msg) => end_ok = self.handle_message(msg).await,
 				}
 			}
@@ -278,6 +352,7 @@ fn get_test_running_task() -> (RunningTask, mpsc::Receiver<FromFetchTask>) {
 			session_index: 0,
 			group_index: GroupIndex(0),
 			group: Vec::new(),
+			speculative_delay: Duration::from_secs(2),
 			request: ChunkFetchingRequest {
 				candidate_hash: CandidateHash([43u8; 32].into()),
 				index: ValidatorIndex(0),
@@ -44,6 +44,11 @@ use super::{Metrics, LOG_TARGET};
 mod session_cache;
 use session_cache::SessionCache;
 
+/// Statistics about how well validators serve our chunk requests.
+mod peer_stats;
+use peer_stats::PeerStats;
+pub use peer_stats::{PeerResponse, PeerScore};
+
 /// A task fetching a particular chunk.
 mod fetch_task;
 use fetch_task::{FetchTask, FetchTaskConfig, FromFetchTask};
@@ -64,6 +69,11 @@ pub struct Requester {
 	/// Localized information about sessions we are currently interested in.
 	session_cache: SessionCache,
 
+	/// Latency and failure statistics of validators we requested chunks from.
+	///
+	/// Kept across sessions, so we can prefer fast and reliable validators right away.
+	peer_stats: PeerStats,
+
 	/// Sender to be cloned for `FetchTask`s.
 	tx: mpsc::Sender<FromFetchTask>,
 
@@ -81,7 +91,14 @@ impl Requester {
 	/// by advancing the stream.
 	pub fn new(metrics: Metrics) -> Self {
 		let (tx, rx) = mpsc::channel(1);
-		Requester { fetches: HashMap::new(), session_cache: SessionCache::new(), tx, rx, metrics }
+		Requester {
+			fetches: HashMap::new(),
+			session_cache: SessionCache::new(),
+			peer_stats: PeerStats::new(),
+			tx,
+			rx,
+			metrics,
+		}
 	}
 	/// Update heads that need availability distribution.
 	///
@@ -163,6 +180,7 @@ impl Requester {
 				Entry::Vacant(e) => {
 					let tx = self.tx.clone();
 					let metrics = self.metrics.clone();
+					let peer_stats = &self.peer_stats;
 
 					let task_cfg = self
 						.session_cache
@@ -173,7 +191,7 @@ impl Requester {
 							// leaf. (Cores are dropped at session boundaries.) At the same time,
 							// only leaves are guaranteed to be fetchable by the state trie.
 							leaf,
-							|info| FetchTaskConfig::new(leaf, &core, tx, metrics, info),
+							|info| FetchTaskConfig::new(leaf, &core, tx, metrics, info, peer_stats),
 						)
 						.await?;
 
@@ -200,6 +218,13 @@ impl Stream for Requester {
 					continue
 				},
 				Poll::Ready(Some(FromFetchTask::Concluded(None))) => continue,
+				Poll::Ready(Some(FromFetchTask::PeerResponses(responses))) => {
+					for response in responses {
+						let score = self.peer_stats.note_response(&response);
+						self.metrics.on_peer_response(&response, &score);
+					}
+					continue
+				},
 				Poll::Ready(Some(FromFetchTask::Failed(candidate_hash))) => {
 					// Make sure we retry on next block still pending availability.
 					self.fetches.remove(&candidate_hash);
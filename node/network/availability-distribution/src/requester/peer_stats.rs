@@ -0,0 +1,215 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracking of how well individual validators serve our chunk requests.
+//!
+//! Statistics are keyed by `AuthorityDiscoveryId`, so they survive session changes for validators
+//! that keep their keys.
+
+use std::time::Duration;
+
+use lru::LruCache;
+
+use diamond_primitives::v1::AuthorityDiscoveryId;
+
+/// How many validators we keep statistics for.
+const PEER_STATS_CAPACITY: usize = 2048;
+
+/// Weight of a new observation in the moving averages.
+const SMOOTHING_FACTOR: f64 = 0.2;
+
+/// Latency we assume for validators we have not heard from yet.
+const DEFAULT_LATENCY: Duration = Duration::from_millis(500);
+
+/// How much a failure rate of 1.0 inflates the expected latency of a validator.
+const FAILURE_PENALTY: f64 = 4.0;
+
+/// Speculative requests are never sent earlier than this ...
+const MIN_SPECULATIVE_DELAY: Duration = Duration::from_millis(200);
+
+/// ... and never later than this.
+const MAX_SPECULATIVE_DELAY: Duration = Duration::from_secs(2);
+
+/// Outcome of a single chunk request, as reported by a fetch task.
+#[derive(Debug, Clone)]
+pub struct PeerResponse {
+	/// The validator we sent the request to.
+	pub validator: AuthorityDiscoveryId,
+	/// Time between sending the request and receiving the response.
+	pub latency: Duration,
+	/// Whether or not the validator served us a valid chunk.
+	pub success: bool,
+}
+
+/// Smoothed performance of a single validator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerScore {
+	/// Moving average of the latency of successful requests.
+	///
+	/// `None` if the validator never served us a chunk.
+	pub latency: Option<Duration>,
+	/// Moving average of the failure rate, between 0.0 and 1.0.
+	pub failure_rate: f64,
+}
+
+impl Default for PeerScore {
+	fn default() -> Self {
+		PeerScore { latency: None, failure_rate: 0.0 }
+	}
+}
+
+impl PeerScore {
+	/// Latency we expect when requesting a chunk from this validator, failures included.
+	///
+	/// Lower is better.
+	pub fn expected_latency(&self) -> Duration {
+		self.latency
+			.unwrap_or(DEFAULT_LATENCY)
+			.mul_f64(1.0 + FAILURE_PENALTY * self.failure_rate)
+	}
+
+	fn note(&mut self, response: &PeerResponse) {
+		let failure = if response.success { 0.0 } else { 1.0 };
+		self.failure_rate += SMOOTHING_FACTOR * (failure - self.failure_rate);
+		if response.success {
+			self.latency = Some(match self.latency {
+				None => response.latency,
+				Some(latency) => latency
+					.mul_f64(1.0 - SMOOTHING_FACTOR)
+					.saturating_add(response.latency.mul_f64(SMOOTHING_FACTOR)),
+			});
+		}
+	}
+}
+
+/// Per validator statistics about chunk fetching.
+pub struct PeerStats {
+	peers: LruCache<AuthorityDiscoveryId, PeerScore>,
+}
+
+impl PeerStats {
+	/// Create a new, empty `PeerStats`.
+	pub fn new() -> Self {
+		PeerStats { peers: LruCache::new(PEER_STATS_CAPACITY) }
+	}
+
+	/// Record the outcome of a request and return the updated score of the validator.
+	pub fn note_response(&mut self, response: &PeerResponse) -> PeerScore {
+		if !self.peers.contains(&response.validator) {
+			self.peers.put(response.validator.clone(), PeerScore::default());
+		}
+		let score = self
+			.peers
+			.get_mut(&response.validator)
+			.expect("Entry has been inserted above. qed.");
+		score.note(response);
+		score.clone()
+	}
+
+	/// Current score of the given validator.
+	pub fn score(&self, validator: &AuthorityDiscoveryId) -> PeerScore {
+		self.peers.peek(validator).cloned().unwrap_or_default()
+	}
+
+	/// Order a group, so that the most promising validators get tried first.
+	///
+	/// Fetch tasks try validators in reverse order, so the best ones end up at the back. The sort
+	/// is stable, so validators we know nothing about keep their (randomized) relative order.
+	pub fn order_group(&self, group: &mut [AuthorityDiscoveryId]) {
+		group.sort_by_cached_key(|v| std::cmp::Reverse(self.score(v).expected_latency()));
+	}
+
+	/// How long to wait for a response from the given validator, before also asking the next one.
+	pub fn speculative_delay(&self, validator: Option<&AuthorityDiscoveryId>) -> Duration {
+		let expected =
+			validator.map(|v| self.score(v).expected_latency()).unwrap_or(DEFAULT_LATENCY);
+		(expected * 2).clamp(MIN_SPECULATIVE_DELAY, MAX_SPECULATIVE_DELAY)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_keyring::Sr25519Keyring;
+
+	fn response(keyring: Sr25519Keyring, latency_ms: u64, success: bool) -> PeerResponse {
+		PeerResponse {
+			validator: keyring.public().into(),
+			latency: Duration::from_millis(latency_ms),
+			success,
+		}
+	}
+
+	#[test]
+	fn fast_and_reliable_validators_are_tried_first() {
+		let mut stats = PeerStats::new();
+		stats.note_response(&response(Sr25519Keyring::Alice, 800, true));
+		stats.note_response(&response(Sr25519Keyring::Bob, 50, true));
+		stats.note_response(&response(Sr25519Keyring::Charlie, 20, false));
+		stats.note_response(&response(Sr25519Keyring::Charlie, 20, false));
+
+		let mut group: Vec<AuthorityDiscoveryId> = [
+			Sr25519Keyring::Bob,
+			Sr25519Keyring::Dave,
+			Sr25519Keyring::Alice,
+			Sr25519Keyring::Charlie,
+		]
+		.iter()
+		.map(|k| k.public().into())
+		.collect();
+		stats.order_group(&mut group);
+
+		// Tried in reverse order: Bob, Dave (unknown), Alice (slow), Charlie (failing).
+		let expected: Vec<AuthorityDiscoveryId> = [
+			Sr25519Keyring::Charlie,
+			Sr25519Keyring::Alice,
+			Sr25519Keyring::Dave,
+			Sr25519Keyring::Bob,
+		]
+		.iter()
+		.map(|k| k.public().into())
+		.collect();
+		assert_eq!(group, expected);
+	}
+
+	#[test]
+	fn scores_are_smoothed() {
+		let mut stats = PeerStats::new();
+		let score = stats.note_response(&response(Sr25519Keyring::Alice, 100, true));
+		assert_eq!(score.latency, Some(Duration::from_millis(100)));
+		assert_eq!(score.failure_rate, 0.0);
+
+		let score = stats.note_response(&response(Sr25519Keyring::Alice, 600, true));
+		assert_eq!(score.latency, Some(Duration::from_millis(200)));
+
+		let score = stats.note_response(&response(Sr25519Keyring::Alice, 10, false));
+		assert_eq!(score.latency, Some(Duration::from_millis(200)));
+		assert!((score.failure_rate - SMOOTHING_FACTOR).abs() < f64::EPSILON);
+	}
+
+	#[test]
+	fn speculative_delay_is_bounded() {
+		let mut stats = PeerStats::new();
+		let alice: AuthorityDiscoveryId = Sr25519Keyring::Alice.public().into();
+		let bob: AuthorityDiscoveryId = Sr25519Keyring::Bob.public().into();
+		stats.note_response(&response(Sr25519Keyring::Alice, 1, true));
+		stats.note_response(&response(Sr25519Keyring::Bob, 5_000, true));
+
+		assert_eq!(stats.speculative_delay(Some(&alice)), MIN_SPECULATIVE_DELAY);
+		assert_eq!(stats.speculative_delay(Some(&bob)), MAX_SPECULATIVE_DELAY);
+		assert_eq!(stats.speculative_delay(None), DEFAULT_LATENCY * 2);
+	}
+}
@@ -25,14 +25,17 @@
 use futures::{channel::oneshot, FutureExt};
 
 use diamond_node_network_protocol::{
-	v1 as protocol_v1, OurView, PeerId, UnifiedReputationChange as Rep, View,
+	peer_set::VALIDATION_PROTOCOL_V2, v1 as protocol_v1, OurView, PeerId, ProtocolVersion,
+	UnifiedReputationChange as Rep, View,
 };
 use diamond_node_subsystem_util::{
 	self as util,
 	metrics::{self, prometheus},
 	MIN_GOSSIP_PEERS,
 };
-use diamond_primitives::v1::{Hash, SignedAvailabilityBitfield, SigningContext, ValidatorId};
+use diamond_primitives::v1::{
+	AvailabilityBitfield, Hash, SignedAvailabilityBitfield, SigningContext, ValidatorId,
+};
 use diamond_subsystem::{
 	jaeger, messages::*, overseer, ActiveLeavesUpdate, FromOverseer, OverseerSignal, PerLeafSpan,
 	SpawnedSubsystem, SubsystemContext, SubsystemError, SubsystemResult,
@@ -83,6 +86,9 @@ struct ProtocolState {
 	/// to determine what is relevant to them.
 	peer_views: HashMap<PeerId, View>,
 
+	/// The version of the validation protocol each active peer is connected with.
+	peer_versions: HashMap<PeerId, ProtocolVersion>,
+
 	/// Track all our neighbors in the current gossip topology.
 	/// We're not necessarily connected to all of them.
 	gossip_peers: HashSet<PeerId>,
@@ -152,6 +158,45 @@ impl PerRelayParentData {
 				.map(|v| !v.contains(validator))
 				.unwrap_or(true)
 	}
+
+	/// Forget about which peers accepting replacement bitfields have seen a message of the given
+	/// validator.
+	///
+	/// Used when a validator's bitfield got replaced, so the new one gets gossiped again to those
+	/// peers. Other peers would consider it a duplicate message.
+	fn forget_messages_from_validator(
+		&mut self,
+		validator: &ValidatorId,
+		peer_versions: &HashMap<PeerId, ProtocolVersion>,
+	) {
+		for (peer, validators) in self
+			.message_sent_to_peer
+			.iter_mut()
+			.chain(self.message_received_from_peer.iter_mut())
+		{
+			if accepts_replacements(peer_versions, peer) {
+				validators.remove(validator);
+			}
+		}
+	}
+}
+
+/// Whether the peer is connected with a version of the validation protocol accepting bitfields
+/// which replace an earlier bitfield of the same validator.
+fn accepts_replacements(peer_versions: &HashMap<PeerId, ProtocolVersion>, peer: &PeerId) -> bool {
+	peer_versions
+		.get(peer)
+		.map_or(false, |version| *version >= VALIDATION_PROTOCOL_V2)
+}
+
+/// Whether `new` has all bits of `old` set and at least one more.
+///
+/// Validators may replace their bitfield for a relay parent with such a bitfield, once further
+/// chunks became available.
+fn is_strict_superset(new: &AvailabilityBitfield, old: &AvailabilityBitfield) -> bool {
+	new.0.len() == old.0.len() &&
+		new.0.count_ones() > old.0.count_ones() &&
+		new.0.iter().zip(old.0.iter()).all(|(new, old)| *new || !*old)
 }
 
 const LOG_TARGET: &str = "parachain::bitfield-distribution";
@@ -306,6 +351,11 @@ async fn handle_bitfield_distribution<Context>(
 
 	let msg = BitfieldGossipMessage { relay_parent, signed_availability };
 
+	// Our bitfield might replace an earlier one, which then needs to be gossiped again:
+	if job_data.one_per_validator.insert(validator.clone(), msg.clone()).is_some() {
+		job_data.forget_messages_from_validator(&validator, &state.peer_versions);
+	}
+
 	let gossip_peers = &state.gossip_peers;
 	let peer_views = &mut state.peer_views;
 	relay_message(ctx, job_data, gossip_peers, peer_views, validator, msg).await;
@@ -452,12 +502,21 @@ async fn process_incoming_peer_message<Context>(
 		return
 	};
 
+	// A validator may replace its bitfield with one having strictly more bits set:
+	let is_replacement = match job_data.one_per_validator.get(&validator) {
+		Some(old_message) => is_strict_superset(
+			bitfield.unchecked_payload(),
+			old_message.signed_availability.payload(),
+		),
+		None => false,
+	};
+
 	// Check if the peer already sent us a message for the validator denoted in the message earlier.
 	// Must be done after validator index verification, in order to avoid storing an unbounded
 	// number of set entries.
 	let received_set = job_data.message_received_from_peer.entry(origin.clone()).or_default();
 
-	if !received_set.contains(&validator) {
+	if !received_set.contains(&validator) || is_replacement {
 		received_set.insert(validator.clone());
 	} else {
 		tracing::trace!(target: LOG_TARGET, ?validator_index, ?origin, "Duplicate message");
@@ -467,17 +526,19 @@ async fn process_incoming_peer_message<Context>(
 
 	let one_per_validator = &mut (job_data.one_per_validator);
 
-	// only relay_message a message of a validator once
+	// only relay_message a message of a validator once (unless it got replaced)
 	if let Some(old_message) = one_per_validator.get(&validator) {
-		tracing::trace!(
-			target: LOG_TARGET,
-			?validator_index,
-			"already received a message for validator",
-		);
-		if old_message.signed_availability.as_unchecked() == &bitfield {
-			modify_reputation(ctx, origin, BENEFIT_VALID_MESSAGE).await;
+		if !is_replacement {
+			tracing::trace!(
+				target: LOG_TARGET,
+				?validator_index,
+				"already received a message for validator",
+			);
+			if old_message.signed_availability.as_unchecked() == &bitfield {
+				modify_reputation(ctx, origin, BENEFIT_VALID_MESSAGE).await;
+			}
+			return
 		}
-		return
 	}
 	let signed_availability = match bitfield.try_into_checked(&signing_context, &validator) {
 		Err(_) => {
@@ -490,7 +551,17 @@ async fn process_incoming_peer_message<Context>(
 	let message = BitfieldGossipMessage { relay_parent, signed_availability };
 
 	metrics.on_bitfield_received();
-	one_per_validator.insert(validator.clone(), message.clone());
+	if one_per_validator.insert(validator.clone(), message.clone()).is_some() {
+		tracing::trace!(target: LOG_TARGET, ?validator_index, "Bitfield got replaced");
+		// Make sure the replacement reaches everybody accepting it, except for the peer we got it
+		// from:
+		job_data.forget_messages_from_validator(&validator, &state.peer_versions);
+		job_data
+			.message_received_from_peer
+			.entry(origin.clone())
+			.or_default()
+			.insert(validator.clone());
+	}
 
 	relay_message(ctx, job_data, &state.gossip_peers, &mut state.peer_views, validator, message)
 		.await;
@@ -511,8 +582,9 @@ async fn handle_network_msg<Context>(
 	let _timer = metrics.time_handle_network_msg();
 
 	match bridge_message {
-		NetworkBridgeEvent::PeerConnected(peerid, role, _) => {
-			tracing::trace!(target: LOG_TARGET, ?peerid, ?role, "Peer connected");
+		NetworkBridgeEvent::PeerConnected(peerid, role, version, _) => {
+			tracing::trace!(target: LOG_TARGET, ?peerid, ?role, version, "Peer connected");
+			state.peer_versions.insert(peerid.clone(), version);
 			// insert if none already present
 			state.peer_views.entry(peerid).or_default();
 		},
@@ -520,6 +592,7 @@ async fn handle_network_msg<Context>(
 			tracing::trace!(target: LOG_TARGET, ?peerid, "Peer disconnected");
 			// get rid of superfluous data
 			state.peer_views.remove(&peerid);
+			state.peer_versions.remove(&peerid);
 		},
 		NetworkBridgeEvent::NewGossipTopology(peers) => {
 			let newly_added: Vec<PeerId> = peers.difference(&state.gossip_peers).cloned().collect();
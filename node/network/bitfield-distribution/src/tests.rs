@@ -19,7 +19,11 @@ use assert_matches::assert_matches;
 use bitvec::bitvec;
 use futures::executor;
 use maplit::hashmap;
-use diamond_node_network_protocol::{our_view, view, ObservedRole};
+use diamond_node_network_protocol::{
+	our_view,
+	peer_set::{VALIDATION_PROTOCOL_V1, VALIDATION_PROTOCOL_V2},
+	view, ObservedRole,
+};
 use diamond_node_subsystem_test_helpers::make_subsystem_context;
 use diamond_node_subsystem_util::TimeoutExt;
 use diamond_primitives::v1::{AvailabilityBitfield, Signed, ValidatorIndex};
@@ -385,6 +389,218 @@ fn receive_duplicate_messages() {
 	});
 }
 
+#[test]
+fn receive_superset_replacement() {
+	let _ = env_logger::builder()
+		.filter(None, log::LevelFilter::Trace)
+		.is_test(true)
+		.try_init();
+
+	let hash: Hash = [0; 32].into();
+
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+	assert_ne!(peer_a, peer_b);
+
+	// validator 0 key pair
+	let (mut state, signing_context, keystore, validator) =
+		state_with_view(our_view![hash], hash.clone());
+
+	let sign = |payload: AvailabilityBitfield| {
+		executor::block_on(Signed::<AvailabilityBitfield>::sign(
+			&keystore,
+			payload,
+			&signing_context,
+			ValidatorIndex(0),
+			&validator,
+		))
+		.ok()
+		.flatten()
+		.expect("should be signed")
+	};
+	let first = sign(AvailabilityBitfield(bitvec![bitvec::order::Lsb0, u8; 1, 1, 0, 0]));
+	let superset = sign(AvailabilityBitfield(bitvec![bitvec::order::Lsb0, u8; 1, 1, 1, 0]));
+	let other = sign(AvailabilityBitfield(bitvec![bitvec::order::Lsb0, u8; 1, 0, 0, 1]));
+
+	let msg = |signed_availability: SignedAvailabilityBitfield| BitfieldGossipMessage {
+		relay_parent: hash.clone(),
+		signed_availability,
+	};
+
+	let pool = sp_core::testing::TaskExecutor::new();
+	let (mut ctx, mut handle) = make_subsystem_context::<BitfieldDistributionMessage, _>(pool);
+
+	executor::block_on(async move {
+		// Peer A sends the first bitfield and later a strict superset of it, both get accepted:
+		for signed in vec![first.clone(), superset.clone()] {
+			launch!(handle_network_msg(
+				&mut ctx,
+				&mut state,
+				&Default::default(),
+				NetworkBridgeEvent::PeerMessage(
+					peer_a.clone(),
+					msg(signed.clone()).into_network_message()
+				),
+			));
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::Provisioner(ProvisionerMessage::ProvisionableData(
+					_,
+					ProvisionableData::Bitfield(h, s)
+				)) => {
+					assert_eq!(h, hash);
+					assert_eq!(s, signed)
+				}
+			);
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::NetworkBridge(
+					NetworkBridgeMessage::ReportPeer(peer, rep)
+				) => {
+					assert_eq!(peer, peer_a);
+					assert_eq!(rep, BENEFIT_VALID_MESSAGE_FIRST)
+				}
+			);
+		}
+		assert_eq!(
+			state.per_relay_parent[&hash].one_per_validator[&validator].signed_availability,
+			superset,
+		);
+
+		// The outdated bitfield does not replace the superset:
+		launch!(handle_network_msg(
+			&mut ctx,
+			&mut state,
+			&Default::default(),
+			NetworkBridgeEvent::PeerMessage(peer_b.clone(), msg(first).into_network_message()),
+		));
+		assert!(handle.recv().timeout(Duration::from_millis(10)).await.is_none());
+
+		// Neither does a bitfield which is not a superset:
+		launch!(handle_network_msg(
+			&mut ctx,
+			&mut state,
+			&Default::default(),
+			NetworkBridgeEvent::PeerMessage(peer_a.clone(), msg(other).into_network_message()),
+		));
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::NetworkBridge(
+				NetworkBridgeMessage::ReportPeer(peer, rep)
+			) => {
+				assert_eq!(peer, peer_a);
+				assert_eq!(rep, COST_PEER_DUPLICATE_MESSAGE)
+			}
+		);
+		assert_eq!(
+			state.per_relay_parent[&hash].one_per_validator[&validator].signed_availability,
+			superset,
+		);
+	});
+}
+
+#[test]
+fn relay_superset_replacement_only_to_peers_accepting_it() {
+	let _ = env_logger::builder()
+		.filter(None, log::LevelFilter::Trace)
+		.is_test(true)
+		.try_init();
+
+	let hash: Hash = [0; 32].into();
+
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+	let peer_c = PeerId::random();
+
+	// validator 0 key pair
+	let (mut state, signing_context, keystore, validator) =
+		state_with_view(our_view![hash], hash.clone());
+
+	let sign = |payload: AvailabilityBitfield| {
+		executor::block_on(Signed::<AvailabilityBitfield>::sign(
+			&keystore,
+			payload,
+			&signing_context,
+			ValidatorIndex(0),
+			&validator,
+		))
+		.ok()
+		.flatten()
+		.expect("should be signed")
+	};
+	let first = sign(AvailabilityBitfield(bitvec![bitvec::order::Lsb0, u8; 1, 1, 0, 0]));
+	let superset = sign(AvailabilityBitfield(bitvec![bitvec::order::Lsb0, u8; 1, 1, 1, 0]));
+
+	let msg = |signed_availability: SignedAvailabilityBitfield| BitfieldGossipMessage {
+		relay_parent: hash.clone(),
+		signed_availability,
+	};
+
+	// Peer A accepts replacements, peer B is still on the first protocol version.
+	state.gossip_peers = HashSet::from_iter(vec![peer_a.clone(), peer_b.clone()].into_iter());
+	state.peer_views.insert(peer_a.clone(), view![hash]);
+	state.peer_views.insert(peer_b.clone(), view![hash]);
+	state.peer_versions.insert(peer_a.clone(), VALIDATION_PROTOCOL_V2);
+	state.peer_versions.insert(peer_b.clone(), VALIDATION_PROTOCOL_V1);
+	state.peer_versions.insert(peer_c.clone(), VALIDATION_PROTOCOL_V2);
+
+	let pool = sp_core::testing::TaskExecutor::new();
+	let (mut ctx, mut handle) = make_subsystem_context::<BitfieldDistributionMessage, _>(pool);
+
+	executor::block_on(async move {
+		// Peer C sends the first bitfield, which is relayed to both peers, and then its
+		// replacement, which is only relayed to peer A:
+		for (signed, expected_peers) in
+			vec![(first, vec![peer_a.clone(), peer_b.clone()]), (superset, vec![peer_a.clone()])]
+		{
+			launch!(handle_network_msg(
+				&mut ctx,
+				&mut state,
+				&Default::default(),
+				NetworkBridgeEvent::PeerMessage(
+					peer_c.clone(),
+					msg(signed.clone()).into_network_message()
+				),
+			));
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::Provisioner(ProvisionerMessage::ProvisionableData(
+					_,
+					ProvisionableData::Bitfield(h, s)
+				)) => {
+					assert_eq!(h, hash);
+					assert_eq!(s, signed)
+				}
+			);
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::NetworkBridge(
+					NetworkBridgeMessage::SendValidationMessage(peers, send_msg),
+				) => {
+					assert_eq!(peers.len(), expected_peers.len());
+					assert!(expected_peers.iter().all(|peer| peers.contains(peer)));
+					assert_eq!(send_msg, msg(signed).into_validation_protocol());
+				}
+			);
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::NetworkBridge(
+					NetworkBridgeMessage::ReportPeer(peer, rep)
+				) => {
+					assert_eq!(peer, peer_c);
+					assert_eq!(rep, BENEFIT_VALID_MESSAGE_FIRST)
+				}
+			);
+		}
+	});
+}
+
 #[test]
 fn do_not_relay_message_twice() {
 	let _ = env_logger::builder()
@@ -532,7 +748,12 @@ fn changing_view() {
 			&mut ctx,
 			&mut state,
 			&Default::default(),
-			NetworkBridgeEvent::PeerConnected(peer_b.clone(), ObservedRole::Full, None),
+			NetworkBridgeEvent::PeerConnected(
+				peer_b.clone(),
+				ObservedRole::Full,
+				VALIDATION_PROTOCOL_V2,
+				None,
+			),
 		));
 
 		// make peer b interested
@@ -28,7 +28,11 @@ use std::{
 
 use sc_network::{Event as NetworkEvent, IfDisconnected};
 
-use diamond_node_network_protocol::{request_response::outgoing::Requests, view, ObservedRole};
+use diamond_node_network_protocol::{
+	peer_set::{COLLATION_PROTOCOL_V1, VALIDATION_PROTOCOL_V2},
+	request_response::outgoing::Requests,
+	view, ObservedRole,
+};
 use diamond_node_subsystem_test_helpers::{
 	SingleItemSink, SingleItemStream, TestSubsystemContextHandle,
 };
@@ -639,7 +643,12 @@ fn peer_view_updates_sent_via_overseer() {
 		// bridge will inform about all connected peers.
 		{
 			assert_sends_validation_event_to_all(
-				NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full, None),
+				NetworkBridgeEvent::PeerConnected(
+					peer.clone(),
+					ObservedRole::Full,
+					VALIDATION_PROTOCOL_V2,
+					None,
+				),
 				&mut virtual_overseer,
 			)
 			.await;
@@ -682,7 +691,12 @@ fn peer_messages_sent_via_overseer() {
 		// bridge will inform about all connected peers.
 		{
 			assert_sends_validation_event_to_all(
-				NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full, None),
+				NetworkBridgeEvent::PeerConnected(
+					peer.clone(),
+					ObservedRole::Full,
+					VALIDATION_PROTOCOL_V2,
+					None,
+				),
 				&mut virtual_overseer,
 			)
 			.await;
@@ -752,7 +766,12 @@ fn peer_disconnect_from_just_one_peerset() {
 		// bridge will inform about all connected peers.
 		{
 			assert_sends_validation_event_to_all(
-				NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full, None),
+				NetworkBridgeEvent::PeerConnected(
+					peer.clone(),
+					ObservedRole::Full,
+					VALIDATION_PROTOCOL_V2,
+					None,
+				),
 				&mut virtual_overseer,
 			)
 			.await;
@@ -766,7 +785,12 @@ fn peer_disconnect_from_just_one_peerset() {
 
 		{
 			assert_sends_collation_event_to_all(
-				NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full, None),
+				NetworkBridgeEvent::PeerConnected(
+					peer.clone(),
+					ObservedRole::Full,
+					COLLATION_PROTOCOL_V1,
+					None,
+				),
 				&mut virtual_overseer,
 			)
 			.await;
@@ -835,7 +859,12 @@ fn relays_collation_protocol_messages() {
 		// bridge will inform about all connected peers.
 		{
 			assert_sends_validation_event_to_all(
-				NetworkBridgeEvent::PeerConnected(peer_a.clone(), ObservedRole::Full, None),
+				NetworkBridgeEvent::PeerConnected(
+					peer_a.clone(),
+					ObservedRole::Full,
+					VALIDATION_PROTOCOL_V2,
+					None,
+				),
 				&mut virtual_overseer,
 			)
 			.await;
@@ -849,7 +878,12 @@ fn relays_collation_protocol_messages() {
 
 		{
 			assert_sends_collation_event_to_all(
-				NetworkBridgeEvent::PeerConnected(peer_b.clone(), ObservedRole::Full, None),
+				NetworkBridgeEvent::PeerConnected(
+					peer_b.clone(),
+					ObservedRole::Full,
+					COLLATION_PROTOCOL_V1,
+					None,
+				),
 				&mut virtual_overseer,
 			)
 			.await;
@@ -928,7 +962,12 @@ fn different_views_on_different_peer_sets() {
 		// bridge will inform about all connected peers.
 		{
 			assert_sends_validation_event_to_all(
-				NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full, None),
+				NetworkBridgeEvent::PeerConnected(
+					peer.clone(),
+					ObservedRole::Full,
+					VALIDATION_PROTOCOL_V2,
+					None,
+				),
 				&mut virtual_overseer,
 			)
 			.await;
@@ -942,7 +981,12 @@ fn different_views_on_different_peer_sets() {
 
 		{
 			assert_sends_collation_event_to_all(
-				NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full, None),
+				NetworkBridgeEvent::PeerConnected(
+					peer.clone(),
+					ObservedRole::Full,
+					COLLATION_PROTOCOL_V1,
+					None,
+				),
 				&mut virtual_overseer,
 			)
 			.await;
@@ -1091,7 +1135,12 @@ fn send_messages_to_peers() {
 		// bridge will inform about all connected peers.
 		{
 			assert_sends_validation_event_to_all(
-				NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full, None),
+				NetworkBridgeEvent::PeerConnected(
+					peer.clone(),
+					ObservedRole::Full,
+					VALIDATION_PROTOCOL_V2,
+					None,
+				),
 				&mut virtual_overseer,
 			)
 			.await;
@@ -1105,7 +1154,12 @@ fn send_messages_to_peers() {
 
 		{
 			assert_sends_collation_event_to_all(
-				NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full, None),
+				NetworkBridgeEvent::PeerConnected(
+					peer.clone(),
+					ObservedRole::Full,
+					COLLATION_PROTOCOL_V1,
+					None,
+				),
 				&mut virtual_overseer,
 			)
 			.await;
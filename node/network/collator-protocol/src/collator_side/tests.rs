@@ -29,7 +29,9 @@ use sp_core::crypto::Pair;
 use sp_keyring::Sr25519Keyring;
 use sp_runtime::traits::AppVerify;
 
-use diamond_node_network_protocol::{our_view, request_response::IncomingRequest, view};
+use diamond_node_network_protocol::{
+	our_view, peer_set::COLLATION_PROTOCOL_V1, request_response::IncomingRequest, view,
+};
 use diamond_node_primitives::BlockData;
 use diamond_node_subsystem_util::TimeoutExt;
 use diamond_primitives::v1::{
@@ -405,6 +407,7 @@ async fn connect_peer(
 		CollatorProtocolMessage::NetworkBridgeUpdateV1(NetworkBridgeEvent::PeerConnected(
 			peer.clone(),
 			diamond_node_network_protocol::ObservedRole::Authority,
+			COLLATION_PROTOCOL_V1,
 			authority_id,
 		)),
 	)
@@ -24,6 +24,7 @@ use std::{iter, sync::Arc, time::Duration};
 
 use diamond_node_network_protocol::{
 	our_view,
+	peer_set::COLLATION_PROTOCOL_V1,
 	request_response::{Requests, ResponseSender},
 	ObservedRole,
 };
@@ -305,6 +306,7 @@ async fn connect_and_declare_collator(
 		CollatorProtocolMessage::NetworkBridgeUpdateV1(NetworkBridgeEvent::PeerConnected(
 			peer.clone(),
 			ObservedRole::Full,
+			COLLATION_PROTOCOL_V1,
 			None,
 		)),
 	)
@@ -456,6 +458,7 @@ fn collator_authentication_verification_works() {
 			CollatorProtocolMessage::NetworkBridgeUpdateV1(NetworkBridgeEvent::PeerConnected(
 				peer_b,
 				ObservedRole::Full,
+				COLLATION_PROTOCOL_V1,
 				None,
 			)),
 		)
@@ -951,6 +954,7 @@ fn disconnect_if_no_declare() {
 			CollatorProtocolMessage::NetworkBridgeUpdateV1(NetworkBridgeEvent::PeerConnected(
 				peer_b.clone(),
 				ObservedRole::Full,
+				COLLATION_PROTOCOL_V1,
 				None,
 			)),
 		)
@@ -988,6 +992,7 @@ fn disconnect_if_wrong_declare() {
 			CollatorProtocolMessage::NetworkBridgeUpdateV1(NetworkBridgeEvent::PeerConnected(
 				peer_b.clone(),
 				ObservedRole::Full,
+				COLLATION_PROTOCOL_V1,
 				None,
 			)),
 		)
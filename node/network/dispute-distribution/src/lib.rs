@@ -69,9 +69,14 @@ use self::sender::{DisputeSender, TaskFinish};
 ///
 ///	- Drop all messages from non validator nodes, for this it requires the [`AuthorityDiscovery`]
 ///	service.
-///	- Drop messages from a node, if we are already importing a message from that node (flood).
+///	- Queue messages per validator and import them round-robin, with at most one import in flight
+///	per validator and a rate limit in between imports. Messages exceeding the queue size get
+///	dropped (flood).
+///	- Answer messages carrying votes we already imported (or are importing) without importing them
+///	again.
 ///	- Drop messages from nodes, that provided us messages where the statement import failed.
-///	- Drop any obviously invalid votes (invalid signatures for example).
+///	- Drop any obviously invalid votes (invalid signatures for example) and ban the sending peer,
+///	including all of its queued messages.
 ///	- Ban peers whose votes were deemed invalid.
 ///
 /// For successfully imported votes, we will confirm the receipt of the message back to the sender.
@@ -15,9 +15,10 @@
 // along with diamond.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::{
-	collections::HashSet,
+	collections::{HashMap, HashSet},
 	pin::Pin,
 	task::{Context, Poll},
+	time::Instant,
 };
 
 use futures::{
@@ -27,6 +28,7 @@ use futures::{
 	stream::{FusedStream, FuturesUnordered, StreamExt},
 	Future, FutureExt, Stream,
 };
+use futures_timer::Delay;
 use lru::LruCache;
 
 use diamond_node_network_protocol::{
@@ -40,6 +42,7 @@ use diamond_node_network_protocol::{
 };
 use diamond_node_primitives::DISPUTE_WINDOW;
 use diamond_node_subsystem_util::{runtime, runtime::RuntimeInfo};
+use diamond_primitives::v1::{AuthorityDiscoveryId, BlakeTwo256, Hash, HashT};
 use diamond_subsystem::{
 	messages::{AllMessages, DisputeCoordinatorMessage, ImportStatementsResult},
 	SubsystemSender,
@@ -53,14 +56,24 @@ use crate::{
 mod error;
 use self::error::{log_error, NonFatal, NonFatalResult, Result};
 
+/// Per validator queues of requests waiting for import.
+pub mod peer_queues;
+use self::peer_queues::PeerQueues;
+
 const COST_INVALID_REQUEST: Rep = Rep::CostMajor("Received message could not be decoded.");
 const COST_INVALID_SIGNATURE: Rep = Rep::Malicious("Signatures were invalid.");
 const COST_INVALID_CANDIDATE: Rep = Rep::Malicious("Reported candidate was not available.");
 const COST_NOT_A_VALIDATOR: Rep = Rep::CostMajor("Reporting peer was not a validator.");
+const COST_APPARENT_FLOOD: Rep = Rep::CostMinor("Peer exceeded the rate limit.");
 
 /// How many statement imports we want to issue in parallel:
 pub const MAX_PARALLEL_IMPORTS: usize = 10;
 
+/// How many results of past imports we remember.
+///
+/// Requests carrying exactly the same votes as a past import get answered right away.
+const KNOWN_IMPORTS_CACHE_SIZE: usize = 1000;
+
 /// State for handling incoming `DisputeRequest` messages.
 ///
 /// This is supposed to run as its own task in order to easily impose back pressure on the incoming
@@ -78,9 +91,15 @@ pub struct DisputesReceiver<Sender, AD> {
 	/// Authority discovery service:
 	authority_discovery: AD,
 
+	/// Requests waiting for import, per validator.
+	queues: PeerQueues,
+
 	/// Imports currently being processed.
 	pending_imports: PendingImports,
 
+	/// Results of recent imports, by hash of the request payload.
+	known_imports: LruCache<Hash, ImportStatementsResult>,
+
 	/// We keep record of the last banned peers.
 	///
 	/// This is needed because once we ban a peer, we will very likely still have pending requests
@@ -101,17 +120,23 @@ enum MuxedMessage {
 	/// - We need to make sure responses are actually sent (therefore we need to await futures
 	/// promptly).
 	/// - We need to update `banned_peers` accordingly to the result.
-	ConfirmedImport(NonFatalResult<(PeerId, ImportStatementsResult)>),
+	ConfirmedImport(ImportOutcome),
 
 	/// A new request has arrived and should be handled.
 	NewRequest(IncomingRequest<DisputeRequest>),
+
+	/// Rate limit of some validator with queued requests elapsed.
+	QueueReady,
 }
 
 impl MuxedMessage {
 	async fn receive(
 		pending_imports: &mut PendingImports,
 		pending_requests: &mut IncomingRequestReceiver<DisputeRequest>,
+		queue_ready_at: Option<Instant>,
 	) -> Result<MuxedMessage> {
+		let mut queue_ready =
+			queue_ready_at.map(|at| Delay::new(at.saturating_duration_since(Instant::now())));
 		poll_fn(|ctx| {
 			let next_req = pending_requests.recv(|| vec![COST_INVALID_REQUEST]);
 			pin_mut!(next_req);
@@ -126,6 +151,9 @@ impl MuxedMessage {
 			if let Poll::Ready(Some(v)) = pending_imports.poll_next_unpin(ctx) {
 				return Poll::Ready(Ok(Self::ConfirmedImport(v)))
 			}
+			if let Some(Poll::Ready(())) = queue_ready.as_mut().map(|d| d.poll_unpin(ctx)) {
+				return Poll::Ready(Ok(Self::QueueReady))
+			}
 			Poll::Pending
 		})
 		.await
@@ -152,7 +180,9 @@ where
 			sender,
 			receiver,
 			authority_discovery,
+			queues: PeerQueues::new(),
 			pending_imports: PendingImports::new(),
+			known_imports: LruCache::new(KNOWN_IMPORTS_CACHE_SIZE),
 			// Size of MAX_PARALLEL_IMPORTS ensures we are going to immediately get rid of any
 			// malicious requests still pending in the incoming queue.
 			banned_peers: LruCache::new(MAX_PARALLEL_IMPORTS),
@@ -181,14 +211,27 @@ where
 
 	/// Actual work happening here.
 	async fn run_inner(&mut self) -> Result<()> {
-		let msg = MuxedMessage::receive(&mut self.pending_imports, &mut self.receiver).await?;
+		// Start imports for queued requests, as far as limits allow:
+		self.start_imports().await?;
+
+		let queue_ready_at = if self.pending_imports.len() < MAX_PARALLEL_IMPORTS {
+			let pending_imports = &self.pending_imports;
+			self.queues.next_ready(|v| pending_imports.validator_is_pending(v))
+		} else {
+			// Finishing imports will wake us up.
+			None
+		};
+		let msg =
+			MuxedMessage::receive(&mut self.pending_imports, &mut self.receiver, queue_ready_at)
+				.await?;
 
 		let incoming = match msg {
 			// We need to clean up futures, to make sure responses are sent:
-			MuxedMessage::ConfirmedImport(m_bad) => {
-				self.ban_bad_peer(m_bad)?;
+			MuxedMessage::ConfirmedImport(outcome) => {
+				self.ban_bad_peer(outcome)?;
 				return Ok(())
 			},
+			MuxedMessage::QueueReady => return Ok(()),
 			MuxedMessage::NewRequest(req) => req,
 		};
 
@@ -197,42 +240,105 @@ where
 		let peer = incoming.peer;
 
 		// Only accept messages from validators:
-		if self.authority_discovery.get_authority_id_by_peer_id(peer).await.is_none() {
+		let validator = match self.authority_discovery.get_authority_id_by_peer_id(peer).await {
+			Some(validator) => validator,
+			None => {
+				incoming
+					.send_outgoing_response(OutgoingResponse {
+						result: Err(()),
+						reputation_changes: vec![COST_NOT_A_VALIDATOR],
+						sent_feedback: None,
+					})
+					.map_err(|_| NonFatal::SendResponse(peer))?;
+
+				return Err(NonFatal::NotAValidator(peer).into())
+			},
+		};
+
+		// Immediately drop requests from peers that have been banned recently:
+		if self.banned_peers.contains(&peer) {
+			tracing::trace!(target: LOG_TARGET, ?peer, "Dropping message from banned peer");
+			return Ok(())
+		}
+
+		// No need to queue requests we already know the answer for:
+		let incoming = match self.batch_or_answer(incoming)? {
+			Some(incoming) => incoming,
+			None => return Ok(()),
+		};
+
+		// Flood protection:
+		if let Err(incoming) = self.queues.push(validator, incoming) {
+			tracing::trace!(target: LOG_TARGET, ?peer, "Dropping message from peer (queue full)");
 			incoming
 				.send_outgoing_response(OutgoingResponse {
 					result: Err(()),
-					reputation_changes: vec![COST_NOT_A_VALIDATOR],
+					reputation_changes: vec![COST_APPARENT_FLOOD],
 					sent_feedback: None,
 				})
 				.map_err(|_| NonFatal::SendResponse(peer))?;
-
-			return Err(NonFatal::NotAValidator(peer).into())
 		}
+		Ok(())
+	}
 
-		// Immediately drop requests from peers that already have requests in flight or have
-		// been banned recently (flood protection):
-		if self.pending_imports.peer_is_pending(&peer) || self.banned_peers.contains(&peer) {
-			tracing::trace!(
-				target: LOG_TARGET,
-				?peer,
-				"Dropping message from peer (banned/pending import)"
-			);
-			return Ok(())
+	/// Start imports for queued requests, until we run out of requests or import slots.
+	async fn start_imports(&mut self) -> Result<()> {
+		while self.pending_imports.len() < MAX_PARALLEL_IMPORTS {
+			let pending_imports = &self.pending_imports;
+			let next = self
+				.queues
+				.pop_next(Instant::now(), |v| pending_imports.validator_is_pending(v));
+			let (validator, incoming) = match next {
+				Some(next) => next,
+				None => break,
+			};
+			// Some other request carrying the same votes might have been imported meanwhile:
+			if let Some(incoming) = self.batch_or_answer(incoming)? {
+				self.start_import(validator, incoming).await?;
+			}
 		}
+		Ok(())
+	}
+
+	/// Handle requests whose votes are already known or currently being imported.
+	///
+	/// Requests with the same payload as a recent import get answered right away, requests with
+	/// the same payload as a pending import get answered together with that import. Any other
+	/// request is handed back.
+	fn batch_or_answer(
+		&mut self,
+		incoming: IncomingRequest<DisputeRequest>,
+	) -> NonFatalResult<Option<IncomingRequest<DisputeRequest>>> {
+		let key = BlakeTwo256::hash_of(&incoming.payload);
+		let peer = incoming.peer;
 
-		// Wait for a free slot:
-		if self.pending_imports.len() >= MAX_PARALLEL_IMPORTS as usize {
-			// Wait for one to finish:
-			let r = self.pending_imports.next().await;
-			self.ban_bad_peer(r.expect("pending_imports.len() is greater 0. qed."))?;
+		if let Some(result) = self.known_imports.get(&key).copied() {
+			tracing::trace!(target: LOG_TARGET, ?peer, "Votes already imported");
+			if result == ImportStatementsResult::InvalidImport {
+				self.ban_peer(peer);
+			}
+			incoming
+				.send_outgoing_response(import_response(result))
+				.map_err(|_| NonFatal::SendResponse(peer))?;
+			return Ok(None)
 		}
 
-		// All good - initiate import.
-		self.start_import(incoming).await
+		match self.pending_imports.batch(key, incoming) {
+			Ok(()) => {
+				tracing::trace!(target: LOG_TARGET, ?peer, "Votes already being imported");
+				Ok(None)
+			},
+			Err(incoming) => Ok(Some(incoming)),
+		}
 	}
 
 	/// Start importing votes for the given request.
-	async fn start_import(&mut self, incoming: IncomingRequest<DisputeRequest>) -> Result<()> {
+	async fn start_import(
+		&mut self,
+		validator: AuthorityDiscoveryId,
+		incoming: IncomingRequest<DisputeRequest>,
+	) -> Result<()> {
+		let key = BlakeTwo256::hash_of(&incoming.payload);
 		let IncomingRequest { peer, payload, pending_response } = incoming;
 
 		let info = self
@@ -248,7 +354,9 @@ where
 
 		let (candidate_receipt, valid_vote, invalid_vote) = match votes_result {
 			Err(()) => {
-				// Signature invalid:
+				// Signature invalid - there is no excuse for that, so we also get rid of anything
+				// else that peer has sent us:
+				self.ban_peer(peer);
 				pending_response
 					.send_outgoing_response(OutgoingResponse {
 						result: Err(()),
@@ -276,57 +384,107 @@ where
 			))
 			.await;
 
-		self.pending_imports.push(peer, confirmation_rx, pending_response);
+		self.pending_imports
+			.push(key, validator, peer, confirmation_rx, pending_response);
 		Ok(())
 	}
 
 	/// Await an import and ban any misbehaving peers.
 	///
 	/// In addition we report import metrics.
-	fn ban_bad_peer(
-		&mut self,
-		result: NonFatalResult<(PeerId, ImportStatementsResult)>,
-	) -> NonFatalResult<()> {
-		match result? {
-			(_, ImportStatementsResult::ValidImport) => {
+	fn ban_bad_peer(&mut self, outcome: ImportOutcome) -> NonFatalResult<()> {
+		let ImportOutcome { key, peers, result } = outcome;
+		let result = result?;
+		self.known_imports.put(key, result);
+		match result {
+			ImportStatementsResult::ValidImport => {
 				self.metrics.on_imported(SUCCEEDED);
 			},
-			(bad_peer, ImportStatementsResult::InvalidImport) => {
+			ImportStatementsResult::InvalidImport => {
 				self.metrics.on_imported(FAILED);
-				self.banned_peers.put(bad_peer, ());
+				for bad_peer in peers {
+					self.ban_peer(bad_peer);
+				}
 			},
 		}
 		Ok(())
 	}
+
+	/// Ban a peer and drop all of its queued requests.
+	fn ban_peer(&mut self, peer: PeerId) {
+		self.banned_peers.put(peer, ());
+		self.queues.drop_from_peer(&peer);
+	}
 }
 
 /// Manage pending imports in a way that preserves invariants.
 struct PendingImports {
 	/// Futures in flight.
-	futures: FuturesUnordered<BoxFuture<'static, (PeerId, NonFatalResult<ImportStatementsResult>)>>,
-	/// Peers whose requests are currently in flight.
-	peers: HashSet<PeerId>,
+	futures: FuturesUnordered<BoxFuture<'static, (Hash, Option<ImportStatementsResult>)>>,
+	/// Requests waiting for the result of a pending import, by hash of the request payload.
+	waiting: HashMap<Hash, PendingImport>,
+	/// Validators whose requests are currently in flight.
+	validators: HashSet<AuthorityDiscoveryId>,
+}
+
+/// Requests waiting for a particular import.
+struct PendingImport {
+	/// The validator whose request triggered the import.
+	validator: AuthorityDiscoveryId,
+	/// Peers and their response channels.
+	///
+	/// The first entry is the request that triggered the import, any further ones got batched.
+	requests: Vec<(PeerId, OutgoingResponseSender<DisputeRequest>)>,
+}
+
+/// Result of an import, after all waiting requests have been answered.
+struct ImportOutcome {
+	/// Hash of the payload of the imported request.
+	key: Hash,
+	/// All peers that sent us the imported votes.
+	peers: Vec<PeerId>,
+	/// The result of the import.
+	result: NonFatalResult<ImportStatementsResult>,
 }
 
 impl PendingImports {
 	pub fn new() -> Self {
-		Self { futures: FuturesUnordered::new(), peers: HashSet::new() }
+		Self {
+			futures: FuturesUnordered::new(),
+			waiting: HashMap::new(),
+			validators: HashSet::new(),
+		}
 	}
 
 	pub fn push(
 		&mut self,
+		key: Hash,
+		validator: AuthorityDiscoveryId,
 		peer: PeerId,
 		handled: oneshot::Receiver<ImportStatementsResult>,
 		pending_response: OutgoingResponseSender<DisputeRequest>,
 	) {
-		self.peers.insert(peer);
-		self.futures.push(
-			async move {
-				let r = respond_to_request(peer, handled, pending_response).await;
-				(peer, r)
-			}
-			.boxed(),
-		)
+		self.validators.insert(validator.clone());
+		self.waiting
+			.insert(key, PendingImport { validator, requests: vec![(peer, pending_response)] });
+		self.futures.push(async move { (key, handled.await.ok()) }.boxed())
+	}
+
+	/// Let a request wait for the result of a pending import of the same payload.
+	///
+	/// The request is handed back if there is no such import.
+	pub fn batch(
+		&mut self,
+		key: Hash,
+		incoming: IncomingRequest<DisputeRequest>,
+	) -> std::result::Result<(), IncomingRequest<DisputeRequest>> {
+		match self.waiting.get_mut(&key) {
+			Some(pending) => {
+				pending.requests.push((incoming.peer, incoming.pending_response));
+				Ok(())
+			},
+			None => Err(incoming),
+		}
 	}
 
 	/// Returns the number of contained futures.
@@ -334,21 +492,33 @@ impl PendingImports {
 		self.futures.len()
 	}
 
-	/// Check whether a peer has a pending import.
-	pub fn peer_is_pending(&self, peer: &PeerId) -> bool {
-		self.peers.contains(peer)
+	/// Check whether a validator has a pending import.
+	pub fn validator_is_pending(&self, validator: &AuthorityDiscoveryId) -> bool {
+		self.validators.contains(validator)
 	}
 }
 
 impl Stream for PendingImports {
-	type Item = NonFatalResult<(PeerId, ImportStatementsResult)>;
+	type Item = ImportOutcome;
 	fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
 		match Pin::new(&mut self.futures).poll_next(ctx) {
 			Poll::Pending => Poll::Pending,
 			Poll::Ready(None) => Poll::Ready(None),
-			Poll::Ready(Some((peer, result))) => {
-				self.peers.remove(&peer);
-				Poll::Ready(Some(result.map(|r| (peer, r))))
+			Poll::Ready(Some((key, result))) => {
+				let PendingImport { validator, requests } = self
+					.waiting
+					.remove(&key)
+					.expect("Every pending future has an entry in `waiting`. qed.");
+				self.validators.remove(&validator);
+				let peers = requests.iter().map(|(peer, _)| *peer).collect::<Vec<_>>();
+				let result = match result {
+					Some(result) => {
+						respond_to_requests(requests, result);
+						Ok(result)
+					},
+					None => Err(NonFatal::ImportCanceled(peers[0])),
+				};
+				Poll::Ready(Some(ImportOutcome { key, peers, result }))
 			},
 		}
 	}
@@ -359,19 +529,23 @@ impl FusedStream for PendingImports {
 	}
 }
 
-// Future for `PendingImports`
-//
-// - Wait for import
-// - Punish peer
-// - Deliver result
-async fn respond_to_request(
-	peer: PeerId,
-	handled: oneshot::Receiver<ImportStatementsResult>,
-	pending_response: OutgoingResponseSender<DisputeRequest>,
-) -> NonFatalResult<ImportStatementsResult> {
-	let result = handled.await.map_err(|_| NonFatal::ImportCanceled(peer))?;
-
-	let response = match result {
+/// Deliver the result of an import to all requests waiting for it.
+///
+/// Peers having sent us votes for an invalid candidate get punished.
+fn respond_to_requests(
+	requests: Vec<(PeerId, OutgoingResponseSender<DisputeRequest>)>,
+	result: ImportStatementsResult,
+) {
+	for (peer, pending_response) in requests {
+		if pending_response.send_outgoing_response(import_response(result)).is_err() {
+			tracing::debug!(target: LOG_TARGET, ?peer, "Sending back response to peer failed");
+		}
+	}
+}
+
+/// The response to send for a given import result.
+fn import_response(result: ImportStatementsResult) -> OutgoingResponse<DisputeResponse> {
+	match result {
 		ImportStatementsResult::ValidImport => OutgoingResponse {
 			result: Ok(DisputeResponse::Confirmed),
 			reputation_changes: Vec::new(),
@@ -382,11 +556,5 @@ async fn respond_to_request(
 			reputation_changes: vec![COST_INVALID_CANDIDATE],
 			sent_feedback: None,
 		},
-	};
-
-	pending_response
-		.send_outgoing_response(response)
-		.map_err(|_| NonFatal::SendResponse(peer))?;
-
-	Ok(result)
+	}
 }
@@ -0,0 +1,140 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per validator queues for incoming dispute requests.
+//!
+//! Requests are queued per sending validator and handed out for import in round-robin fashion, so
+//! a single validator flooding us with disputes cannot starve everybody else.
+
+use std::{
+	collections::{HashMap, VecDeque},
+	time::{Duration, Instant},
+};
+
+use diamond_node_network_protocol::{
+	request_response::{v1::DisputeRequest, IncomingRequest},
+	PeerId,
+};
+use diamond_primitives::v1::AuthorityDiscoveryId;
+
+/// How many requests we keep queued per validator.
+///
+/// Further requests get rejected until the queue drained. Honest senders will retry.
+pub const MAX_QUEUED_REQUESTS_PER_VALIDATOR: usize = 20;
+
+/// Minimum time between two imports started for the same validator.
+pub const RATE_LIMIT: Duration = Duration::from_millis(100);
+
+/// Queues of incoming requests, one per validator.
+pub struct PeerQueues {
+	/// The actual queues.
+	queues: HashMap<AuthorityDiscoveryId, PeerQueue>,
+
+	/// Validators in the order they get served next.
+	///
+	/// Contains exactly the keys of `queues`.
+	order: VecDeque<AuthorityDiscoveryId>,
+}
+
+/// Queue for a single validator.
+struct PeerQueue {
+	/// Requests waiting for import.
+	requests: VecDeque<IncomingRequest<DisputeRequest>>,
+
+	/// We won't start another import for this validator before that time.
+	ready_at: Instant,
+}
+
+impl PeerQueues {
+	/// Create empty queues.
+	pub fn new() -> Self {
+		Self { queues: HashMap::new(), order: VecDeque::new() }
+	}
+
+	/// Queue a request received from the given validator.
+	///
+	/// The request is handed back, if the validator's queue is full already.
+	pub fn push(
+		&mut self,
+		validator: AuthorityDiscoveryId,
+		req: IncomingRequest<DisputeRequest>,
+	) -> std::result::Result<(), IncomingRequest<DisputeRequest>> {
+		let order = &mut self.order;
+		let queue = self.queues.entry(validator.clone()).or_insert_with(|| {
+			order.push_back(validator);
+			PeerQueue { requests: VecDeque::new(), ready_at: Instant::now() }
+		});
+		if queue.requests.len() >= MAX_QUEUED_REQUESTS_PER_VALIDATOR {
+			return Err(req)
+		}
+		queue.requests.push_back(req);
+		Ok(())
+	}
+
+	/// Get the next request to import.
+	///
+	/// Validators are served in round-robin order. A validator is skipped if it is `busy` or has
+	/// been served less than `RATE_LIMIT` ago.
+	pub fn pop_next(
+		&mut self,
+		now: Instant,
+		busy: impl Fn(&AuthorityDiscoveryId) -> bool,
+	) -> Option<(AuthorityDiscoveryId, IncomingRequest<DisputeRequest>)> {
+		for _ in 0..self.order.len() {
+			let validator = self.order.pop_front()?;
+			let queue = self
+				.queues
+				.get_mut(&validator)
+				.expect("`order` contains exactly the keys of `queues`. qed.");
+
+			if queue.requests.is_empty() && queue.ready_at <= now {
+				// Nothing left to remember about that validator:
+				self.queues.remove(&validator);
+				continue
+			}
+			if queue.requests.is_empty() || queue.ready_at > now || busy(&validator) {
+				self.order.push_back(validator);
+				continue
+			}
+
+			let req = queue.requests.pop_front().expect("Queue is not empty, checked above. qed.");
+			queue.ready_at = now + RATE_LIMIT;
+			self.order.push_back(validator.clone());
+			return Some((validator, req))
+		}
+		None
+	}
+
+	/// Point in time when the next request will be ready for import.
+	///
+	/// Requests of `busy` validators are not considered.
+	pub fn next_ready(&self, busy: impl Fn(&AuthorityDiscoveryId) -> bool) -> Option<Instant> {
+		self.queues
+			.iter()
+			.filter(|(validator, queue)| !queue.requests.is_empty() && !busy(validator))
+			.map(|(_, queue)| queue.ready_at)
+			.min()
+	}
+
+	/// Drop all queued requests sent by the given peer.
+	///
+	/// The requests won't get a response.
+	pub fn drop_from_peer(&mut self, peer: &PeerId) {
+		for queue in self.queues.values_mut() {
+			queue.requests.retain(|req| &req.peer != peer);
+		}
+	}
+}
@@ -60,7 +60,10 @@ use self::mock::{
 	MOCK_AUTHORITY_DISCOVERY, MOCK_NEXT_SESSION_INDEX, MOCK_NEXT_SESSION_INFO, MOCK_SESSION_INDEX,
 	MOCK_SESSION_INFO,
 };
-use crate::{DisputeDistributionSubsystem, Metrics, LOG_TARGET};
+use crate::{
+	receiver::peer_queues::MAX_QUEUED_REQUESTS_PER_VALIDATOR, DisputeDistributionSubsystem,
+	Metrics, LOG_TARGET,
+};
 
 /// Useful mock providers.
 pub mod mock;
@@ -141,103 +144,56 @@ fn received_request_triggers_import() {
 			}
 		);
 
-		// Nested valid and invalid import.
-		//
-		// Nested requests from same peer should get dropped. For the invalid request even
-		// subsequent requests should get dropped.
-		nested_network_dispute_request(
-			&mut handle,
-			req_tx,
-			MOCK_AUTHORITY_DISCOVERY.get_peer_id_by_authority(Sr25519Keyring::Alice),
-			message.clone().into(),
-			ImportStatementsResult::InvalidImport,
-			true,
-			move |handle, req_tx, message| {
-				nested_network_dispute_request(
-					handle,
-					req_tx,
-					MOCK_AUTHORITY_DISCOVERY.get_peer_id_by_authority(Sr25519Keyring::Bob),
-					message.clone().into(),
-					ImportStatementsResult::ValidImport,
-					false,
-					move |_, req_tx, message| async move {
-						// Another request from Alice should get dropped (request already in
-						// flight):
-						{
-							let rx_response = send_network_dispute_request(
-								req_tx,
-								MOCK_AUTHORITY_DISCOVERY
-									.get_peer_id_by_authority(Sr25519Keyring::Alice),
-								message.clone(),
-							)
-							.await;
-
-							assert_matches!(
-								rx_response.await,
-								Err(err) => {
-									tracing::trace!(
-										target: LOG_TARGET,
-										?err,
-										"Request got dropped - other request already in flight"
-									);
-								}
-							);
-						}
-						// Another request from Bob should get dropped (request already in
-						// flight):
-						{
-							let rx_response = send_network_dispute_request(
-								req_tx,
-								MOCK_AUTHORITY_DISCOVERY
-									.get_peer_id_by_authority(Sr25519Keyring::Bob),
-								message.clone(),
-							)
-							.await;
-
-							assert_matches!(
-								rx_response.await,
-								Err(err) => {
-									tracing::trace!(
-										target: LOG_TARGET,
-										?err,
-										"Request got dropped - other request already in flight"
-									);
-								}
-							);
-						}
-					},
-				)
-			},
-		)
-		.await;
+		let alice = MOCK_AUTHORITY_DISCOVERY.get_peer_id_by_authority(Sr25519Keyring::Alice);
+		let bob = MOCK_AUTHORITY_DISCOVERY.get_peer_id_by_authority(Sr25519Keyring::Bob);
+
+		// Invalid import from Alice:
+		let rx_alice = send_network_dispute_request(req_tx, alice, message.clone().into()).await;
+		let alice_import = expect_import(&mut handle, &message.clone().into(), true).await;
+
+		// The same votes again get answered together with the running import:
+		let rx_alice_again =
+			send_network_dispute_request(req_tx, alice, message.clone().into()).await;
+
+		// Other votes from Alice get queued, as her import is still in flight:
+		let queued_message =
+			make_dispute_message(make_candidate_receipt(relay_parent), ALICE_INDEX, FERDIE_INDEX)
+				.await;
+		let rx_alice_queued =
+			send_network_dispute_request(req_tx, alice, queued_message.into()).await;
+
+		// Bob is not held up by Alice:
+		let bob_message =
+			make_dispute_message(make_candidate_receipt(relay_parent), ALICE_INDEX, FERDIE_INDEX)
+				.await;
+		let rx_bob = send_network_dispute_request(req_tx, bob, bob_message.clone().into()).await;
+		let bob_import = expect_import(&mut handle, &bob_message.clone().into(), false).await;
+
+		alice_import.send(ImportStatementsResult::InvalidImport).unwrap();
+		assert_punished(rx_alice, "Reported candidate was not available.").await;
+		assert_punished(rx_alice_again, "Reported candidate was not available.").await;
+		// Alice is banned now, so her queued request got dropped:
+		assert_dropped(rx_alice_queued).await;
+
+		bob_import.send(ImportStatementsResult::ValidImport).unwrap();
+		assert_confirmed(rx_bob).await;
 
 		// Subsequent sends from Alice should fail (peer is banned):
-		{
-			let rx_response = send_network_dispute_request(
-				req_tx,
-				MOCK_AUTHORITY_DISCOVERY.get_peer_id_by_authority(Sr25519Keyring::Alice),
-				message.clone().into(),
-			)
+		assert_dropped(send_network_dispute_request(req_tx, alice, message.clone().into()).await)
 			.await;
 
-			assert_matches!(
-				rx_response.await,
-				Err(err) => {
-					tracing::trace!(
-						target: LOG_TARGET,
-						?err,
-						"Request got dropped - peer is banned."
-						);
-				}
-			);
-		}
+		// Votes we imported already get confirmed right away:
+		assert_confirmed(send_network_dispute_request(req_tx, bob, bob_message.into()).await).await;
 
-		// But should work fine for Bob:
+		// New votes from Bob still get imported:
+		let new_message =
+			make_dispute_message(make_candidate_receipt(relay_parent), ALICE_INDEX, FERDIE_INDEX)
+				.await;
 		nested_network_dispute_request(
 			&mut handle,
 			req_tx,
-			MOCK_AUTHORITY_DISCOVERY.get_peer_id_by_authority(Sr25519Keyring::Bob),
-			message.clone().into(),
+			bob,
+			new_message.into(),
 			ImportStatementsResult::ValidImport,
 			false,
 			|_, _, _| async {},
@@ -250,6 +206,74 @@ fn received_request_triggers_import() {
 	test_harness(test);
 }
 
+#[test]
+fn flooding_validator_does_not_starve_others() {
+	let test = |mut handle: TestSubsystemContextHandle<DisputeDistributionMessage>,
+	            mut req_cfg: RequestResponseConfig| async move {
+		let req_tx = req_cfg.inbound_queue.as_mut().unwrap();
+		let _ = handle_subsystem_startup(&mut handle, None).await;
+
+		let relay_parent = Hash::random();
+		let alice = MOCK_AUTHORITY_DISCOVERY.get_peer_id_by_authority(Sr25519Keyring::Alice);
+		let bob = MOCK_AUTHORITY_DISCOVERY.get_peer_id_by_authority(Sr25519Keyring::Bob);
+		let charlie = MOCK_AUTHORITY_DISCOVERY.get_peer_id_by_authority(Sr25519Keyring::Charlie);
+
+		// Alice starts an import ...
+		let message =
+			make_dispute_message(make_candidate_receipt(relay_parent), ALICE_INDEX, FERDIE_INDEX)
+				.await;
+		let rx_alice = send_network_dispute_request(req_tx, alice, message.clone().into()).await;
+		let alice_import = expect_import(&mut handle, &message.into(), true).await;
+
+		// ... and floods us with further disputes:
+		let mut rx_queued = Vec::new();
+		for i in 0..MAX_QUEUED_REQUESTS_PER_VALIDATOR + 5 {
+			let message = make_dispute_message(
+				make_candidate_receipt(relay_parent),
+				ALICE_INDEX,
+				FERDIE_INDEX,
+			)
+			.await;
+			let rx = send_network_dispute_request(req_tx, alice, message.into()).await;
+			if i < MAX_QUEUED_REQUESTS_PER_VALIDATOR {
+				rx_queued.push(rx);
+			} else {
+				// Queue is full:
+				assert_punished(rx, "Peer exceeded the rate limit.").await;
+			}
+		}
+
+		// Bob still gets served right away:
+		let bob_message =
+			make_dispute_message(make_candidate_receipt(relay_parent), ALICE_INDEX, FERDIE_INDEX)
+				.await;
+		let rx_bob = send_network_dispute_request(req_tx, bob, bob_message.clone().into()).await;
+		let bob_import = expect_import(&mut handle, &bob_message.into(), false).await;
+		bob_import.send(ImportStatementsResult::ValidImport).unwrap();
+		assert_confirmed(rx_bob).await;
+
+		// Charlie sends garbage signatures and gets banned:
+		let mut bad_request: DisputeRequest =
+			make_dispute_message(make_candidate_receipt(relay_parent), ALICE_INDEX, FERDIE_INDEX)
+				.await
+				.into();
+		bad_request.0.candidate_receipt.commitments_hash = Hash::random();
+		let rx_charlie = send_network_dispute_request(req_tx, charlie, bad_request.clone()).await;
+		assert_punished(rx_charlie, "Signatures were invalid.").await;
+		assert_dropped(send_network_dispute_request(req_tx, charlie, bad_request).await).await;
+
+		// Alice's dispute turns out to be invalid, all of her queued requests get dropped:
+		alice_import.send(ImportStatementsResult::InvalidImport).unwrap();
+		assert_punished(rx_alice, "Reported candidate was not available.").await;
+		for rx in rx_queued {
+			assert_dropped(rx).await;
+		}
+
+		conclude(&mut handle).await;
+	};
+	test_harness(test);
+}
+
 #[test]
 fn disputes_are_recovered_at_startup() {
 	let test = |mut handle: TestSubsystemContextHandle<DisputeDistributionMessage>, _| async move {
@@ -481,6 +505,69 @@ async fn send_network_dispute_request(
 	rx_response
 }
 
+/// Expect an import of the votes in the given request and return the confirmation sender.
+async fn expect_import(
+	handle: &mut TestSubsystemContextHandle<DisputeDistributionMessage>,
+	message: &DisputeRequest,
+	need_session_info: bool,
+) -> oneshot::Sender<ImportStatementsResult> {
+	if need_session_info {
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				_,
+				RuntimeApiRequest::SessionInfo(_, tx),
+			)) => {
+				tx.send(Ok(Some(MOCK_SESSION_INFO.clone())))
+					.expect("Receiver should stay alive.");
+			}
+		);
+	}
+	assert_matches!(
+		handle.recv().await,
+		AllMessages::DisputeCoordinator(
+			DisputeCoordinatorMessage::ImportStatements {
+				candidate_hash,
+				pending_confirmation,
+				..
+			}
+		) => {
+			assert_eq!(candidate_hash, message.0.candidate_receipt.hash());
+			pending_confirmation
+		}
+	)
+}
+
+/// The request got confirmed.
+async fn assert_confirmed(rx_response: oneshot::Receiver<sc_network::config::OutgoingResponse>) {
+	assert_matches!(
+		rx_response.await,
+		Ok(sc_network::config::OutgoingResponse { result: Ok(result), .. }) => {
+			let decoded = <DisputeResponse as Decode>::decode(&mut result.as_slice()).unwrap();
+			assert!(decoded == DisputeResponse::Confirmed);
+		}
+	);
+}
+
+/// The request got rejected and the peer punished for the given reason.
+async fn assert_punished(
+	rx_response: oneshot::Receiver<sc_network::config::OutgoingResponse>,
+	reason: &str,
+) {
+	assert_matches!(
+		rx_response.await,
+		Ok(sc_network::config::OutgoingResponse { result: Err(()), reputation_changes, .. }) => {
+			assert_eq!(reputation_changes.len(), 1);
+			assert_eq!(reputation_changes[0].reason, reason);
+		}
+	);
+}
+
+/// The request got dropped without a response.
+async fn assert_dropped(rx_response: oneshot::Receiver<sc_network::config::OutgoingResponse>) {
+	assert_matches!(rx_response.await, Err(oneshot::Canceled));
+}
+
 /// Send request and handle its reactions.
 ///
 /// Passed in function will be called while votes are still being imported.
@@ -16,6 +16,7 @@
 
 //! All peersets and protocols used for parachains.
 
+use super::ProtocolVersion;
 use sc_network::config::{NonDefaultSetConfig, SetConfig};
 use std::{
 	borrow::Cow,
@@ -23,6 +24,16 @@ use std::{
 };
 use strum::{EnumIter, IntoEnumIterator};
 
+/// The first version of the validation protocol.
+pub const VALIDATION_PROTOCOL_V1: ProtocolVersion = 1;
+
+/// The version of the validation protocol from which on peers accept availability bitfields
+/// replacing an earlier bitfield of the same validator. Its wire format is the one of version 1.
+pub const VALIDATION_PROTOCOL_V2: ProtocolVersion = 2;
+
+/// The first version of the collation protocol.
+pub const COLLATION_PROTOCOL_V1: ProtocolVersion = 1;
+
 /// The peer-sets and thus the protocols which are used for the network.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
 pub enum PeerSet {
@@ -56,7 +67,7 @@ impl PeerSet {
 		match self {
 			PeerSet::Validation => NonDefaultSetConfig {
 				notifications_protocol: protocol,
-				fallback_names: Vec::new(),
+				fallback_names: vec![self.get_versioned_protocol_name(VALIDATION_PROTOCOL_V1)],
 				max_notification_size,
 				set_config: sc_network::config::SetConfig {
 					// we allow full nodes to connect to validators for gossip
@@ -89,13 +100,46 @@ impl PeerSet {
 	}
 
 	/// Get the protocol name associated with each peer set as static str.
+	///
+	/// This is the name of the main version of the protocol, see `get_main_version`.
 	pub const fn get_protocol_name_static(self) -> &'static str {
 		match self {
-			PeerSet::Validation => "/diamond/validation/1",
+			PeerSet::Validation => "/diamond/validation/2",
 			PeerSet::Collation => "/diamond/collation/1",
 		}
 	}
 
+	/// Get the main version of the protocol of each peer set, which is negotiated with peers
+	/// supporting it. Peers which don't are connected with an earlier version, as listed in the
+	/// fallback names of the peer set.
+	pub const fn get_main_version(self) -> ProtocolVersion {
+		match self {
+			PeerSet::Validation => VALIDATION_PROTOCOL_V2,
+			PeerSet::Collation => COLLATION_PROTOCOL_V1,
+		}
+	}
+
+	/// Get the protocol name of the given version of the protocol of each peer set.
+	pub fn get_versioned_protocol_name(self, version: ProtocolVersion) -> Cow<'static, str> {
+		match self {
+			PeerSet::Validation => format!("/diamond/validation/{}", version).into(),
+			PeerSet::Collation => format!("/diamond/collation/{}", version).into(),
+		}
+	}
+
+	/// Get the version of the protocol of the peer set a peer got connected with, given the
+	/// fallback name negotiated with it, if any.
+	pub fn get_negotiated_version(
+		self,
+		negotiated_fallback: Option<&Cow<'static, str>>,
+	) -> Option<ProtocolVersion> {
+		match negotiated_fallback {
+			None => Some(self.get_main_version()),
+			Some(name) => (1..self.get_main_version())
+				.find(|version| &self.get_versioned_protocol_name(*version) == name),
+		}
+	}
+
 	/// Convert a peer set into a protocol name as understood by Substrate.
 	pub fn into_protocol_name(self) -> Cow<'static, str> {
 		self.get_protocol_name_static().into()
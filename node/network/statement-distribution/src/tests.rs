@@ -20,6 +20,7 @@ use futures::executor::{self, block_on};
 use futures_timer::Delay;
 use parity_scale_codec::{Decode, Encode};
 use diamond_node_network_protocol::{
+	peer_set::VALIDATION_PROTOCOL_V2,
 	request_response::{
 		v1::{StatementFetchingRequest, StatementFetchingResponse},
 		IncomingRequest, Recipient, Requests,
@@ -749,7 +750,12 @@ fn receiving_from_one_sends_to_another_and_to_candidate_backing() {
 		handle
 			.send(FromOverseer::Communication {
 				msg: StatementDistributionMessage::NetworkBridgeUpdateV1(
-					NetworkBridgeEvent::PeerConnected(peer_a.clone(), ObservedRole::Full, None),
+					NetworkBridgeEvent::PeerConnected(
+						peer_a.clone(),
+						ObservedRole::Full,
+						VALIDATION_PROTOCOL_V2,
+						None,
+					),
 				),
 			})
 			.await;
@@ -757,7 +763,12 @@ fn receiving_from_one_sends_to_another_and_to_candidate_backing() {
 		handle
 			.send(FromOverseer::Communication {
 				msg: StatementDistributionMessage::NetworkBridgeUpdateV1(
-					NetworkBridgeEvent::PeerConnected(peer_b.clone(), ObservedRole::Full, None),
+					NetworkBridgeEvent::PeerConnected(
+						peer_b.clone(),
+						ObservedRole::Full,
+						VALIDATION_PROTOCOL_V2,
+						None,
+					),
 				),
 			})
 			.await;
@@ -944,6 +955,7 @@ fn receiving_large_statement_from_one_sends_to_another_and_to_candidate_backing(
 					NetworkBridgeEvent::PeerConnected(
 						peer_a.clone(),
 						ObservedRole::Full,
+						VALIDATION_PROTOCOL_V2,
 						Some(Sr25519Keyring::Alice.public().into()),
 					),
 				),
@@ -956,6 +968,7 @@ fn receiving_large_statement_from_one_sends_to_another_and_to_candidate_backing(
 					NetworkBridgeEvent::PeerConnected(
 						peer_b.clone(),
 						ObservedRole::Full,
+						VALIDATION_PROTOCOL_V2,
 						Some(Sr25519Keyring::Bob.public().into()),
 					),
 				),
@@ -967,6 +980,7 @@ fn receiving_large_statement_from_one_sends_to_another_and_to_candidate_backing(
 					NetworkBridgeEvent::PeerConnected(
 						peer_c.clone(),
 						ObservedRole::Full,
+						VALIDATION_PROTOCOL_V2,
 						Some(Sr25519Keyring::Charlie.public().into()),
 					),
 				),
@@ -975,7 +989,12 @@ fn receiving_large_statement_from_one_sends_to_another_and_to_candidate_backing(
 		handle
 			.send(FromOverseer::Communication {
 				msg: StatementDistributionMessage::NetworkBridgeUpdateV1(
-					NetworkBridgeEvent::PeerConnected(peer_bad.clone(), ObservedRole::Full, None),
+					NetworkBridgeEvent::PeerConnected(
+						peer_bad.clone(),
+						ObservedRole::Full,
+						VALIDATION_PROTOCOL_V2,
+						None,
+					),
 				),
 			})
 			.await;
@@ -1444,6 +1463,7 @@ fn share_prioritizes_backing_group() {
 						NetworkBridgeEvent::PeerConnected(
 							peer,
 							ObservedRole::Full,
+							VALIDATION_PROTOCOL_V2,
 							Some(pair.public().into()),
 						),
 					),
@@ -1466,6 +1486,7 @@ fn share_prioritizes_backing_group() {
 					NetworkBridgeEvent::PeerConnected(
 						peer_a.clone(),
 						ObservedRole::Full,
+						VALIDATION_PROTOCOL_V2,
 						Some(Sr25519Keyring::Alice.public().into()),
 					),
 				),
@@ -1477,6 +1498,7 @@ fn share_prioritizes_backing_group() {
 					NetworkBridgeEvent::PeerConnected(
 						peer_b.clone(),
 						ObservedRole::Full,
+						VALIDATION_PROTOCOL_V2,
 						Some(Sr25519Keyring::Bob.public().into()),
 					),
 				),
@@ -1488,6 +1510,7 @@ fn share_prioritizes_backing_group() {
 					NetworkBridgeEvent::PeerConnected(
 						peer_c.clone(),
 						ObservedRole::Full,
+						VALIDATION_PROTOCOL_V2,
 						Some(Sr25519Keyring::Charlie.public().into()),
 					),
 				),
@@ -1496,7 +1519,12 @@ fn share_prioritizes_backing_group() {
 		handle
 			.send(FromOverseer::Communication {
 				msg: StatementDistributionMessage::NetworkBridgeUpdateV1(
-					NetworkBridgeEvent::PeerConnected(peer_bad.clone(), ObservedRole::Full, None),
+					NetworkBridgeEvent::PeerConnected(
+						peer_bad.clone(),
+						ObservedRole::Full,
+						VALIDATION_PROTOCOL_V2,
+						None,
+					),
 				),
 			})
 			.await;
@@ -1506,6 +1534,7 @@ fn share_prioritizes_backing_group() {
 					NetworkBridgeEvent::PeerConnected(
 						peer_other_group.clone(),
 						ObservedRole::Full,
+						VALIDATION_PROTOCOL_V2,
 						Some(Sr25519Keyring::Dave.public().into()),
 					),
 				),
@@ -1728,6 +1757,7 @@ fn peer_cant_flood_with_large_statements() {
 					NetworkBridgeEvent::PeerConnected(
 						peer_a.clone(),
 						ObservedRole::Full,
+						VALIDATION_PROTOCOL_V2,
 						Some(Sr25519Keyring::Alice.public().into()),
 					),
 				),
@@ -63,7 +63,7 @@ pub use {
 };
 
 #[cfg(feature = "full-node")]
-use diamond_subsystem::jaeger;
+pub use diamond_subsystem::jaeger;
 
 use std::{sync::Arc, time::Duration};
 
@@ -284,19 +284,16 @@ fn set_prometheus_registry(config: &mut Configuration) -> Result<(), Error> {
 	Ok(())
 }
 
-/// Initialize the `Jeager` collector. The destination must listen
-/// on the given address and port for `UDP` packets.
+/// Initialize the `Jeager` collector, which sends the collected spans
+/// to the exporters configured in `jaeger_config`.
 #[cfg(any(test, feature = "full-node"))]
-fn jaeger_launch_collector_with_agent(
+fn jaeger_launch_collector(
 	spawner: impl SpawnNamed,
 	config: &Configuration,
-	agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<jaeger::JaegerConfigBuilder>,
 ) -> Result<(), Error> {
-	if let Some(agent) = agent {
-		let cfg = jaeger::JaegerConfig::builder()
-			.agent(agent)
-			.named(&config.network.node_name)
-			.build();
+	if let Some(jaeger_config) = jaeger_config {
+		let cfg = jaeger_config.named(&config.network.node_name).build();
 
 		jaeger::Jaeger::new(cfg).launch(spawner)?;
 	}
@@ -342,7 +339,7 @@ where
 #[cfg(feature = "full-node")]
 fn new_partial_basics<RuntimeApi, ExecutorDispatch>(
 	config: &mut Configuration,
-	jaeger_agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<jaeger::JaegerConfigBuilder>,
 	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
 ) -> Result<Basics<RuntimeApi, ExecutorDispatch>, Error>
 where
@@ -387,7 +384,7 @@ where
 		)?;
 	let client = Arc::new(client);
 
-	jaeger_launch_collector_with_agent(task_manager.spawn_handle(), &*config, jaeger_agent)?;
+	jaeger_launch_collector(task_manager.spawn_handle(), &*config, jaeger_config)?;
 
 	let telemetry: Option<_> = telemetry.map(|(worker, telemetry)| {
 		if let Some(worker) = worker {
@@ -676,7 +673,7 @@ pub fn new_full<RuntimeApi, ExecutorDispatch, OverseerGenerator>(
 	is_collator: IsCollator,
 	grandpa_pause: Option<(u32, u32)>,
 	disable_beefy: bool,
-	jaeger_agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<jaeger::JaegerConfigBuilder>,
 	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
 	program_path: Option<std::path::PathBuf>,
 	overseer_gen: OverseerGenerator,
@@ -717,7 +714,7 @@ where
 
 	let basics = new_partial_basics::<RuntimeApi, ExecutorDispatch>(
 		&mut config,
-		jaeger_agent,
+		jaeger_config,
 		telemetry_worker_handle,
 	)?;
 
@@ -939,6 +936,8 @@ where
 					candidate_validation_config,
 					chain_selection_config,
 					dispute_coordinator_config,
+					col_session_info_archive: crate::parachains_db::REAL_COLUMNS
+						.col_session_info_archive,
 				},
 			)?;
 
@@ -1284,13 +1283,13 @@ where
 }
 
 macro_rules! chain_ops {
-	($config:expr, $jaeger_agent:expr, $telemetry_worker_handle:expr; $scope:ident, $executor:ident, $variant:ident) => {{
+	($config:expr, $jaeger_config:expr, $telemetry_worker_handle:expr; $scope:ident, $executor:ident, $variant:ident) => {{
 		let telemetry_worker_handle = $telemetry_worker_handle;
-		let jaeger_agent = $jaeger_agent;
+		let jaeger_config = $jaeger_config;
 		let mut config = $config;
 		let basics = new_partial_basics::<$scope::RuntimeApi, $executor>(
 			config,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 		)?;
 
@@ -1312,7 +1311,7 @@ macro_rules! chain_ops {
 #[cfg(feature = "full-node")]
 pub fn new_chain_ops(
 	mut config: &mut Configuration,
-	jaeger_agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<jaeger::JaegerConfigBuilder>,
 ) -> Result<
 	(
 		Arc<Client>,
@@ -1328,22 +1327,22 @@ pub fn new_chain_ops(
 
 	#[cfg(feature = "titan-native")]
 	if config.chain_spec.is_titan() || config.chain_spec.is_wococo() {
-		return chain_ops!(config, jaeger_agent, telemetry_worker_handle; titan_runtime, TitanExecutorDispatch, Titan)
+		return chain_ops!(config, jaeger_config, telemetry_worker_handle; titan_runtime, TitanExecutorDispatch, Titan)
 	}
 
 	#[cfg(feature = "gold-native")]
 	if config.chain_spec.is_gold() {
-		return chain_ops!(config, jaeger_agent, telemetry_worker_handle; gold_runtime, GoldExecutorDispatch, Gold)
+		return chain_ops!(config, jaeger_config, telemetry_worker_handle; gold_runtime, GoldExecutorDispatch, Gold)
 	}
 
 	#[cfg(feature = "ruby-native")]
 	if config.chain_spec.is_ruby() {
-		return chain_ops!(config, jaeger_agent, telemetry_worker_handle; ruby_runtime, RubyExecutorDispatch, Ruby)
+		return chain_ops!(config, jaeger_config, telemetry_worker_handle; ruby_runtime, RubyExecutorDispatch, Ruby)
 	}
 
 	#[cfg(feature = "diamond-native")]
 	{
-		chain_ops!(config, jaeger_agent, telemetry_worker_handle; diamond_runtime, DiamondExecutorDispatch, Diamond)
+		chain_ops!(config, jaeger_config, telemetry_worker_handle; diamond_runtime, DiamondExecutorDispatch, Diamond)
 	}
 
 	#[cfg(not(feature = "diamond-native"))]
@@ -1383,7 +1382,7 @@ pub fn build_full(
 	is_collator: IsCollator,
 	grandpa_pause: Option<(u32, u32)>,
 	disable_beefy: bool,
-	jaeger_agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<jaeger::JaegerConfigBuilder>,
 	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
 	overseer_gen: impl OverseerGen,
 ) -> Result<NewFull<Client>, Error> {
@@ -1394,7 +1393,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			disable_beefy,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 			None,
 			overseer_gen,
@@ -1409,7 +1408,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			disable_beefy,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 			None,
 			overseer_gen,
@@ -1424,7 +1423,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			disable_beefy,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 			None,
 			overseer_gen,
@@ -1439,7 +1438,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			disable_beefy,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 			None,
 			overseer_gen,
@@ -59,7 +59,7 @@ pub use diamond_node_core_chain_selection::ChainSelectionSubsystem;
 pub use diamond_node_core_dispute_coordinator::DisputeCoordinatorSubsystem;
 pub use diamond_node_core_dispute_participation::DisputeParticipationSubsystem;
 pub use diamond_node_core_provisioner::ProvisioningSubsystem as ProvisionerSubsystem;
-pub use diamond_node_core_runtime_api::RuntimeApiSubsystem;
+pub use diamond_node_core_runtime_api::{RuntimeApiSubsystem, SessionInfoArchive};
 pub use diamond_statement_distribution::StatementDistribution as StatementDistributionSubsystem;
 
 /// Arguments passed for overseer construction.
@@ -105,6 +105,8 @@ where
 	pub chain_selection_config: ChainSelectionConfig,
 	/// Configuration for the dispute coordinator subsystem.
 	pub dispute_coordinator_config: DisputeCoordinatorConfig,
+	/// The column of the parachains DB used to archive session info.
+	pub col_session_info_archive: u32,
 }
 
 /// Create a default, unaltered set of subsystems.
@@ -132,6 +134,7 @@ pub fn create_default_subsystems<'a, Spawner, RuntimeClient>(
 		candidate_validation_config,
 		chain_selection_config,
 		dispute_coordinator_config,
+		col_session_info_archive,
 		..
 	}: OverseerGenArgs<'a, Spawner, RuntimeClient>,
 ) -> Result<
@@ -230,7 +233,11 @@ where
 			runtime_client.clone(),
 			Metrics::register(registry)?,
 			spawner.clone(),
-		),
+		)
+		.with_session_info_archive(SessionInfoArchive::new(
+			parachains_db.clone(),
+			col_session_info_archive,
+		)),
 		statement_distribution: StatementDistributionSubsystem::new(
 			keystore.clone(),
 			statement_req_receiver,
@@ -287,6 +294,7 @@ pub fn prepared_overseer_builder<'a, Spawner, RuntimeClient>(
 		candidate_validation_config,
 		chain_selection_config,
 		dispute_coordinator_config,
+		col_session_info_archive,
 	}: OverseerGenArgs<'a, Spawner, RuntimeClient>,
 ) -> Result<
 	OverseerBuilder<
@@ -385,11 +393,17 @@ where
 			Metrics::register(registry)?,
 		))
 		.provisioner(ProvisionerSubsystem::new(spawner.clone(), (), Metrics::register(registry)?))
-		.runtime_api(RuntimeApiSubsystem::new(
-			runtime_client.clone(),
-			Metrics::register(registry)?,
-			spawner.clone(),
-		))
+		.runtime_api(
+			RuntimeApiSubsystem::new(
+				runtime_client.clone(),
+				Metrics::register(registry)?,
+				spawner.clone(),
+			)
+			.with_session_info_archive(SessionInfoArchive::new(
+				parachains_db.clone(),
+				col_session_info_archive,
+			)),
+		)
 		.statement_distribution(StatementDistributionSubsystem::new(
 			keystore.clone(),
 			statement_req_receiver,
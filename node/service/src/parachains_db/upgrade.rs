@@ -27,7 +27,7 @@ type Version = u32;
 const VERSION_FILE_NAME: &'static str = "parachain_db_version";
 
 /// Current db version.
-const CURRENT_VERSION: Version = 1;
+const CURRENT_VERSION: Version = 2;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -53,7 +53,11 @@ pub fn try_upgrade_db(db_path: &Path) -> Result<(), Error> {
 	let is_empty = db_path.read_dir().map_or(true, |mut d| d.next().is_none());
 	if !is_empty {
 		match current_version(db_path)? {
-			0 => migrate_from_version_0_to_1(db_path)?,
+			0 => {
+				migrate_from_version_0_to_1(db_path)?;
+				migrate_from_version_1_to_2(db_path)?;
+			},
+			1 => migrate_from_version_1_to_2(db_path)?,
 			CURRENT_VERSION => (),
 			v => return Err(Error::FutureVersion { current: CURRENT_VERSION, got: v }),
 		}
@@ -102,3 +106,19 @@ fn migrate_from_version_0_to_1(path: &Path) -> Result<(), Error> {
 
 	Ok(())
 }
+
+/// Migration from version 1 to version 2:
+/// * the number of columns has changed from 5 to 6;
+fn migrate_from_version_1_to_2(path: &Path) -> Result<(), Error> {
+	use kvdb_rocksdb::{Database, DatabaseConfig};
+
+	let db_path = path
+		.to_str()
+		.ok_or_else(|| super::other_io_error("Invalid database path".into()))?;
+	let db_cfg = DatabaseConfig::with_columns(super::columns::v1::NUM_COLUMNS);
+	let db = Database::open(&db_cfg, db_path)?;
+
+	db.add_column()?;
+
+	Ok(())
+}
@@ -40,11 +40,11 @@ use diamond_node_primitives::{
 use diamond_primitives::v1::{
 	AuthorityDiscoveryId, BackedCandidate, BlockNumber, CandidateDescriptor, CandidateEvent,
 	CandidateHash, CandidateIndex, CandidateReceipt, CollatorId, CommittedCandidateReceipt,
-	CoreState, GroupIndex, GroupRotationInfo, Hash, Header as BlockHeader, Id as ParaId,
-	InboundDownwardMessage, InboundHrmpMessage, MultiDisputeStatementSet, OccupiedCoreAssumption,
-	PersistedValidationData, SessionIndex, SessionInfo, SignedAvailabilityBitfield,
-	SignedAvailabilityBitfields, ValidationCode, ValidationCodeHash, ValidatorId, ValidatorIndex,
-	ValidatorSignature,
+	CoreState, GroupIndex, GroupRotationInfo, Hash, Header as BlockHeader, HrmpParaState,
+	Id as ParaId, InboundDownwardMessage, InboundHrmpMessage, MultiDisputeStatementSet,
+	OccupiedCoreAssumption, PersistedValidationData, SessionIndex, SessionInfo,
+	SignedAvailabilityBitfield, SignedAvailabilityBitfields, ValidationCode, ValidationCodeHash,
+	ValidatorId, ValidatorIndex, ValidatorSignature,
 };
 use diamond_statement_table::v1::Misbehavior;
 use std::{
@@ -624,6 +624,9 @@ pub enum RuntimeApiRequest {
 		ParaId,
 		RuntimeApiSender<BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>>>,
 	),
+	/// Get the state of the HRMP channels and channel requests of a para, along with its
+	/// watermark.
+	HrmpState(ParaId, RuntimeApiSender<HrmpParaState<BlockNumber>>),
 	/// Get information about the BABE epoch the block was included in.
 	CurrentBabeEpoch(RuntimeApiSender<BabeEpoch>),
 }
@@ -633,6 +636,10 @@ pub enum RuntimeApiRequest {
 pub enum RuntimeApiMessage {
 	/// Make a request of the runtime API against the post-state of the given relay-parent.
 	Request(Hash, RuntimeApiRequest),
+	/// Get the info of the given session from the archive of every session info the node has
+	/// fetched, independently of the runtime state. This is useful for sessions which have been
+	/// pruned from the runtime state already.
+	SessionInfoArchive(SessionIndex, oneshot::Sender<Option<SessionInfo>>),
 }
 
 impl RuntimeApiMessage {
@@ -640,6 +647,7 @@ impl RuntimeApiMessage {
 	pub fn relay_parent(&self) -> Option<Hash> {
 		match self {
 			Self::Request(hash, _) => Some(*hash),
+			Self::SessionInfoArchive(..) => None,
 		}
 	}
 }
@@ -18,14 +18,14 @@ use std::{collections::HashSet, convert::TryFrom};
 
 pub use sc_network::{PeerId, ReputationChange};
 
-use diamond_node_network_protocol::{ObservedRole, OurView, View, WrongVariant};
+use diamond_node_network_protocol::{ObservedRole, OurView, ProtocolVersion, View, WrongVariant};
 use diamond_primitives::v1::AuthorityDiscoveryId;
 
 /// Events from network.
 #[derive(Debug, Clone, PartialEq)]
 pub enum NetworkBridgeEvent<M> {
-	/// A peer has connected.
-	PeerConnected(PeerId, ObservedRole, Option<AuthorityDiscoveryId>),
+	/// A peer has connected, using the given version of the protocol of the peer set.
+	PeerConnected(PeerId, ObservedRole, ProtocolVersion, Option<AuthorityDiscoveryId>),
 
 	/// A peer has disconnected.
 	PeerDisconnected(PeerId),
@@ -73,8 +73,13 @@ impl<M> NetworkBridgeEvent<M> {
 		Ok(match *self {
 			NetworkBridgeEvent::PeerMessage(ref peer, ref msg) =>
 				NetworkBridgeEvent::PeerMessage(peer.clone(), <&'a T>::try_from(msg)?.clone()),
-			NetworkBridgeEvent::PeerConnected(ref peer, ref role, ref authority_id) =>
-				NetworkBridgeEvent::PeerConnected(peer.clone(), role.clone(), authority_id.clone()),
+			NetworkBridgeEvent::PeerConnected(ref peer, ref role, version, ref authority_id) =>
+				NetworkBridgeEvent::PeerConnected(
+					peer.clone(),
+					role.clone(),
+					version,
+					authority_id.clone(),
+				),
 			NetworkBridgeEvent::PeerDisconnected(ref peer) =>
 				NetworkBridgeEvent::PeerDisconnected(peer.clone()),
 			NetworkBridgeEvent::NewGossipTopology(ref peers) =>
@@ -272,7 +272,10 @@ async fn load_all_sessions(
 
 		let session_info = match rx.await {
 			Ok(Ok(Some(s))) => s,
-			Ok(Ok(None)) => return Err(SessionsUnavailableKind::Missing),
+			Ok(Ok(None)) => match load_archived_session(ctx, i).await? {
+				Some(s) => s,
+				None => return Err(SessionsUnavailableKind::Missing),
+			},
 			Ok(Err(e)) => return Err(SessionsUnavailableKind::RuntimeApi(e)),
 			Err(canceled) => return Err(SessionsUnavailableKind::RuntimeApiUnavailable(canceled)),
 		};
@@ -283,6 +286,18 @@ async fn load_all_sessions(
 	Ok(v)
 }
 
+/// Load the info of a session which is not available in the runtime state anymore from the
+/// archive of the node.
+async fn load_archived_session(
+	ctx: &mut (impl SubsystemContext + overseer::SubsystemContext),
+	index: SessionIndex,
+) -> Result<Option<SessionInfo>, SessionsUnavailableKind> {
+	let (tx, rx) = oneshot::channel();
+	ctx.send_message(RuntimeApiMessage::SessionInfoArchive(index, tx)).await;
+
+	rx.await.map_err(SessionsUnavailableKind::RuntimeApiUnavailable)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -535,6 +550,15 @@ mod tests {
 					}
 				);
 			}
+
+			// the session is not archived either.
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::RuntimeApi(RuntimeApiMessage::SessionInfoArchive(s, s_tx)) => {
+					assert_eq!(s, session);
+					let _ = s_tx.send(None);
+				}
+			);
 		});
 
 		futures::executor::block_on(futures::future::join(test_fut, aux_fut));
@@ -596,4 +620,66 @@ mod tests {
 
 		futures::executor::block_on(futures::future::join(test_fut, aux_fut));
 	}
+
+	#[test]
+	fn session_info_pruned_from_state_is_loaded_from_archive() {
+		let session: SessionIndex = 0;
+
+		let header = Header {
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+			number: 0,
+			state_root: Default::default(),
+			parent_hash: Default::default(),
+		};
+
+		let pool = TaskExecutor::new();
+		let (mut ctx, mut handle) = make_subsystem_context::<(), _>(pool.clone());
+
+		let mut window = RollingSessionWindow::new(TEST_WINDOW_SIZE);
+		let hash = header.hash();
+
+		let test_fut = {
+			let header = header.clone();
+			Box::pin(async move {
+				window.cache_session_info_for_head(&mut ctx, hash, &header).await.unwrap();
+
+				assert_eq!(window.earliest_session, Some(session));
+				assert_eq!(window.session_info, vec![dummy_session_info(session)]);
+			})
+		};
+
+		let aux_fut = Box::pin(async move {
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+					_,
+					RuntimeApiRequest::SessionIndexForChild(s_tx),
+				)) => {
+					let _ = s_tx.send(Ok(session));
+				}
+			);
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+					_,
+					RuntimeApiRequest::SessionInfo(s, s_tx),
+				)) => {
+					assert_eq!(s, session);
+					let _ = s_tx.send(Ok(None));
+				}
+			);
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::RuntimeApi(RuntimeApiMessage::SessionInfoArchive(s, s_tx)) => {
+					assert_eq!(s, session);
+					let _ = s_tx.send(Some(dummy_session_info(s)));
+				}
+			);
+		});
+
+		futures::executor::block_on(futures::future::join(test_fut, aux_fut));
+	}
 }
@@ -202,6 +202,26 @@ pub mod well_known_keys {
 		})
 	}
 
+	/// The relay-chain block number at which the pending validation code upgrade of the para is
+	/// expected. The upgrade is applied by the first parablock executed in the context of a
+	/// relay-chain block with a number greater or equal to it.
+	///
+	/// The storage entry is absent when there is no pending upgrade and otherwise stores a value
+	/// of `BlockNumber` type.
+	pub fn future_code_upgrade_at(para_id: Id) -> Vec<u8> {
+		let prefix = hex!["cd710b30bd2eab0352ddcc26417aa19440a2e3ef2d6b1b7b64ce36356e02087e"];
+
+		para_id.using_encoded(|para_id: &[u8]| {
+			prefix
+				.as_ref()
+				.iter()
+				.chain(twox_64(para_id).iter())
+				.chain(para_id.iter())
+				.cloned()
+				.collect()
+		})
+	}
+
 	/// The signal that indicates whether the parachain is disallowed to signal an upgrade at this
 	/// relay-parent.
 	///
@@ -953,6 +973,9 @@ impl ApprovalVote {
 
 sp_api::decl_runtime_apis! {
 	/// The API for querying the state of parachains on-chain.
+	///
+	/// Version 2 added `hrmp_state`, see [`HRMP_STATE_API_VERSION`].
+	#[api_version(2)]
 	pub trait ParachainHost<H: Decode = Hash, N: Encode + Decode = BlockNumber> {
 		/// Get the current validators.
 		fn validators() -> Vec<ValidatorId>;
@@ -1010,9 +1033,19 @@ sp_api::decl_runtime_apis! {
 
 		/// Get the validation code from its hash.
 		fn validation_code_by_hash(hash: ValidationCodeHash) -> Option<ValidationCode>;
+
+		/// Get the state of the HRMP channels and channel requests of the given para, along with
+		/// its watermark.
+		///
+		/// Only available from version [`HRMP_STATE_API_VERSION`] of the API on.
+		fn hrmp_state(para_id: Id) -> HrmpParaState<N>;
 	}
 }
 
+/// The version of the `ParachainHost` API which added `hrmp_state`. Callers have to check that a
+/// runtime implements at least this version before calling it.
+pub const HRMP_STATE_API_VERSION: u32 = 2;
+
 /// Custom validity errors used in diamond while validating transactions.
 #[repr(u8)]
 pub enum ValidityError {
@@ -1024,6 +1057,10 @@ pub enum ValidityError {
 	NoPermission = 2,
 	/// An invalid statement was made for a claim.
 	InvalidStatement = 3,
+	/// The signature of a claim from a Merkle root is invalid.
+	InvalidSignature = 4,
+	/// The Merkle proof of a claim is invalid.
+	InvalidMerkleProof = 5,
 }
 
 impl From<ValidityError> for u8 {
@@ -1092,6 +1129,66 @@ pub struct AbridgedHrmpChannel {
 	pub mqc_head: Option<Hash>,
 }
 
+/// The state of an HRMP channel, as seen by the paras at either of its ends.
+#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(PartialEq, MallocSizeOf))]
+pub struct HrmpChannelState {
+	/// The para sending messages over the channel.
+	pub sender: Id,
+	/// The para receiving messages over the channel.
+	pub recipient: Id,
+	/// The maximum number of messages that can be pending in the channel at once.
+	pub max_capacity: u32,
+	/// The maximum total size of the messages that can be pending in the channel at once.
+	pub max_total_size: u32,
+	/// The maximum message size that could be put into the channel.
+	pub max_message_size: u32,
+	/// The current number of messages pending in the channel.
+	pub msg_count: u32,
+	/// The total size in bytes of all message payloads in the channel.
+	pub total_size: u32,
+	/// The head of the Message Queue Chain for this channel, if any message was ever sent.
+	pub mqc_head: Option<Hash>,
+	/// The sizes in bytes of the messages pending in the channel, oldest first.
+	pub message_sizes: Vec<u32>,
+}
+
+/// A request to open an HRMP channel which is yet to be enacted.
+#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(PartialEq, MallocSizeOf))]
+pub struct HrmpOpenChannelRequestState {
+	/// The para which requested to open the channel.
+	pub sender: Id,
+	/// The para the channel is requested to.
+	pub recipient: Id,
+	/// Whether the recipient accepted the request. Accepted requests are enacted at the next
+	/// session change.
+	pub confirmed: bool,
+	/// The requested maximum number of messages pending in the channel at once.
+	pub max_capacity: u32,
+	/// The requested maximum total size of the messages pending in the channel at once.
+	pub max_total_size: u32,
+	/// The requested maximum size of a single message.
+	pub max_message_size: u32,
+}
+
+/// The state of all HRMP channels and requests concerning a para.
+#[derive(Clone, Encode, Decode, Default, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(PartialEq, MallocSizeOf))]
+pub struct HrmpParaState<N = BlockNumber> {
+	/// The channels over which the para receives messages.
+	pub inbound: Vec<HrmpChannelState>,
+	/// The channels over which the para sends messages.
+	pub outbound: Vec<HrmpChannelState>,
+	/// The requests to open a channel from or to the para, in the order they are processed.
+	pub open_requests: Vec<HrmpOpenChannelRequestState>,
+	/// The channels from or to the para requested to be closed at the next session change, as
+	/// `(sender, recipient)` pairs.
+	pub close_requests: Vec<(Id, Id)>,
+	/// The relay-chain block number up to which the para has processed its inbound messages.
+	pub watermark: Option<N>,
+}
+
 /// A possible upgrade restriction that prevents a parachain from performing an upgrade.
 #[derive(Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
 pub enum UpgradeRestriction {
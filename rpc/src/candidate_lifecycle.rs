@@ -0,0 +1,133 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC access to the candidate lifecycles tracked by this node.
+
+use diamond_node_jaeger::lifecycle::{self, LATENCY_BUCKETS_MS};
+use diamond_primitives::v1::{CandidateHash, Hash};
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use serde::Serialize;
+
+/// The time a candidate first reached a stage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageObservation {
+	/// The name of the stage.
+	pub stage: &'static str,
+	/// Milliseconds since the unix epoch.
+	pub at: u64,
+}
+
+/// The lifecycle of a candidate as observed by this node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateLifecycle {
+	/// The para the candidate belongs to, if known.
+	pub para_id: Option<u32>,
+	/// The reached stages, in order.
+	pub stages: Vec<StageObservation>,
+}
+
+/// A single bucket of a latency histogram.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyBucket {
+	/// The inclusive upper bound in milliseconds, `None` for the overflow bucket.
+	pub le_ms: Option<u64>,
+	/// Number of observations in this bucket.
+	pub count: u64,
+}
+
+/// The latency histogram of a stage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageLatencies {
+	/// The name of the stage.
+	pub stage: &'static str,
+	/// The histogram buckets, not cumulative.
+	pub buckets: Vec<LatencyBucket>,
+	/// The total number of observations.
+	pub count: u64,
+	/// The sum of all observed latencies in milliseconds.
+	pub sum_ms: u64,
+}
+
+/// Candidate lifecycle RPC methods.
+#[rpc]
+pub trait CandidateLifecycleApi {
+	/// Get the lifecycle of a candidate by its hash.
+	///
+	/// Returns `None` if the candidate was not observed by this node recently.
+	#[rpc(name = "parachain_candidateLifecycle")]
+	fn candidate_lifecycle(&self, candidate_hash: Hash) -> Result<Option<CandidateLifecycle>>;
+
+	/// Get the latency histograms of all candidate stages.
+	///
+	/// The latency of a stage is measured from the preceding stage observed
+	/// for the same candidate.
+	#[rpc(name = "parachain_candidateStageLatencies")]
+	fn stage_latencies(&self) -> Result<Vec<StageLatencies>>;
+}
+
+/// Implements the [`CandidateLifecycleApi`] on top of the in-node tracker.
+#[derive(Default)]
+pub struct CandidateLifecycleRpcHandler;
+
+impl CandidateLifecycleRpcHandler {
+	/// Create a new handler.
+	pub fn new() -> Self {
+		Self
+	}
+}
+
+impl CandidateLifecycleApi for CandidateLifecycleRpcHandler {
+	fn candidate_lifecycle(&self, candidate_hash: Hash) -> Result<Option<CandidateLifecycle>> {
+		Ok(lifecycle::candidate_lifecycle(&CandidateHash(candidate_hash)).map(|tracked| {
+			CandidateLifecycle {
+				para_id: tracked.para_id.map(u32::from),
+				stages: tracked
+					.stages
+					.iter()
+					.map(|(stage, at)| StageObservation {
+						stage: stage.as_str(),
+						at: lifecycle::unix_millis(*at),
+					})
+					.collect(),
+			}
+		}))
+	}
+
+	fn stage_latencies(&self) -> Result<Vec<StageLatencies>> {
+		Ok(lifecycle::stage_latencies()
+			.into_iter()
+			.map(|(stage, histogram)| StageLatencies {
+				stage: stage.as_str(),
+				buckets: histogram
+					.buckets
+					.iter()
+					.enumerate()
+					.map(|(i, count)| LatencyBucket {
+						le_ms: LATENCY_BUCKETS_MS.get(i).copied(),
+						count: *count,
+					})
+					.collect(),
+				count: histogram.count,
+				sum_ms: histogram.sum_ms,
+			})
+			.collect())
+	}
+}
@@ -37,6 +37,10 @@ use sp_consensus_babe::BabeApi;
 use sp_keystore::SyncCryptoStorePtr;
 use txpool_api::TransactionPool;
 
+mod candidate_lifecycle;
+
+pub use candidate_lifecycle::{CandidateLifecycleApi, CandidateLifecycleRpcHandler};
+
 /// A type representing all RPC extensions.
 pub type RpcExtension = jsonrpc_core::IoHandler<sc_rpc::Metadata>;
 
@@ -177,6 +181,8 @@ where
 		),
 	));
 
+	io.extend_with(CandidateLifecycleApi::to_delegate(CandidateLifecycleRpcHandler::new()));
+
 	Ok(io)
 }
 
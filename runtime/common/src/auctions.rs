@@ -29,11 +29,19 @@ use frame_support::{
 	weights::Weight,
 };
 pub use pallet::*;
-use parity_scale_codec::Decode;
+use parity_scale_codec::{Decode, Encode};
 use primitives::v1::Id as ParaId;
-use sp_runtime::traits::{CheckedSub, One, Saturating, Zero};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{CheckedSub, Hash as HashT, One, Saturating, Zero},
+	RuntimeDebug, SaturatedConversion,
+};
 use sp_std::{mem::swap, prelude::*};
 
+#[cfg(feature = "std")]
+pub mod replay;
+pub mod runtime_api;
+
 type CurrencyOf<T> = <<T as Config>::Leaser as Leaser>::Currency;
 type BalanceOf<T> = <<<T as Config>::Leaser as Leaser>::Currency as Currency<
 	<T as frame_system::Config>::AccountId,
@@ -73,6 +81,30 @@ type WinningData<T> = [Option<(<T as frame_system::Config>::AccountId, ParaId, B
 // index assigned to them, their winning bid and the range that they won.
 type WinnersData<T> =
 	Vec<(<T as frame_system::Config>::AccountId, ParaId, BalanceOf<T>, SlotRange)>;
+// Winning samples data type. This encodes the top bidders of each range at each sample of an
+// ending period, ordered by sample.
+type WinningSamples<T> = Vec<(
+	<T as frame_system::Config>::BlockNumber,
+	Vec<Option<(<T as frame_system::Config>::AccountId, ParaId, BalanceOf<T>)>>,
+)>;
+
+/// How the winning sample of an auction was chosen.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct AuctionOutcome<BlockNumber, Hash> {
+	/// The first lease period of the four that were auctioned.
+	pub lease_period_index: BlockNumber,
+	/// The first block of the ending period.
+	pub ending: BlockNumber,
+	/// The sample of the ending period whose winning bids won the auction.
+	pub sample: BlockNumber,
+	/// The randomness the sample was chosen with.
+	pub randomness: Hash,
+	/// The block number since which the randomness has been known.
+	pub known_since: BlockNumber,
+	/// The commitment to the winning bids at every sample of the ending period, see
+	/// [`Pallet::commit_sample`].
+	pub samples_commitment: Hash,
+}
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -109,6 +141,10 @@ pub mod pallet {
 		/// Something that provides randomness in the runtime.
 		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
 
+		/// The number of past auctions whose winning bids at the chosen sample are retained.
+		#[pallet::constant]
+		type HistoryDepth: Get<u32>;
+
 		/// The origin which may initiate auctions.
 		type InitiateOrigin: EnsureOrigin<Self::Origin>;
 
@@ -140,6 +176,9 @@ pub mod pallet {
 		/// The winning offset was chosen for an auction. This will map into the `Winning` storage map.
 		/// `[auction_index, block_number]`
 		WinningOffset(AuctionIndex, T::BlockNumber),
+		/// The winning sample of an auction was chosen with randomness known since some block.
+		/// `[auction_index, sample, randomness, known_since]`
+		SampleChosen(AuctionIndex, T::BlockNumber, T::Hash, T::BlockNumber),
 	}
 
 	#[pallet::error]
@@ -188,6 +227,18 @@ pub mod pallet {
 	#[pallet::getter(fn winning)]
 	pub type Winning<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, WinningData<T>>;
 
+	/// The winning bids for each of the 10 ranges at the chosen sample of the last `HistoryDepth`
+	/// auctions. The bids at the other samples are only retained as a commitment in the outcome of
+	/// the auction.
+	#[pallet::storage]
+	pub type WinningHistory<T: Config> = StorageMap<_, Twox64Concat, AuctionIndex, WinningData<T>>;
+
+	/// How the winning sample of each of the last `HistoryDepth` auctions was chosen.
+	#[pallet::storage]
+	#[pallet::getter(fn auction_outcome)]
+	pub type AuctionOutcomes<T: Config> =
+		StorageMap<_, Twox64Concat, AuctionIndex, AuctionOutcome<T::BlockNumber, T::Hash>>;
+
 	#[pallet::extra_constants]
 	impl<T: Config> Pallet<T> {
 		//TODO: rename to snake case after https://github.com/paritytech/substrate/issues/8826 fixed.
@@ -229,6 +280,13 @@ pub mod pallet {
 				// acts as the offset. Handle it.
 				Self::manage_auction_end(auction_lease_period_index, winning_ranges);
 				weight = weight.saturating_add(T::WeightInfo::on_initialize());
+				// Reading the samples to commit to them, on top of removing them, and retaining the
+				// outcome while pruning that of an older auction.
+				let samples: Weight = (T::EndingPeriod::get() /
+					T::SampleLength::get().max(One::one()))
+				.saturated_into::<Weight>()
+				.saturating_add(1);
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(samples, 4));
 			}
 
 			weight
@@ -528,11 +586,25 @@ impl<T: Config> Pallet<T> {
 
 					let auction_counter = AuctionCounter::<T>::get();
 					Self::deposit_event(Event::<T>::WinningOffset(auction_counter, offset));
-					let res = Winning::<T>::get(offset)
-						.unwrap_or([Self::EMPTY; SlotRange::SLOT_RANGE_COUNT]);
-					// This `remove_all` statement should remove at most `EndingPeriod` / `SampleLength` items,
-					// which should be bounded and sensibly configured in the runtime.
-					Winning::<T>::remove_all(None);
+					Self::deposit_event(Event::<T>::SampleChosen(
+						auction_counter,
+						offset,
+						raw_offset,
+						known_since,
+					));
+					let (res, samples_commitment) = Self::take_samples(offset);
+					Self::retain_outcome(
+						auction_counter,
+						&res,
+						AuctionOutcome {
+							lease_period_index,
+							ending: early_end,
+							sample: offset,
+							randomness: raw_offset,
+							known_since,
+							samples_commitment,
+						},
+					);
 					AuctionInfo::<T>::kill();
 					return Some((res, lease_period_index))
 				}
@@ -541,6 +613,84 @@ impl<T: Config> Pallet<T> {
 		None
 	}
 
+	/// Remove the winning bids at each sample of the ending period of the auction which just
+	/// ended, returning those at the chosen `sample` along with the commitment to all of them.
+	fn take_samples(sample: T::BlockNumber) -> (WinningData<T>, T::Hash) {
+		let last_sample = T::EndingPeriod::get().saturating_sub(One::one()) /
+			T::SampleLength::get().max(One::one());
+		let mut commitment = T::Hash::default();
+		let mut winning = [Self::EMPTY; SlotRange::SLOT_RANGE_COUNT];
+		let mut chosen = winning.clone();
+		let mut current = Zero::zero();
+		// This loop should take at most `EndingPeriod` / `SampleLength` items, which should be
+		// bounded and sensibly configured in the runtime.
+		while current <= last_sample {
+			// A missing sample carries over the bids of the previous one.
+			if let Some(taken) = Winning::<T>::take(current) {
+				winning = taken;
+			}
+			commitment = Self::commit_sample(commitment, current, &winning);
+			if current == sample {
+				chosen = winning.clone();
+			}
+			current = current.saturating_add(One::one());
+		}
+		(chosen, commitment)
+	}
+
+	/// Extend the `commitment` to the winning bids at the samples of an ending period before
+	/// `sample` with the `winning` bids at `sample`.
+	///
+	/// The commitment to the samples of an auction starts out as the default hash and is extended
+	/// with each sample of its ending period in order.
+	pub fn commit_sample(
+		commitment: T::Hash,
+		sample: T::BlockNumber,
+		winning: &WinningData<T>,
+	) -> T::Hash {
+		(commitment, sample, winning).using_encoded(T::Hashing::hash)
+	}
+
+	/// Retain the `winning` bids at the chosen sample and the outcome of the auction which just
+	/// ended, forgetting those of the auction which falls out of the history.
+	fn retain_outcome(
+		auction_index: AuctionIndex,
+		winning: &WinningData<T>,
+		outcome: AuctionOutcome<T::BlockNumber, T::Hash>,
+	) {
+		WinningHistory::<T>::insert(auction_index, winning);
+		AuctionOutcomes::<T>::insert(auction_index, outcome);
+
+		if let Some(expired) = auction_index.checked_sub(T::HistoryDepth::get()) {
+			WinningHistory::<T>::remove(expired);
+			AuctionOutcomes::<T>::remove(expired);
+		}
+	}
+
+	/// The winning bids for each range at each sample of the ending period of the auction
+	/// `auction_index`, ordered by sample.
+	///
+	/// These are the samples so far of the current auction, or the chosen sample of a past one.
+	pub fn winning_samples(auction_index: AuctionIndex) -> WinningSamples<T> {
+		let current = auction_index == AuctionCounter::<T>::get() && AuctionInfo::<T>::exists();
+		let mut samples = if current {
+			Winning::<T>::iter().collect::<Vec<_>>()
+		} else {
+			match (
+				WinningHistory::<T>::get(auction_index),
+				AuctionOutcomes::<T>::get(auction_index),
+			) {
+				(Some(winning), Some(outcome)) => vec![(outcome.sample, winning)],
+				_ => Vec::new(),
+			}
+		};
+		samples.sort_by_key(|(sample, _)| *sample);
+		samples
+			.into_iter()
+			.map(|(sample, winning)| (sample, winning.to_vec()))
+			.collect()
+	}
+
 	/// Auction just ended. We have the current lease period, the auction's lease period (which
 	/// is guaranteed to be at least the current period) and the bidders that were winning each
 	/// range at the time of the auction's close.
@@ -826,6 +976,7 @@ mod tests {
 	parameter_types! {
 		pub static EndingPeriod: BlockNumber = 3;
 		pub static SampleLength: BlockNumber = 1;
+		pub static HistoryDepth: u32 = 2;
 	}
 
 	impl Config for Test {
@@ -835,6 +986,7 @@ mod tests {
 		type EndingPeriod = EndingPeriod;
 		type SampleLength = SampleLength;
 		type Randomness = TestPastRandomness;
+		type HistoryDepth = HistoryDepth;
 		type InitiateOrigin = RootOrSix;
 		type WeightInfo = crate::auctions::TestWeightInfo;
 	}
@@ -1578,6 +1730,131 @@ mod tests {
 		});
 	}
 
+	fn take_auction_events() -> Vec<(BlockNumber, super::Event<Test>)> {
+		let block_number = System::block_number();
+		let events = System::events()
+			.into_iter()
+			.filter_map(|record| match record.event {
+				Event::Auctions(event) => Some((block_number, event)),
+				_ => None,
+			})
+			.collect();
+		System::reset_events();
+		events
+	}
+
+	#[test]
+	fn auctions_can_be_replayed_from_events() {
+		new_test_ext().execute_with(|| {
+			EndingPeriod::set(30);
+			SampleLength::set(10);
+			let para_1 = ParaId::from(1);
+			let para_2 = ParaId::from(2);
+			let para_3 = ParaId::from(3);
+			let mut events = Vec::new();
+
+			run_to_block(1);
+			assert_ok!(Auctions::new_auction(Origin::signed(6), 9, 11));
+			assert_ok!(Auctions::bid(Origin::signed(1), para_1, 1, 11, 14, 10));
+			assert_ok!(Auctions::bid(Origin::signed(2), para_2, 1, 13, 14, 20));
+			events.extend(take_auction_events());
+
+			run_to_block(10);
+			assert_ok!(Auctions::bid(Origin::signed(3), para_3, 1, 14, 14, 30));
+			events.extend(take_auction_events());
+			run_to_block(25);
+			assert_ok!(Auctions::bid(Origin::signed(3), para_3, 1, 13, 14, 30));
+			events.extend(take_auction_events());
+			assert_eq!(Auctions::winning_samples(1).len(), 2);
+
+			// Every sample of the ending period has been taken by the last block of it.
+			run_to_block(39);
+			let samples = (0..3)
+				.map(|sample| (sample, Auctions::winning(sample).unwrap()))
+				.collect::<Vec<_>>();
+
+			set_last_random(H256::from([254; 32]), 40);
+			run_to_block(40);
+			events.extend(take_auction_events());
+			assert!(events.contains(&(
+				40,
+				super::Event::<Test>::SampleChosen(1, 2, H256::from([254; 32]), 40)
+			)));
+
+			// The chosen sample is retained along with how it was chosen and a commitment to all
+			// of them.
+			let samples_commitment =
+				samples.iter().fold(H256::default(), |commitment, (sample, winning)| {
+					Auctions::commit_sample(commitment, *sample, winning)
+				});
+			assert_eq!(
+				Auctions::auction_outcome(1),
+				Some(AuctionOutcome {
+					lease_period_index: 11,
+					ending: 10,
+					sample: 2,
+					randomness: H256::from([254; 32]),
+					known_since: 40,
+					samples_commitment,
+				})
+			);
+			assert_eq!(Auctions::winning_samples(1), vec![(2, samples[2].1.to_vec())]);
+			assert!(Winning::<Test>::iter().next().is_none());
+
+			// Replaying the events leads to the same samples and winners.
+			let replay = replay::AuctionReplay::<Test>::from_events(1, events.clone()).unwrap();
+			assert_eq!(replay.chosen_sample(), Some(2));
+			assert_eq!(
+				replay.samples(),
+				samples.iter().map(|(s, w)| (*s, w.to_vec())).collect::<Vec<_>>()
+			);
+			assert_eq!(replay.samples_commitment(), samples_commitment);
+			assert_eq!(replay.winners(), Some(vec![(3, para_3, 30, SlotRange::TwoThree)]));
+			assert_eq!(
+				leases(),
+				vec![
+					((3.into(), 13), LeaseData { leaser: 3, amount: 30 }),
+					((3.into(), 14), LeaseData { leaser: 3, amount: 30 }),
+				]
+			);
+
+			// A sample which does not follow from the randomness is caught.
+			let tampered = events.into_iter().map(|(block_number, event)| match event {
+				super::Event::<Test>::SampleChosen(index, _, randomness, known_since) => (
+					block_number,
+					super::Event::<Test>::SampleChosen(index, 1, randomness, known_since),
+				),
+				event => (block_number, event),
+			});
+			assert_eq!(
+				replay::AuctionReplay::<Test>::from_events(1, tampered).err(),
+				Some(replay::ReplayError::SampleMismatch)
+			);
+		});
+	}
+
+	#[test]
+	fn winning_samples_are_retained_for_history_depth_auctions() {
+		new_test_ext().execute_with(|| {
+			HistoryDepth::set(1);
+
+			run_to_block(1);
+			assert_ok!(Auctions::new_auction(Origin::signed(6), 5, 1));
+			assert_ok!(Auctions::bid(Origin::signed(1), 0.into(), 1, 1, 4, 1));
+			run_to_block(9);
+			assert_eq!(Auctions::winning_samples(1).len(), 1);
+			assert!(Auctions::auction_outcome(1).is_some());
+
+			assert_ok!(Auctions::new_auction(Origin::signed(6), 5, 2));
+			run_to_block(18);
+			assert_eq!(Auctions::winning_samples(2).len(), 1);
+			assert!(Auctions::auction_outcome(2).is_some());
+			// The first auction fell out of the history.
+			assert!(Auctions::winning_samples(1).is_empty());
+			assert!(Auctions::auction_outcome(1).is_none());
+		});
+	}
+
 	#[test]
 	fn auction_status_works() {
 		new_test_ext().execute_with(|| {
@@ -0,0 +1,166 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Off-chain replay of auctions from the events they deposited.
+//!
+//! The bids accepted during an auction, the blocks they were accepted in and the randomness
+//! used at its end are enough to work out the winning bids at each sample of the ending period,
+//! the sample which should have been chosen and the final winners. This lets anyone check an
+//! auction against its events, and against the outcome retained on chain.
+
+use super::*;
+use sp_runtime::traits::UniqueSaturatedInto;
+
+/// Why an auction could not be replayed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReplayError {
+	/// The auction was not started within the events.
+	NotStarted,
+	/// A bid was accepted for a range which is not valid for the auction.
+	InvalidRange,
+	/// A bid was accepted after the ending period.
+	BidAfterEnding,
+	/// The chosen sample does not follow from the randomness it was chosen with.
+	SampleMismatch,
+}
+
+/// An auction rebuilt from the events it deposited.
+pub struct AuctionReplay<T: Config> {
+	auction_index: AuctionIndex,
+	lease_period_index: LeasePeriodOf<T>,
+	ending: T::BlockNumber,
+	samples: Vec<WinningData<T>>,
+	chosen_sample: Option<T::BlockNumber>,
+}
+
+impl<T: Config> AuctionReplay<T> {
+	/// Replay the auction `auction_index` from the events of the pallet, in the order they were
+	/// deposited and each along with the number of the block it was deposited in.
+	///
+	/// The `EndingPeriod` and `SampleLength` of `T` must be those the auction ran with.
+	pub fn from_events(
+		auction_index: AuctionIndex,
+		events: impl IntoIterator<Item = (T::BlockNumber, Event<T>)>,
+	) -> Result<Self, ReplayError> {
+		let ending_period = T::EndingPeriod::get();
+		let sample_length = T::SampleLength::get().max(One::one());
+		let mut replay: Option<Self> = None;
+
+		for (block_number, event) in events {
+			match event {
+				Event::<T>::AuctionStarted(index, lease_period_index, ending) => {
+					if replay.is_some() {
+						// Only one auction runs at a time, so ours is over.
+						break
+					}
+					if index == auction_index {
+						replay = Some(Self {
+							auction_index,
+							lease_period_index,
+							ending,
+							samples: vec![[Pallet::<T>::EMPTY; SlotRange::SLOT_RANGE_COUNT]],
+							chosen_sample: None,
+						});
+					}
+				},
+				Event::<T>::BidAccepted(who, para, amount, first_slot, last_slot) => {
+					let replay = match replay.as_mut() {
+						Some(replay) if replay.chosen_sample.is_none() => replay,
+						_ => continue,
+					};
+					let sample = match block_number.checked_sub(&replay.ending) {
+						None => Zero::zero(),
+						Some(after_early_end) if after_early_end < ending_period =>
+							after_early_end / sample_length,
+						Some(_) => return Err(ReplayError::BidAfterEnding),
+					};
+					let range =
+						SlotRange::new_bounded(replay.lease_period_index, first_slot, last_slot)
+							.map_err(|_| ReplayError::InvalidRange)?;
+					let winning = replay.sample_mut(sample);
+					winning[range as u8 as usize] = Some((who, para, amount));
+				},
+				Event::<T>::SampleChosen(index, sample, randomness, _known_since)
+					if index == auction_index =>
+				{
+					let replay = replay.as_mut().ok_or(ReplayError::NotStarted)?;
+					let raw_offset = T::BlockNumber::decode(&mut randomness.as_ref())
+						.map_err(|_| ReplayError::SampleMismatch)?;
+					ensure!(
+						sample == (raw_offset % ending_period) / sample_length,
+						ReplayError::SampleMismatch
+					);
+					// Every sample of the ending period has been taken by now.
+					let last_sample = ending_period.saturating_sub(One::one()) / sample_length;
+					replay.sample_mut(last_sample.max(sample));
+					replay.chosen_sample = Some(sample);
+				},
+				_ => {},
+			}
+		}
+
+		replay.ok_or(ReplayError::NotStarted)
+	}
+
+	/// The index of the replayed auction.
+	pub fn auction_index(&self) -> AuctionIndex {
+		self.auction_index
+	}
+
+	/// The winning bids for each range at each sample of the ending period, ordered by sample.
+	pub fn samples(&self) -> WinningSamples<T> {
+		self.samples
+			.iter()
+			.enumerate()
+			.map(|(sample, winning)| ((sample as u32).into(), winning.to_vec()))
+			.collect()
+	}
+
+	/// The commitment to the winning bids at every sample of the ending period.
+	///
+	/// Once the auction has ended, this matches the `samples_commitment` of the outcome retained
+	/// on chain.
+	pub fn samples_commitment(&self) -> T::Hash {
+		self.samples
+			.iter()
+			.enumerate()
+			.fold(T::Hash::default(), |commitment, (sample, winning)| {
+				Pallet::<T>::commit_sample(commitment, (sample as u32).into(), winning)
+			})
+	}
+
+	/// The sample chosen at the end of the auction, if it has ended.
+	pub fn chosen_sample(&self) -> Option<T::BlockNumber> {
+		self.chosen_sample
+	}
+
+	/// The final winners of the auction, if it has ended: each bidder along with the para, the
+	/// amount and the range they won.
+	pub fn winners(&self) -> Option<WinnersData<T>> {
+		let sample: usize = self.chosen_sample?.unique_saturated_into();
+		Some(Pallet::<T>::calculate_winners(self.samples[sample].clone()))
+	}
+
+	/// The winning bids at `sample`, carrying over those of the previous samples up to it.
+	fn sample_mut(&mut self, sample: T::BlockNumber) -> &mut WinningData<T> {
+		let sample: usize = sample.unique_saturated_into();
+		while self.samples.len() <= sample {
+			let last = self.samples.last().cloned().expect("replays start with a sample; qed");
+			self.samples.push(last);
+		}
+		&mut self.samples[sample]
+	}
+}
@@ -0,0 +1,45 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API for auditing auctions.
+
+use super::{AuctionIndex, AuctionOutcome};
+use parity_scale_codec::{Decode, Encode};
+use primitives::v1::Id as ParaId;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	/// The API for auditing how the winners of auctions were chosen.
+	pub trait AuctionsApi<
+		AccountId: Encode + Decode,
+		Balance: Encode + Decode,
+		BlockNumber: Encode + Decode,
+		Hash: Encode + Decode,
+	> {
+		/// Get the winning bids for each range at each sample of the ending period of an auction,
+		/// ordered by sample. Ranges are indexed as in `SlotRange`.
+		///
+		/// These are the samples so far of the current auction, or the chosen sample of a past
+		/// one.
+		fn winning_samples(
+			auction_index: AuctionIndex,
+		) -> Vec<(BlockNumber, Vec<Option<(AccountId, ParaId, Balance)>>)>;
+
+		/// Get how the winning sample of a past auction was chosen, along with the commitment to
+		/// all of its samples, if it is still retained.
+		fn auction_outcome(auction_index: AuctionIndex) -> Option<AuctionOutcome<BlockNumber, Hash>>;
+	}
+}
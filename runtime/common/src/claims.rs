@@ -15,6 +15,10 @@
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Pallet to process claims from Ethereum addresses.
+//!
+//! Besides claims minted one by one, claims can be committed to in bulk as the root of a Merkle
+//! tree of [`MerkleClaim`]s, whose leaves are proven by the claimants themselves. Such claims may
+//! also be made by Ed25519 and Sr25519 keys of other chains.
 
 use frame_support::{
 	ensure,
@@ -27,11 +31,15 @@ use primitives::v1::ValidityError;
 use scale_info::TypeInfo;
 #[cfg(feature = "std")]
 use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
-use sp_io::{crypto::secp256k1_ecdsa_recover, hashing::keccak_256};
+use sp_core::{ed25519, sr25519, H256};
+use sp_io::{
+	crypto::{ed25519_verify, secp256k1_ecdsa_recover, sr25519_verify},
+	hashing::{blake2_256, keccak_256},
+};
 #[cfg(feature = "std")]
 use sp_runtime::traits::Zero;
 use sp_runtime::{
-	traits::{CheckedSub, DispatchInfoOf, SignedExtension},
+	traits::{CheckedSub, DispatchInfoOf, Saturating, SignedExtension},
 	transaction_validity::{
 		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
 	},
@@ -50,6 +58,11 @@ pub trait WeightInfo {
 	fn claim_attest() -> Weight;
 	fn attest() -> Weight;
 	fn move_claim() -> Weight;
+	fn add_claim_root() -> Weight;
+	fn remove_claim_root() -> Weight;
+	fn claim_from_root(p: u32) -> Weight;
+	fn claim_from_root_ed25519(p: u32) -> Weight;
+	fn claim_from_root_sr25519(p: u32) -> Weight;
 }
 
 pub struct TestWeightInfo;
@@ -69,6 +82,21 @@ impl WeightInfo for TestWeightInfo {
 	fn move_claim() -> Weight {
 		0
 	}
+	fn add_claim_root() -> Weight {
+		0
+	}
+	fn remove_claim_root() -> Weight {
+		0
+	}
+	fn claim_from_root(_p: u32) -> Weight {
+		0
+	}
+	fn claim_from_root_ed25519(_p: u32) -> Weight {
+		0
+	}
+	fn claim_from_root_sr25519(_p: u32) -> Weight {
+		0
+	}
 }
 
 /// The kind of statement an account needs to make for a claim to be valid.
@@ -157,6 +185,103 @@ impl sp_std::fmt::Debug for EcdsaSignature {
 	}
 }
 
+/// An account of another chain which may be given a claim in a Merkle root.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub enum ExternalSigner {
+	/// An Ethereum address.
+	Ethereum(EthereumAddress),
+	/// An Ed25519 public key.
+	Ed25519(ed25519::Public),
+	/// An Sr25519 public key.
+	Sr25519(sr25519::Public),
+}
+
+/// A signature of an `ExternalSigner`.
+#[derive(Clone, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub enum ExternalSignature {
+	/// A signature of an Ethereum signed message, as `claim` expects.
+	Ethereum(EcdsaSignature),
+	/// An Ed25519 signature.
+	Ed25519(ed25519::Signature),
+	/// An Sr25519 signature.
+	Sr25519(sr25519::Signature),
+}
+
+/// A claim committed to as a leaf of a claims Merkle tree.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct MerkleClaim<Balance, BlockNumber> {
+	/// The index of the leaf in the tree.
+	pub index: u32,
+	/// The account allowed to collect the claim.
+	pub signer: ExternalSigner,
+	/// The number of DOTs that will be claimed.
+	pub value: Balance,
+	/// An optional vesting schedule for these DOTs.
+	pub vesting: Option<(Balance, Balance, BlockNumber)>,
+	/// The statement that must be signed, if any.
+	pub statement: Option<StatementKind>,
+}
+
+impl<Balance: Encode, BlockNumber: Encode> MerkleClaim<Balance, BlockNumber> {
+	/// The hash of the claim as a leaf of a claims Merkle tree.
+	pub fn leaf_hash(&self) -> H256 {
+		blake2_256(&self.encode()).into()
+	}
+}
+
+/// The root of a claims Merkle tree, along with what is left to claim from it.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct ClaimRoot<Balance> {
+	/// The root hash of the tree.
+	pub root: H256,
+	/// The depth of the tree, which is the length of the proof of every leaf.
+	pub depth: u32,
+	/// The amount that is yet to be claimed.
+	pub remaining: Balance,
+}
+
+/// The maximum depth of a claims Merkle tree.
+pub const MAX_MERKLE_DEPTH: u32 = 32;
+
+/// Hash two sibling nodes of a claims Merkle tree into their parent.
+fn merkle_parent(left: &H256, right: &H256) -> H256 {
+	let mut pair = [0u8; 64];
+	pair[..32].copy_from_slice(left.as_bytes());
+	pair[32..].copy_from_slice(right.as_bytes());
+	blake2_256(&pair).into()
+}
+
+/// Compute the root of a claims Merkle tree from the hash of the leaf at `index` and the hashes
+/// of its siblings, ordered from the leaf upwards.
+pub fn merkle_root_from_proof(leaf: H256, index: u32, proof: &[H256]) -> H256 {
+	proof
+		.iter()
+		.enumerate()
+		.fold(leaf, |node, (level, sibling)| match (index >> level) & 1 {
+			0 => merkle_parent(&node, sibling),
+			_ => merkle_parent(sibling, &node),
+		})
+}
+
+/// Build a claims Merkle tree over the given leaf hashes, returning its root and the proof of
+/// each leaf. A node without a sibling is paired with itself.
+pub fn merkle_tree(leaves: &[H256]) -> (H256, Vec<Vec<H256>>) {
+	let mut proofs = vec![Vec::new(); leaves.len()];
+	let mut positions: Vec<usize> = (0..leaves.len()).collect();
+	let mut level = leaves.to_vec();
+	while level.len() > 1 {
+		if level.len() % 2 == 1 {
+			level.push(level[level.len() - 1]);
+		}
+		for (proof, position) in proofs.iter_mut().zip(positions.iter_mut()) {
+			proof.push(level[*position ^ 1]);
+			*position /= 2;
+		}
+		level = level.chunks(2).map(|pair| merkle_parent(&pair[0], &pair[1])).collect();
+	}
+	(level.first().copied().unwrap_or_default(), proofs)
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -184,6 +309,13 @@ pub mod pallet {
 	pub enum Event<T: Config> {
 		/// Someone claimed some DOTs. `[who, ethereum_address, amount]`
 		Claimed(T::AccountId, EthereumAddress, BalanceOf<T>),
+		/// Claims have been committed to as a Merkle root. `[root_index, root, total]`
+		ClaimRootAdded(u32, H256, BalanceOf<T>),
+		/// A claims Merkle root has been removed along with its unclaimed DOTs.
+		/// `[root_index, unclaimed]`
+		ClaimRootRemoved(u32, BalanceOf<T>),
+		/// Someone claimed some DOTs from a Merkle root. `[who, root_index, signer, amount]`
+		ClaimedFromRoot(T::AccountId, u32, ExternalSigner, BalanceOf<T>),
 	}
 
 	#[pallet::error]
@@ -201,6 +333,16 @@ pub mod pallet {
 		InvalidStatement,
 		/// The account already has a vested balance.
 		VestedBalanceExists,
+		/// Invalid signature of a claim from a Merkle root.
+		InvalidSignature,
+		/// The Merkle proof of a claim does not match its root.
+		InvalidMerkleProof,
+		/// There is no claims Merkle root with the given index.
+		UnknownClaimRoot,
+		/// The claim has already been made.
+		AlreadyClaimed,
+		/// The claims Merkle tree is deeper than `MAX_MERKLE_DEPTH`.
+		MerkleTreeTooDeep,
 	}
 
 	#[pallet::storage]
@@ -228,6 +370,22 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type Preclaims<T: Config> = StorageMap<_, Identity, T::AccountId, EthereumAddress>;
 
+	/// Claims committed to as Merkle roots, by root index.
+	#[pallet::storage]
+	#[pallet::getter(fn claim_root)]
+	pub(super) type ClaimRoots<T: Config> =
+		StorageMap<_, Twox64Concat, u32, ClaimRoot<BalanceOf<T>>>;
+
+	/// The index of the next claims Merkle root.
+	#[pallet::storage]
+	pub(super) type NextClaimRootIndex<T> = StorageValue<_, u32, ValueQuery>;
+
+	/// Bitmaps of the leaves of each claims Merkle root which have been claimed, in words of 128
+	/// leaves.
+	#[pallet::storage]
+	pub(super) type ClaimedLeaves<T> =
+		StorageDoubleMap<_, Twox64Concat, u32, Twox64Concat, u32, u128, ValueQuery>;
+
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
 		pub claims:
@@ -460,6 +618,108 @@ pub mod pallet {
 			});
 			Ok(Pays::No.into())
 		}
+
+		/// Commit to claims in bulk as the root of a Merkle tree of `MerkleClaim`s.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		///
+		/// Parameters:
+		/// - `root`: The root hash of the tree, as built by `merkle_tree`.
+		/// - `depth`: The depth of the tree. At most `MAX_MERKLE_DEPTH`.
+		/// - `total`: The total number of DOTs that can be claimed from the tree.
+		///
+		/// <weight>
+		/// The weight of this call is invariant over the input parameters.
+		///
+		/// Total Complexity: O(1)
+		/// </weight>
+		#[pallet::weight(T::WeightInfo::add_claim_root())]
+		pub fn add_claim_root(
+			origin: OriginFor<T>,
+			root: H256,
+			depth: u32,
+			total: BalanceOf<T>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(depth <= MAX_MERKLE_DEPTH, Error::<T>::MerkleTreeTooDeep);
+
+			let index = NextClaimRootIndex::<T>::get();
+			NextClaimRootIndex::<T>::put(index + 1);
+			<Total<T>>::mutate(|t| *t += total);
+			ClaimRoots::<T>::insert(index, ClaimRoot { root, depth, remaining: total });
+			Self::deposit_event(Event::<T>::ClaimRootAdded(index, root, total));
+			Ok(())
+		}
+
+		/// Remove a claims Merkle root, so that no more claims can be made from it.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		///
+		/// <weight>
+		/// The weight of this call is invariant over the input parameters.
+		///
+		/// Total Complexity: O(1)
+		/// </weight>
+		#[pallet::weight(T::WeightInfo::remove_claim_root())]
+		pub fn remove_claim_root(origin: OriginFor<T>, root_index: u32) -> DispatchResult {
+			ensure_root(origin)?;
+			let claim_root =
+				ClaimRoots::<T>::take(root_index).ok_or(Error::<T>::UnknownClaimRoot)?;
+
+			<Total<T>>::mutate(|t| *t = t.saturating_sub(claim_root.remaining));
+			Self::deposit_event(Event::<T>::ClaimRootRemoved(root_index, claim_root.remaining));
+			Ok(())
+		}
+
+		/// Make a claim committed to in a Merkle root to collect your DOTs.
+		///
+		/// The dispatch origin for this call must be _None_.
+		///
+		/// Unsigned Validation:
+		/// A call to `claim_from_root` is deemed valid if `proof` shows that `claim` is a leaf of
+		/// the root, the claim hasn't been made yet, and the signature of `claim.signer` matches
+		/// the message:
+		///
+		/// > (configured prefix string)(address)(statement)
+		///
+		/// where `address` matches the `dest` account and `statement` is the text of the claim's
+		/// statement, if any. Ethereum signers sign it as an Ethereum signed message, as for
+		/// `claim_attest`.
+		///
+		/// Parameters:
+		/// - `dest`: The destination account to payout the claim.
+		/// - `root_index`: The index of the Merkle root the claim is committed to in.
+		/// - `claim`: The claim, as a leaf of the Merkle tree.
+		/// - `proof`: The hashes of the leaf's siblings, ordered from the leaf upwards.
+		/// - `signature`: The signature of `claim.signer` over the message described above.
+		///
+		/// <weight>
+		/// Weight includes logic to validate unsigned `claim_from_root` call, and depends on the
+		/// kind of `signature` to verify.
+		///
+		/// Total Complexity: O(P) where P is the length of the proof
+		/// </weight>
+		#[pallet::weight(match signature {
+			ExternalSignature::Ethereum(_) => T::WeightInfo::claim_from_root(proof.len() as u32),
+			ExternalSignature::Ed25519(_) =>
+				T::WeightInfo::claim_from_root_ed25519(proof.len() as u32),
+			ExternalSignature::Sr25519(_) =>
+				T::WeightInfo::claim_from_root_sr25519(proof.len() as u32),
+		})]
+		pub fn claim_from_root(
+			origin: OriginFor<T>,
+			dest: T::AccountId,
+			root_index: u32,
+			claim: MerkleClaim<BalanceOf<T>, T::BlockNumber>,
+			proof: Vec<H256>,
+			signature: ExternalSignature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+
+			Self::check_merkle_claim(&dest, root_index, &claim, &proof, &signature)?;
+			Self::process_merkle_claim(dest, root_index, claim)?;
+			Ok(())
+		}
 	}
 
 	#[pallet::validate_unsigned]
@@ -487,6 +747,29 @@ pub mod pallet {
 						Some(statement.as_slice()),
 					)
 				},
+				// <weight>
+				// The weight of this logic is included in the `claim_from_root` dispatchable.
+				// </weight>
+				Call::claim_from_root { dest, root_index, claim, proof, signature } => {
+					Self::check_merkle_claim(dest, *root_index, claim, proof, signature).map_err(
+						|e| {
+							let e = match e {
+								Error::<T>::InvalidSignature => ValidityError::InvalidSignature,
+								Error::<T>::InvalidMerkleProof => ValidityError::InvalidMerkleProof,
+								_ => ValidityError::SignerHasNoClaim,
+							};
+							InvalidTransaction::Custom(e.into())
+						},
+					)?;
+
+					return Ok(ValidTransaction {
+						priority: PRIORITY,
+						requires: vec![],
+						provides: vec![("claims", root_index, claim.index).encode()],
+						longevity: TransactionLongevity::max_value(),
+						propagate: true,
+					})
+				},
 				_ => return Err(InvalidTransaction::Call.into()),
 			};
 
@@ -553,28 +836,127 @@ impl<T: Config> Pallet<T> {
 		Some(res)
 	}
 
-	fn process_claim(signer: EthereumAddress, dest: T::AccountId) -> sp_runtime::DispatchResult {
-		let balance_due = <Claims<T>>::get(&signer).ok_or(Error::<T>::SignerHasNoClaim)?;
+	// Constructs the message that Ed25519 and Sr25519 keys sign for a claim.
+	fn signable_message(what: &[u8], extra: &[u8]) -> Vec<u8> {
+		let prefix = T::Prefix::get();
+		let mut v = Vec::with_capacity(prefix.len() + what.len() + extra.len());
+		v.extend_from_slice(&prefix[..]);
+		v.extend_from_slice(what);
+		v.extend_from_slice(extra);
+		v
+	}
+
+	// Checks that `signature` is a signature of `signer` over the message for a claim.
+	fn verify_external_signature(
+		signer: &ExternalSigner,
+		signature: &ExternalSignature,
+		what: &[u8],
+		extra: &[u8],
+	) -> bool {
+		match (signer, signature) {
+			(ExternalSigner::Ethereum(address), ExternalSignature::Ethereum(s)) =>
+				Self::eth_recover(s, what, extra).as_ref() == Some(address),
+			(ExternalSigner::Ed25519(public), ExternalSignature::Ed25519(s)) =>
+				ed25519_verify(s, &Self::signable_message(what, extra), public),
+			(ExternalSigner::Sr25519(public), ExternalSignature::Sr25519(s)) =>
+				sr25519_verify(s, &Self::signable_message(what, extra), public),
+			_ => false,
+		}
+	}
+
+	// Checks that a claim from a Merkle root can be made to `dest`.
+	fn check_merkle_claim(
+		dest: &T::AccountId,
+		root_index: u32,
+		claim: &MerkleClaim<BalanceOf<T>, T::BlockNumber>,
+		proof: &[H256],
+		signature: &ExternalSignature,
+	) -> Result<(), Error<T>> {
+		let claim_root = ClaimRoots::<T>::get(root_index).ok_or(Error::<T>::UnknownClaimRoot)?;
+		// Bounding the index by the depth ensures every leaf has a single index to be claimed at.
+		ensure!(
+			proof.len() as u32 == claim_root.depth &&
+				claim.index.checked_shr(claim_root.depth).unwrap_or(0) == 0,
+			Error::<T>::InvalidMerkleProof
+		);
+		ensure!(
+			merkle_root_from_proof(claim.leaf_hash(), claim.index, proof) == claim_root.root,
+			Error::<T>::InvalidMerkleProof
+		);
+		ensure!(!Self::is_leaf_claimed(root_index, claim.index), Error::<T>::AlreadyClaimed);
+
+		let data = dest.using_encoded(to_ascii_hex);
+		let statement = claim.statement.map_or(&[][..], |s| s.to_text());
+		ensure!(
+			Self::verify_external_signature(&claim.signer, signature, &data, statement),
+			Error::<T>::InvalidSignature
+		);
+		Ok(())
+	}
+
+	/// Whether the leaf at `index` of the claims Merkle root at `root_index` has been claimed.
+	pub fn is_leaf_claimed(root_index: u32, index: u32) -> bool {
+		ClaimedLeaves::<T>::get(root_index, index / 128) & (1 << (index % 128)) != 0
+	}
+
+	fn process_merkle_claim(
+		dest: T::AccountId,
+		root_index: u32,
+		claim: MerkleClaim<BalanceOf<T>, T::BlockNumber>,
+	) -> sp_runtime::DispatchResult {
+		let mut claim_root =
+			ClaimRoots::<T>::get(root_index).ok_or(Error::<T>::UnknownClaimRoot)?;
+		claim_root.remaining =
+			claim_root.remaining.checked_sub(&claim.value).ok_or(Error::<T>::PotUnderflow)?;
+
+		Self::pay_out(&dest, claim.value, claim.vesting)?;
+
+		ClaimRoots::<T>::insert(root_index, claim_root);
+		ClaimedLeaves::<T>::mutate(root_index, claim.index / 128, |bits| {
+			*bits |= 1 << (claim.index % 128)
+		});
+
+		Self::deposit_event(Event::<T>::ClaimedFromRoot(
+			dest,
+			root_index,
+			claim.signer,
+			claim.value,
+		));
+		Ok(())
+	}
 
+	// Pays out `balance_due` to `dest` from the pot, with the given vesting schedule.
+	fn pay_out(
+		dest: &T::AccountId,
+		balance_due: BalanceOf<T>,
+		vesting: Option<(BalanceOf<T>, BalanceOf<T>, T::BlockNumber)>,
+	) -> sp_runtime::DispatchResult {
 		let new_total = Self::total().checked_sub(&balance_due).ok_or(Error::<T>::PotUnderflow)?;
 
-		let vesting = Vesting::<T>::get(&signer);
-		if vesting.is_some() && T::VestingSchedule::vesting_balance(&dest).is_some() {
+		if vesting.is_some() && T::VestingSchedule::vesting_balance(dest).is_some() {
 			return Err(Error::<T>::VestedBalanceExists.into())
 		}
 
 		// We first need to deposit the balance to ensure that the account exists.
-		CurrencyOf::<T>::deposit_creating(&dest, balance_due);
+		CurrencyOf::<T>::deposit_creating(dest, balance_due);
 
 		// Check if this claim should have a vesting schedule.
 		if let Some(vs) = vesting {
 			// This can only fail if the account already has a vesting schedule,
 			// but this is checked above.
-			T::VestingSchedule::add_vesting_schedule(&dest, vs.0, vs.1, vs.2)
+			T::VestingSchedule::add_vesting_schedule(dest, vs.0, vs.1, vs.2)
 				.expect("No other vesting schedule exists, as checked above; qed");
 		}
 
 		<Total<T>>::put(new_total);
+		Ok(())
+	}
+
+	fn process_claim(signer: EthereumAddress, dest: T::AccountId) -> sp_runtime::DispatchResult {
+		let balance_due = <Claims<T>>::get(&signer).ok_or(Error::<T>::SignerHasNoClaim)?;
+		let vesting = Vesting::<T>::get(&signer);
+		Self::pay_out(&dest, balance_due, vesting)?;
+
 		<Claims<T>>::remove(&signer);
 		<Vesting<T>>::remove(&signer);
 		Signing::<T>::remove(&signer);
@@ -693,7 +1075,7 @@ mod tests {
 	use secp_utils::*;
 
 	use parity_scale_codec::Encode;
-	use sp_core::H256;
+	use sp_core::{Pair, H256};
 	// The testing primitives are very useful for avoiding having to work with signatures
 	// or public keys. `u64` is used as the `AccountId` and no `Signature`s are required.
 	use crate::claims;
@@ -706,11 +1088,13 @@ mod tests {
 		weights::{GetDispatchInfo, Pays},
 	};
 	use pallet_balances;
+	use sp_keystore::{testing::KeyStore, KeystoreExt};
 	use sp_runtime::{
 		testing::Header,
 		traits::{BlakeTwo256, Identity, IdentityLookup},
 		transaction_validity::TransactionLongevity,
 	};
+	use sp_std::sync::Arc;
 
 	type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 	type Block = frame_system::mocking::MockBlock<Test>;
@@ -836,7 +1220,10 @@ mod tests {
 		}
 		.assimilate_storage(&mut t)
 		.unwrap();
-		t.into()
+		// The benchmarks of external signers generate their keys in the keystore.
+		let mut t: sp_io::TestExternalities = t.into();
+		t.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
+		t
 	}
 
 	fn total_claims() -> u64 {
@@ -1381,6 +1768,275 @@ mod tests {
 			);
 		});
 	}
+
+	fn ed25519_pair() -> sp_core::ed25519::Pair {
+		sp_core::ed25519::Pair::from_seed(&[1; 32])
+	}
+
+	fn sr25519_pair() -> sp_core::sr25519::Pair {
+		sp_core::sr25519::Pair::from_seed(&[2; 32])
+	}
+
+	// The message an Ed25519 or Sr25519 key signs to claim to `dest`.
+	fn external_message(dest: u64, statement: Option<StatementKind>) -> Vec<u8> {
+		<Pallet<Test>>::signable_message(
+			&dest.using_encoded(to_ascii_hex),
+			statement.map_or(&[][..], |s| s.to_text()),
+		)
+	}
+
+	fn merkle_claims() -> Vec<MerkleClaim<u64, u64>> {
+		vec![
+			MerkleClaim {
+				index: 0,
+				signer: ExternalSigner::Ethereum(eth(&bob())),
+				value: 10,
+				vesting: None,
+				statement: None,
+			},
+			MerkleClaim {
+				index: 1,
+				signer: ExternalSigner::Ed25519(ed25519_pair().public()),
+				value: 20,
+				vesting: None,
+				statement: Some(StatementKind::Regular),
+			},
+			MerkleClaim {
+				index: 2,
+				signer: ExternalSigner::Sr25519(sr25519_pair().public()),
+				value: 30,
+				vesting: Some((15, 1, 1)),
+				statement: None,
+			},
+		]
+	}
+
+	// Commits to `merkle_claims` as a claims Merkle root, returning the proofs of the claims.
+	fn add_merkle_claims() -> Vec<Vec<H256>> {
+		let leaves: Vec<_> = merkle_claims().iter().map(|c| c.leaf_hash()).collect();
+		let (root, proofs) = merkle_tree(&leaves);
+		assert_ok!(Claims::add_claim_root(Origin::root(), root, 2, 60));
+		proofs
+	}
+
+	#[test]
+	fn merkle_tree_proofs_work() {
+		let leaves: Vec<_> = (0..5u8).map(H256::repeat_byte).collect();
+		let (root, proofs) = merkle_tree(&leaves);
+		for (index, (leaf, proof)) in leaves.iter().zip(proofs.iter()).enumerate() {
+			assert_eq!(proof.len(), 3);
+			assert_eq!(merkle_root_from_proof(*leaf, index as u32, proof), root);
+		}
+		assert!(merkle_root_from_proof(leaves[0], 1, &proofs[0]) != root);
+		assert_eq!(merkle_tree(&leaves[..1]), (leaves[0], vec![vec![]]));
+	}
+
+	#[test]
+	fn merkle_claims_work() {
+		new_test_ext().execute_with(|| {
+			let claims = merkle_claims();
+			let leaves: Vec<_> = claims.iter().map(|c| c.leaf_hash()).collect();
+			let (root, proofs) = merkle_tree(&leaves);
+			assert_noop!(Claims::add_claim_root(Origin::signed(1), root, 2, 60), BadOrigin);
+			assert_noop!(
+				Claims::add_claim_root(Origin::root(), root, MAX_MERKLE_DEPTH + 1, 60),
+				Error::<Test>::MerkleTreeTooDeep
+			);
+			assert_ok!(Claims::add_claim_root(Origin::root(), root, 2, 60));
+			assert_eq!(Claims::total(), total_claims() + 60);
+
+			// An Ethereum address claims.
+			let signature =
+				ExternalSignature::Ethereum(sig::<Test>(&bob(), &42u64.encode(), &[][..]));
+			assert_ok!(Claims::claim_from_root(
+				Origin::none(),
+				42,
+				0,
+				claims[0].clone(),
+				proofs[0].clone(),
+				signature.clone()
+			));
+			assert_eq!(Balances::free_balance(&42), 10);
+			assert!(Claims::is_leaf_claimed(0, 0));
+			assert_noop!(
+				Claims::claim_from_root(
+					Origin::none(),
+					42,
+					0,
+					claims[0].clone(),
+					proofs[0].clone(),
+					signature
+				),
+				Error::<Test>::AlreadyClaimed
+			);
+
+			// An Ed25519 key claims, which requires signing the statement.
+			let signature =
+				ExternalSignature::Ed25519(ed25519_pair().sign(&external_message(43, None)));
+			assert_noop!(
+				Claims::claim_from_root(
+					Origin::none(),
+					43,
+					0,
+					claims[1].clone(),
+					proofs[1].clone(),
+					signature
+				),
+				Error::<Test>::InvalidSignature
+			);
+			let signature = ExternalSignature::Ed25519(
+				ed25519_pair().sign(&external_message(43, Some(StatementKind::Regular))),
+			);
+			assert_noop!(
+				Claims::claim_from_root(
+					Origin::none(),
+					43,
+					0,
+					claims[1].clone(),
+					proofs[0].clone(),
+					signature.clone()
+				),
+				Error::<Test>::InvalidMerkleProof
+			);
+			assert_ok!(Claims::claim_from_root(
+				Origin::none(),
+				43,
+				0,
+				claims[1].clone(),
+				proofs[1].clone(),
+				signature
+			));
+			assert_eq!(Balances::free_balance(&43), 20);
+
+			// An Sr25519 key claims, with vesting.
+			let signature =
+				ExternalSignature::Sr25519(sr25519_pair().sign(&external_message(44, None)));
+			assert_ok!(Claims::claim_from_root(
+				Origin::none(),
+				44,
+				0,
+				claims[2].clone(),
+				proofs[2].clone(),
+				signature
+			));
+			assert_eq!(Balances::free_balance(&44), 30);
+			assert_eq!(Vesting::vesting_balance(&44), Some(15));
+
+			assert_eq!(Claims::total(), total_claims());
+			assert_eq!(Claims::claim_root(0).map(|r| r.remaining), Some(0));
+		});
+	}
+
+	#[test]
+	fn merkle_claim_roots_can_be_removed() {
+		new_test_ext().execute_with(|| {
+			let claims = merkle_claims();
+			let proofs = add_merkle_claims();
+			let signature =
+				ExternalSignature::Ethereum(sig::<Test>(&bob(), &42u64.encode(), &[][..]));
+			assert_ok!(Claims::claim_from_root(
+				Origin::none(),
+				42,
+				0,
+				claims[0].clone(),
+				proofs[0].clone(),
+				signature
+			));
+
+			assert_noop!(Claims::remove_claim_root(Origin::signed(1), 0), BadOrigin);
+			assert_ok!(Claims::remove_claim_root(Origin::root(), 0));
+			assert_eq!(Claims::total(), total_claims());
+			assert_noop!(
+				Claims::remove_claim_root(Origin::root(), 0),
+				Error::<Test>::UnknownClaimRoot
+			);
+
+			let signature =
+				ExternalSignature::Sr25519(sr25519_pair().sign(&external_message(44, None)));
+			assert_noop!(
+				Claims::claim_from_root(
+					Origin::none(),
+					44,
+					0,
+					claims[2].clone(),
+					proofs[2].clone(),
+					signature
+				),
+				Error::<Test>::UnknownClaimRoot
+			);
+		});
+	}
+
+	#[test]
+	fn validate_unsigned_works_for_merkle_claims() {
+		use sp_runtime::traits::ValidateUnsigned;
+		let source = sp_runtime::transaction_validity::TransactionSource::External;
+
+		new_test_ext().execute_with(|| {
+			let claims = merkle_claims();
+			let proofs = add_merkle_claims();
+			let signature =
+				ExternalSignature::Sr25519(sr25519_pair().sign(&external_message(44, None)));
+			assert_eq!(
+				<Pallet<Test>>::validate_unsigned(
+					source,
+					&ClaimsCall::claim_from_root {
+						dest: 44,
+						root_index: 0,
+						claim: claims[2].clone(),
+						proof: proofs[2].clone(),
+						signature: signature.clone(),
+					}
+				),
+				Ok(ValidTransaction {
+					priority: 100,
+					requires: vec![],
+					provides: vec![("claims", 0u32, 2u32).encode()],
+					longevity: TransactionLongevity::max_value(),
+					propagate: true,
+				})
+			);
+			assert_eq!(
+				<Pallet<Test>>::validate_unsigned(
+					source,
+					&ClaimsCall::claim_from_root {
+						dest: 45,
+						root_index: 0,
+						claim: claims[2].clone(),
+						proof: proofs[2].clone(),
+						signature: signature.clone(),
+					}
+				),
+				InvalidTransaction::Custom(ValidityError::InvalidSignature.into()).into(),
+			);
+			assert_eq!(
+				<Pallet<Test>>::validate_unsigned(
+					source,
+					&ClaimsCall::claim_from_root {
+						dest: 44,
+						root_index: 0,
+						claim: claims[2].clone(),
+						proof: proofs[1].clone(),
+						signature: signature.clone(),
+					}
+				),
+				InvalidTransaction::Custom(ValidityError::InvalidMerkleProof.into()).into(),
+			);
+			assert_eq!(
+				<Pallet<Test>>::validate_unsigned(
+					source,
+					&ClaimsCall::claim_from_root {
+						dest: 44,
+						root_index: 1,
+						claim: claims[2].clone(),
+						proof: proofs[2].clone(),
+						signature,
+					}
+				),
+				InvalidTransaction::Custom(ValidityError::SignerHasNoClaim.into()).into(),
+			);
+		});
+	}
 }
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -1390,6 +2046,7 @@ mod benchmarking {
 	use frame_benchmarking::{account, benchmarks};
 	use frame_system::RawOrigin;
 	use secp_utils::*;
+	use sp_io::crypto::{ed25519_generate, ed25519_sign, sr25519_generate, sr25519_sign};
 	use sp_runtime::{traits::ValidateUnsigned, DispatchResult};
 
 	const SEED: u32 = 0;
@@ -1425,6 +2082,58 @@ mod benchmarking {
 		Ok(())
 	}
 
+	// The kinds of signer a claim committed to in a Merkle root can have.
+	enum MerkleSigner {
+		Ethereum,
+		Ed25519,
+		Sr25519,
+	}
+
+	// Commits to a claim of a `signer` in a new Merkle root of depth `p`, returning what
+	// `claim_from_root` needs to collect it.
+	fn create_merkle_claim<T: Config>(
+		p: u32,
+		signer: MerkleSigner,
+	) -> Result<
+		(T::AccountId, MerkleClaim<BalanceOf<T>, T::BlockNumber>, Vec<H256>, ExternalSignature),
+		&'static str,
+	> {
+		let account: T::AccountId = account("user", p, SEED);
+		let statement = StatementKind::Regular;
+		let message = super::Pallet::<T>::signable_message(
+			&account.using_encoded(to_ascii_hex),
+			statement.to_text(),
+		);
+		let (signer, signature) = match signer {
+			MerkleSigner::Ethereum => {
+				let secret_key = libsecp256k1::SecretKey::parse(&keccak_256(&p.encode())).unwrap();
+				let signature = sig::<T>(&secret_key, &account.encode(), statement.to_text());
+				(ExternalSigner::Ethereum(eth(&secret_key)), ExternalSignature::Ethereum(signature))
+			},
+			MerkleSigner::Ed25519 => {
+				let public = ed25519_generate(0.into(), Some(b"//claimant".to_vec()));
+				let signature = ed25519_sign(0.into(), &public, &message).ok_or("cannot sign")?;
+				(ExternalSigner::Ed25519(public), ExternalSignature::Ed25519(signature))
+			},
+			MerkleSigner::Sr25519 => {
+				let public = sr25519_generate(0.into(), Some(b"//claimant".to_vec()));
+				let signature = sr25519_sign(0.into(), &public, &message).ok_or("cannot sign")?;
+				(ExternalSigner::Sr25519(public), ExternalSignature::Sr25519(signature))
+			},
+		};
+		let claim = MerkleClaim {
+			index: 0,
+			signer,
+			value: VALUE.into(),
+			vesting: Some((100_000u32.into(), 1_000u32.into(), 100u32.into())),
+			statement: Some(statement),
+		};
+		let proof = vec![H256::repeat_byte(1); p as usize];
+		let root = merkle_root_from_proof(claim.leaf_hash(), 0, &proof);
+		super::Pallet::<T>::add_claim_root(RawOrigin::Root.into(), root, p, VALUE.into())?;
+		Ok((account, claim, proof, signature))
+	}
+
 	benchmarks! {
 		// Benchmark `claim` including `validate_unsigned` logic.
 		claim {
@@ -1563,6 +2272,88 @@ mod benchmarking {
 			assert!(Claims::<T>::contains_key(new_eth_address));
 		}
 
+		add_claim_root {
+			let root = H256::repeat_byte(1);
+		}: _(RawOrigin::Root, root, MAX_MERKLE_DEPTH, VALUE.into())
+		verify {
+			assert_eq!(ClaimRoots::<T>::get(0).map(|r| r.root), Some(root));
+		}
+
+		remove_claim_root {
+			super::Pallet::<T>::add_claim_root(
+				RawOrigin::Root.into(),
+				H256::repeat_byte(1),
+				MAX_MERKLE_DEPTH,
+				VALUE.into(),
+			)?;
+		}: _(RawOrigin::Root, 0)
+		verify {
+			assert!(ClaimRoots::<T>::get(0).is_none());
+		}
+
+		// Benchmark `claim_from_root` of an Ethereum signer including `validate_unsigned` logic.
+		claim_from_root {
+			let p in 0 .. MAX_MERKLE_DEPTH;
+			let (account, claim, proof, signature) =
+				create_merkle_claim::<T>(p, MerkleSigner::Ethereum)?;
+			let call = Call::<T>::claim_from_root {
+				dest: account.clone(),
+				root_index: 0,
+				claim: claim.clone(),
+				proof: proof.clone(),
+				signature: signature.clone(),
+			};
+			let source = sp_runtime::transaction_validity::TransactionSource::External;
+		}: {
+			super::Pallet::<T>::validate_unsigned(source, &call).map_err(|e| -> &'static str { e.into() })?;
+			super::Pallet::<T>::claim_from_root(RawOrigin::None.into(), account, 0, claim, proof, signature)?;
+		}
+		verify {
+			assert!(super::Pallet::<T>::is_leaf_claimed(0, 0));
+		}
+
+		// Benchmark `claim_from_root` of an Ed25519 signer including `validate_unsigned` logic.
+		claim_from_root_ed25519 {
+			let p in 0 .. MAX_MERKLE_DEPTH;
+			let (account, claim, proof, signature) =
+				create_merkle_claim::<T>(p, MerkleSigner::Ed25519)?;
+			let call = Call::<T>::claim_from_root {
+				dest: account.clone(),
+				root_index: 0,
+				claim: claim.clone(),
+				proof: proof.clone(),
+				signature: signature.clone(),
+			};
+			let source = sp_runtime::transaction_validity::TransactionSource::External;
+		}: {
+			super::Pallet::<T>::validate_unsigned(source, &call).map_err(|e| -> &'static str { e.into() })?;
+			super::Pallet::<T>::claim_from_root(RawOrigin::None.into(), account, 0, claim, proof, signature)?;
+		}
+		verify {
+			assert!(super::Pallet::<T>::is_leaf_claimed(0, 0));
+		}
+
+		// Benchmark `claim_from_root` of an Sr25519 signer including `validate_unsigned` logic.
+		claim_from_root_sr25519 {
+			let p in 0 .. MAX_MERKLE_DEPTH;
+			let (account, claim, proof, signature) =
+				create_merkle_claim::<T>(p, MerkleSigner::Sr25519)?;
+			let call = Call::<T>::claim_from_root {
+				dest: account.clone(),
+				root_index: 0,
+				claim: claim.clone(),
+				proof: proof.clone(),
+				signature: signature.clone(),
+			};
+			let source = sp_runtime::transaction_validity::TransactionSource::External;
+		}: {
+			super::Pallet::<T>::validate_unsigned(source, &call).map_err(|e| -> &'static str { e.into() })?;
+			super::Pallet::<T>::claim_from_root(RawOrigin::None.into(), account, 0, claim, proof, signature)?;
+		}
+		verify {
+			assert!(super::Pallet::<T>::is_leaf_claimed(0, 0));
+		}
+
 		// Benchmark the time it takes to do `repeat` number of keccak256 hashes
 		#[extra]
 		keccak256 {
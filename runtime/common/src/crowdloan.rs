@@ -48,6 +48,11 @@
 //! Successful funds remain tracked (in the `Funds` storage item and the associated child trie) as long as
 //! the parachain remains active. Users can withdraw their funds once the slot is completed and funds are
 //! returned to the crowdloan account.
+//!
+//! Contributions may also come from other consensus systems, e.g. from a parachain's sovereign account
+//! or from a user account on a parachain through an XCM `Transact`. Such contributions are taken from
+//! the local account of the contributor's location, and the location is recorded in a second child
+//! trie of the fund. Refunds of these contributions are routed back to the location over XCM.
 
 use crate::{
 	slot_range::SlotRange,
@@ -57,20 +62,25 @@ use frame_support::{
 	ensure,
 	pallet_prelude::Weight,
 	storage::{child, ChildTriePrefixIterator},
-	traits::{Currency, ExistenceRequirement::AllowDeath, Get, ReservableCurrency},
-	Identity, PalletId,
+	traits::{Contains, Currency, ExistenceRequirement::AllowDeath, Get, ReservableCurrency},
+	transactional, Identity, PalletId,
 };
 pub use pallet::*;
+
+pub mod runtime_api;
 use parity_scale_codec::{Decode, Encode};
 use primitives::v1::Id as ParaId;
 use scale_info::TypeInfo;
 use sp_runtime::{
 	traits::{
-		AccountIdConversion, CheckedAdd, Hash, IdentifyAccount, One, Saturating, Verify, Zero,
+		AccountIdConversion, CheckedAdd, Hash, IdentifyAccount, One, Saturating,
+		UniqueSaturatedInto, Verify, Zero,
 	},
-	MultiSignature, MultiSigner, RuntimeDebug,
+	DispatchError, MultiSignature, MultiSigner, RuntimeDebug,
 };
-use sp_std::vec::Vec;
+use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+use xcm::latest::{MultiLocation, SendXcm};
+use xcm_executor::traits::Convert;
 
 type CurrencyOf<T> = <<T as Config>::Auctioneer as Auctioneer>::Currency;
 type LeasePeriodOf<T> = <<T as Config>::Auctioneer as Auctioneer>::LeasePeriod;
@@ -85,6 +95,7 @@ type TrieIndex = u32;
 pub trait WeightInfo {
 	fn create() -> Weight;
 	fn contribute() -> Weight;
+	fn contribute_from_location() -> Weight;
 	fn withdraw() -> Weight;
 	fn refund(k: u32) -> Weight;
 	fn dissolve() -> Weight;
@@ -92,6 +103,9 @@ pub trait WeightInfo {
 	fn add_memo() -> Weight;
 	fn on_initialize(n: u32) -> Weight;
 	fn poke() -> Weight;
+	fn set_auto_rollover() -> Weight;
+	fn on_idle(n: u32) -> Weight;
+	fn roll_over(r: u32) -> Weight;
 }
 
 pub struct TestWeightInfo;
@@ -102,6 +116,9 @@ impl WeightInfo for TestWeightInfo {
 	fn contribute() -> Weight {
 		0
 	}
+	fn contribute_from_location() -> Weight {
+		0
+	}
 	fn withdraw() -> Weight {
 		0
 	}
@@ -123,6 +140,15 @@ impl WeightInfo for TestWeightInfo {
 	fn poke() -> Weight {
 		0
 	}
+	fn set_auto_rollover() -> Weight {
+		0
+	}
+	fn on_idle(n: u32) -> Weight {
+		n as Weight
+	}
+	fn roll_over(_r: u32) -> Weight {
+		0
+	}
 }
 
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
@@ -167,6 +193,21 @@ pub struct FundInfo<AccountId, Balance, BlockNumber, LeasePeriod> {
 	trie_index: TrieIndex,
 }
 
+/// The position of a contributor in the crowdloans of a para.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct CrowdloanPosition<Balance> {
+	/// The para the crowdloans are for.
+	pub para_id: ParaId,
+	/// The contribution to the current crowdloan of the para.
+	pub contributed: Balance,
+	/// The memo attached to the contribution.
+	pub memo: Vec<u8>,
+	/// Whether refunds are rolled over into the next crowdloan of the para.
+	pub auto_rollover: bool,
+	/// The refunds reserved until the next crowdloan of the para accepts contributions.
+	pub pending_rollover: Balance,
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -211,6 +252,16 @@ pub mod pallet {
 		/// The maximum length for the memo attached to a crowdloan contribution.
 		type MaxMemoLength: Get<u8>;
 
+		/// The origin of contributions made from other consensus systems, e.g. through an XCM
+		/// `Transact`. It yields the location of the contributor.
+		type RemoteContributorOrigin: EnsureOrigin<Self::Origin, Success = MultiLocation>;
+
+		/// Converts the location of a remote contributor into the local account holding its funds.
+		type LocationToAccountId: Convert<MultiLocation, Self::AccountId>;
+
+		/// The router used to send refunds back to remote contributors.
+		type XcmRouter: SendXcm;
+
 		/// Weight Information for the Extrinsics in the Pallet
 		type WeightInfo: WeightInfo;
 	}
@@ -236,11 +287,34 @@ pub mod pallet {
 	#[pallet::getter(fn endings_count)]
 	pub(super) type EndingsCount<T> = StorageValue<_, u32, ValueQuery>;
 
+	/// The last fund fully processed in idle time. The next idle time resumes with the funds
+	/// after it.
+	#[pallet::storage]
+	pub(super) type LastIdleFund<T> = StorageValue<_, ParaId>;
+
 	/// Tracker for the next available trie index
 	#[pallet::storage]
 	#[pallet::getter(fn next_trie_index)]
 	pub(super) type NextTrieIndex<T> = StorageValue<_, u32, ValueQuery>;
 
+	/// The contributors whose refunds are rolled over into the next crowdloan of a para.
+	#[pallet::storage]
+	pub(super) type AutoRollover<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Twox64Concat, ParaId, ()>;
+
+	/// Refunds reserved in the accounts of their contributors until the next crowdloan of the
+	/// para accepts contributions.
+	#[pallet::storage]
+	pub(super) type PendingRollovers<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		ParaId,
+		Blake2_128Concat,
+		T::AccountId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -265,6 +339,27 @@ pub mod pallet {
 		MemoUpdated(T::AccountId, ParaId, Vec<u8>),
 		/// A parachain has been moved to `NewRaise`
 		AddedToNewRaise(ParaId),
+		/// Contributed to a crowd sale from another consensus system.
+		/// `[location, who, fund_index, amount]`
+		ContributedFromLocation(MultiLocation, T::AccountId, ParaId, BalanceOf<T>),
+		/// A refund has been sent back to the location of a remote contributor.
+		/// `[location, fund_index, amount]`
+		RefundRouted(MultiLocation, ParaId, BalanceOf<T>),
+		/// A refund could not be sent back to the location of a remote contributor and remains in
+		/// its local account. `[who, location, fund_index]`
+		RefundRoutingFailed(T::AccountId, MultiLocation, ParaId),
+		/// Refunds of a contributor are rolled over into the next crowdloan of a para, or no
+		/// longer are. `[who, fund_index, enabled]`
+		AutoRolloverSet(T::AccountId, ParaId, bool),
+		/// A refund has been reserved to be rolled over into the next crowdloan of a para.
+		/// `[who, fund_index, amount]`
+		RolloverReserved(T::AccountId, ParaId, BalanceOf<T>),
+		/// A reserved refund has been contributed to the next crowdloan of a para.
+		/// `[who, fund_index, amount]`
+		RolledOver(T::AccountId, ParaId, BalanceOf<T>),
+		/// A reserved refund could not be contributed to the next crowdloan of a para and has
+		/// been released to its contributor. `[who, fund_index, amount]`
+		RolloverReleased(T::AccountId, ParaId, BalanceOf<T>),
 	}
 
 	#[pallet::error]
@@ -313,6 +408,12 @@ pub mod pallet {
 		AlreadyInNewRaise,
 		/// No contributions allowed during the VRF delay
 		VrfDelayInProgress,
+		/// The location of a remote contributor has no local account.
+		UnknownLocation,
+		/// The refund could not be sent back to the location of a remote contributor.
+		CannotRouteRefund,
+		/// Refunds of the contributor are not rolled over into the next crowdloan of this para.
+		NotRollingOver,
 	}
 
 	#[pallet::hooks]
@@ -346,6 +447,10 @@ pub mod pallet {
 				T::DbWeight::get().reads(1)
 			}
 		}
+
+		fn on_idle(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::process_idle_funds(now, remaining_weight)
+		}
 	}
 
 	#[pallet::call]
@@ -428,79 +533,24 @@ pub mod pallet {
 			signature: Option<MultiSignature>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::do_contribute(who, index, value, signature)
+		}
 
-			ensure!(value >= T::MinContribution::get(), Error::<T>::ContributionTooSmall);
-			let mut fund = Self::funds(index).ok_or(Error::<T>::InvalidParaId)?;
-			fund.raised = fund.raised.checked_add(&value).ok_or(Error::<T>::Overflow)?;
-			ensure!(fund.raised <= fund.cap, Error::<T>::CapExceeded);
-
-			// Make sure crowdloan has not ended
-			let now = <frame_system::Pallet<T>>::block_number();
-			ensure!(now < fund.end, Error::<T>::ContributionPeriodOver);
-
-			// Make sure crowdloan is in a valid lease period
-			let current_lease_period = T::Auctioneer::lease_period_index();
-			ensure!(current_lease_period <= fund.first_period, Error::<T>::ContributionPeriodOver);
-
-			// Make sure crowdloan has not already won.
-			let fund_account = Self::fund_account_id(index);
-			ensure!(
-				!T::Auctioneer::has_won_an_auction(index, &fund_account),
-				Error::<T>::BidOrLeaseActive
-			);
-
-			// We disallow any crowdloan contributions during the VRF Period, so that people do not sneak their
-			// contributions into the auction when it would not impact the outcome.
-			ensure!(!T::Auctioneer::auction_status(now).is_vrf(), Error::<T>::VrfDelayInProgress);
-
-			let (old_balance, memo) = Self::contribution_get(fund.trie_index, &who);
-
-			if let Some(ref verifier) = fund.verifier {
-				let signature = signature.ok_or(Error::<T>::InvalidSignature)?;
-				let payload = (index, &who, old_balance, value);
-				let valid = payload.using_encoded(|encoded| {
-					signature.verify(encoded, &verifier.clone().into_account())
-				});
-				ensure!(valid, Error::<T>::InvalidSignature);
-			}
-
-			CurrencyOf::<T>::transfer(&who, &fund_account, value, AllowDeath)?;
-
-			let balance = old_balance.saturating_add(value);
-			Self::contribution_put(fund.trie_index, &who, &balance, &memo);
-
-			if T::Auctioneer::auction_status(now).is_ending().is_some() {
-				match fund.last_contribution {
-					// In ending period; must ensure that we are in NewRaise.
-					LastContribution::Ending(n) if n == now => {
-						// do nothing - already in NewRaise
-					},
-					_ => {
-						NewRaise::<T>::append(index);
-						fund.last_contribution = LastContribution::Ending(now);
-					},
-				}
-			} else {
-				let endings_count = Self::endings_count();
-				match fund.last_contribution {
-					LastContribution::PreEnding(a) if a == endings_count => {
-						// Not in ending period and no auctions have ended ending since our
-						// previous bid which was also not in an ending period.
-						// `NewRaise` will contain our ID still: Do nothing.
-					},
-					_ => {
-						// Not in ending period; but an auction has been ending since our previous
-						// bid, or we never had one to begin with. Add bid.
-						NewRaise::<T>::append(index);
-						fund.last_contribution = LastContribution::PreEnding(endings_count);
-					},
-				}
-			}
-
-			Funds::<T>::insert(index, &fund);
-
-			Self::deposit_event(Event::<T>::Contributed(who, index, value));
-			Ok(())
+		/// Contribute to a crowd sale from another consensus system, e.g. from a parachain or from
+		/// an account on a parachain through an XCM `Transact`.
+		///
+		/// The contribution is taken from the local account of the contributor's location, as given
+		/// by `LocationToAccountId`. The location is recorded along with the contribution, so that
+		/// refunds are sent back to it.
+		#[pallet::weight(T::WeightInfo::contribute_from_location())]
+		pub fn contribute_from_location(
+			origin: OriginFor<T>,
+			#[pallet::compact] index: ParaId,
+			#[pallet::compact] value: BalanceOf<T>,
+			signature: Option<MultiSignature>,
+		) -> DispatchResult {
+			let location = T::RemoteContributorOrigin::ensure_origin(origin)?;
+			Self::do_contribute_from_location(location, index, value, signature)
 		}
 
 		/// Withdraw full balance of a specific contributor.
@@ -536,7 +586,7 @@ pub mod pallet {
 			let (balance, _) = Self::contribution_get(fund.trie_index, &who);
 			ensure!(balance > Zero::zero(), Error::<T>::NoContributions);
 
-			CurrencyOf::<T>::transfer(&fund_account, &who, balance, AllowDeath)?;
+			Self::pay_out(index, fund.trie_index, &fund_account, &who, balance)?;
 
 			Self::contribution_kill(fund.trie_index, &who);
 			fund.raised = fund.raised.saturating_sub(balance);
@@ -564,25 +614,12 @@ pub mod pallet {
 			let fund_account = Self::fund_account_id(index);
 			Self::ensure_crowdloan_ended(now, &fund_account, &fund)?;
 
-			let mut refund_count = 0u32;
-			// Try killing the crowdloan child trie
-			let contributions = Self::contribution_iterator(fund.trie_index);
-			// Assume everyone will be refunded.
-			let mut all_refunded = true;
-			for (who, (balance, _)) in contributions {
-				if refund_count >= T::RemoveKeysLimit::get() {
-					// Not everyone was able to be refunded this time around.
-					all_refunded = false;
-					break
-				}
-				CurrencyOf::<T>::transfer(&fund_account, &who, balance, AllowDeath)?;
-				Self::contribution_kill(fund.trie_index, &who);
-				fund.raised = fund.raised.saturating_sub(balance);
-				refund_count += 1;
-			}
-
-			// Save the changes.
-			Funds::<T>::insert(index, &fund);
+			let (refund_count, all_refunded) = Self::refund_contributors(
+				index,
+				&mut fund,
+				&fund_account,
+				T::RemoveKeysLimit::get(),
+			)?;
 
 			if all_refunded {
 				Self::deposit_event(Event::<T>::AllRefunded(index));
@@ -688,6 +725,38 @@ pub mod pallet {
 			Self::deposit_event(Event::<T>::AddedToNewRaise(index));
 			Ok(())
 		}
+
+		/// Roll refunds of the crowdloan for a para over into its next crowdloan, or stop doing
+		/// so.
+		///
+		/// Refunds are reserved in the account of the contributor until the next crowdloan of the
+		/// para accepts contributions, at which point they are contributed to it. Stopping
+		/// releases any refund still reserved.
+		///
+		/// Origin must be Signed, and the user must have contributed to the crowdloan to start
+		/// rolling over.
+		#[pallet::weight(T::WeightInfo::set_auto_rollover())]
+		pub fn set_auto_rollover(
+			origin: OriginFor<T>,
+			index: ParaId,
+			enabled: bool,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			if enabled {
+				let fund = Self::funds(index).ok_or(Error::<T>::InvalidParaId)?;
+				let (balance, _) = Self::contribution_get(fund.trie_index, &who);
+				ensure!(balance > Zero::zero(), Error::<T>::NoContributions);
+				AutoRollover::<T>::insert(&who, index, ());
+			} else {
+				ensure!(AutoRollover::<T>::take(&who, index).is_some(), Error::<T>::NotRollingOver);
+				let pending = PendingRollovers::<T>::take(index, &who);
+				CurrencyOf::<T>::unreserve(&who, pending);
+			}
+
+			Self::deposit_event(Event::<T>::AutoRolloverSet(who, index, enabled));
+			Ok(())
+		}
 	}
 }
 
@@ -726,6 +795,30 @@ impl<T: Config> Pallet<T> {
 		who.using_encoded(|b| child::kill(&Self::id_from_index(index), b));
 	}
 
+	/// The child trie holding the locations of the remote contributors of a fund.
+	pub fn location_id_from_index(index: TrieIndex) -> child::ChildInfo {
+		let mut buf = Vec::new();
+		buf.extend_from_slice(b"crowdloan_location");
+		buf.extend_from_slice(&index.encode()[..]);
+		child::ChildInfo::new_default(T::Hashing::hash(&buf[..]).as_ref())
+	}
+
+	pub fn contributor_location_put(
+		index: TrieIndex,
+		who: &T::AccountId,
+		location: &MultiLocation,
+	) {
+		who.using_encoded(|b| child::put(&Self::location_id_from_index(index), b, location));
+	}
+
+	pub fn contributor_location_get(index: TrieIndex, who: &T::AccountId) -> Option<MultiLocation> {
+		who.using_encoded(|b| child::get(&Self::location_id_from_index(index), b))
+	}
+
+	pub fn contributor_location_kill(index: TrieIndex, who: &T::AccountId) {
+		who.using_encoded(|b| child::kill(&Self::location_id_from_index(index), b));
+	}
+
 	pub fn crowdloan_kill(index: TrieIndex) -> child::KillStorageResult {
 		child::kill_storage(&Self::id_from_index(index), Some(T::RemoveKeysLimit::get()))
 	}
@@ -739,6 +832,278 @@ impl<T: Config> Pallet<T> {
 		)
 	}
 
+	fn do_contribute(
+		who: T::AccountId,
+		index: ParaId,
+		value: BalanceOf<T>,
+		signature: Option<MultiSignature>,
+	) -> DispatchResult {
+		ensure!(value >= T::MinContribution::get(), Error::<T>::ContributionTooSmall);
+		let mut fund = Self::funds(index).ok_or(Error::<T>::InvalidParaId)?;
+		fund.raised = fund.raised.checked_add(&value).ok_or(Error::<T>::Overflow)?;
+		ensure!(fund.raised <= fund.cap, Error::<T>::CapExceeded);
+
+		// Make sure crowdloan has not ended
+		let now = <frame_system::Pallet<T>>::block_number();
+		ensure!(now < fund.end, Error::<T>::ContributionPeriodOver);
+
+		// Make sure crowdloan is in a valid lease period
+		let current_lease_period = T::Auctioneer::lease_period_index();
+		ensure!(current_lease_period <= fund.first_period, Error::<T>::ContributionPeriodOver);
+
+		// Make sure crowdloan has not already won.
+		let fund_account = Self::fund_account_id(index);
+		ensure!(
+			!T::Auctioneer::has_won_an_auction(index, &fund_account),
+			Error::<T>::BidOrLeaseActive
+		);
+
+		// We disallow any crowdloan contributions during the VRF Period, so that people do not sneak their
+		// contributions into the auction when it would not impact the outcome.
+		ensure!(!T::Auctioneer::auction_status(now).is_vrf(), Error::<T>::VrfDelayInProgress);
+
+		let (old_balance, memo) = Self::contribution_get(fund.trie_index, &who);
+
+		if let Some(ref verifier) = fund.verifier {
+			let signature = signature.ok_or(Error::<T>::InvalidSignature)?;
+			let payload = (index, &who, old_balance, value);
+			let valid = payload.using_encoded(|encoded| {
+				signature.verify(encoded, &verifier.clone().into_account())
+			});
+			ensure!(valid, Error::<T>::InvalidSignature);
+		}
+
+		CurrencyOf::<T>::transfer(&who, &fund_account, value, AllowDeath)?;
+
+		let balance = old_balance.saturating_add(value);
+		Self::contribution_put(fund.trie_index, &who, &balance, &memo);
+
+		if T::Auctioneer::auction_status(now).is_ending().is_some() {
+			match fund.last_contribution {
+				// In ending period; must ensure that we are in NewRaise.
+				LastContribution::Ending(n) if n == now => {
+					// do nothing - already in NewRaise
+				},
+				_ => {
+					NewRaise::<T>::append(index);
+					fund.last_contribution = LastContribution::Ending(now);
+				},
+			}
+		} else {
+			let endings_count = Self::endings_count();
+			match fund.last_contribution {
+				LastContribution::PreEnding(a) if a == endings_count => {
+					// Not in ending period and no auctions have ended ending since our
+					// previous bid which was also not in an ending period.
+					// `NewRaise` will contain our ID still: Do nothing.
+				},
+				_ => {
+					// Not in ending period; but an auction has been ending since our previous
+					// bid, or we never had one to begin with. Add bid.
+					NewRaise::<T>::append(index);
+					fund.last_contribution = LastContribution::PreEnding(endings_count);
+				},
+			}
+		}
+
+		Funds::<T>::insert(index, &fund);
+
+		Self::deposit_event(Event::<T>::Contributed(who, index, value));
+		Ok(())
+	}
+
+	fn do_contribute_from_location(
+		location: MultiLocation,
+		index: ParaId,
+		value: BalanceOf<T>,
+		signature: Option<MultiSignature>,
+	) -> DispatchResult {
+		let who = T::LocationToAccountId::convert_ref(&location)
+			.map_err(|()| Error::<T>::UnknownLocation)?;
+
+		Self::do_contribute(who.clone(), index, value, signature)?;
+
+		let fund = Self::funds(index).ok_or(Error::<T>::InvalidParaId)?;
+		Self::contributor_location_put(fund.trie_index, &who, &location);
+
+		Self::deposit_event(Event::<T>::ContributedFromLocation(location, who, index, value));
+		Ok(())
+	}
+
+	/// The positions of `who` in the crowdloans of every para, ordered by para.
+	///
+	/// This goes through all funds, so it is only meant to be called off-chain.
+	pub fn contributor_positions(who: &T::AccountId) -> Vec<CrowdloanPosition<BalanceOf<T>>> {
+		let mut positions = BTreeMap::<ParaId, CrowdloanPosition<BalanceOf<T>>>::new();
+		let empty = |para_id| CrowdloanPosition {
+			para_id,
+			contributed: Zero::zero(),
+			memo: Vec::new(),
+			auto_rollover: false,
+			pending_rollover: Zero::zero(),
+		};
+
+		for (index, fund) in Funds::<T>::iter() {
+			let (contributed, memo) = Self::contribution_get(fund.trie_index, who);
+			if !contributed.is_zero() {
+				positions.insert(index, CrowdloanPosition { contributed, memo, ..empty(index) });
+			}
+		}
+		for (index, ()) in AutoRollover::<T>::iter_prefix(who) {
+			let position = positions.entry(index).or_insert_with(|| empty(index));
+			position.auto_rollover = true;
+			position.pending_rollover = PendingRollovers::<T>::get(index, who);
+		}
+
+		positions.into_iter().map(|(_, position)| position).collect()
+	}
+
+	/// Refund at most `limit` contributors of the ended crowdloan `index`, saving the changes to
+	/// `fund`.
+	///
+	/// Returns the number of contributors refunded and whether all of them have been.
+	#[transactional]
+	fn refund_contributors(
+		index: ParaId,
+		fund: &mut FundInfo<T::AccountId, BalanceOf<T>, T::BlockNumber, LeasePeriodOf<T>>,
+		fund_account: &T::AccountId,
+		limit: u32,
+	) -> Result<(u32, bool), DispatchError> {
+		let mut refund_count = 0u32;
+		// Try killing the crowdloan child trie
+		let contributions = Self::contribution_iterator(fund.trie_index);
+		// Assume everyone will be refunded.
+		let mut all_refunded = true;
+		for (who, (balance, _)) in contributions {
+			if refund_count >= limit {
+				// Not everyone was able to be refunded this time around.
+				all_refunded = false;
+				break
+			}
+			Self::pay_out(index, fund.trie_index, fund_account, &who, balance)?;
+			Self::contribution_kill(fund.trie_index, &who);
+			fund.raised = fund.raised.saturating_sub(balance);
+			refund_count += 1;
+		}
+
+		// Save the changes.
+		Funds::<T>::insert(index, &*fund);
+
+		Ok((refund_count, all_refunded))
+	}
+
+	/// Refund the contributors of ended crowdloans, and roll reserved refunds over into the
+	/// crowdloans accepting contributions, within `limit`.
+	///
+	/// Funds are processed in storage order, resuming after the last fund fully processed by the
+	/// previous call, so that no fund is starved by the ones before it.
+	///
+	/// Returns the weight used.
+	fn process_idle_funds(now: T::BlockNumber, limit: Weight) -> Weight {
+		let idle_base = T::WeightInfo::on_idle(0);
+		if idle_base > limit {
+			return 0
+		}
+		let per_fund = T::WeightInfo::on_idle(1).saturating_sub(idle_base);
+		let refund_base = T::WeightInfo::refund(0);
+		let per_refund = T::WeightInfo::refund(1).saturating_sub(refund_base).max(1);
+		let per_rollover =
+			T::WeightInfo::roll_over(1).saturating_sub(T::WeightInfo::roll_over(0)).max(1);
+		let mut weight_used = idle_base;
+
+		let mut last_processed = LastIdleFund::<T>::get();
+		let funds = match last_processed {
+			Some(last) => Funds::<T>::iter_from(Funds::<T>::hashed_key_for(last)),
+			None => Funds::<T>::iter(),
+		};
+		let mut all_processed = true;
+
+		for (index, mut fund) in funds {
+			if weight_used.saturating_add(per_fund) > limit {
+				all_processed = false;
+				break
+			}
+			weight_used += per_fund;
+
+			let fund_account = Self::fund_account_id(index);
+			if !fund.raised.is_zero() &&
+				Self::ensure_crowdloan_ended(now, &fund_account, &fund).is_ok()
+			{
+				let available = limit.saturating_sub(weight_used).saturating_sub(refund_base);
+				let max_refunds =
+					(available / per_refund).min(T::RemoveKeysLimit::get() as Weight) as u32;
+				if max_refunds.is_zero() {
+					all_processed = false;
+					break
+				}
+				weight_used += refund_base;
+				// A failed refund changes nothing and is retried later.
+				if let Ok((refunded, all_refunded)) =
+					Self::refund_contributors(index, &mut fund, &fund_account, max_refunds)
+				{
+					weight_used += per_refund.saturating_mul(refunded as Weight);
+					if all_refunded {
+						Self::deposit_event(Event::<T>::AllRefunded(index));
+					} else {
+						Self::deposit_event(Event::<T>::PartiallyRefunded(index));
+						all_processed = false;
+						break
+					}
+				}
+			} else if Self::accepts_contributions(now, index, &fund_account, &fund) {
+				let max_rollovers = (limit.saturating_sub(weight_used) / per_rollover) as usize;
+				let mut rollovers = PendingRollovers::<T>::iter_prefix(index)
+					.take(max_rollovers.saturating_add(1))
+					.collect::<Vec<_>>();
+				let all_rolled_over = rollovers.len() <= max_rollovers;
+				rollovers.truncate(max_rollovers);
+				for (who, amount) in rollovers {
+					weight_used += per_rollover;
+					Self::roll_over(index, who, amount);
+				}
+				if !all_rolled_over {
+					all_processed = false;
+					break
+				}
+			}
+			last_processed = Some(index);
+		}
+
+		if all_processed {
+			// The next call starts over with the first fund.
+			LastIdleFund::<T>::kill();
+		} else if let Some(last) = last_processed {
+			LastIdleFund::<T>::put(last);
+		}
+
+		weight_used
+	}
+
+	/// Whether the crowdloan `index` can currently be contributed to, regardless of the
+	/// contribution.
+	fn accepts_contributions(
+		now: T::BlockNumber,
+		index: ParaId,
+		fund_account: &T::AccountId,
+		fund: &FundInfo<T::AccountId, BalanceOf<T>, T::BlockNumber, LeasePeriodOf<T>>,
+	) -> bool {
+		now < fund.end &&
+			T::Auctioneer::lease_period_index() <= fund.first_period &&
+			!T::Auctioneer::has_won_an_auction(index, fund_account) &&
+			!T::Auctioneer::auction_status(now).is_vrf()
+	}
+
+	/// Contribute the refund reserved by `who` to the crowdloan `index`. If the contribution is
+	/// not accepted, the refund is released instead.
+	fn roll_over(index: ParaId, who: T::AccountId, amount: BalanceOf<T>) {
+		PendingRollovers::<T>::remove(index, &who);
+		CurrencyOf::<T>::unreserve(&who, amount);
+		match Self::do_contribute(who.clone(), index, amount, None) {
+			Ok(()) => Self::deposit_event(Event::<T>::RolledOver(who, index, amount)),
+			Err(_) => Self::deposit_event(Event::<T>::RolloverReleased(who, index, amount)),
+		}
+	}
+
 	/// This function checks all conditions which would qualify a crowdloan has ended.
 	/// * If we have reached the `fund.end` block OR the first lease period the fund is
 	///   trying to bid for has started already.
@@ -765,6 +1130,97 @@ impl<T: Config> Pallet<T> {
 
 		Ok(())
 	}
+
+	/// Pay the contribution of `who` back from the fund account. If the contribution came from
+	/// another consensus system, the funds are then sent back to the contributor's location.
+	///
+	/// Failing to send the funds back is not an error: they remain in the local account of the
+	/// contributor, from where its location can still move them.
+	///
+	/// Otherwise, if the contributor rolls refunds over into the next crowdloan of the para, the
+	/// funds are reserved until it accepts contributions.
+	fn pay_out(
+		index: ParaId,
+		trie_index: TrieIndex,
+		fund_account: &T::AccountId,
+		who: &T::AccountId,
+		balance: BalanceOf<T>,
+	) -> DispatchResult {
+		CurrencyOf::<T>::transfer(fund_account, who, balance, AllowDeath)?;
+
+		if let Some(location) = Self::contributor_location_get(trie_index, who) {
+			Self::contributor_location_kill(trie_index, who);
+			match Self::route_refund(who, &location, balance) {
+				Ok(()) => Self::deposit_event(Event::<T>::RefundRouted(location, index, balance)),
+				Err(_) => Self::deposit_event(Event::<T>::RefundRoutingFailed(
+					who.clone(),
+					location,
+					index,
+				)),
+			}
+		} else if AutoRollover::<T>::contains_key(who, index) {
+			// Funds which cannot be reserved are simply left free.
+			if CurrencyOf::<T>::reserve(who, balance).is_ok() {
+				PendingRollovers::<T>::mutate(index, who, |pending| {
+					*pending = pending.saturating_add(balance)
+				});
+				Self::deposit_event(Event::<T>::RolloverReserved(who.clone(), index, balance));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Send `amount` from the local account `who` back to the location of the remote contributor
+	/// owning it.
+	///
+	/// Only accounts on child parachains are supported: the funds are moved into the sovereign
+	/// account of the parachain, which is then told to deposit them to the account through a
+	/// reserve asset transfer. The funds of a parachain itself are already in its sovereign
+	/// account, so nothing needs to be sent.
+	#[transactional]
+	fn route_refund(
+		who: &T::AccountId,
+		location: &MultiLocation,
+		amount: BalanceOf<T>,
+	) -> Result<(), DispatchError> {
+		use xcm::latest::prelude::*;
+
+		let (para, beneficiary) = match location {
+			MultiLocation { parents: 0, interior } => match interior.clone().split_first() {
+				(beneficiary, Some(Parachain(para))) => (para, beneficiary),
+				_ => return Err(Error::<T>::CannotRouteRefund.into()),
+			},
+			_ => return Err(Error::<T>::CannotRouteRefund.into()),
+		};
+		if beneficiary == Here {
+			return Ok(())
+		}
+
+		let dest = MultiLocation::new(0, X1(Parachain(para)));
+		let sovereign = T::LocationToAccountId::convert_ref(&dest)
+			.map_err(|()| Error::<T>::CannotRouteRefund)?;
+		CurrencyOf::<T>::transfer(who, &sovereign, amount, AllowDeath)?;
+
+		// The relay chain is the reserve of its native asset, which the parachain sees as its
+		// parent.
+		let fees: MultiAsset =
+			(MultiLocation::parent(), UniqueSaturatedInto::<u128>::unique_saturated_into(amount))
+				.into();
+		let message = Xcm(vec![
+			ReserveAssetDeposited(fees.clone().into()),
+			ClearOrigin,
+			BuyExecution { fees, weight_limit: Unlimited },
+			DepositAsset {
+				assets: Wild(All),
+				max_assets: 1,
+				beneficiary: MultiLocation::new(0, beneficiary),
+			},
+		]);
+		T::XcmRouter::send_xcm(dest, message).map_err(|_| Error::<T>::CannotRouteRefund)?;
+
+		Ok(())
+	}
 }
 
 impl<T: Config> crate::traits::OnSwap for Pallet<T> {
@@ -773,6 +1229,28 @@ impl<T: Config> crate::traits::OnSwap for Pallet<T> {
 	}
 }
 
+/// The locations of remote contributors whose refunds can be routed back to them: child
+/// parachains and the accounts on them.
+///
+/// Meant as the filter of the `RemoteContributorOrigin`, so that contributions are only accepted
+/// from where they can be refunded.
+pub struct RemoteContributorLocations;
+impl Contains<MultiLocation> for RemoteContributorLocations {
+	fn contains(location: &MultiLocation) -> bool {
+		use xcm::latest::prelude::*;
+
+		matches!(
+			location,
+			MultiLocation {
+				parents: 0,
+				interior: X1(Parachain(_)) |
+					X2(Parachain(_), AccountId32 { .. }) |
+					X2(Parachain(_), AccountKey20 { .. }),
+			}
+		)
+	}
+}
+
 #[cfg(any(feature = "runtime-benchmarks", test))]
 mod crypto {
 	use sp_core::ed25519;
@@ -797,11 +1275,12 @@ mod tests {
 
 	use frame_support::{
 		assert_noop, assert_ok, parameter_types,
-		traits::{OnFinalize, OnInitialize},
+		traits::{EnsureOrigin, OnFinalize, OnIdle, OnInitialize},
 	};
 	use primitives::v1::Id as ParaId;
 	use sp_core::H256;
 	use std::{cell::RefCell, collections::BTreeMap, sync::Arc};
+	use xcm::latest::prelude::*;
 	// The testing primitives are very useful for avoiding having to work with signatures
 	// or public keys. `u64` is used as the `AccountId` and no `Signature`s are requried.
 	use crate::{
@@ -812,7 +1291,7 @@ mod tests {
 	use sp_keystore::{testing::KeyStore, KeystoreExt};
 	use sp_runtime::{
 		testing::Header,
-		traits::{BlakeTwo256, IdentityLookup},
+		traits::{BadOrigin, BlakeTwo256, IdentityLookup},
 		DispatchResult,
 	};
 
@@ -894,6 +1373,8 @@ mod tests {
 		static ENDING_PERIOD: RefCell<u64> = RefCell::new(5);
 		static BIDS_PLACED: RefCell<Vec<BidPlaced>> = RefCell::new(Vec::new());
 		static HAS_WON: RefCell<BTreeMap<(ParaId, u64), bool>> = RefCell::new(BTreeMap::new());
+		static REMOTE_PARA: RefCell<u32> = RefCell::new(2000);
+		static SENT_XCM: RefCell<Vec<(MultiLocation, Xcm<()>)>> = RefCell::new(Vec::new());
 	}
 
 	#[allow(unused)]
@@ -930,6 +1411,65 @@ mod tests {
 		HAS_WON.with(|p| p.borrow_mut().insert((para, who), winner));
 	}
 
+	fn set_remote_para(para: u32) {
+		REMOTE_PARA.with(|p| *p.borrow_mut() = para);
+	}
+	fn sent_xcm() -> Vec<(MultiLocation, Xcm<()>)> {
+		SENT_XCM.with(|p| p.borrow().clone())
+	}
+	// The location of the test account `who` on the remote parachain.
+	fn remote_location(who: u64) -> MultiLocation {
+		let para = REMOTE_PARA.with(|p| *p.borrow());
+		MultiLocation::new(0, X2(Parachain(para), AccountIndex64 { network: Any, index: who }))
+	}
+
+	// Signed origins act as the origin of the same account on the remote parachain.
+	pub struct TestRemoteContributorOrigin;
+	impl EnsureOrigin<Origin> for TestRemoteContributorOrigin {
+		type Success = MultiLocation;
+
+		fn try_origin(o: Origin) -> Result<MultiLocation, Origin> {
+			match o.clone().into() {
+				Ok(frame_system::RawOrigin::Signed(who)) => Ok(remote_location(who)),
+				_ => Err(o),
+			}
+		}
+
+		#[cfg(feature = "runtime-benchmarks")]
+		fn successful_origin() -> Origin {
+			Origin::signed(1)
+		}
+	}
+
+	// Accounts on remote parachains map to the test account with the same index, and
+	// parachains map to their sovereign account `10_000 + para`.
+	pub struct TestLocationToAccountId;
+	impl Convert<MultiLocation, u64> for TestLocationToAccountId {
+		fn convert(location: MultiLocation) -> Result<u64, MultiLocation> {
+			match location {
+				MultiLocation { parents: 0, interior: X1(Parachain(para)) } =>
+					Ok(10_000 + para as u64),
+				MultiLocation {
+					parents: 0,
+					interior: X2(Parachain(_), AccountIndex64 { index, .. }),
+				} => Ok(index),
+				location => Err(location),
+			}
+		}
+	}
+
+	// Only parachain 2000 can be reached.
+	pub struct TestXcmRouter;
+	impl SendXcm for TestXcmRouter {
+		fn send_xcm(dest: MultiLocation, msg: Xcm<()>) -> SendResult {
+			if dest != MultiLocation::new(0, X1(Parachain(2000))) {
+				return Err(SendError::CannotReachDestination(dest, msg))
+			}
+			SENT_XCM.with(|p| p.borrow_mut().push((dest, msg)));
+			Ok(())
+		}
+	}
+
 	pub struct TestAuctioneer;
 	impl Auctioneer for TestAuctioneer {
 		type AccountId = u64;
@@ -1021,6 +1561,9 @@ mod tests {
 		type Registrar = TestRegistrar<Test>;
 		type Auctioneer = TestAuctioneer;
 		type MaxMemoLength = MaxMemoLength;
+		type RemoteContributorOrigin = TestRemoteContributorOrigin;
+		type LocationToAccountId = TestLocationToAccountId;
+		type XcmRouter = TestXcmRouter;
 		type WeightInfo = crate::crowdloan::TestWeightInfo;
 	}
 
@@ -1492,6 +2035,142 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn contribute_from_location_works() {
+		new_test_ext().execute_with(|| {
+			// So events are emitted
+			run_to_block(1);
+			let para = new_para();
+
+			// Set up a crowdloan
+			assert_ok!(Crowdloan::create(Origin::signed(1), para, 1000, 1, 4, 9, None));
+			let trie_index = Crowdloan::funds(para).unwrap().trie_index;
+
+			// Only remote origins can contribute from a location
+			assert_noop!(
+				Crowdloan::contribute_from_location(Origin::root(), para, 49, None),
+				BadOrigin,
+			);
+
+			// User 2 on the remote parachain contributes from its local account
+			assert_ok!(Crowdloan::contribute_from_location(Origin::signed(2), para, 49, None));
+			assert_eq!(Balances::free_balance(2), 1951);
+			assert_eq!(Crowdloan::contribution_get(trie_index, &2).0, 49);
+			assert_eq!(Balances::free_balance(Crowdloan::fund_account_id(para)), 49);
+			// The location is recorded along with the contribution
+			assert_eq!(
+				Crowdloan::contributor_location_get(trie_index, &2),
+				Some(remote_location(2))
+			);
+			assert_eq!(
+				last_event(),
+				super::Event::<Test>::ContributedFromLocation(remote_location(2), 2, para, 49)
+					.into(),
+			);
+			// Local contributions don't record a location
+			assert_ok!(Crowdloan::contribute(Origin::signed(3), para, 50, None));
+			assert_eq!(Crowdloan::contributor_location_get(trie_index, &3), None);
+		});
+	}
+
+	#[test]
+	fn refunds_are_routed_to_remote_contributors() {
+		new_test_ext().execute_with(|| {
+			let para = new_para();
+			let sovereign = 12_000;
+
+			// Set up a crowdloan ending on 9
+			assert_ok!(Crowdloan::create(Origin::signed(1), para, 1000, 1, 1, 9, None));
+			let trie_index = Crowdloan::funds(para).unwrap().trie_index;
+			assert_ok!(Crowdloan::contribute_from_location(Origin::signed(2), para, 100, None));
+			assert_ok!(Crowdloan::contribute_from_location(Origin::signed(3), para, 200, None));
+			assert_ok!(Crowdloan::contribute(Origin::signed(4), para, 300, None));
+
+			run_to_block(10);
+
+			assert_ok!(Crowdloan::withdraw(Origin::signed(1337), 2, para));
+			// The funds left the local account of the contributor for the parachain's
+			// sovereign account...
+			assert_eq!(Balances::free_balance(2), 1900);
+			assert_eq!(Balances::free_balance(sovereign), 100);
+			assert_eq!(Crowdloan::contributor_location_get(trie_index, &2), None);
+			// ...and the parachain was told to deposit them to the contributor.
+			let expected_message = |amount: u128| {
+				Xcm(vec![
+					ReserveAssetDeposited((Parent, amount).into()),
+					ClearOrigin,
+					BuyExecution { fees: (Parent, amount).into(), weight_limit: Unlimited },
+					DepositAsset {
+						assets: Wild(All),
+						max_assets: 1,
+						beneficiary: AccountIndex64 { network: Any, index: 2 }.into(),
+					},
+				])
+			};
+			let dest = MultiLocation::new(0, X1(Parachain(2000)));
+			assert_eq!(sent_xcm(), vec![(dest.clone(), expected_message(100))]);
+			assert!(System::events().iter().any(|r| r.event ==
+				super::Event::<Test>::RefundRouted(remote_location(2), para, 100).into()));
+
+			assert_ok!(Crowdloan::refund(Origin::signed(1337), para));
+			assert_eq!(Balances::free_balance(sovereign), 300);
+			assert_eq!(Balances::free_balance(4), 4000);
+			assert_eq!(sent_xcm().len(), 2);
+			assert_eq!(Crowdloan::contributor_location_get(trie_index, &3), None);
+		});
+	}
+
+	#[test]
+	fn failed_refund_routing_keeps_funds_in_local_account() {
+		new_test_ext().execute_with(|| {
+			let para = new_para();
+
+			// Set up a crowdloan ending on 9
+			assert_ok!(Crowdloan::create(Origin::signed(1), para, 1000, 1, 1, 9, None));
+			// The contributor is on a parachain which can't be reached
+			set_remote_para(3000);
+			assert_ok!(Crowdloan::contribute_from_location(Origin::signed(2), para, 100, None));
+
+			run_to_block(10);
+
+			assert_ok!(Crowdloan::withdraw(Origin::signed(1337), 2, para));
+			assert_eq!(Balances::free_balance(2), 2000);
+			assert_eq!(Balances::free_balance(13_000), 0);
+			assert!(sent_xcm().is_empty());
+			assert!(System::events().iter().any(|r| r.event ==
+				super::Event::<Test>::RefundRoutingFailed(2, remote_location(2), para).into()));
+		});
+	}
+
+	#[test]
+	fn remote_contributor_locations_are_child_paras_and_their_accounts() {
+		let para = Parachain(2000);
+		let account = AccountId32 { network: Any, id: [1; 32] };
+		let key = AccountKey20 { network: Any, key: [1; 20] };
+
+		assert!(RemoteContributorLocations::contains(&MultiLocation::new(0, X1(para.clone()))));
+		assert!(RemoteContributorLocations::contains(&MultiLocation::new(
+			0,
+			X2(para.clone(), account.clone())
+		)));
+		assert!(RemoteContributorLocations::contains(&MultiLocation::new(
+			0,
+			X2(para.clone(), key)
+		)));
+
+		assert!(!RemoteContributorLocations::contains(&MultiLocation::here()));
+		assert!(!RemoteContributorLocations::contains(&MultiLocation::parent()));
+		assert!(!RemoteContributorLocations::contains(&MultiLocation::new(0, X1(account.clone()))));
+		assert!(!RemoteContributorLocations::contains(&MultiLocation::new(
+			1,
+			X2(para.clone(), account.clone())
+		)));
+		assert!(!RemoteContributorLocations::contains(&MultiLocation::new(
+			0,
+			X3(para, account, GeneralIndex(1))
+		)));
+	}
+
 	#[test]
 	fn withdraw_cannot_be_griefed() {
 		new_test_ext().execute_with(|| {
@@ -1622,6 +2301,124 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn on_idle_refunds_ended_crowdloans() {
+		new_test_ext().execute_with(|| {
+			let para = new_para();
+			let account_id = Crowdloan::fund_account_id(para);
+
+			assert_ok!(Crowdloan::create(Origin::signed(1), para, 1000, 1, 1, 9, None));
+			assert_ok!(Crowdloan::contribute(Origin::signed(1), para, 100, None));
+			assert_ok!(Crowdloan::contribute(Origin::signed(2), para, 200, None));
+			assert_ok!(Crowdloan::contribute(Origin::signed(3), para, 300, None));
+
+			// Nothing to do before the crowdloan has ended.
+			Crowdloan::on_idle(9, Weight::max_value());
+			assert_eq!(Balances::free_balance(account_id), 600);
+
+			run_to_block(10);
+			// The budget only allows visiting the fund and two refunds.
+			assert_eq!(Crowdloan::on_idle(10, 3), 3);
+			assert_eq!(last_event(), super::Event::<Test>::PartiallyRefunded(para).into());
+			assert_eq!(Crowdloan::contribution_iterator(0).count(), 1);
+
+			Crowdloan::on_idle(10, Weight::max_value());
+			assert_eq!(last_event(), super::Event::<Test>::AllRefunded(para).into());
+			assert_eq!(Balances::free_balance(account_id), 0);
+			assert_eq!(Balances::free_balance(1), 1000 - 1);
+			assert_eq!(Balances::free_balance(2), 2000);
+			assert_eq!(Balances::free_balance(3), 3000);
+			assert!(Crowdloan::funds(para).unwrap().raised.is_zero());
+		});
+	}
+
+	#[test]
+	fn on_idle_resumes_after_the_last_processed_fund() {
+		new_test_ext().execute_with(|| {
+			let para_1 = new_para();
+			let para_2 = new_para();
+
+			assert_ok!(Crowdloan::create(Origin::signed(1), para_1, 1000, 1, 1, 9, None));
+			assert_ok!(Crowdloan::create(Origin::signed(1), para_2, 1000, 1, 1, 9, None));
+			assert_ok!(Crowdloan::contribute(Origin::signed(2), para_1, 100, None));
+			assert_ok!(Crowdloan::contribute(Origin::signed(2), para_2, 100, None));
+
+			run_to_block(10);
+			// Each budget allows visiting a fund and a single refund, so the second call only
+			// gets to the second fund by resuming after the first one.
+			assert_eq!(Crowdloan::on_idle(10, 2), 2);
+			assert_eq!(Crowdloan::on_idle(10, 2), 2);
+			assert!(Crowdloan::funds(para_1).unwrap().raised.is_zero());
+			assert!(Crowdloan::funds(para_2).unwrap().raised.is_zero());
+			assert_eq!(LastIdleFund::<Test>::get(), None);
+		});
+	}
+
+	#[test]
+	fn refunds_roll_over_into_next_crowdloan() {
+		new_test_ext().execute_with(|| {
+			let para = new_para();
+
+			assert_ok!(Crowdloan::create(Origin::signed(1), para, 1000, 1, 1, 9, None));
+			assert_ok!(Crowdloan::contribute(Origin::signed(2), para, 100, None));
+			assert_ok!(Crowdloan::contribute(Origin::signed(3), para, 50, None));
+
+			run_to_block(1);
+			assert_noop!(
+				Crowdloan::set_auto_rollover(Origin::signed(4), para, true),
+				Error::<Test>::NoContributions,
+			);
+			assert_noop!(
+				Crowdloan::set_auto_rollover(Origin::signed(2), para, false),
+				Error::<Test>::NotRollingOver,
+			);
+			assert_ok!(Crowdloan::set_auto_rollover(Origin::signed(2), para, true));
+			assert_eq!(last_event(), super::Event::<Test>::AutoRolloverSet(2, para, true).into());
+			assert_ok!(Crowdloan::set_auto_rollover(Origin::signed(3), para, true));
+
+			// Refunds are reserved until the next crowdloan of the para.
+			run_to_block(10);
+			Crowdloan::on_idle(10, Weight::max_value());
+			assert_eq!(Balances::reserved_balance(2), 100);
+			assert_eq!(Balances::free_balance(2), 1900);
+			assert_eq!(Balances::reserved_balance(3), 50);
+			assert_eq!(
+				Crowdloan::contributor_positions(&2),
+				vec![CrowdloanPosition {
+					para_id: para,
+					contributed: 0,
+					memo: vec![],
+					auto_rollover: true,
+					pending_rollover: 100,
+				}],
+			);
+
+			// Stopping releases the reserved refund.
+			assert_ok!(Crowdloan::set_auto_rollover(Origin::signed(3), para, false));
+			assert_eq!(Balances::reserved_balance(3), 0);
+			assert_eq!(Balances::free_balance(3), 3000);
+
+			assert_ok!(Crowdloan::dissolve(Origin::signed(1), para));
+			assert_ok!(Crowdloan::create(Origin::signed(1), para, 1000, 1, 1, 19, None));
+			Crowdloan::on_idle(10, Weight::max_value());
+			assert_eq!(last_event(), super::Event::<Test>::RolledOver(2, para, 100).into());
+			assert_eq!(Balances::reserved_balance(2), 0);
+			assert_eq!(Balances::free_balance(2), 1900);
+			assert_eq!(Crowdloan::funds(para).unwrap().raised, 100);
+			assert_eq!(
+				Crowdloan::contributor_positions(&2),
+				vec![CrowdloanPosition {
+					para_id: para,
+					contributed: 100,
+					memo: vec![],
+					auto_rollover: true,
+					pending_rollover: 0,
+				}],
+			);
+			assert!(Crowdloan::contributor_positions(&3).is_empty());
+		});
+	}
+
 	#[test]
 	fn dissolve_works() {
 		new_test_ext().execute_with(|| {
@@ -1888,6 +2685,29 @@ mod benchmarking {
 		));
 	}
 
+	fn assert_has_event<T: Config>(generic_event: <T as Config>::Event) {
+		let system_event: <T as frame_system::Config>::Event = generic_event.into();
+		assert!(frame_system::Pallet::<T>::events().iter().any(|r| r.event == system_event));
+	}
+
+	// The location of the `i`th account on a child parachain.
+	fn remote_location(i: u32) -> MultiLocation {
+		use xcm::latest::prelude::*;
+
+		let mut id = [0u8; 32];
+		id[..4].copy_from_slice(&i.to_le_bytes());
+		MultiLocation::new(0, X2(Parachain(2000), AccountId32 { network: Any, id }))
+	}
+
+	// Record `who` as the local account of the `i`th account on a child parachain, as if it had
+	// contributed from there.
+	fn set_remote_location<T: Config>(who: &T::AccountId, index: ParaId, i: u32) -> MultiLocation {
+		let fund = Funds::<T>::get(index).expect("fund was created...");
+		let location = remote_location(i);
+		Crowdloan::<T>::contributor_location_put(fund.trie_index, who, &location);
+		location
+	}
+
 	benchmarks! {
 		create {
 			let para_id = ParaId::from(1);
@@ -1930,31 +2750,80 @@ mod benchmarking {
 			assert_last_event::<T>(Event::<T>::Contributed(caller, fund_index, contribution).into());
 		}
 
+		// The origin check is left out: it only filters the location, and the `EnsureXcm` origin
+		// of the runtimes has no successful origin at the location of a remote contributor.
+		contribute_from_location {
+			let fund_index = create_fund::<T>(1, 100u32.into());
+			let location = remote_location(0);
+			let who = T::LocationToAccountId::convert_ref(&location)
+				.map_err(|()| "remote contributor has no local account")?;
+			let contribution = T::MinContribution::get();
+			CurrencyOf::<T>::make_free_balance_be(&who, BalanceOf::<T>::max_value());
+
+			let pubkey = crypto::create_ed25519_pubkey(b"//verifier".to_vec());
+			let payload = (fund_index, &who, BalanceOf::<T>::default(), contribution);
+			let sig = crypto::create_ed25519_signature(&payload.encode(), pubkey);
+		}: {
+			Crowdloan::<T>::do_contribute_from_location(
+				location.clone(),
+				fund_index,
+				contribution,
+				Some(sig),
+			)?;
+		} verify {
+			let fund = Funds::<T>::get(fund_index).expect("fund was created...");
+			assert_eq!(
+				Crowdloan::<T>::contributor_location_get(fund.trie_index, &who),
+				Some(location.clone()),
+			);
+			assert_last_event::<T>(
+				Event::<T>::ContributedFromLocation(location, who, fund_index, contribution).into()
+			);
+		}
+
+		// Worst case: the contributor is an account on a child parachain, so the refund is sent
+		// back to it over XCM.
 		withdraw {
 			let fund_index = create_fund::<T>(1337, 100u32.into());
 			let caller: T::AccountId = whitelisted_caller();
 			let contributor = account("contributor", 0, 0);
 			contribute_fund::<T>(&contributor, fund_index);
+			let location = set_remote_location::<T>(&contributor, fund_index, 0);
 			frame_system::Pallet::<T>::set_block_number(200u32.into());
 		}: _(RawOrigin::Signed(caller), contributor.clone(), fund_index)
 		verify {
-			assert_last_event::<T>(Event::<T>::Withdrew(contributor, fund_index, T::MinContribution::get()).into());
+			let fund = Funds::<T>::get(fund_index).expect("fund was created...");
+			let amount = T::MinContribution::get();
+			assert_eq!(
+				Crowdloan::<T>::contributor_location_get(fund.trie_index, &contributor),
+				None,
+			);
+			assert_has_event::<T>(Event::<T>::RefundRouted(location, fund_index, amount).into());
+			assert_last_event::<T>(Event::<T>::Withdrew(contributor, fund_index, amount).into());
 		}
 
-		// Worst case: Refund removes `RemoveKeysLimit` keys, and is fully refunded.
+		// Worst case: Refund removes `RemoveKeysLimit` keys, and is fully refunded. Every
+		// contributor is an account on a child parachain, so each refund is sent back over XCM.
 		refund {
 			let k in 0 .. T::RemoveKeysLimit::get();
 			let fund_index = create_fund::<T>(1337, 100u32.into());
 
 			// Dissolve will remove at most `RemoveKeysLimit` at once.
 			for i in 0 .. k {
-				contribute_fund::<T>(&account("contributor", i, 0), fund_index);
+				let contributor = account("contributor", i, 0);
+				contribute_fund::<T>(&contributor, fund_index);
+				set_remote_location::<T>(&contributor, fund_index, i);
 			}
 
 			let caller: T::AccountId = whitelisted_caller();
 			frame_system::Pallet::<T>::set_block_number(200u32.into());
 		}: _(RawOrigin::Signed(caller), fund_index)
 		verify {
+			let amount = T::MinContribution::get();
+			for i in 0 .. k {
+				let routed = Event::<T>::RefundRouted(remote_location(i), fund_index, amount);
+				assert_has_event::<T>(routed.into());
+			}
 			assert_last_event::<T>(Event::<T>::AllRefunded(fund_index).into());
 		}
 
@@ -2021,6 +2890,62 @@ mod benchmarking {
 			assert_last_event::<T>(Event::<T>::AddedToNewRaise(fund_index).into())
 		}
 
+		set_auto_rollover {
+			let fund_index = create_fund::<T>(1, 100u32.into());
+			let caller: T::AccountId = whitelisted_caller();
+			contribute_fund::<T>(&caller, fund_index);
+		}: _(RawOrigin::Signed(caller.clone()), fund_index, true)
+		verify {
+			assert!(AutoRollover::<T>::contains_key(&caller, fund_index));
+			assert_last_event::<T>(Event::<T>::AutoRolloverSet(caller, fund_index, true).into())
+		}
+
+		// Worst case: each of the `n` funds accepts contributions, so every check is made, but
+		// has nothing to roll over.
+		on_idle {
+			let n in 0 .. 100;
+			let end_block: T::BlockNumber = 100u32.into();
+
+			for i in 0 .. n {
+				let fund_index = create_fund::<T>(i, end_block);
+				contribute_fund::<T>(&account("contributor", i, 0), fund_index);
+			}
+
+			let now = frame_system::Pallet::<T>::block_number();
+		}: {
+			Crowdloan::<T>::process_idle_funds(now, Weight::max_value());
+		} verify {
+			assert_eq!(Funds::<T>::iter().count(), n as usize);
+		}
+
+		// Rolling over the refunds reserved by `r` contributors into a single crowdloan.
+		roll_over {
+			let r in 0 .. 100;
+			let fund_index = create_fund::<T>(1, 100u32.into());
+			// Rollovers are contributed without a signature, so only succeed without a verifier.
+			Funds::<T>::mutate(fund_index, |fund| {
+				if let Some(fund) = fund {
+					fund.verifier = None;
+				}
+			});
+			let amount = T::MinContribution::get();
+
+			for i in 0 .. r {
+				let who: T::AccountId = account("contributor", i, 0);
+				CurrencyOf::<T>::make_free_balance_be(&who, BalanceOf::<T>::max_value());
+				CurrencyOf::<T>::reserve(&who, amount)?;
+				PendingRollovers::<T>::insert(fund_index, &who, amount);
+			}
+
+			let now = frame_system::Pallet::<T>::block_number();
+		}: {
+			Crowdloan::<T>::process_idle_funds(now, Weight::max_value());
+		} verify {
+			assert_eq!(PendingRollovers::<T>::iter_prefix(fund_index).count(), 0);
+			let fund = Funds::<T>::get(fund_index).expect("fund was created...");
+			assert_eq!(fund.raised, amount * r.into());
+		}
+
 		// Worst case scenario: N funds are all in the `NewRaise` list, we are
 		// in the beginning of the ending period, and each fund outbids the next
 		// over the same periods.
@@ -0,0 +1,30 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API for inspecting crowdloan contributions.
+
+use super::CrowdloanPosition;
+use parity_scale_codec::{Decode, Encode};
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	/// The API for inspecting the crowdloan positions of contributors.
+	pub trait CrowdloanApi<AccountId: Encode + Decode, Balance: Encode + Decode> {
+		/// Get the positions of a contributor in the crowdloans of every para, including the
+		/// refunds waiting to be rolled over into the next crowdloan of a para.
+		fn contributor_positions(who: AccountId) -> Vec<CrowdloanPosition<Balance>>;
+	}
+}
@@ -17,7 +17,19 @@
 //! Auxiliary `struct`/`enum`s for diamond runtime.
 
 use crate::NegativeImbalance;
-use frame_support::traits::{Currency, Imbalance, OnUnbalanced};
+use frame_support::{
+	traits::{Currency, Get, Imbalance, OnUnbalanced, SameOrOther},
+	weights::{DispatchClass, DispatchInfo, PostDispatchInfo},
+};
+use pallet_transaction_payment::{CurrencyAdapter, OnChargeTransaction};
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, Saturating},
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+	RuntimeDebug,
+};
+use sp_std::{convert::TryFrom, marker::PhantomData, prelude::*};
+use xcm::latest::{AssetId::Concrete, Fungibility::Fungible, MultiAsset, MultiLocation};
+use xcm_builder::TakeRevenue;
 
 /// Logic for the author to get a portion of fees.
 pub struct ToAuthor<R>(sp_std::marker::PhantomData<R>);
@@ -29,47 +41,243 @@ where
 	<R as frame_system::Config>::Event: From<pallet_balances::Event<R>>,
 {
 	fn on_nonzero_unbalanced(amount: NegativeImbalance<R>) {
-		let numeric_amount = amount.peek();
-		let author = <pallet_authorship::Pallet<R>>::author();
-		<pallet_balances::Pallet<R>>::resolve_creating(
-			&<pallet_authorship::Pallet<R>>::author(),
-			amount,
-		);
-		<frame_system::Pallet<R>>::deposit_event(pallet_balances::Event::Deposit(
-			author,
-			numeric_amount,
-		));
+		resolve_to::<R>(&<pallet_authorship::Pallet<R>>::author(), amount);
 	}
 }
 
-pub struct DealWithFees<R>(sp_std::marker::PhantomData<R>);
-impl<R> OnUnbalanced<NegativeImbalance<R>> for DealWithFees<R>
+/// Deposit `amount` into the account `who`, creating it if need be.
+fn resolve_to<R>(who: &R::AccountId, amount: NegativeImbalance<R>)
+where
+	R: pallet_balances::Config,
+	<R as frame_system::Config>::Event: From<pallet_balances::Event<R>>,
+{
+	let numeric_amount = amount.peek();
+	<pallet_balances::Pallet<R>>::resolve_creating(who, amount);
+	<frame_system::Pallet<R>>::deposit_event(pallet_balances::Event::Deposit(
+		who.clone(),
+		numeric_amount,
+	));
+}
+
+/// Where a share of fees or other revenue goes.
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum RevenueDestination<AccountId> {
+	/// The share is burned.
+	Burn,
+	/// The share goes to the treasury.
+	Treasury,
+	/// The share goes to the author of the current block.
+	Author,
+	/// The share goes to the given account, such as the account of some pot.
+	Account(AccountId),
+}
+
+/// Destinations of revenue along with the number of parts of it each of them gets.
+pub type RevenueShares<AccountId> = Vec<(RevenueDestination<AccountId>, u32)>;
+
+/// A policy for splitting transaction fees, tips and other revenue between destinations.
+pub trait FeePolicy<AccountId> {
+	/// The shares of the fee paid for a call of the given class, including its weight fee.
+	fn fee_shares(class: DispatchClass) -> RevenueShares<AccountId>;
+
+	/// The shares of a tip paid for a call of the given class. All to the author by default.
+	fn tip_shares(_class: DispatchClass) -> RevenueShares<AccountId> {
+		vec![(RevenueDestination::Author, 1)]
+	}
+
+	/// The shares of revenue not paid for a call, such as payment for XCM weight. Split as the fee
+	/// of a `Normal` call by default.
+	fn revenue_shares() -> RevenueShares<AccountId> {
+		Self::fee_shares(DispatchClass::Normal)
+	}
+}
+
+/// Split `amount` between the destinations of `shares`.
+///
+/// Each destination's share is split off what is left in turn, so the last one gets any rounding
+/// remainder. Without any parts, `amount` is burned.
+pub fn split_revenue<R>(mut amount: NegativeImbalance<R>, shares: RevenueShares<R::AccountId>)
 where
 	R: pallet_balances::Config + pallet_treasury::Config + pallet_authorship::Config,
 	pallet_treasury::Pallet<R>: OnUnbalanced<NegativeImbalance<R>>,
 	<R as frame_system::Config>::AccountId: From<primitives::v1::AccountId>,
 	<R as frame_system::Config>::AccountId: Into<primitives::v1::AccountId>,
 	<R as frame_system::Config>::Event: From<pallet_balances::Event<R>>,
+{
+	let mut remaining_parts = shares.iter().fold(0u32, |parts, (_, p)| parts.saturating_add(*p));
+	for (destination, parts) in shares.into_iter().filter(|(_, parts)| *parts > 0) {
+		remaining_parts = remaining_parts.saturating_sub(parts);
+		let (share, rest) = amount.ration(parts, remaining_parts);
+		amount = rest;
+		match destination {
+			RevenueDestination::Burn => drop(share),
+			RevenueDestination::Treasury =>
+				<pallet_treasury::Pallet<R> as OnUnbalanced<_>>::on_unbalanced(share),
+			RevenueDestination::Author => <ToAuthor<R> as OnUnbalanced<_>>::on_unbalanced(share),
+			RevenueDestination::Account(who) => resolve_to::<R>(&who, share),
+		}
+	}
+}
+
+/// Split fees then tips according to the policy `P`, as for calls of the `Normal` class.
+///
+/// Use [`SplitFeesAdapter`] for transaction payment to take the class of each call into account.
+pub struct SplitFees<R, P>(PhantomData<(R, P)>);
+impl<R, P> OnUnbalanced<NegativeImbalance<R>> for SplitFees<R, P>
+where
+	R: pallet_balances::Config + pallet_treasury::Config + pallet_authorship::Config,
+	P: FeePolicy<R::AccountId>,
+	pallet_treasury::Pallet<R>: OnUnbalanced<NegativeImbalance<R>>,
+	<R as frame_system::Config>::AccountId: From<primitives::v1::AccountId>,
+	<R as frame_system::Config>::AccountId: Into<primitives::v1::AccountId>,
+	<R as frame_system::Config>::Event: From<pallet_balances::Event<R>>,
 {
 	fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = NegativeImbalance<R>>) {
 		if let Some(fees) = fees_then_tips.next() {
-			// for fees, 80% to treasury, 20% to author
-			let mut split = fees.ration(80, 20);
+			split_revenue::<R>(fees, P::fee_shares(DispatchClass::Normal));
 			if let Some(tips) = fees_then_tips.next() {
-				// for tips, if any, 100% to author
-				tips.merge_into(&mut split.1);
+				split_revenue::<R>(tips, P::tip_shares(DispatchClass::Normal));
 			}
-			use pallet_treasury::Pallet as Treasury;
-			<Treasury<R> as OnUnbalanced<_>>::on_unbalanced(split.0);
-			<ToAuthor<R> as OnUnbalanced<_>>::on_unbalanced(split.1);
 		}
 	}
 }
 
+/// Transaction payment in the native currency, splitting fees and tips according to the policy
+/// `P` for the class of each call.
+///
+/// Fees are withdrawn and refunded just as by `CurrencyAdapter`.
+pub struct SplitFeesAdapter<R, P>(PhantomData<(R, P)>);
+impl<R, P> OnChargeTransaction<R> for SplitFeesAdapter<R, P>
+where
+	R: pallet_transaction_payment::Config
+		+ pallet_balances::Config
+		+ pallet_treasury::Config
+		+ pallet_authorship::Config,
+	<R as frame_system::Config>::Call:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	R::TransactionByteFee: Get<<R as pallet_balances::Config>::Balance>,
+	P: FeePolicy<R::AccountId>,
+	pallet_treasury::Pallet<R>: OnUnbalanced<NegativeImbalance<R>>,
+	<R as frame_system::Config>::AccountId: From<primitives::v1::AccountId>,
+	<R as frame_system::Config>::AccountId: Into<primitives::v1::AccountId>,
+	<R as frame_system::Config>::Event: From<pallet_balances::Event<R>>,
+{
+	type Balance = <R as pallet_balances::Config>::Balance;
+	type LiquidityInfo = Option<NegativeImbalance<R>>;
+
+	fn withdraw_fee(
+		who: &R::AccountId,
+		call: &<R as frame_system::Config>::Call,
+		dispatch_info: &DispatchInfoOf<<R as frame_system::Config>::Call>,
+		fee: Self::Balance,
+		tip: Self::Balance,
+	) -> Result<Self::LiquidityInfo, TransactionValidityError> {
+		<CurrencyAdapter<pallet_balances::Pallet<R>, ()> as OnChargeTransaction<R>>::withdraw_fee(
+			who,
+			call,
+			dispatch_info,
+			fee,
+			tip,
+		)
+	}
+
+	fn correct_and_deposit_fee(
+		who: &R::AccountId,
+		dispatch_info: &DispatchInfoOf<<R as frame_system::Config>::Call>,
+		_post_info: &PostDispatchInfoOf<<R as frame_system::Config>::Call>,
+		corrected_fee: Self::Balance,
+		tip: Self::Balance,
+		already_withdrawn: Self::LiquidityInfo,
+	) -> Result<(), TransactionValidityError> {
+		if let Some(paid) = already_withdrawn {
+			// Refund whatever was paid over the corrected fee. If the account was reaped in the
+			// meantime, nothing is refunded.
+			let refund_amount = paid.peek().saturating_sub(corrected_fee);
+			let refund = <pallet_balances::Pallet<R>>::deposit_into_existing(who, refund_amount)
+				.unwrap_or_else(|_| Imbalance::zero());
+			let adjusted_paid = match paid.offset(refund) {
+				SameOrOther::Same(adjusted_paid) => adjusted_paid,
+				SameOrOther::None => Imbalance::zero(),
+				SameOrOther::Other(_) =>
+					return Err(TransactionValidityError::Invalid(InvalidTransaction::Payment)),
+			};
+			let (tip, fee) = adjusted_paid.split(tip);
+			split_revenue::<R>(fee, P::fee_shares(dispatch_info.class));
+			split_revenue::<R>(tip, P::tip_shares(dispatch_info.class));
+		}
+		Ok(())
+	}
+}
+
+/// Split revenue other than transaction fees according to the policy `P`.
+///
+/// As `OnUnbalanced`, this handles payment for weight bought over XCM by `UsingComponents`. As
+/// `TakeRevenue`, it handles revenue of XCM weight traders in the native asset, whose location is
+/// given by `NativeLocation`. Revenue in any other asset is dropped.
+pub struct SplitRevenue<R, P, NativeLocation>(PhantomData<(R, P, NativeLocation)>);
+impl<R, P, NativeLocation> OnUnbalanced<NegativeImbalance<R>> for SplitRevenue<R, P, NativeLocation>
+where
+	R: pallet_balances::Config + pallet_treasury::Config + pallet_authorship::Config,
+	P: FeePolicy<R::AccountId>,
+	pallet_treasury::Pallet<R>: OnUnbalanced<NegativeImbalance<R>>,
+	<R as frame_system::Config>::AccountId: From<primitives::v1::AccountId>,
+	<R as frame_system::Config>::AccountId: Into<primitives::v1::AccountId>,
+	<R as frame_system::Config>::Event: From<pallet_balances::Event<R>>,
+{
+	fn on_nonzero_unbalanced(amount: NegativeImbalance<R>) {
+		split_revenue::<R>(amount, P::revenue_shares());
+	}
+}
+
+impl<R, P, NativeLocation> TakeRevenue for SplitRevenue<R, P, NativeLocation>
+where
+	R: pallet_balances::Config + pallet_treasury::Config + pallet_authorship::Config,
+	R::Balance: TryFrom<u128>,
+	P: FeePolicy<R::AccountId>,
+	NativeLocation: Get<MultiLocation>,
+	pallet_treasury::Pallet<R>: OnUnbalanced<NegativeImbalance<R>>,
+	<R as frame_system::Config>::AccountId: From<primitives::v1::AccountId>,
+	<R as frame_system::Config>::AccountId: Into<primitives::v1::AccountId>,
+	<R as frame_system::Config>::Event: From<pallet_balances::Event<R>>,
+{
+	fn take_revenue(revenue: MultiAsset) {
+		match revenue {
+			MultiAsset { id: Concrete(ref location), fun: Fungible(amount) }
+				if *location == NativeLocation::get() =>
+				if let Ok(amount) = R::Balance::try_from(amount) {
+					let revenue = <pallet_balances::Pallet<R>>::issue(amount);
+					<Self as OnUnbalanced<_>>::on_unbalanced(revenue);
+				},
+			_ => log::debug!(
+				target: "runtime::fees",
+				"Dropping XCM revenue in a foreign asset: {:?}",
+				revenue,
+			),
+		}
+	}
+}
+
+/// The fee policy of sending fees 80% to the treasury and 20% to the block author, and tips to
+/// the block author.
+pub struct TreasuryAndAuthor;
+impl<AccountId> FeePolicy<AccountId> for TreasuryAndAuthor {
+	fn fee_shares(_class: DispatchClass) -> RevenueShares<AccountId> {
+		vec![(RevenueDestination::Treasury, 80), (RevenueDestination::Author, 20)]
+	}
+}
+
+/// Split fees 80% to the treasury and 20% to the block author, and tips to the block author.
+pub type DealWithFees<R> = SplitFees<R, TreasuryAndAuthor>;
+
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use frame_support::{parameter_types, traits::FindAuthor, weights::DispatchClass, PalletId};
+	use frame_support::{
+		parameter_types,
+		traits::FindAuthor,
+		weights::{DispatchClass, IdentityFee},
+		PalletId,
+	};
 	use frame_system::limits;
 	use primitives::v1::AccountId;
 	use sp_core::H256;
@@ -186,6 +394,45 @@ mod tests {
 		type EventHandler = ();
 	}
 
+	impl pallet_transaction_payment::Config for Test {
+		type OnChargeTransaction = SplitFeesAdapter<Test, TestPolicy>;
+		type TransactionByteFee = ();
+		type WeightToFee = IdentityFee<u64>;
+		type FeeMultiplierUpdate = ();
+	}
+
+	parameter_types! {
+		pub Pot: AccountId = AccountId::from([7u8; 32]);
+		pub NativeLocation: MultiLocation = MultiLocation::here();
+	}
+
+	/// Operational fees are burned and their tips go to the treasury. Other fees are half burned
+	/// and split evenly between the treasury and the pot, and their tips go to the author.
+	pub struct TestPolicy;
+	impl FeePolicy<AccountId> for TestPolicy {
+		fn fee_shares(class: DispatchClass) -> RevenueShares<AccountId> {
+			match class {
+				DispatchClass::Operational => vec![(RevenueDestination::Burn, 1)],
+				_ => vec![
+					(RevenueDestination::Burn, 2),
+					(RevenueDestination::Treasury, 1),
+					(RevenueDestination::Account(Pot::get()), 1),
+				],
+			}
+		}
+
+		fn tip_shares(class: DispatchClass) -> RevenueShares<AccountId> {
+			match class {
+				DispatchClass::Operational => vec![(RevenueDestination::Treasury, 1)],
+				_ => vec![(RevenueDestination::Author, 1)],
+			}
+		}
+
+		fn revenue_shares() -> RevenueShares<AccountId> {
+			vec![(RevenueDestination::Treasury, 1), (RevenueDestination::Account(Pot::get()), 1)]
+		}
+	}
+
 	pub fn new_test_ext() -> sp_io::TestExternalities {
 		let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 		// We use default for brevity, but you can configure as desired if needed.
@@ -212,4 +459,109 @@ mod tests {
 			assert_eq!(Balances::free_balance(Treasury::account_id()), 8);
 		});
 	}
+
+	#[test]
+	fn split_revenue_follows_shares() {
+		new_test_ext().execute_with(|| {
+			split_revenue::<Test>(
+				Balances::issue(101),
+				TestPolicy::fee_shares(DispatchClass::Normal),
+			);
+
+			// Each share is split off what is left, so the last one gets the remainder.
+			assert_eq!(Balances::free_balance(Treasury::account_id()), 25);
+			assert_eq!(Balances::free_balance(Pot::get()), 26);
+			// The rest is burned.
+			assert_eq!(Balances::total_issuance(), 51);
+
+			// Without any parts, everything is burned.
+			split_revenue::<Test>(Balances::issue(100), vec![]);
+			split_revenue::<Test>(Balances::issue(100), vec![(RevenueDestination::Treasury, 0)]);
+			assert_eq!(Balances::free_balance(Treasury::account_id()), 25);
+			assert_eq!(Balances::total_issuance(), 51);
+		});
+	}
+
+	#[test]
+	fn split_fees_uses_normal_class() {
+		new_test_ext().execute_with(|| {
+			let fee = Balances::issue(40);
+			let tip = Balances::issue(20);
+
+			SplitFees::<Test, TestPolicy>::on_unbalanceds(vec![fee, tip].into_iter());
+
+			assert_eq!(Balances::free_balance(AccountId::default()), 20);
+			assert_eq!(Balances::free_balance(Treasury::account_id()), 10);
+			assert_eq!(Balances::free_balance(Pot::get()), 10);
+			assert_eq!(Balances::total_issuance(), 40);
+		});
+	}
+
+	#[test]
+	fn split_fees_adapter_splits_by_class() {
+		new_test_ext().execute_with(|| {
+			let who = AccountId::from([1u8; 32]);
+			let call = Call::System(frame_system::Call::remark { remark: vec![] });
+			let charge = |class: DispatchClass| {
+				let info = DispatchInfo { weight: 100, class, ..Default::default() };
+				let paid =
+					<SplitFeesAdapter<Test, TestPolicy> as OnChargeTransaction<Test>>::withdraw_fee(
+						&who, &call, &info, 100, 20,
+					)
+					.unwrap();
+				// Only 60 of the 100 withheld for the fee is due in the end.
+				<SplitFeesAdapter<Test, TestPolicy> as OnChargeTransaction<Test>>::correct_and_deposit_fee(
+					&who,
+					&info,
+					&Default::default(),
+					60,
+					20,
+					paid,
+				)
+				.unwrap();
+			};
+			Balances::make_free_balance_be(&who, 1000);
+
+			charge(DispatchClass::Normal);
+			assert_eq!(Balances::free_balance(&who), 920);
+			assert_eq!(Balances::free_balance(AccountId::default()), 20);
+			assert_eq!(Balances::free_balance(Treasury::account_id()), 15);
+			assert_eq!(Balances::free_balance(Pot::get()), 15);
+			assert_eq!(Balances::total_issuance(), 970);
+
+			charge(DispatchClass::Operational);
+			assert_eq!(Balances::free_balance(&who), 840);
+			assert_eq!(Balances::free_balance(AccountId::default()), 20);
+			assert_eq!(Balances::free_balance(Treasury::account_id()), 35);
+			assert_eq!(Balances::free_balance(Pot::get()), 15);
+			assert_eq!(Balances::total_issuance(), 910);
+		});
+	}
+
+	#[test]
+	fn split_revenue_takes_native_xcm_revenue() {
+		new_test_ext().execute_with(|| {
+			type Revenue = SplitRevenue<Test, TestPolicy, NativeLocation>;
+
+			Revenue::take_revenue(MultiAsset {
+				id: Concrete(MultiLocation::here()),
+				fun: Fungible(100),
+			});
+			assert_eq!(Balances::free_balance(Treasury::account_id()), 50);
+			assert_eq!(Balances::free_balance(Pot::get()), 50);
+			assert_eq!(Balances::total_issuance(), 100);
+
+			// Revenue in any other asset is dropped.
+			Revenue::take_revenue(MultiAsset {
+				id: Concrete(MultiLocation::parent()),
+				fun: Fungible(100),
+			});
+			assert_eq!(Balances::total_issuance(), 100);
+
+			// Weight bought with the native currency is split the same way.
+			Revenue::on_unbalanced(Balances::issue(10));
+			assert_eq!(Balances::free_balance(Treasury::account_id()), 55);
+			assert_eq!(Balances::free_balance(Pot::get()), 55);
+		});
+	}
 }
@@ -24,7 +24,7 @@ use crate::{
 };
 use frame_support::{
 	assert_noop, assert_ok, parameter_types,
-	traits::{Currency, GenesisBuild, KeyOwnerProofSystem, OnFinalize, OnInitialize},
+	traits::{Currency, EnsureOrigin, GenesisBuild, KeyOwnerProofSystem, OnFinalize, OnInitialize},
 	PalletId,
 };
 use frame_support_test::TestRandomness;
@@ -38,6 +38,12 @@ use sp_io::TestExternalities;
 use sp_keystore::{testing::KeyStore, KeystoreExt};
 use sp_runtime::traits::{BlakeTwo256, IdentityLookup, One};
 use sp_std::sync::Arc;
+use xcm::latest::{
+	Junction::{AccountId32, Parachain},
+	Junctions::{X1, X2},
+	MultiLocation, SendError, SendResult, SendXcm, Xcm,
+};
+use xcm_executor::traits::Convert;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -165,6 +171,7 @@ impl shared::Config for Test {}
 impl paras::Config for Test {
 	type Origin = Origin;
 	type Event = Event;
+	type DowngradeGracePeriod = ();
 }
 
 parameter_types! {
@@ -185,6 +192,7 @@ impl paras_registrar::Config for Test {
 parameter_types! {
 	pub const EndingPeriod: BlockNumber = 10;
 	pub const SampleLength: BlockNumber = 1;
+	pub const AuctionHistoryDepth: u32 = 2;
 }
 
 impl auctions::Config for Test {
@@ -194,6 +202,7 @@ impl auctions::Config for Test {
 	type EndingPeriod = EndingPeriod;
 	type SampleLength = SampleLength;
 	type Randomness = TestRandomness<Self>;
+	type HistoryDepth = AuctionHistoryDepth;
 	type InitiateOrigin = EnsureRoot<AccountId>;
 	type WeightInfo = crate::auctions::TestWeightInfo;
 }
@@ -227,9 +236,52 @@ impl crowdloan::Config for Test {
 	type Registrar = Registrar;
 	type Auctioneer = Auctions;
 	type MaxMemoLength = MaxMemoLength;
+	type RemoteContributorOrigin = NoRemoteContributors;
+	type LocationToAccountId = RemoteContributorAccounts;
+	type XcmRouter = ParaRouter;
 	type WeightInfo = crate::crowdloan::TestWeightInfo;
 }
 
+/// Contributions from other consensus systems are not covered by these tests.
+pub struct NoRemoteContributors;
+impl EnsureOrigin<Origin> for NoRemoteContributors {
+	type Success = MultiLocation;
+
+	fn try_origin(o: Origin) -> Result<MultiLocation, Origin> {
+		Err(o)
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn successful_origin() -> Origin {
+		Origin::root()
+	}
+}
+
+/// Accounts on parachains map to the account with the index in the first bytes of their ID, and
+/// parachains map to `10_000 + para`, so that the benchmarks can refund remote contributors.
+pub struct RemoteContributorAccounts;
+impl Convert<MultiLocation, AccountId> for RemoteContributorAccounts {
+	fn convert(location: MultiLocation) -> Result<AccountId, MultiLocation> {
+		match location {
+			MultiLocation { parents: 0, interior: X1(Parachain(para)) } => Ok(10_000 + para),
+			MultiLocation { parents: 0, interior: X2(Parachain(_), AccountId32 { id, .. }) } =>
+				Ok(AccountId::from_le_bytes([id[0], id[1], id[2], id[3]])),
+			location => Err(location),
+		}
+	}
+}
+
+/// Messages can be sent to any parachain, and are dropped.
+pub struct ParaRouter;
+impl SendXcm for ParaRouter {
+	fn send_xcm(dest: MultiLocation, msg: Xcm<()>) -> SendResult {
+		match dest {
+			MultiLocation { parents: 0, interior: X1(Parachain(_)) } => Ok(()),
+			dest => Err(SendError::CannotReachDestination(dest, msg)),
+		}
+	}
+}
+
 /// Create a new set of test externalities.
 pub fn new_test_ext() -> TestExternalities {
 	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
@@ -1313,3 +1365,39 @@ fn cant_bid_on_existing_lease_periods() {
 		));
 	});
 }
+
+#[test]
+fn lease_renewed_after_downgrade_is_final_onboards_again() {
+	new_test_ext().execute_with(|| {
+		assert!(System::block_number().is_one()); // So events are emitted
+		let para_id = ParaId::from(2000);
+		Balances::make_free_balance_be(&1, 1_000_000_000);
+		assert_ok!(Registrar::reserve(Origin::signed(1)));
+		assert_ok!(Registrar::register(
+			Origin::signed(1),
+			para_id,
+			test_genesis_head(10),
+			test_validation_code(10),
+		));
+		run_to_session(2);
+		assert_eq!(Paras::lifecycle(para_id), Some(ParaLifecycle::Parathread));
+
+		// Lease the current period, which onboards the para right away.
+		assert_ok!(Slots::force_lease(Origin::root(), para_id, 1, 100, 0, 1));
+		run_to_session(4);
+		assert_eq!(Paras::lifecycle(para_id), Some(ParaLifecycle::Parachain));
+
+		// The lease ends, and the downgrade takes effect at the next scheduled session without
+		// any grace period, so it can't be cancelled anymore.
+		run_to_block(100);
+		assert_eq!(Paras::lifecycle(para_id), Some(ParaLifecycle::DowngradingParachain));
+
+		// The lease is renewed, so the para is upgraded again once downgraded.
+		assert_ok!(Slots::force_lease(Origin::root(), para_id, 1, 100, 1, 1));
+		assert_eq!(Paras::lifecycle(para_id), Some(ParaLifecycle::DowngradingParachain));
+		run_to_session(12);
+		assert_eq!(Paras::lifecycle(para_id), Some(ParaLifecycle::UpgradingParathread));
+		run_to_session(14);
+		assert_eq!(Paras::lifecycle(para_id), Some(ParaLifecycle::Parachain));
+	});
+}
@@ -56,6 +56,10 @@ impl<T: frame_system::Config> Registrar for TestRegistrar<T> {
 		LOCKS.with(|x| x.borrow_mut().insert(id, false));
 	}
 
+	fn is_locked(id: ParaId) -> bool {
+		LOCKS.with(|x| x.borrow().get(&id).copied().unwrap_or(false))
+	}
+
 	fn register(
 		manager: Self::AccountId,
 		id: ParaId,
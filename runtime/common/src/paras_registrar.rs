@@ -16,6 +16,13 @@
 
 //! Pallet to handle parathread/parachain registration and related fund management.
 //! In essence this is a simple wrapper around `paras`.
+//!
+//! Validation code can also be uploaded ahead of registration, in as many chunks as needed, and
+//! then shared by any number of paras which are registered with its hash. The uploader holds the
+//! deposit for the code, so those paras only pay for their own genesis head.
+//!
+//! The manager's permissions over a para can be split into roles, each of which can be assigned to
+//! a separate account or to the para's own origin. See [`ParaRole`].
 
 use frame_support::{
 	dispatch::DispatchResult,
@@ -24,7 +31,9 @@ use frame_support::{
 	traits::{Currency, Get, ReservableCurrency},
 };
 use frame_system::{self, ensure_root, ensure_signed};
-use primitives::v1::{HeadData, Id as ParaId, ValidationCode, LOWEST_PUBLIC_ID};
+use primitives::v1::{
+	HeadData, Id as ParaId, ValidationCode, ValidationCodeHash, LOWEST_PUBLIC_ID, MAX_CODE_SIZE,
+};
 use runtime_parachains::{
 	configuration, ensure_parachain,
 	paras::{self, ParaGenesisArgs},
@@ -38,7 +47,7 @@ use parity_scale_codec::{Decode, Encode};
 use scale_info::TypeInfo;
 use sp_runtime::{
 	traits::{CheckedSub, Saturating},
-	RuntimeDebug,
+	DispatchError, RuntimeDebug,
 };
 
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug, TypeInfo)]
@@ -51,6 +60,73 @@ pub struct ParaInfo<Account, Balance> {
 	locked: bool,
 }
 
+/// Validation code uploaded, or being uploaded, ahead of the registration of paras using it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug, TypeInfo)]
+pub struct CodeUpload<Account, Balance> {
+	/// The account uploading the code, which holds the deposit for storing it.
+	pub(crate) uploader: Account,
+	/// The amount reserved by the `uploader` account for the code.
+	deposit: Balance,
+	/// The length of the complete code in bytes.
+	code_len: u32,
+	/// The number of bytes uploaded so far.
+	uploaded_len: u32,
+	/// The number of chunks uploaded so far.
+	chunks: u32,
+	/// The number of registered paras using the code.
+	users: u32,
+}
+
+impl<Account, Balance> CodeUpload<Account, Balance> {
+	/// Whether all of the code has been uploaded.
+	pub fn is_complete(&self) -> bool {
+		self.uploaded_len == self.code_len
+	}
+}
+
+/// A permission over a para which can be assigned to someone other than its manager.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum ParaRole {
+	/// Scheduling upgrades of the para's validation code.
+	CodeUpgrade,
+	/// Overriding the para's current head data.
+	HeadOverride,
+	/// Swapping the para with another one.
+	Swap,
+	/// Deregistering the para.
+	Deregister,
+}
+
+impl ParaRole {
+	/// Whether the role falls back to the para's manager and the para itself when unassigned.
+	/// Otherwise, it is only available to Root until assigned.
+	fn defaults_to_manager(&self) -> bool {
+		matches!(self, ParaRole::Swap | ParaRole::Deregister)
+	}
+}
+
+/// Who, besides Root, holds a role for a para.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum RoleHolder<Account> {
+	/// A signed account.
+	Account(Account),
+	/// The para's own origin.
+	Para,
+}
+
+/// The assignment of a role for a para.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct RoleAssignment<Account> {
+	/// The holder of the role.
+	pub holder: RoleHolder<Account>,
+	/// Whether the role was assigned by the manager rather than by Root or the para itself.
+	/// Such assignments are suspended while the para is locked.
+	by_manager: bool,
+}
+
+/// The maximum number of chunks a single validation code may be uploaded in.
+pub const MAX_CODE_UPLOAD_CHUNKS: u32 = 64;
+
 type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
@@ -60,6 +136,14 @@ pub trait WeightInfo {
 	fn force_register() -> Weight;
 	fn deregister() -> Weight;
 	fn swap() -> Weight;
+	fn start_code_upload() -> Weight;
+	fn upload_code_chunk(b: u32) -> Weight;
+	fn complete_code_upload(c: u32) -> Weight;
+	fn remove_code() -> Weight;
+	fn register_with_code_hash() -> Weight;
+	fn set_role() -> Weight;
+	fn schedule_code_upgrade(b: u32) -> Weight;
+	fn set_current_head() -> Weight;
 }
 
 pub struct TestWeightInfo;
@@ -79,6 +163,30 @@ impl WeightInfo for TestWeightInfo {
 	fn swap() -> Weight {
 		0
 	}
+	fn start_code_upload() -> Weight {
+		0
+	}
+	fn upload_code_chunk(_b: u32) -> Weight {
+		0
+	}
+	fn complete_code_upload(_c: u32) -> Weight {
+		0
+	}
+	fn remove_code() -> Weight {
+		0
+	}
+	fn register_with_code_hash() -> Weight {
+		0
+	}
+	fn set_role() -> Weight {
+		0
+	}
+	fn schedule_code_upgrade(_b: u32) -> Weight {
+		0
+	}
+	fn set_current_head() -> Weight {
+		0
+	}
 }
 
 #[frame_support::pallet]
@@ -129,6 +237,14 @@ pub mod pallet {
 		Registered(ParaId, T::AccountId),
 		Deregistered(ParaId),
 		Reserved(ParaId, T::AccountId),
+		/// The upload of validation code has started. [code_hash, uploader]
+		CodeUploadStarted(ValidationCodeHash, T::AccountId),
+		/// Validation code has been completely uploaded. [code_hash]
+		CodeUploaded(ValidationCodeHash),
+		/// Uploaded validation code has been removed. [code_hash]
+		CodeRemoved(ValidationCodeHash),
+		/// A role for a para has been assigned, or cleared if `None`. [para_id, role, holder]
+		RoleSet(ParaId, ParaRole, Option<RoleHolder<T::AccountId>>),
 	}
 
 	#[pallet::error]
@@ -157,6 +273,24 @@ pub mod pallet {
 		ParaLocked,
 		/// The ID given for registration has not been reserved.
 		NotReserved,
+		/// The code, or code chunk, is empty.
+		EmptyCode,
+		/// An upload for this code hash has already been started.
+		CodeAlreadyUploaded,
+		/// No upload has been started for this code hash.
+		UnknownCode,
+		/// The caller is not the uploader of this code.
+		NotCodeUploader,
+		/// The code has been split into too many chunks.
+		TooManyChunks,
+		/// The uploaded code does not match its hash.
+		CodeHashMismatch,
+		/// The code has not been completely uploaded yet.
+		CodeNotUploaded,
+		/// The code is still used by a registered para.
+		CodeInUse,
+		/// The para is not a live parathread or parachain.
+		NotLivePara,
 	}
 
 	/// Pending swap operations.
@@ -175,6 +309,54 @@ pub mod pallet {
 	#[pallet::storage]
 	pub type NextFreeParaId<T> = StorageValue<_, ParaId, ValueQuery>;
 
+	/// Validation code uploads in progress, keyed by the hash of the complete code and the
+	/// uploader.
+	///
+	/// Any number of accounts may upload the same code, the first to complete it makes it
+	/// available to paras.
+	#[pallet::storage]
+	pub type CodeUploads<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		ValidationCodeHash,
+		Twox64Concat,
+		T::AccountId,
+		CodeUpload<T::AccountId, BalanceOf<T>>,
+	>;
+
+	/// The chunks of validation code uploads in progress, by chunk index.
+	#[pallet::storage]
+	pub(super) type CodeUploadChunks<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		(ValidationCodeHash, T::AccountId),
+		Twox64Concat,
+		u32,
+		Vec<u8>,
+	>;
+
+	/// Completely uploaded validation code, which paras can be registered with.
+	///
+	/// The code itself is handed to the paras pallet, which stores it once for all paras using it.
+	#[pallet::storage]
+	pub type UploadedCode<T: Config> =
+		StorageMap<_, Identity, ValidationCodeHash, CodeUpload<T::AccountId, BalanceOf<T>>>;
+
+	/// The hash of the uploaded code each para registered with `register_with_code_hash` uses.
+	#[pallet::storage]
+	pub type ParaCodeHashes<T> = StorageMap<_, Twox64Concat, ParaId, ValidationCodeHash>;
+
+	/// The roles assigned for each para.
+	#[pallet::storage]
+	pub type ParaRoles<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		ParaId,
+		Twox64Concat,
+		ParaRole,
+		RoleAssignment<T::AccountId>,
+	>;
+
 	#[pallet::genesis_config]
 	pub struct GenesisConfig {
 		pub next_free_para_id: ParaId,
@@ -246,16 +428,18 @@ pub mod pallet {
 
 		/// Deregister a Para Id, freeing all data and returning any deposit.
 		///
-		/// The caller must be Root, the `para` owner, or the `para` itself. The para must be a parathread.
+		/// The caller must be Root, or hold the `Deregister` role. Unless assigned, the role is held
+		/// by the `para` owner and the `para` itself. The para must be a parathread.
 		#[pallet::weight(<T as Config>::WeightInfo::deregister())]
 		pub fn deregister(origin: OriginFor<T>, id: ParaId) -> DispatchResult {
-			Self::ensure_root_para_or_owner(origin, id)?;
+			Self::ensure_role(origin, id, ParaRole::Deregister)?;
 			Self::do_deregister(id)
 		}
 
 		/// Swap a parachain with another parachain or parathread.
 		///
-		/// The origin must be Root, the `para` owner, or the `para` itself.
+		/// The origin must be Root, or hold the `Swap` role. Unless assigned, the role is held by the
+		/// `para` owner and the `para` itself.
 		///
 		/// The swap will happen only if there is already an opposite swap pending. If there is not,
 		/// the swap will be stored in the pending swaps map, ready for a later confirmatory swap.
@@ -266,7 +450,7 @@ pub mod pallet {
 		/// and the auction deposit are switched.
 		#[pallet::weight(<T as Config>::WeightInfo::swap())]
 		pub fn swap(origin: OriginFor<T>, id: ParaId, other: ParaId) -> DispatchResult {
-			Self::ensure_root_para_or_owner(origin, id)?;
+			Self::ensure_role(origin, id, ParaRole::Swap)?;
 
 			if PendingSwap::<T>::get(other) == Some(id) {
 				if let Some(other_lifecycle) = paras::Pallet::<T>::lifecycle(other) {
@@ -333,6 +517,243 @@ pub mod pallet {
 			NextFreeParaId::<T>::set(id + 1);
 			Ok(())
 		}
+
+		/// Start uploading validation code, which paras can later be registered with by its hash.
+		///
+		/// ## Arguments
+		/// - `origin`: Must be called by a `Signed` origin. Becomes the uploader of the code.
+		/// - `code_hash`: The hash of the complete validation code.
+		/// - `code_len`: The length of the complete validation code in bytes.
+		///
+		/// ## Deposits/Fees
+		/// The origin must reserve `DataDepositPerByte` for every byte of the code. It is returned
+		/// when the code is removed with `remove_code`.
+		///
+		/// ## Events
+		/// The `CodeUploadStarted` event is emitted in case of success.
+		#[pallet::weight(<T as Config>::WeightInfo::start_code_upload())]
+		pub fn start_code_upload(
+			origin: OriginFor<T>,
+			code_hash: ValidationCodeHash,
+			code_len: u32,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(code_len > 0, Error::<T>::EmptyCode);
+			let config = configuration::Pallet::<T>::config();
+			ensure!(code_len <= config.max_code_size, Error::<T>::CodeTooLarge);
+			ensure!(!UploadedCode::<T>::contains_key(&code_hash), Error::<T>::CodeAlreadyUploaded);
+			ensure!(
+				!CodeUploads::<T>::contains_key(&code_hash, &who),
+				Error::<T>::CodeAlreadyUploaded
+			);
+
+			let deposit = T::DataDepositPerByte::get().saturating_mul(code_len.into());
+			<T as Config>::Currency::reserve(&who, deposit)?;
+			let upload = CodeUpload {
+				uploader: who.clone(),
+				deposit,
+				code_len,
+				uploaded_len: 0,
+				chunks: 0,
+				users: 0,
+			};
+
+			CodeUploads::<T>::insert(&code_hash, &who, upload);
+			Self::deposit_event(Event::<T>::CodeUploadStarted(code_hash, who));
+			Ok(())
+		}
+
+		/// Upload the next chunk of validation code started with `start_code_upload`.
+		///
+		/// Chunks are appended in order, and a code may be uploaded in at most
+		/// `MAX_CODE_UPLOAD_CHUNKS` chunks. The chunk completing the code is rejected if the code
+		/// does not match its hash, in which case the upload has to be removed and started again.
+		/// Once complete, the code is handed to the paras pallet and the upload buffer is dropped.
+		///
+		/// The origin must have started the upload, and no one else may have completed the same
+		/// code in the meantime.
+		///
+		/// The weight of completing a code of the maximum size is charged upfront, and the excess
+		/// refunded once it is known whether and which code the chunk completes.
+		///
+		/// ## Events
+		/// The `CodeUploaded` event is emitted once the code is complete.
+		#[pallet::weight(
+			<T as Config>::WeightInfo::upload_code_chunk(chunk.len() as u32)
+				.max(<T as Config>::WeightInfo::complete_code_upload(MAX_CODE_SIZE))
+		)]
+		pub fn upload_code_chunk(
+			origin: OriginFor<T>,
+			code_hash: ValidationCodeHash,
+			chunk: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let mut upload =
+				CodeUploads::<T>::get(&code_hash, &who).ok_or(Error::<T>::UnknownCode)?;
+			ensure!(!UploadedCode::<T>::contains_key(&code_hash), Error::<T>::CodeAlreadyUploaded);
+			ensure!(!chunk.is_empty(), Error::<T>::EmptyCode);
+			ensure!(upload.chunks < MAX_CODE_UPLOAD_CHUNKS, Error::<T>::TooManyChunks);
+			let uploaded_len = upload.uploaded_len.saturating_add(chunk.len() as u32);
+			ensure!(uploaded_len <= upload.code_len, Error::<T>::CodeTooLarge);
+
+			let index = upload.chunks;
+			upload.uploaded_len = uploaded_len;
+			upload.chunks += 1;
+
+			let chunks_key = (code_hash, who.clone());
+			if upload.is_complete() {
+				let mut code = Vec::with_capacity(upload.code_len as usize);
+				for i in 0..index {
+					code.extend(CodeUploadChunks::<T>::get(&chunks_key, i).unwrap_or_default());
+				}
+				code.extend(chunk);
+				let code = ValidationCode(code);
+				ensure!(code.hash() == code_hash, Error::<T>::CodeHashMismatch);
+
+				CodeUploadChunks::<T>::remove_prefix(&chunks_key, None);
+				CodeUploads::<T>::remove(&code_hash, &who);
+				paras::Pallet::<T>::retain_code(&code);
+				let code_len = upload.code_len;
+				UploadedCode::<T>::insert(&code_hash, upload);
+				Self::deposit_event(Event::<T>::CodeUploaded(code_hash));
+				Ok(Some(<T as Config>::WeightInfo::complete_code_upload(code_len)).into())
+			} else {
+				let chunk_len = chunk.len() as u32;
+				CodeUploadChunks::<T>::insert(&chunks_key, index, chunk);
+				CodeUploads::<T>::insert(&code_hash, &who, upload);
+				Ok(Some(<T as Config>::WeightInfo::upload_code_chunk(chunk_len)).into())
+			}
+		}
+
+		/// Remove uploaded, or partially uploaded, validation code and return its deposit.
+		///
+		/// The partial upload of the origin is removed if there is one. Otherwise, the origin must
+		/// be the uploader of the complete code, and no registered para may use it.
+		///
+		/// ## Events
+		/// The `CodeRemoved` event is emitted in case of success.
+		#[pallet::weight(<T as Config>::WeightInfo::remove_code())]
+		pub fn remove_code(origin: OriginFor<T>, code_hash: ValidationCodeHash) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let upload = if let Some(upload) = CodeUploads::<T>::take(&code_hash, &who) {
+				CodeUploadChunks::<T>::remove_prefix(&(code_hash, who), None);
+				upload
+			} else {
+				let upload = UploadedCode::<T>::get(&code_hash).ok_or(Error::<T>::UnknownCode)?;
+				ensure!(upload.uploader == who, Error::<T>::NotCodeUploader);
+				ensure!(upload.users == 0, Error::<T>::CodeInUse);
+				UploadedCode::<T>::remove(&code_hash);
+				paras::Pallet::<T>::release_code(&code_hash);
+				upload
+			};
+			<T as Config>::Currency::unreserve(&upload.uploader, upload.deposit);
+
+			Self::deposit_event(Event::<T>::CodeRemoved(code_hash));
+			Ok(())
+		}
+
+		/// Register head data for a reserved Para Id, using validation code uploaded earlier.
+		///
+		/// ## Arguments
+		/// - `origin`: Must be called by a `Signed` origin.
+		/// - `id`: The para ID. Must be owned/managed by the `origin` signing account.
+		/// - `genesis_head`: The genesis head data of the parachain/thread.
+		/// - `code_hash`: The hash of validation code completely uploaded with `upload_code_chunk`.
+		///
+		/// ## Deposits/Fees
+		/// As for `register`, except that the validation code is paid for by its uploader, so only
+		/// the genesis head is charged per byte. The code cannot be removed while the para uses it.
+		///
+		/// ## Events
+		/// The `Registered` event is emitted in case of success.
+		#[pallet::weight(<T as Config>::WeightInfo::register_with_code_hash())]
+		pub fn register_with_code_hash(
+			origin: OriginFor<T>,
+			id: ParaId,
+			genesis_head: HeadData,
+			code_hash: ValidationCodeHash,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_register_with_code_hash(who, id, genesis_head, code_hash)
+		}
+
+		/// Assign a role for a para to an account or to the para itself, or clear it with `None`.
+		///
+		/// The origin must be Root, the `para` owner, or the `para` itself. Roles assigned by the
+		/// owner are suspended while the para is locked.
+		///
+		/// Once assigned, a role is only held by Root and its holder. Unassigned, the `Swap` and
+		/// `Deregister` roles are held by the `para` owner and the `para` itself, while the
+		/// `CodeUpgrade` and `HeadOverride` roles are only held by Root.
+		///
+		/// ## Events
+		/// The `RoleSet` event is emitted in case of success.
+		#[pallet::weight(<T as Config>::WeightInfo::set_role())]
+		pub fn set_role(
+			origin: OriginFor<T>,
+			id: ParaId,
+			role: ParaRole,
+			holder: Option<RoleHolder<T::AccountId>>,
+		) -> DispatchResult {
+			Self::ensure_root_para_or_owner(origin.clone(), id)?;
+			ensure!(Paras::<T>::contains_key(id), Error::<T>::NotRegistered);
+			let by_manager = ensure_signed(origin).is_ok();
+
+			match holder.clone() {
+				Some(holder) =>
+					ParaRoles::<T>::insert(id, role, RoleAssignment { holder, by_manager }),
+				None => ParaRoles::<T>::remove(id, role),
+			}
+
+			Self::deposit_event(Event::<T>::RoleSet(id, role, holder));
+			Ok(())
+		}
+
+		/// Schedule an upgrade of the validation code of a para in the context of the current
+		/// block, as if the para signalled it itself.
+		///
+		/// The upgrade is subject to the same restrictions as those the para signals: it fails
+		/// while another upgrade is pending or the upgrade cooldown hasn't expired yet.
+		///
+		/// The origin must be Root, or hold the `CodeUpgrade` role.
+		#[pallet::weight(<T as Config>::WeightInfo::schedule_code_upgrade(new_code.0.len() as u32))]
+		pub fn schedule_code_upgrade(
+			origin: OriginFor<T>,
+			id: ParaId,
+			new_code: ValidationCode,
+		) -> DispatchResult {
+			Self::ensure_role(origin, id, ParaRole::CodeUpgrade)?;
+			ensure!(paras::Pallet::<T>::is_valid_para(id), Error::<T>::NotLivePara);
+			let config = configuration::Pallet::<T>::config();
+			ensure!(new_code.0.len() <= config.max_code_size as usize, Error::<T>::CodeTooLarge);
+
+			paras::Pallet::<T>::schedule_code_upgrade_now(id, new_code)
+		}
+
+		/// Override the current head data of a para, as the paras pallet's
+		/// `force_set_current_head` does.
+		///
+		/// The origin must be Root, or hold the `HeadOverride` role.
+		#[pallet::weight(<T as Config>::WeightInfo::set_current_head())]
+		pub fn set_current_head(
+			origin: OriginFor<T>,
+			id: ParaId,
+			new_head: HeadData,
+		) -> DispatchResult {
+			Self::ensure_role(origin, id, ParaRole::HeadOverride)?;
+			ensure!(paras::Pallet::<T>::is_valid_para(id), Error::<T>::NotLivePara);
+			let config = configuration::Pallet::<T>::config();
+			ensure!(
+				new_head.0.len() <= config.max_head_data_size as usize,
+				Error::<T>::HeadDataTooLarge
+			);
+
+			paras::Pallet::<T>::force_set_current_head(
+				frame_system::RawOrigin::Root.into(),
+				id,
+				new_head,
+			)
+		}
 	}
 }
 
@@ -369,6 +790,11 @@ impl<T: Config> Registrar for Pallet<T> {
 		Paras::<T>::mutate(id, |x| x.as_mut().map(|mut info| info.locked = false));
 	}
 
+	// Return if the parachain is locked.
+	fn is_locked(id: ParaId) -> bool {
+		Paras::<T>::get(id).map_or(false, |info| info.locked)
+	}
+
 	// Register a Para ID under control of `manager`.
 	//
 	// Note this is a backend registration API, so verification of ParaId
@@ -388,10 +814,15 @@ impl<T: Config> Registrar for Pallet<T> {
 	}
 
 	// Upgrade a registered parathread into a parachain.
+	//
+	// A parachain whose downgrade is still within its grace period is renewed instead.
 	fn make_parachain(id: ParaId) -> DispatchResult {
-		// Para backend should think this is a parathread...
+		// Para backend should think this is a parathread, or a parachain about to become one...
 		ensure!(
-			paras::Pallet::<T>::lifecycle(id) == Some(ParaLifecycle::Parathread),
+			matches!(
+				paras::Pallet::<T>::lifecycle(id),
+				Some(ParaLifecycle::Parathread) | Some(ParaLifecycle::DowngradingParachain)
+			),
 			Error::<T>::NotParathread
 		);
 		runtime_parachains::schedule_parathread_upgrade::<T>(id)
@@ -402,14 +833,18 @@ impl<T: Config> Registrar for Pallet<T> {
 		Ok(())
 	}
 
-	// Downgrade a registered para into a parathread.
+	// Downgrade a registered para into a parathread once its grace period has passed.
 	fn make_parathread(id: ParaId) -> DispatchResult {
-		// Para backend should think this is a parachain...
+		// Para backend should think this is a parachain, or a parathread about to become one
+		// again once downgraded...
 		ensure!(
-			paras::Pallet::<T>::lifecycle(id) == Some(ParaLifecycle::Parachain),
+			matches!(
+				paras::Pallet::<T>::lifecycle(id),
+				Some(ParaLifecycle::Parachain) | Some(ParaLifecycle::DowngradingParachain)
+			),
 			Error::<T>::NotParachain
 		);
-		runtime_parachains::schedule_parachain_downgrade::<T>(id)
+		runtime_parachains::schedule_delayed_parachain_downgrade::<T>(id)
 			.map_err(|_| Error::<T>::CannotDowngrade)?;
 		Ok(())
 	}
@@ -464,6 +899,34 @@ impl<T: Config> Pallet<T> {
 			})
 	}
 
+	/// Ensure the origin is Root or holds the given role for the `para`.
+	fn ensure_role(
+		origin: <T as frame_system::Config>::Origin,
+		id: ParaId,
+		role: ParaRole,
+	) -> DispatchResult {
+		if ensure_root(origin.clone()).is_ok() {
+			return Ok(())
+		}
+
+		// Roles assigned by the manager don't apply while the para is locked.
+		let assignment = ParaRoles::<T>::get(id, role)
+			.filter(|assignment| !(assignment.by_manager && Self::is_locked(id)));
+		match assignment.map(|assignment| assignment.holder) {
+			Some(RoleHolder::Account(holder)) => {
+				ensure!(ensure_signed(origin)? == holder, DispatchError::BadOrigin);
+				Ok(())
+			},
+			Some(RoleHolder::Para) => {
+				let caller_id = ensure_parachain(<T as Config>::Origin::from(origin))?;
+				ensure!(caller_id == id, Error::<T>::NotOwner);
+				Ok(())
+			},
+			None if role.defaults_to_manager() => Self::ensure_root_para_or_owner(origin, id),
+			None => Err(DispatchError::BadOrigin),
+		}
+	}
+
 	fn do_reserve(
 		who: T::AccountId,
 		deposit_override: Option<BalanceOf<T>>,
@@ -491,8 +954,46 @@ impl<T: Config> Pallet<T> {
 		validation_code: ValidationCode,
 		ensure_reserved: bool,
 	) -> DispatchResult {
+		let deposited = Self::ensure_can_register(&who, id, ensure_reserved)?;
+		let (genesis, deposit) =
+			Self::validate_onboarding_data(genesis_head, validation_code, false)?;
+		let deposit = deposit_override.unwrap_or(deposit);
+		Self::complete_registration(who, id, genesis, deposit, deposited)
+	}
+
+	/// Attempt to register a new Para Id under management of `who` with the uploaded validation
+	/// code of the given hash.
+	fn do_register_with_code_hash(
+		who: T::AccountId,
+		id: ParaId,
+		genesis_head: HeadData,
+		code_hash: ValidationCodeHash,
+	) -> DispatchResult {
+		let deposited = Self::ensure_can_register(&who, id, true)?;
+		let mut upload = UploadedCode::<T>::get(&code_hash).ok_or(Error::<T>::CodeNotUploaded)?;
+		let validation_code =
+			paras::Pallet::<T>::code_by_hash(&code_hash).ok_or(Error::<T>::CodeNotUploaded)?;
+		let (genesis, deposit) =
+			Self::validate_onboarding_data(genesis_head, validation_code, false)?;
+		// The code itself is already paid for by its uploader.
+		let code_deposit = T::DataDepositPerByte::get().saturating_mul(upload.code_len.into());
+		let deposit = deposit.saturating_sub(code_deposit);
+		Self::complete_registration(who, id, genesis, deposit, deposited)?;
+
+		upload.users += 1;
+		UploadedCode::<T>::insert(&code_hash, upload);
+		ParaCodeHashes::<T>::insert(id, code_hash);
+		Ok(())
+	}
+
+	/// Check that `who` may register `id`, returning the deposit already held for it.
+	fn ensure_can_register(
+		who: &T::AccountId,
+		id: ParaId,
+		ensure_reserved: bool,
+	) -> Result<BalanceOf<T>, sp_runtime::DispatchError> {
 		let deposited = if let Some(para_data) = Paras::<T>::get(id) {
-			ensure!(para_data.manager == who, Error::<T>::NotOwner);
+			ensure!(&para_data.manager == who, Error::<T>::NotOwner);
 			ensure!(!para_data.locked, Error::<T>::ParaLocked);
 			para_data.deposit
 		} else {
@@ -500,10 +1001,17 @@ impl<T: Config> Pallet<T> {
 			Default::default()
 		};
 		ensure!(paras::Pallet::<T>::lifecycle(id).is_none(), Error::<T>::AlreadyRegistered);
-		let (genesis, deposit) =
-			Self::validate_onboarding_data(genesis_head, validation_code, false)?;
-		let deposit = deposit_override.unwrap_or(deposit);
+		Ok(deposited)
+	}
 
+	/// Settle the deposit of `who` for registering `id` and schedule the para's initialization.
+	fn complete_registration(
+		who: T::AccountId,
+		id: ParaId,
+		genesis: ParaGenesisArgs,
+		deposit: BalanceOf<T>,
+		deposited: BalanceOf<T>,
+	) -> DispatchResult {
 		if let Some(additional) = deposit.checked_sub(&deposited) {
 			<T as Config>::Currency::reserve(&who, additional)?;
 		} else if let Some(rebate) = deposited.checked_sub(&deposit) {
@@ -532,7 +1040,15 @@ impl<T: Config> Pallet<T> {
 		if let Some(info) = Paras::<T>::take(&id) {
 			<T as Config>::Currency::unreserve(&info.manager, info.deposit);
 		}
+		if let Some(code_hash) = ParaCodeHashes::<T>::take(&id) {
+			UploadedCode::<T>::mutate(&code_hash, |upload| {
+				if let Some(upload) = upload {
+					upload.users = upload.users.saturating_sub(1);
+				}
+			});
+		}
 
+		ParaRoles::<T>::remove_prefix(id, None);
 		PendingSwap::<T>::remove(id);
 		Self::deposit_event(Event::<T>::Deregistered(id));
 		Ok(())
@@ -657,6 +1173,7 @@ mod tests {
 	impl paras::Config for Test {
 		type Origin = Origin;
 		type Event = Event;
+		type DowngradeGracePeriod = ();
 	}
 
 	impl configuration::Config for Test {
@@ -1036,6 +1553,298 @@ mod tests {
 			assert_noop!(Registrar::swap(Origin::signed(1), para_id, para_id + 2), BadOrigin);
 		});
 	}
+
+	#[test]
+	fn para_roles_work() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+			let para_id = LOWEST_PUBLIC_ID;
+			assert_ok!(Registrar::reserve(Origin::signed(1)));
+			assert_ok!(Registrar::register(
+				Origin::signed(1),
+				para_id,
+				test_genesis_head(32),
+				test_validation_code(32),
+			));
+			run_to_session(2);
+
+			// Unassigned, code upgrades are only available to Root.
+			assert_noop!(
+				Registrar::schedule_code_upgrade(Origin::signed(1), para_id, vec![1; 32].into()),
+				BadOrigin
+			);
+
+			// Only Root, the manager, or the para itself can assign roles.
+			assert_noop!(
+				Registrar::set_role(
+					Origin::signed(2),
+					para_id,
+					ParaRole::CodeUpgrade,
+					Some(RoleHolder::Account(3))
+				),
+				BadOrigin
+			);
+			assert_ok!(Registrar::set_role(
+				Origin::signed(1),
+				para_id,
+				ParaRole::CodeUpgrade,
+				Some(RoleHolder::Account(3))
+			));
+			assert_ok!(Registrar::set_role(
+				Origin::root(),
+				para_id,
+				ParaRole::HeadOverride,
+				Some(RoleHolder::Para)
+			));
+			assert_ok!(Registrar::set_role(
+				para_origin(para_id),
+				para_id,
+				ParaRole::Swap,
+				Some(RoleHolder::Account(3))
+			));
+
+			// Only the holders can make use of their roles.
+			assert_noop!(
+				Registrar::schedule_code_upgrade(Origin::signed(1), para_id, vec![1; 32].into()),
+				BadOrigin
+			);
+			assert_noop!(
+				Registrar::schedule_code_upgrade(
+					Origin::signed(3),
+					para_id,
+					test_validation_code((max_code_size() + 1) as usize)
+				),
+				Error::<Test>::CodeTooLarge
+			);
+			assert_ok!(Registrar::schedule_code_upgrade(
+				Origin::signed(3),
+				para_id,
+				vec![1; 32].into()
+			));
+			assert!(Parachains::future_code_upgrade_at(para_id).is_some());
+			// The upgrade is pending, so another one cannot be scheduled.
+			assert_noop!(
+				Registrar::schedule_code_upgrade(Origin::signed(3), para_id, vec![2; 32].into()),
+				paras::Error::<Test>::CodeUpgradeAlreadyPending
+			);
+
+			assert_noop!(
+				Registrar::set_current_head(Origin::signed(1), para_id, vec![1; 32].into()),
+				BadOrigin
+			);
+			assert_ok!(Registrar::set_current_head(
+				para_origin(para_id),
+				para_id,
+				vec![1; 32].into()
+			));
+			assert_eq!(Parachains::para_head(para_id), Some(vec![1; 32].into()));
+
+			assert_noop!(Registrar::swap(Origin::signed(1), para_id, para_id + 1), BadOrigin);
+			assert_noop!(Registrar::swap(para_origin(para_id), para_id, para_id + 1), BadOrigin);
+			assert_ok!(Registrar::swap(Origin::signed(3), para_id, para_id + 1));
+
+			// Roles assigned by the manager are suspended while the para is locked.
+			Registrar::apply_lock(para_id);
+			assert_noop!(
+				Registrar::schedule_code_upgrade(Origin::signed(3), para_id, vec![2; 32].into()),
+				BadOrigin
+			);
+			assert_ok!(Registrar::swap(Origin::signed(3), para_id, para_id + 1));
+			assert_ok!(Registrar::set_current_head(
+				para_origin(para_id),
+				para_id,
+				vec![2; 32].into()
+			));
+
+			// Roles are cleared along with the para.
+			assert_ok!(Registrar::deregister(Origin::root(), para_id));
+			assert_eq!(ParaRoles::<Test>::iter_prefix(para_id).count(), 0);
+		});
+	}
+
+	#[test]
+	fn register_with_code_hash_works() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+			let para_1 = LOWEST_PUBLIC_ID;
+			let para_2 = LOWEST_PUBLIC_ID + 1;
+			let code = test_validation_code(100);
+			let code_hash = code.hash();
+			let per_byte = <Test as Config>::DataDepositPerByte::get();
+
+			// Account 1 uploads the code in two chunks.
+			assert_ok!(Registrar::start_code_upload(Origin::signed(1), code_hash, 100));
+			assert_eq!(Balances::reserved_balance(&1), 100 * per_byte);
+			assert_ok!(Registrar::upload_code_chunk(
+				Origin::signed(1),
+				code_hash,
+				code.0[..60].to_vec()
+			));
+			assert!(UploadedCode::<Test>::get(&code_hash).is_none());
+
+			// Account 2 can't register with the code until it is complete.
+			assert_ok!(Registrar::reserve(Origin::signed(2)));
+			assert_ok!(Registrar::reserve(Origin::signed(2)));
+			assert_noop!(
+				Registrar::register_with_code_hash(
+					Origin::signed(2),
+					para_1,
+					test_genesis_head(32),
+					code_hash
+				),
+				Error::<Test>::CodeNotUploaded
+			);
+
+			assert_ok!(Registrar::upload_code_chunk(
+				Origin::signed(1),
+				code_hash,
+				code.0[60..].to_vec()
+			));
+			// The code is handed to paras, and the upload buffer dropped.
+			assert!(UploadedCode::<Test>::get(&code_hash).is_some());
+			assert_eq!(Parachains::code_by_hash(&code_hash), Some(code));
+			assert!(CodeUploads::<Test>::get(&code_hash, &1).is_none());
+			assert_eq!(CodeUploadChunks::<Test>::iter_prefix(&(code_hash, 1)).count(), 0);
+
+			// Both paras share the code, and only pay for their genesis heads.
+			assert_ok!(Registrar::register_with_code_hash(
+				Origin::signed(2),
+				para_1,
+				test_genesis_head(32),
+				code_hash
+			));
+			assert_ok!(Registrar::register_with_code_hash(
+				Origin::signed(2),
+				para_2,
+				test_genesis_head(32),
+				code_hash
+			));
+			assert_eq!(
+				Balances::reserved_balance(&2),
+				2 * (<Test as Config>::ParaDeposit::get() + 32 * per_byte)
+			);
+			run_to_session(2);
+			assert!(Parachains::is_parathread(para_1));
+			assert!(Parachains::is_parathread(para_2));
+
+			// The code can't be removed while a para still uses it.
+			assert_ok!(Registrar::deregister(Origin::root(), para_1));
+			assert_noop!(
+				Registrar::remove_code(Origin::signed(1), code_hash),
+				Error::<Test>::CodeInUse
+			);
+			assert_ok!(Registrar::deregister(Origin::root(), para_2));
+			assert_ok!(Registrar::remove_code(Origin::signed(1), code_hash));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert!(UploadedCode::<Test>::get(&code_hash).is_none());
+		});
+	}
+
+	#[test]
+	fn code_upload_handles_basic_errors() {
+		new_test_ext().execute_with(|| {
+			let code_hash = test_validation_code(10).hash();
+
+			assert_noop!(
+				Registrar::start_code_upload(Origin::signed(1), code_hash, 0),
+				Error::<Test>::EmptyCode
+			);
+			assert_noop!(
+				Registrar::start_code_upload(Origin::signed(1), code_hash, max_code_size() + 1),
+				Error::<Test>::CodeTooLarge
+			);
+			assert_noop!(
+				Registrar::upload_code_chunk(Origin::signed(1), code_hash, vec![0; 10]),
+				Error::<Test>::UnknownCode
+			);
+
+			assert_ok!(Registrar::start_code_upload(Origin::signed(1), code_hash, 10));
+			assert_noop!(
+				Registrar::start_code_upload(Origin::signed(1), code_hash, 10),
+				Error::<Test>::CodeAlreadyUploaded
+			);
+			assert_noop!(
+				Registrar::upload_code_chunk(Origin::signed(2), code_hash, vec![0; 10]),
+				Error::<Test>::UnknownCode
+			);
+			assert_noop!(
+				Registrar::upload_code_chunk(Origin::signed(1), code_hash, vec![]),
+				Error::<Test>::EmptyCode
+			);
+			assert_noop!(
+				Registrar::upload_code_chunk(Origin::signed(1), code_hash, vec![0; 11]),
+				Error::<Test>::CodeTooLarge
+			);
+			assert_ok!(Registrar::upload_code_chunk(Origin::signed(1), code_hash, vec![0; 5]));
+			assert_noop!(
+				Registrar::upload_code_chunk(Origin::signed(1), code_hash, vec![1; 5]),
+				Error::<Test>::CodeHashMismatch
+			);
+
+			// A code can only be split into so many chunks.
+			let chunked_hash = test_validation_code(100).hash();
+			assert_ok!(Registrar::start_code_upload(Origin::signed(1), chunked_hash, 100));
+			for _ in 0..MAX_CODE_UPLOAD_CHUNKS {
+				assert_ok!(Registrar::upload_code_chunk(Origin::signed(1), chunked_hash, vec![0]));
+			}
+			assert_noop!(
+				Registrar::upload_code_chunk(Origin::signed(1), chunked_hash, vec![0]),
+				Error::<Test>::TooManyChunks
+			);
+
+			// Partial uploads can be removed by their uploader.
+			assert_noop!(
+				Registrar::remove_code(Origin::signed(2), chunked_hash),
+				Error::<Test>::UnknownCode
+			);
+			assert_ok!(Registrar::remove_code(Origin::signed(1), chunked_hash));
+			assert_ok!(Registrar::remove_code(Origin::signed(1), code_hash));
+			assert_eq!(CodeUploadChunks::<Test>::iter().count(), 0);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+		});
+	}
+
+	#[test]
+	fn code_upload_cannot_be_squatted() {
+		new_test_ext().execute_with(|| {
+			let code = test_validation_code(10);
+			let code_hash = code.hash();
+			let per_byte = <Test as Config>::DataDepositPerByte::get();
+
+			// Account 2 starts uploading the code with a bogus length, which doesn't prevent
+			// account 1 from uploading the code.
+			assert_ok!(Registrar::start_code_upload(Origin::signed(2), code_hash, 5));
+			assert_ok!(Registrar::start_code_upload(Origin::signed(1), code_hash, 10));
+			assert_ok!(Registrar::upload_code_chunk(Origin::signed(1), code_hash, code.0.clone()));
+			assert!(UploadedCode::<Test>::get(&code_hash).is_some());
+
+			// Once the code is complete, no one else can upload it.
+			assert_noop!(
+				Registrar::upload_code_chunk(Origin::signed(2), code_hash, vec![0; 5]),
+				Error::<Test>::CodeAlreadyUploaded
+			);
+			assert_noop!(
+				Registrar::start_code_upload(Origin::signed(3), code_hash, 10),
+				Error::<Test>::CodeAlreadyUploaded
+			);
+
+			// Account 2 can get its deposit back, but not remove the code of account 1.
+			assert_eq!(Balances::reserved_balance(&2), 5 * per_byte);
+			assert_ok!(Registrar::remove_code(Origin::signed(2), code_hash));
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert_noop!(
+				Registrar::remove_code(Origin::signed(2), code_hash),
+				Error::<Test>::NotCodeUploader
+			);
+			assert!(UploadedCode::<Test>::get(&code_hash).is_some());
+
+			// The code is dropped from paras along with the upload.
+			assert_ok!(Registrar::remove_code(Origin::signed(1), code_hash));
+			assert!(Parachains::code_by_hash(&code_hash).is_none());
+			assert_eq!(Balances::reserved_balance(&1), 0);
+		});
+	}
 }
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -1159,6 +1968,153 @@ mod benchmarking {
 			assert_eq!(paras::Pallet::<T>::lifecycle(parachain), Some(ParaLifecycle::Parathread));
 			assert_eq!(paras::Pallet::<T>::lifecycle(parathread), Some(ParaLifecycle::Parachain));
 		}
+
+		start_code_upload {
+			let code = Registrar::<T>::worst_validation_code();
+			let code_hash = code.hash();
+			let caller: T::AccountId = whitelisted_caller();
+			T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+		}: _(RawOrigin::Signed(caller.clone()), code_hash, code.0.len() as u32)
+		verify {
+			assert_last_event::<T>(Event::<T>::CodeUploadStarted(code_hash, caller).into());
+		}
+
+		// A chunk of `b` bytes which does not complete the code.
+		upload_code_chunk {
+			let code = Registrar::<T>::worst_validation_code();
+			let b in 1 .. code.0.len() as u32 - 1;
+			let code_hash = code.hash();
+			let caller: T::AccountId = whitelisted_caller();
+			T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+			Registrar::<T>::start_code_upload(
+				RawOrigin::Signed(caller.clone()).into(),
+				code_hash,
+				code.0.len() as u32,
+			)?;
+			let chunk = code.0[..b as usize].to_vec();
+		}: _(RawOrigin::Signed(caller.clone()), code_hash, chunk)
+		verify {
+			let upload = CodeUploads::<T>::get(&code_hash, &caller).unwrap();
+			assert_eq!(upload.uploaded_len, b);
+		}
+
+		// Worst case: the chunk completes a code of `c` bytes, split into the maximum number of
+		// chunks.
+		complete_code_upload {
+			let c in MAX_CODE_UPLOAD_CHUNKS .. Registrar::<T>::worst_validation_code().0.len() as u32;
+			let code = ValidationCode(vec![0u8; c as usize]);
+			let code_hash = code.hash();
+			let caller: T::AccountId = whitelisted_caller();
+			T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+			Registrar::<T>::start_code_upload(
+				RawOrigin::Signed(caller.clone()).into(),
+				code_hash,
+				c,
+			)?;
+			let chunk_len = (c / MAX_CODE_UPLOAD_CHUNKS) as usize;
+			let split = chunk_len * (MAX_CODE_UPLOAD_CHUNKS - 1) as usize;
+			for chunk in code.0[..split].chunks(chunk_len) {
+				Registrar::<T>::upload_code_chunk(
+					RawOrigin::Signed(caller.clone()).into(),
+					code_hash,
+					chunk.to_vec(),
+				)?;
+			}
+			let chunk = code.0[split..].to_vec();
+		}: upload_code_chunk(RawOrigin::Signed(caller), code_hash, chunk)
+		verify {
+			assert_last_event::<T>(Event::<T>::CodeUploaded(code_hash).into());
+		}
+
+		// Worst case: a partial upload with the maximum number of chunks.
+		remove_code {
+			let code = Registrar::<T>::worst_validation_code();
+			let code_hash = code.hash();
+			let caller: T::AccountId = whitelisted_caller();
+			T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+			Registrar::<T>::start_code_upload(
+				RawOrigin::Signed(caller.clone()).into(),
+				code_hash,
+				code.0.len() as u32,
+			)?;
+			for _ in 0 .. MAX_CODE_UPLOAD_CHUNKS {
+				Registrar::<T>::upload_code_chunk(
+					RawOrigin::Signed(caller.clone()).into(),
+					code_hash,
+					vec![0u8],
+				)?;
+			}
+		}: _(RawOrigin::Signed(caller), code_hash)
+		verify {
+			assert_last_event::<T>(Event::<T>::CodeRemoved(code_hash).into());
+		}
+
+		set_role {
+			let para = register_para::<T>(LOWEST_PUBLIC_ID.into());
+			let caller: T::AccountId = whitelisted_caller();
+			let holder = Some(RoleHolder::Account(account("holder", 0, 0)));
+		}: _(RawOrigin::Signed(caller), para, ParaRole::CodeUpgrade, holder.clone())
+		verify {
+			assert_last_event::<T>(Event::<T>::RoleSet(para, ParaRole::CodeUpgrade, holder).into());
+		}
+
+		// A new code of `b` bytes, distinct from the current code so that it is stored.
+		schedule_code_upgrade {
+			let b in 1 .. Registrar::<T>::worst_validation_code().0.len() as u32;
+			let para = register_para::<T>(LOWEST_PUBLIC_ID.into());
+			next_scheduled_session::<T>();
+			let new_code = ValidationCode(vec![1u8; b as usize]);
+			let holder: T::AccountId = account("holder", 0, 0);
+			Registrar::<T>::set_role(
+				RawOrigin::Root.into(),
+				para,
+				ParaRole::CodeUpgrade,
+				Some(RoleHolder::Account(holder.clone())),
+			)?;
+		}: _(RawOrigin::Signed(holder), para, new_code)
+		verify {
+			assert!(paras::Pallet::<T>::future_code_upgrade_at(para).is_some());
+		}
+
+		set_current_head {
+			let para = register_para::<T>(LOWEST_PUBLIC_ID.into());
+			next_scheduled_session::<T>();
+			let new_head = Registrar::<T>::worst_head_data();
+			let holder: T::AccountId = account("holder", 0, 0);
+			Registrar::<T>::set_role(
+				RawOrigin::Root.into(),
+				para,
+				ParaRole::HeadOverride,
+				Some(RoleHolder::Account(holder.clone())),
+			)?;
+		}: _(RawOrigin::Signed(holder), para, new_head.clone())
+		verify {
+			assert_eq!(paras::Pallet::<T>::para_head(para), Some(new_head));
+		}
+
+		register_with_code_hash {
+			let para = LOWEST_PUBLIC_ID;
+			let genesis_head = Registrar::<T>::worst_head_data();
+			let code = Registrar::<T>::worst_validation_code();
+			let code_hash = code.hash();
+			let caller: T::AccountId = whitelisted_caller();
+			T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+			Registrar::<T>::start_code_upload(
+				RawOrigin::Signed(caller.clone()).into(),
+				code_hash,
+				code.0.len() as u32,
+			)?;
+			Registrar::<T>::upload_code_chunk(
+				RawOrigin::Signed(caller.clone()).into(),
+				code_hash,
+				code.0,
+			)?;
+			assert_ok!(Registrar::<T>::reserve(RawOrigin::Signed(caller.clone()).into()));
+		}: _(RawOrigin::Signed(caller.clone()), para, genesis_head, code_hash)
+		verify {
+			assert_last_event::<T>(Event::<T>::Registered(para, caller).into());
+			assert_eq!(paras::Pallet::<T>::lifecycle(para), Some(ParaLifecycle::Onboarding));
+		}
 	}
 
 	impl_benchmark_test_suite!(
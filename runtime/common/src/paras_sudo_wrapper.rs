@@ -52,6 +52,8 @@ pub mod pallet {
 		/// A DMP message couldn't be sent because it exceeds the maximum size allowed for a downward
 		/// message.
 		ExceedsMaxMessageSize,
+		/// A DMP message couldn't be sent because the downward message queue of the para is full.
+		ExceedsMaxQueueSize,
 		/// Could not schedule para cleanup.
 		CouldntCleanup,
 		/// Not a parathread.
@@ -142,6 +144,8 @@ pub mod pallet {
 			{
 				dmp::QueueDownwardMessageError::ExceedsMaxMessageSize =>
 					Error::<T>::ExceedsMaxMessageSize.into(),
+				dmp::QueueDownwardMessageError::ExceedsMaxQueueSize =>
+					Error::<T>::ExceedsMaxQueueSize.into(),
 			})
 		}
 
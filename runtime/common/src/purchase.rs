@@ -26,11 +26,16 @@ use parity_scale_codec::{Decode, Encode};
 use scale_info::TypeInfo;
 use sp_core::sr25519;
 use sp_runtime::{
-	traits::{CheckedAdd, Saturating, Verify, Zero},
+	traits::{
+		AtLeast32BitUnsigned, CheckedAdd, One, Saturating, UniqueSaturatedFrom,
+		UniqueSaturatedInto, Verify, Zero,
+	},
 	AnySignature, DispatchError, DispatchResult, Permill, RuntimeDebug,
 };
 use sp_std::prelude::*;
 
+pub mod runtime_api;
+
 type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
@@ -86,6 +91,86 @@ pub struct AccountStatus<Balance> {
 	vat: Permill,
 }
 
+/// The identifier of a vesting template.
+pub type VestingTemplateId = u32;
+
+/// A reusable description of how the locked DOTs of purchasers vest.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct VestingTemplate<BlockNumber> {
+	/// The block from which locked DOTs start to unlock.
+	pub starting_block: BlockNumber,
+	/// The number of blocks over which locked DOTs unlock linearly. With zero, everything unlocks
+	/// at `starting_block`.
+	pub duration: BlockNumber,
+}
+
+impl<BlockNumber: AtLeast32BitUnsigned + Copy> VestingTemplate<BlockNumber> {
+	/// The amount to unlock per block so that all of `locked` is unlocked after `duration` blocks.
+	pub fn per_block<Balance: AtLeast32BitUnsigned + Copy>(&self, locked: Balance) -> Balance {
+		let duration: u128 = self.duration.unique_saturated_into();
+		let duration = Balance::unique_saturated_from(duration);
+		if duration.is_zero() {
+			return locked
+		}
+		let per_block = locked / duration;
+		if (locked % duration).is_zero() {
+			per_block
+		} else {
+			per_block.saturating_add(One::one())
+		}
+	}
+}
+
+/// An account to onboard to the purchase process as part of a batch.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct AccountOnboarding<AccountId, Balance> {
+	/// The purchasing account.
+	pub who: AccountId,
+	/// Their sr25519/ed25519 signature of our required statement.
+	pub signature: Vec<u8>,
+	/// Their validity status, as determined by KYC.
+	pub validity: AccountValidity,
+	/// The amount of free DOTs they have purchased. Must be zero unless `validity` is valid.
+	pub free_balance: Balance,
+	/// The amount of locked DOTs they have purchased. Must be zero unless `validity` is valid.
+	pub locked_balance: Balance,
+	/// The percentage of VAT the purchaser is responsible for.
+	pub vat: Permill,
+	/// The vesting template of their locked DOTs, if they shouldn't unlock at the `UnlockBlock`.
+	pub vesting_template: Option<VestingTemplateId>,
+}
+
+/// How the locked DOTs of a purchaser vest once paid out.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct PurchaseVesting<Balance, BlockNumber> {
+	/// The amount that stays locked, after unlocking a portion for basic usage.
+	pub locked: Balance,
+	/// The amount unlocked per block.
+	pub per_block: Balance,
+	/// The block from which the locked amount starts to unlock.
+	pub starting_block: BlockNumber,
+}
+
+/// The status of an account in the purchase process.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct PurchaseStatus<Balance, BlockNumber> {
+	/// The current validity status of the account.
+	pub validity: AccountValidity,
+	/// The amount of free DOTs they have purchased.
+	pub free_balance: Balance,
+	/// The amount of locked DOTs they have purchased.
+	pub locked_balance: Balance,
+	/// The percentage of VAT the purchaser is responsible for.
+	pub vat: Permill,
+	/// The vesting template of their locked DOTs, if they don't unlock at the `UnlockBlock`.
+	pub vesting_template: Option<VestingTemplateId>,
+	/// How their locked DOTs will vest once paid out. `None` once paid out, if nothing stays
+	/// locked or if their vesting template was removed.
+	pub vesting: Option<PurchaseVesting<Balance, BlockNumber>>,
+	/// The amount of their DOTs still locked by vesting.
+	pub vesting_balance: Option<Balance>,
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -145,6 +230,14 @@ pub mod pallet {
 		StatementUpdated,
 		/// A new statement was set. `[block_number]`
 		UnlockBlockUpdated(T::BlockNumber),
+		/// An account was onboarded in a batch. `[who, validity, free, locked]`
+		AccountOnboarded(T::AccountId, AccountValidity, BalanceOf<T>, BalanceOf<T>),
+		/// An account was skipped in a batch because of an error. `[who, error]`
+		BatchItemFailed(T::AccountId, DispatchError),
+		/// A vesting template was set or removed. `[id]`
+		VestingTemplateUpdated(VestingTemplateId),
+		/// The vesting template of an account was changed. `[who, template]`
+		VestingTemplateAssigned(T::AccountId, Option<VestingTemplateId>),
 	}
 
 	#[pallet::error]
@@ -165,6 +258,8 @@ pub mod pallet {
 		InvalidUnlockBlock,
 		/// Vesting schedule already exists for this account.
 		VestingScheduleExists,
+		/// There is no vesting template with the given id.
+		UnknownVestingTemplate,
 	}
 
 	// A map of all participants in the DOT purchase process.
@@ -184,6 +279,16 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type UnlockBlock<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
 
+	// The reusable templates for vesting locked dots.
+	#[pallet::storage]
+	pub(super) type VestingTemplates<T: Config> =
+		StorageMap<_, Twox64Concat, VestingTemplateId, VestingTemplate<T::BlockNumber>>;
+
+	// The vesting templates of the purchasers whose locked dots don't unlock at the `UnlockBlock`.
+	#[pallet::storage]
+	pub(super) type AccountVestingTemplates<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, VestingTemplateId>;
+
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
 
@@ -201,16 +306,7 @@ pub mod pallet {
 			signature: Vec<u8>,
 		) -> DispatchResult {
 			T::ValidityOrigin::ensure_origin(origin)?;
-			// Account is already being tracked by the pallet.
-			ensure!(!Accounts::<T>::contains_key(&who), Error::<T>::ExistingAccount);
-			// Account should not have a vesting schedule.
-			ensure!(
-				T::VestingSchedule::vesting_balance(&who).is_none(),
-				Error::<T>::VestingScheduleExists
-			);
-
-			// Verify the signature provided is valid for the statement.
-			Self::verify_signature(&who, &signature)?;
+			Self::ensure_can_create_account(&who, &signature)?;
 
 			// Create a new pending account.
 			let status = AccountStatus {
@@ -238,20 +334,7 @@ pub mod pallet {
 			validity: AccountValidity,
 		) -> DispatchResult {
 			T::ValidityOrigin::ensure_origin(origin)?;
-			ensure!(Accounts::<T>::contains_key(&who), Error::<T>::InvalidAccount);
-			Accounts::<T>::try_mutate(
-				&who,
-				|status: &mut AccountStatus<BalanceOf<T>>| -> DispatchResult {
-					ensure!(
-						status.validity != AccountValidity::Completed,
-						Error::<T>::AlreadyCompleted
-					);
-					status.validity = validity;
-					Ok(())
-				},
-			)?;
-			Self::deposit_event(Event::<T>::ValidityUpdated(who, validity));
-			Ok(())
+			Self::do_update_validity_status(who, validity)
 		}
 
 		/// Update the balance of a valid account.
@@ -286,12 +369,93 @@ pub mod pallet {
 			Ok(())
 		}
 
+		/// Set the vesting template of an existing account, or have its locked DOTs unlock at the
+		/// `UnlockBlock` with `None`.
+		///
+		/// We check that the account exists at this stage, but has not completed the process.
+		///
+		/// Origin must match the `ValidityOrigin`.
+		#[pallet::weight(T::DbWeight::get().reads_writes(2, 1))]
+		pub fn assign_vesting_template(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			template: Option<VestingTemplateId>,
+		) -> DispatchResult {
+			T::ValidityOrigin::ensure_origin(origin)?;
+			ensure!(Accounts::<T>::contains_key(&who), Error::<T>::InvalidAccount);
+			ensure!(
+				Accounts::<T>::get(&who).validity != AccountValidity::Completed,
+				Error::<T>::AlreadyCompleted
+			);
+			match template {
+				Some(id) => {
+					ensure!(
+						VestingTemplates::<T>::contains_key(id),
+						Error::<T>::UnknownVestingTemplate
+					);
+					AccountVestingTemplates::<T>::insert(&who, id);
+				},
+				None => AccountVestingTemplates::<T>::remove(&who),
+			}
+			Self::deposit_event(Event::<T>::VestingTemplateAssigned(who, template));
+			Ok(())
+		}
+
+		/// Create many new accounts at once, along with their validity status and balances.
+		///
+		/// Each account is checked as by `create_account`, and its balances as by `update_balance`.
+		/// An account failing the checks is skipped with a `BatchItemFailed` event, without
+		/// affecting the others.
+		///
+		/// Origin must match the `ValidityOrigin`.
+		#[pallet::weight(
+			(200_000_000 + T::DbWeight::get().reads_writes(4, 2))
+				.saturating_mul(accounts.len() as Weight)
+		)]
+		pub fn onboard_accounts(
+			origin: OriginFor<T>,
+			accounts: Vec<AccountOnboarding<T::AccountId, BalanceOf<T>>>,
+		) -> DispatchResult {
+			T::ValidityOrigin::ensure_origin(origin)?;
+			for account in accounts {
+				let who = account.who.clone();
+				if let Err(error) = Self::do_onboard_account(account) {
+					Self::deposit_event(Event::<T>::BatchItemFailed(who, error));
+				}
+			}
+			Ok(())
+		}
+
+		/// Update the validity status of many existing accounts at once, such as with the results
+		/// of KYC.
+		///
+		/// Each update is checked as by `update_validity_status`. An update failing the checks is
+		/// skipped with a `BatchItemFailed` event, without affecting the others.
+		///
+		/// Origin must match the `ValidityOrigin`.
+		#[pallet::weight(
+			(100_000_000 + T::DbWeight::get().reads_writes(1, 1))
+				.saturating_mul(updates.len() as Weight)
+		)]
+		pub fn batch_update_validity_status(
+			origin: OriginFor<T>,
+			updates: Vec<(T::AccountId, AccountValidity)>,
+		) -> DispatchResult {
+			T::ValidityOrigin::ensure_origin(origin)?;
+			for (who, validity) in updates {
+				if let Err(error) = Self::do_update_validity_status(who.clone(), validity) {
+					Self::deposit_event(Event::<T>::BatchItemFailed(who, error));
+				}
+			}
+			Ok(())
+		}
+
 		/// Pay the user and complete the purchase process.
 		///
 		/// We reverify all assumptions about the state of an account, and complete the process.
 		///
 		/// Origin must match the configured `PaymentAccount`.
-		#[pallet::weight(T::DbWeight::get().reads_writes(4, 2))]
+		#[pallet::weight(T::DbWeight::get().reads_writes(6, 2))]
 		pub fn payout(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
 			// Payments must be made directly by the `PaymentAccount`.
 			let payment_account = ensure_signed(origin)?;
@@ -308,6 +472,7 @@ pub mod pallet {
 				|status: &mut AccountStatus<BalanceOf<T>>| -> DispatchResult {
 					// Account has a valid status (not Invalid, Pending, or Completed)...
 					ensure!(status.validity.is_valid(), Error::<T>::InvalidAccount);
+					let vesting = Self::vesting_of(&who, status)?;
 
 					// Transfer funds from the payment account into the purchasing user.
 					let total_balance = status
@@ -321,28 +486,25 @@ pub mod pallet {
 						ExistenceRequirement::AllowDeath,
 					)?;
 
-					if !status.locked_balance.is_zero() {
-						let unlock_block = UnlockBlock::<T>::get();
-						// We allow some configurable portion of the purchased locked DOTs to be unlocked for basic usage.
-						let unlocked = (T::UnlockedProportion::get() * status.locked_balance)
-							.min(T::MaxUnlocked::get());
-						let locked = status.locked_balance.saturating_sub(unlocked);
+					if let Some(vesting) = vesting {
 						// We checked that this account has no existing vesting schedule. So this function should
 						// never fail, however if it does, not much we can do about it at this point.
 						let _ = T::VestingSchedule::add_vesting_schedule(
 							// Apply vesting schedule to this user
 							&who,
 							// For this much amount
-							locked,
-							// Unlocking the full amount after one block
-							locked,
-							// When everything unlocks
-							unlock_block,
+							vesting.locked,
+							// Unlocking this much per block
+							vesting.per_block,
+							// When unlocking starts
+							vesting.starting_block,
 						);
 					}
 
 					// Setting the user account to `Completed` ends the purchase process for this user.
 					status.validity = AccountValidity::Completed;
+					// The vesting template is no longer needed once the vesting is in place.
+					AccountVestingTemplates::<T>::remove(&who);
 					Self::deposit_event(Event::<T>::PaymentComplete(
 						who.clone(),
 						status.free_balance,
@@ -402,10 +564,154 @@ pub mod pallet {
 			Self::deposit_event(Event::<T>::UnlockBlockUpdated(unlock_block));
 			Ok(())
 		}
+
+		/// Set the vesting template with the given id, or remove it with `None`.
+		///
+		/// Accounts with a removed template cannot be paid out until it is set again or they are
+		/// assigned another one.
+		///
+		/// Origin must match the `ConfigurationOrigin`
+		#[pallet::weight(T::DbWeight::get().writes(1))]
+		pub fn set_vesting_template(
+			origin: OriginFor<T>,
+			id: VestingTemplateId,
+			template: Option<VestingTemplate<T::BlockNumber>>,
+		) -> DispatchResult {
+			T::ConfigurationOrigin::ensure_origin(origin)?;
+			match template {
+				Some(template) => {
+					ensure!(
+						template.starting_block > frame_system::Pallet::<T>::block_number(),
+						Error::<T>::InvalidUnlockBlock
+					);
+					VestingTemplates::<T>::insert(id, template);
+				},
+				None => VestingTemplates::<T>::remove(id),
+			}
+			Self::deposit_event(Event::<T>::VestingTemplateUpdated(id));
+			Ok(())
+		}
 	}
 }
 
 impl<T: Config> Pallet<T> {
+	/// The status of `who` in the purchase process, or `None` if they don't take part in it.
+	pub fn purchase_status(
+		who: &T::AccountId,
+	) -> Option<PurchaseStatus<BalanceOf<T>, T::BlockNumber>> {
+		if !Accounts::<T>::contains_key(who) {
+			return None
+		}
+		let status = Accounts::<T>::get(who);
+		let vesting = if status.validity == AccountValidity::Completed {
+			None
+		} else {
+			Self::vesting_of(who, &status).ok().flatten()
+		};
+		Some(PurchaseStatus {
+			validity: status.validity,
+			free_balance: status.free_balance,
+			locked_balance: status.locked_balance,
+			vat: status.vat,
+			vesting_template: AccountVestingTemplates::<T>::get(who),
+			vesting,
+			vesting_balance: T::VestingSchedule::vesting_balance(who),
+		})
+	}
+
+	/// How the locked DOTs of `who` vest once paid out, if any of them stay locked.
+	fn vesting_of(
+		who: &T::AccountId,
+		status: &AccountStatus<BalanceOf<T>>,
+	) -> Result<Option<PurchaseVesting<BalanceOf<T>, T::BlockNumber>>, DispatchError> {
+		// We allow some configurable portion of the purchased locked DOTs to be unlocked for basic usage.
+		let unlocked =
+			(T::UnlockedProportion::get() * status.locked_balance).min(T::MaxUnlocked::get());
+		let locked = status.locked_balance.saturating_sub(unlocked);
+		if locked.is_zero() {
+			return Ok(None)
+		}
+
+		let (per_block, starting_block) = match AccountVestingTemplates::<T>::get(who) {
+			Some(id) => {
+				let template =
+					VestingTemplates::<T>::get(id).ok_or(Error::<T>::UnknownVestingTemplate)?;
+				(template.per_block(locked), template.starting_block)
+			},
+			// Unlocking the full amount after one block.
+			None => (locked, UnlockBlock::<T>::get()),
+		};
+		Ok(Some(PurchaseVesting { locked, per_block, starting_block }))
+	}
+
+	/// Check that an account for `who` can be created with the given signature.
+	fn ensure_can_create_account(who: &T::AccountId, signature: &[u8]) -> DispatchResult {
+		// Account is already being tracked by the pallet.
+		ensure!(!Accounts::<T>::contains_key(who), Error::<T>::ExistingAccount);
+		// Account should not have a vesting schedule.
+		ensure!(
+			T::VestingSchedule::vesting_balance(who).is_none(),
+			Error::<T>::VestingScheduleExists
+		);
+
+		// Verify the signature provided is valid for the statement.
+		Self::verify_signature(who, signature)
+	}
+
+	fn do_update_validity_status(who: T::AccountId, validity: AccountValidity) -> DispatchResult {
+		ensure!(Accounts::<T>::contains_key(&who), Error::<T>::InvalidAccount);
+		Accounts::<T>::try_mutate(
+			&who,
+			|status: &mut AccountStatus<BalanceOf<T>>| -> DispatchResult {
+				ensure!(
+					status.validity != AccountValidity::Completed,
+					Error::<T>::AlreadyCompleted
+				);
+				status.validity = validity;
+				Ok(())
+			},
+		)?;
+		Self::deposit_event(Event::<T>::ValidityUpdated(who, validity));
+		Ok(())
+	}
+
+	fn do_onboard_account(
+		account: AccountOnboarding<T::AccountId, BalanceOf<T>>,
+	) -> DispatchResult {
+		let AccountOnboarding {
+			who,
+			signature,
+			validity,
+			free_balance,
+			locked_balance,
+			vat,
+			vesting_template,
+		} = account;
+
+		// Everything is checked before anything is written.
+		Self::ensure_can_create_account(&who, &signature)?;
+		if !free_balance.is_zero() || !locked_balance.is_zero() {
+			ensure!(validity.is_valid(), Error::<T>::InvalidAccount);
+			free_balance.checked_add(&locked_balance).ok_or(Error::<T>::Overflow)?;
+		}
+		if let Some(id) = vesting_template {
+			ensure!(VestingTemplates::<T>::contains_key(id), Error::<T>::UnknownVestingTemplate);
+		}
+
+		let status = AccountStatus { validity, free_balance, locked_balance, signature, vat };
+		Accounts::<T>::insert(&who, status);
+		if let Some(id) = vesting_template {
+			AccountVestingTemplates::<T>::insert(&who, id);
+		}
+		Self::deposit_event(Event::<T>::AccountOnboarded(
+			who,
+			validity,
+			free_balance,
+			locked_balance,
+		));
+		Ok(())
+	}
+
 	fn verify_signature(who: &T::AccountId, signature: &[u8]) -> Result<(), DispatchError> {
 		// sr25519 always expects a 64 byte signature.
 		ensure!(signature.len() == 64, Error::<T>::InvalidSignature);
@@ -448,6 +754,8 @@ where
 	remove_storage_prefix(b"Purchase", b"PaymentAccount", b"");
 	remove_storage_prefix(b"Purchase", b"Statement", b"");
 	remove_storage_prefix(b"Purchase", b"UnlockBlock", b"");
+	remove_storage_prefix(b"Purchase", b"VestingTemplates", b"");
+	remove_storage_prefix(b"Purchase", b"AccountVestingTemplates", b"");
 
 	<T as frame_system::Config>::BlockWeights::get().max_block
 }
@@ -1128,6 +1436,331 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn set_vesting_template_works_and_handles_basic_errors() {
+		new_test_ext().execute_with(|| {
+			let template = VestingTemplate { starting_block: 50, duration: 10 };
+			// Invalid origin
+			assert_noop!(
+				Purchase::set_vesting_template(Origin::signed(alice()), 1, Some(template.clone())),
+				BadOrigin,
+			);
+			// Starting block in past
+			System::set_block_number(50);
+			assert_noop!(
+				Purchase::set_vesting_template(
+					Origin::signed(configuration_origin()),
+					1,
+					Some(template.clone())
+				),
+				Error::<Test>::InvalidUnlockBlock,
+			);
+			System::set_block_number(1);
+			// Just right...
+			assert_ok!(Purchase::set_vesting_template(
+				Origin::signed(configuration_origin()),
+				1,
+				Some(template.clone())
+			));
+			assert_eq!(VestingTemplates::<Test>::get(1), Some(template));
+			// And it can be removed again.
+			assert_ok!(Purchase::set_vesting_template(
+				Origin::signed(configuration_origin()),
+				1,
+				None
+			));
+			assert!(!VestingTemplates::<Test>::contains_key(1));
+		});
+	}
+
+	#[test]
+	fn vesting_template_unlocks_everything_within_its_duration() {
+		let template = VestingTemplate { starting_block: 50u64, duration: 10 };
+		assert_eq!(template.per_block(100u64), 10);
+		// Rounded up, so nothing is left after `duration` blocks.
+		assert_eq!(template.per_block(45u64), 5);
+		assert_eq!(template.per_block(5u64), 1);
+		// Without a duration, everything unlocks at once.
+		let cliff = VestingTemplate { starting_block: 50u64, duration: 0 };
+		assert_eq!(cliff.per_block(45u64), 45);
+	}
+
+	fn set_vesting_template(id: VestingTemplateId, starting_block: u64, duration: u64) {
+		assert_ok!(Purchase::set_vesting_template(
+			Origin::signed(configuration_origin()),
+			id,
+			Some(VestingTemplate { starting_block, duration }),
+		));
+	}
+
+	fn onboarding(who: AccountId, signature: [u8; 64]) -> AccountOnboarding<AccountId, u64> {
+		AccountOnboarding {
+			who,
+			signature: signature.to_vec(),
+			validity: AccountValidity::ValidLow,
+			free_balance: 50,
+			locked_balance: 50,
+			vat: Permill::zero(),
+			vesting_template: None,
+		}
+	}
+
+	#[test]
+	fn assign_vesting_template_works_and_handles_basic_errors() {
+		new_test_ext().execute_with(|| {
+			set_vesting_template(1, 50, 10);
+			// Wrong Origin
+			assert_noop!(
+				Purchase::assign_vesting_template(Origin::signed(alice()), alice(), Some(1)),
+				BadOrigin,
+			);
+			// Inactive Account
+			assert_noop!(
+				Purchase::assign_vesting_template(
+					Origin::signed(validity_origin()),
+					alice(),
+					Some(1)
+				),
+				Error::<Test>::InvalidAccount,
+			);
+			assert_ok!(Purchase::create_account(
+				Origin::signed(validity_origin()),
+				alice(),
+				alice_signature().to_vec()
+			));
+			// Unknown Template
+			assert_noop!(
+				Purchase::assign_vesting_template(
+					Origin::signed(validity_origin()),
+					alice(),
+					Some(2)
+				),
+				Error::<Test>::UnknownVestingTemplate,
+			);
+			// Just right...
+			assert_ok!(Purchase::assign_vesting_template(
+				Origin::signed(validity_origin()),
+				alice(),
+				Some(1)
+			));
+			assert_eq!(AccountVestingTemplates::<Test>::get(alice()), Some(1));
+			assert_ok!(Purchase::assign_vesting_template(
+				Origin::signed(validity_origin()),
+				alice(),
+				None
+			));
+			assert_eq!(AccountVestingTemplates::<Test>::get(alice()), None);
+			// Already Completed
+			assert_ok!(Purchase::update_validity_status(
+				Origin::signed(validity_origin()),
+				alice(),
+				AccountValidity::Completed,
+			));
+			assert_noop!(
+				Purchase::assign_vesting_template(
+					Origin::signed(validity_origin()),
+					alice(),
+					Some(1)
+				),
+				Error::<Test>::AlreadyCompleted,
+			);
+		});
+	}
+
+	#[test]
+	fn onboard_accounts_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			set_vesting_template(1, 50, 10);
+			assert_ok!(Purchase::create_account(
+				Origin::signed(validity_origin()),
+				bob(),
+				bob_signature().to_vec()
+			));
+
+			// Wrong Origin
+			assert_noop!(Purchase::onboard_accounts(Origin::signed(alice()), vec![]), BadOrigin);
+
+			assert_ok!(Purchase::onboard_accounts(
+				Origin::signed(validity_origin()),
+				vec![
+					AccountOnboarding {
+						vesting_template: Some(1),
+						..onboarding(alice(), alice_signature())
+					},
+					// Bob already exists.
+					onboarding(bob(), bob_signature()),
+					// A balance cannot be set before KYC passes.
+					AccountOnboarding {
+						validity: AccountValidity::Pending,
+						..onboarding(alice_ed25519(), alice_signature_ed25519())
+					},
+				],
+			));
+
+			// Alice is onboarded...
+			assert_eq!(
+				Accounts::<Test>::get(alice()),
+				AccountStatus {
+					validity: AccountValidity::ValidLow,
+					free_balance: 50,
+					locked_balance: 50,
+					signature: alice_signature().to_vec(),
+					vat: Permill::zero(),
+				}
+			);
+			assert_eq!(AccountVestingTemplates::<Test>::get(alice()), Some(1));
+			// ...while the others are skipped.
+			assert_eq!(Accounts::<Test>::get(bob()).validity, AccountValidity::Initiated);
+			assert!(!Accounts::<Test>::contains_key(alice_ed25519()));
+			System::assert_has_event(
+				purchase::Event::<Test>::BatchItemFailed(
+					bob(),
+					Error::<Test>::ExistingAccount.into(),
+				)
+				.into(),
+			);
+			System::assert_has_event(
+				purchase::Event::<Test>::BatchItemFailed(
+					alice_ed25519(),
+					Error::<Test>::InvalidAccount.into(),
+				)
+				.into(),
+			);
+		});
+	}
+
+	#[test]
+	fn batch_update_validity_status_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(Purchase::create_account(
+				Origin::signed(validity_origin()),
+				alice(),
+				alice_signature().to_vec()
+			));
+			assert_ok!(Purchase::create_account(
+				Origin::signed(validity_origin()),
+				bob(),
+				bob_signature().to_vec()
+			));
+			assert_ok!(Purchase::update_validity_status(
+				Origin::signed(validity_origin()),
+				bob(),
+				AccountValidity::Completed,
+			));
+
+			// Wrong Origin
+			assert_noop!(
+				Purchase::batch_update_validity_status(Origin::signed(alice()), vec![]),
+				BadOrigin,
+			);
+
+			assert_ok!(Purchase::batch_update_validity_status(
+				Origin::signed(validity_origin()),
+				vec![
+					(alice(), AccountValidity::ValidHigh),
+					(bob(), AccountValidity::ValidHigh),
+					(alice_ed25519(), AccountValidity::ValidHigh),
+				],
+			));
+			assert_eq!(Accounts::<Test>::get(alice()).validity, AccountValidity::ValidHigh);
+			assert_eq!(Accounts::<Test>::get(bob()).validity, AccountValidity::Completed);
+			System::assert_has_event(
+				purchase::Event::<Test>::BatchItemFailed(
+					bob(),
+					Error::<Test>::AlreadyCompleted.into(),
+				)
+				.into(),
+			);
+			System::assert_has_event(
+				purchase::Event::<Test>::BatchItemFailed(
+					alice_ed25519(),
+					Error::<Test>::InvalidAccount.into(),
+				)
+				.into(),
+			);
+		});
+	}
+
+	#[test]
+	fn payout_with_vesting_template_works() {
+		new_test_ext().execute_with(|| {
+			set_vesting_template(1, 50, 10);
+			assert_ok!(Purchase::onboard_accounts(
+				Origin::signed(validity_origin()),
+				vec![AccountOnboarding {
+					vesting_template: Some(1),
+					..onboarding(alice(), alice_signature())
+				}],
+			));
+
+			// Without its template, the account cannot be paid out.
+			assert_ok!(Purchase::set_vesting_template(
+				Origin::signed(configuration_origin()),
+				1,
+				None
+			));
+			assert_noop!(
+				Purchase::payout(Origin::signed(payment_account()), alice()),
+				Error::<Test>::UnknownVestingTemplate
+			);
+			set_vesting_template(1, 50, 10);
+
+			assert_ok!(Purchase::payout(Origin::signed(payment_account()), alice()));
+			assert_eq!(<Test as Config>::Currency::free_balance(&alice()), 100);
+			assert_eq!(AccountVestingTemplates::<Test>::get(alice()), None);
+			// 10% of the 50 units is unlocked automatically, the rest vests over 10 blocks.
+			assert_eq!(<Test as Config>::VestingSchedule::vesting_balance(&alice()), Some(45));
+			let vest_call = Call::Vesting(pallet_vesting::Call::<Test>::vest {});
+			System::set_block_number(55);
+			assert_ok!(vest_call.clone().dispatch(Origin::signed(alice())));
+			assert_eq!(<Test as Config>::VestingSchedule::vesting_balance(&alice()), Some(20));
+			System::set_block_number(59);
+			assert_ok!(vest_call.clone().dispatch(Origin::signed(alice())));
+			assert_eq!(<Test as Config>::VestingSchedule::vesting_balance(&alice()), None);
+		});
+	}
+
+	#[test]
+	fn purchase_status_works() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Purchase::purchase_status(&alice()), None);
+
+			set_vesting_template(1, 50, 10);
+			assert_ok!(Purchase::onboard_accounts(
+				Origin::signed(validity_origin()),
+				vec![AccountOnboarding {
+					vesting_template: Some(1),
+					..onboarding(alice(), alice_signature())
+				}],
+			));
+			let status = PurchaseStatus {
+				validity: AccountValidity::ValidLow,
+				free_balance: 50,
+				locked_balance: 50,
+				vat: Permill::zero(),
+				vesting_template: Some(1),
+				vesting: Some(PurchaseVesting { locked: 45, per_block: 5, starting_block: 50 }),
+				vesting_balance: None,
+			};
+			assert_eq!(Purchase::purchase_status(&alice()), Some(status.clone()));
+
+			// Once paid out, the vesting is in place.
+			assert_ok!(Purchase::payout(Origin::signed(payment_account()), alice()));
+			assert_eq!(
+				Purchase::purchase_status(&alice()),
+				Some(PurchaseStatus {
+					validity: AccountValidity::Completed,
+					vesting_template: None,
+					vesting: None,
+					vesting_balance: Some(45),
+					..status
+				})
+			);
+		});
+	}
+
 	#[test]
 	fn remove_pallet_works() {
 		new_test_ext().execute_with(|| {
@@ -1145,12 +1778,16 @@ mod tests {
 			PaymentAccount::<Test>::put(alice());
 			Statement::<Test>::put(b"hello, world!".to_vec());
 			UnlockBlock::<Test>::put(4);
+			VestingTemplates::<Test>::insert(1, VestingTemplate { starting_block: 4, duration: 2 });
+			AccountVestingTemplates::<Test>::insert(alice(), 1);
 
 			// Verify storage exists.
 			assert_eq!(Accounts::<Test>::iter().count(), 2);
 			assert!(PaymentAccount::<Test>::exists());
 			assert!(Statement::<Test>::exists());
 			assert!(UnlockBlock::<Test>::exists());
+			assert_eq!(VestingTemplates::<Test>::iter().count(), 1);
+			assert_eq!(AccountVestingTemplates::<Test>::iter().count(), 1);
 
 			// Remove storage.
 			remove_pallet::<Test>();
@@ -1160,6 +1797,8 @@ mod tests {
 			assert!(!PaymentAccount::<Test>::exists());
 			assert!(!Statement::<Test>::exists());
 			assert!(!UnlockBlock::<Test>::exists());
+			assert_eq!(VestingTemplates::<Test>::iter().count(), 0);
+			assert_eq!(AccountVestingTemplates::<Test>::iter().count(), 0);
 		});
 	}
 }
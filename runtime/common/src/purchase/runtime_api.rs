@@ -0,0 +1,33 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API for inspecting the purchase process.
+
+use super::PurchaseStatus;
+use parity_scale_codec::{Decode, Encode};
+
+sp_api::decl_runtime_apis! {
+	/// The API for inspecting the accounts taking part in the purchase process.
+	pub trait PurchaseApi<
+		AccountId: Encode + Decode,
+		Balance: Encode + Decode,
+		BlockNumber: Encode + Decode,
+	> {
+		/// Get the purchase status of an account along with how its locked balance vests, or
+		/// `None` if it doesn't take part in the purchase.
+		fn purchase_status(who: AccountId) -> Option<PurchaseStatus<Balance, BlockNumber>>;
+	}
+}
@@ -18,18 +18,25 @@
 //! parachain slots (i.e. continuous scheduling) to be leased. Also allows for parachains and parathreads to be
 //! swapped.
 //!
+//! The holder of a lease can offer some or all of its future periods to another registered para, at a price. The
+//! manager of that para takes over the lease by accepting the offer, paying the price and reserving the deposit for
+//! the periods in place of the holder. Paras whose registration is locked cannot take part until governance removes
+//! the lock.
+//!
 //! This doesn't handle the mechanics of determining which para ID actually ends up with a parachain lease. This
 //! must handled by a separately, through the trait interface that this pallet provides or the root dispatchables.
 
 use crate::traits::{LeaseError, Leaser, Registrar};
 use frame_support::{
 	pallet_prelude::*,
-	traits::{Currency, ReservableCurrency},
+	traits::{Currency, ExistenceRequirement::KeepAlive, ReservableCurrency},
+	transactional,
 	weights::Weight,
 };
 use frame_system::pallet_prelude::*;
 pub use pallet::*;
 use primitives::v1::Id as ParaId;
+use scale_info::TypeInfo;
 use sp_runtime::traits::{CheckedConversion, CheckedSub, Saturating, Zero};
 use sp_std::prelude::*;
 
@@ -42,6 +49,9 @@ pub trait WeightInfo {
 	fn manage_lease_period_start(c: u32, t: u32) -> Weight;
 	fn clear_all_leases() -> Weight;
 	fn trigger_onboard() -> Weight;
+	fn offer_lease() -> Weight;
+	fn cancel_lease_offer() -> Weight;
+	fn accept_lease_offer() -> Weight;
 }
 
 pub struct TestWeightInfo;
@@ -58,6 +68,28 @@ impl WeightInfo for TestWeightInfo {
 	fn trigger_onboard() -> Weight {
 		0
 	}
+	fn offer_lease() -> Weight {
+		0
+	}
+	fn cancel_lease_offer() -> Weight {
+		0
+	}
+	fn accept_lease_offer() -> Weight {
+		0
+	}
+}
+
+/// An offer by the holder of some lease periods of a para to transfer them to another para.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct LeaseOffer<AccountId, Balance, LeasePeriod> {
+	/// The account holding the deposit for the periods.
+	pub leaser: AccountId,
+	/// The first lease period offered.
+	pub period_begin: LeasePeriod,
+	/// The number of lease periods offered.
+	pub period_count: LeasePeriod,
+	/// The amount to be paid to the leaser for the periods.
+	pub price: Balance,
 }
 
 #[frame_support::pallet]
@@ -108,6 +140,18 @@ pub mod pallet {
 	pub type Leases<T: Config> =
 		StorageMap<_, Twox64Concat, ParaId, Vec<Option<(T::AccountId, BalanceOf<T>)>>, ValueQuery>;
 
+	/// Offers to transfer lease periods of a para (the first key) to another para (the second key).
+	#[pallet::storage]
+	#[pallet::getter(fn lease_offer)]
+	pub type LeaseOffers<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		ParaId,
+		Twox64Concat,
+		ParaId,
+		LeaseOffer<T::AccountId, BalanceOf<T>, LeasePeriodOf<T>>,
+	>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -125,6 +169,29 @@ pub mod pallet {
 			BalanceOf<T>,
 			BalanceOf<T>,
 		),
+		/// Lease periods of a para have been offered to another para.
+		/// `[parachain_id, new_parachain_id, leaser, period_begin, period_count, price]`
+		LeaseOffered(
+			ParaId,
+			ParaId,
+			T::AccountId,
+			LeasePeriodOf<T>,
+			LeasePeriodOf<T>,
+			BalanceOf<T>,
+		),
+		/// An offer of lease periods has been withdrawn. `[parachain_id, new_parachain_id]`
+		LeaseOfferCancelled(ParaId, ParaId),
+		/// Lease periods of a para have been transferred to another para, along with their
+		/// deposit.
+		/// `[parachain_id, new_parachain_id, leaser, new_leaser, period_begin, period_count]`
+		LeaseTransferred(
+			ParaId,
+			ParaId,
+			T::AccountId,
+			T::AccountId,
+			LeasePeriodOf<T>,
+			LeasePeriodOf<T>,
+		),
 	}
 
 	#[pallet::error]
@@ -133,6 +200,20 @@ pub mod pallet {
 		ParaNotOnboarding,
 		/// There was an error with the lease.
 		LeaseError,
+		/// The lease periods are not all held by the account, or have already begun.
+		NotLeaseHolder,
+		/// Lease periods cannot be transferred to the para holding them.
+		SamePara,
+		/// The para is not registered.
+		NotRegistered,
+		/// The para registration is locked, so its manager cannot act on its behalf.
+		ParaLocked,
+		/// The account is not the manager of the para.
+		NotParaManager,
+		/// There is no such offer of lease periods.
+		NoLeaseOffer,
+		/// The para already has a lease for some of the periods.
+		AlreadyLeased,
 	}
 
 	#[pallet::hooks]
@@ -208,6 +289,104 @@ pub mod pallet {
 			};
 			Ok(())
 		}
+
+		/// Offer the lease periods `period_begin..period_begin + period_count` of `para` to
+		/// `new_para`, for `price`. The periods may be all the remaining ones of the lease, or
+		/// only some of them, splitting the lease. Any previous offer to `new_para` is replaced.
+		///
+		/// Origin must be Signed by the account holding the deposit for all of the periods, none
+		/// of which may have begun. The registration of `para` must not be locked.
+		#[pallet::weight(T::WeightInfo::offer_lease())]
+		pub fn offer_lease(
+			origin: OriginFor<T>,
+			para: ParaId,
+			new_para: ParaId,
+			period_begin: LeasePeriodOf<T>,
+			period_count: LeasePeriodOf<T>,
+			price: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(para != new_para, Error::<T>::SamePara);
+			ensure!(!T::Registrar::is_locked(para), Error::<T>::ParaLocked);
+			ensure!(T::Registrar::is_registered(new_para), Error::<T>::NotRegistered);
+			Self::ensure_lease_holder(para, &who, period_begin, period_count)?;
+
+			LeaseOffers::<T>::insert(
+				para,
+				new_para,
+				LeaseOffer { leaser: who.clone(), period_begin, period_count, price },
+			);
+			Self::deposit_event(Event::<T>::LeaseOffered(
+				para,
+				new_para,
+				who,
+				period_begin,
+				period_count,
+				price,
+			));
+			Ok(())
+		}
+
+		/// Withdraw an offer of the lease periods of `para` to `new_para`.
+		///
+		/// Origin must be Signed by the account which made the offer.
+		#[pallet::weight(T::WeightInfo::cancel_lease_offer())]
+		pub fn cancel_lease_offer(
+			origin: OriginFor<T>,
+			para: ParaId,
+			new_para: ParaId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let offer = LeaseOffers::<T>::get(para, new_para).ok_or(Error::<T>::NoLeaseOffer)?;
+			ensure!(offer.leaser == who, Error::<T>::NotLeaseHolder);
+
+			LeaseOffers::<T>::remove(para, new_para);
+			Self::deposit_event(Event::<T>::LeaseOfferCancelled(para, new_para));
+			Ok(())
+		}
+
+		/// Accept the offer of the lease periods of `para` to `new_para`: the price is paid to
+		/// the leaser, and the deposit for the periods is reserved from the caller, who holds
+		/// them for `new_para` from now on. The deposit of the leaser is released as far as it is
+		/// no longer needed.
+		///
+		/// Origin must be Signed by the manager of `new_para`, whose registration must not be
+		/// locked.
+		#[pallet::weight(T::WeightInfo::accept_lease_offer())]
+		#[transactional]
+		pub fn accept_lease_offer(
+			origin: OriginFor<T>,
+			para: ParaId,
+			new_para: ParaId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let offer = LeaseOffers::<T>::take(para, new_para).ok_or(Error::<T>::NoLeaseOffer)?;
+			ensure!(
+				T::Registrar::manager_of(new_para).as_ref() == Some(&who),
+				Error::<T>::NotParaManager
+			);
+			ensure!(!T::Registrar::is_locked(new_para), Error::<T>::ParaLocked);
+
+			T::Currency::transfer(&who, &offer.leaser, offer.price, KeepAlive)?;
+			Self::transfer_lease(
+				para,
+				new_para,
+				&offer.leaser,
+				&who,
+				offer.period_begin,
+				offer.period_count,
+			)?;
+
+			Self::deposit_event(Event::<T>::LeaseTransferred(
+				para,
+				new_para,
+				offer.leaser,
+				who,
+				offer.period_begin,
+				offer.period_count,
+			));
+			Ok(())
+		}
 	}
 }
 
@@ -293,6 +472,87 @@ impl<T: Config> Pallet<T> {
 		)
 	}
 
+	/// Ensure `leaser` holds the deposit for all of the lease periods
+	/// `period_begin..period_begin + period_count` of `para`, none of which has begun.
+	///
+	/// Returns the offset of `period_begin` into the lease periods of `para`.
+	fn ensure_lease_holder(
+		para: ParaId,
+		leaser: &T::AccountId,
+		period_begin: LeasePeriodOf<T>,
+		period_count: LeasePeriodOf<T>,
+	) -> Result<usize, DispatchError> {
+		let current_lease_period = Self::lease_period_index();
+		ensure!(period_begin > current_lease_period, Error::<T>::NotLeaseHolder);
+		let offset = (period_begin - current_lease_period)
+			.checked_into::<usize>()
+			.ok_or(Error::<T>::NotLeaseHolder)?;
+		let period_count = period_count.checked_into::<usize>().ok_or(Error::<T>::LeaseError)?;
+
+		let leases = Leases::<T>::get(para);
+		let held = period_count > 0 &&
+			(offset..offset.saturating_add(period_count)).all(|i| match leases.get(i) {
+				Some(Some((who, _))) => who == leaser,
+				_ => false,
+			});
+		ensure!(held, Error::<T>::NotLeaseHolder);
+
+		Ok(offset)
+	}
+
+	/// Move the lease periods `period_begin..period_begin + period_count` of `para`, held by
+	/// `leaser`, to `new_para` where they are held by `new_leaser`.
+	///
+	/// The deposit for the periods is reserved from `new_leaser`, and the deposit of `leaser` for
+	/// `para` is reduced to what its remaining periods need.
+	fn transfer_lease(
+		para: ParaId,
+		new_para: ParaId,
+		leaser: &T::AccountId,
+		new_leaser: &T::AccountId,
+		period_begin: LeasePeriodOf<T>,
+		period_count: LeasePeriodOf<T>,
+	) -> DispatchResult {
+		let offset = Self::ensure_lease_holder(para, leaser, period_begin, period_count)?;
+		let end = offset + period_count.checked_into::<usize>().ok_or(Error::<T>::LeaseError)?;
+
+		let held_before = Self::deposit_held(para, leaser);
+		let new_held_before = Self::deposit_held(new_para, new_leaser);
+
+		let mut leases = Leases::<T>::get(para);
+		let mut new_leases = Leases::<T>::get(new_para);
+		if new_leases.len() < end {
+			new_leases.resize_with(end, || None);
+		}
+		for i in offset..end {
+			ensure!(new_leases[i].is_none(), Error::<T>::AlreadyLeased);
+			new_leases[i] = leases[i].take().map(|(_, amount)| (new_leaser.clone(), amount));
+		}
+		// It is illegal for a `None` value to trail in the list.
+		while let Some(None) = leases.last() {
+			leases.pop();
+		}
+
+		if leases.is_empty() {
+			Leases::<T>::remove(para);
+		} else {
+			Leases::<T>::insert(para, &leases);
+		}
+		Leases::<T>::insert(new_para, &new_leases);
+
+		if let Some(rebate) = held_before.checked_sub(&Self::deposit_held(para, leaser)) {
+			let err_amount = T::Currency::unreserve(leaser, rebate);
+			debug_assert!(err_amount.is_zero());
+		}
+		if let Some(additional) =
+			Self::deposit_held(new_para, new_leaser).checked_sub(&new_held_before)
+		{
+			T::Currency::reserve(new_leaser, additional)?;
+		}
+
+		Ok(())
+	}
+
 	// Return a vector of (user, balance) for all deposits for a parachain.
 	// Useful when trying to clean up a parachain leases, as this would tell
 	// you all the balances you need to unreserve.
@@ -913,6 +1173,125 @@ mod tests {
 			assert_eq!(TestRegistrar::<Test>::operations(), vec![(2.into(), 1, true),]);
 		});
 	}
+
+	#[test]
+	fn lease_offer_can_be_accepted() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			assert_ok!(TestRegistrar::<Test>::register(
+				1,
+				ParaId::from(1),
+				Default::default(),
+				Default::default()
+			));
+			assert_ok!(TestRegistrar::<Test>::register(
+				2,
+				ParaId::from(2),
+				Default::default(),
+				Default::default()
+			));
+			assert_ok!(Slots::lease_out(1.into(), &1, 6, 1, 3));
+
+			// Only the leaser can offer periods it holds which have not begun.
+			assert_noop!(
+				Slots::offer_lease(Origin::signed(2), 1.into(), 2.into(), 2, 2, 5),
+				Error::<Test>::NotLeaseHolder
+			);
+			assert_noop!(
+				Slots::offer_lease(Origin::signed(1), 1.into(), 2.into(), 3, 2, 5),
+				Error::<Test>::NotLeaseHolder
+			);
+			assert_noop!(
+				Slots::offer_lease(Origin::signed(1), 1.into(), 2.into(), 0, 2, 5),
+				Error::<Test>::NotLeaseHolder
+			);
+			assert_noop!(
+				Slots::offer_lease(Origin::signed(1), 1.into(), 1.into(), 2, 2, 5),
+				Error::<Test>::SamePara
+			);
+
+			// Split off the last two periods of the lease.
+			assert_ok!(Slots::offer_lease(Origin::signed(1), 1.into(), 2.into(), 2, 2, 5));
+			assert_eq!(
+				Slots::lease_offer(ParaId::from(1), ParaId::from(2)),
+				Some(LeaseOffer { leaser: 1, period_begin: 2, period_count: 2, price: 5 })
+			);
+
+			// Only the manager of the new para can accept.
+			assert_noop!(
+				Slots::accept_lease_offer(Origin::signed(3), 1.into(), 2.into()),
+				Error::<Test>::NotParaManager
+			);
+			assert_ok!(Slots::accept_lease_offer(Origin::signed(2), 1.into(), 2.into()));
+			assert_eq!(
+				System::events().pop().unwrap().event,
+				slots::Event::<Test>::LeaseTransferred(1.into(), 2.into(), 1, 2, 2, 2).into()
+			);
+			assert_eq!(Slots::lease_offer(ParaId::from(1), ParaId::from(2)), None);
+
+			assert_eq!(Slots::lease(ParaId::from(1)), vec![None, Some((1, 6))]);
+			assert_eq!(Slots::lease(ParaId::from(2)), vec![None, None, Some((2, 6)), Some((2, 6))]);
+			assert_eq!(Balances::reserved_balance(1), 6);
+			assert_eq!(Balances::free_balance(1), 10 - 6 + 5);
+			assert_eq!(Balances::reserved_balance(2), 6);
+			assert_eq!(Balances::free_balance(2), 20 - 6 - 5);
+
+			run_to_block(20);
+			assert_eq!(Balances::reserved_balance(1), 0);
+			assert_eq!(Balances::reserved_balance(2), 6);
+			assert_eq!(
+				TestRegistrar::<Test>::operations(),
+				vec![(1.into(), 10, true), (2.into(), 20, true), (1.into(), 20, false)]
+			);
+		});
+	}
+
+	#[test]
+	fn lease_offers_respect_registration_locks() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			assert_ok!(TestRegistrar::<Test>::register(
+				1,
+				ParaId::from(1),
+				Default::default(),
+				Default::default()
+			));
+			assert_ok!(TestRegistrar::<Test>::register(
+				2,
+				ParaId::from(2),
+				Default::default(),
+				Default::default()
+			));
+			assert_ok!(Slots::lease_out(1.into(), &1, 6, 1, 3));
+
+			TestRegistrar::<Test>::apply_lock(1.into());
+			assert_noop!(
+				Slots::offer_lease(Origin::signed(1), 1.into(), 2.into(), 1, 3, 5),
+				Error::<Test>::ParaLocked
+			);
+			TestRegistrar::<Test>::remove_lock(1.into());
+			assert_ok!(Slots::offer_lease(Origin::signed(1), 1.into(), 2.into(), 1, 3, 5));
+
+			TestRegistrar::<Test>::apply_lock(2.into());
+			assert_noop!(
+				Slots::accept_lease_offer(Origin::signed(2), 1.into(), 2.into()),
+				Error::<Test>::ParaLocked
+			);
+
+			// Only the leaser can withdraw the offer.
+			assert_noop!(
+				Slots::cancel_lease_offer(Origin::signed(2), 1.into(), 2.into()),
+				Error::<Test>::NotLeaseHolder
+			);
+			assert_ok!(Slots::cancel_lease_offer(Origin::signed(1), 1.into(), 2.into()));
+			assert_noop!(
+				Slots::accept_lease_offer(Origin::signed(2), 1.into(), 2.into()),
+				Error::<Test>::NoLeaseOffer
+			);
+		});
+	}
 }
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -924,7 +1303,7 @@ mod benchmarking {
 
 	use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
 
-	use crate::slots::Pallet as Slots;
+	use crate::{slot_range::SlotRange, slots::Pallet as Slots};
 
 	fn assert_last_event<T: Config>(generic_event: <T as Config>::Event) {
 		let events = frame_system::Pallet::<T>::events();
@@ -1048,6 +1427,55 @@ mod benchmarking {
 			}
 		}
 
+		// Worst case scenario: the offered periods are the later half of a lease spanning two
+		// slots.
+		offer_lease {
+			let (para, leaser) = register_a_parathread::<T>(1);
+			let (new_para, _) = register_a_parathread::<T>(2);
+			let amount = T::Currency::minimum_balance();
+			let period_count: LeasePeriodOf<T> = (SlotRange::LEASE_PERIODS_PER_SLOT as u32).into();
+			let period_begin = Slots::<T>::lease_period_index() + 1u32.into();
+			Slots::<T>::force_lease(RawOrigin::Root.into(), para, leaser.clone(), amount, period_begin, period_count * 2u32.into())?;
+			let offer_begin = period_begin + period_count;
+		}: _(RawOrigin::Signed(leaser.clone()), para, new_para, offer_begin, period_count, amount)
+		verify {
+			assert_last_event::<T>(Event::<T>::LeaseOffered(para, new_para, leaser, offer_begin, period_count, amount).into());
+		}
+
+		cancel_lease_offer {
+			let (para, leaser) = register_a_parathread::<T>(1);
+			let (new_para, _) = register_a_parathread::<T>(2);
+			let amount = T::Currency::minimum_balance();
+			let period_begin = Slots::<T>::lease_period_index() + 1u32.into();
+			let period_count = 4u32.into();
+			Slots::<T>::force_lease(RawOrigin::Root.into(), para, leaser.clone(), amount, period_begin, period_count)?;
+			Slots::<T>::offer_lease(RawOrigin::Signed(leaser.clone()).into(), para, new_para, period_begin, period_count, amount)?;
+		}: _(RawOrigin::Signed(leaser), para, new_para)
+		verify {
+			assert_last_event::<T>(Event::<T>::LeaseOfferCancelled(para, new_para).into());
+		}
+
+		// Worst case scenario: the offered periods are the later half of a lease spanning two
+		// slots and need a larger deposit than the rest, so the leaser keeps the earlier half and
+		// gets a rebate. The new para already holds a lease before the offered periods, for which
+		// its leaser has to add to the deposit.
+		accept_lease_offer {
+			let (para, leaser) = register_a_parathread::<T>(1);
+			let (new_para, new_leaser) = register_a_parathread::<T>(2);
+			let amount = T::Currency::minimum_balance();
+			let period_count: LeasePeriodOf<T> = (SlotRange::LEASE_PERIODS_PER_SLOT as u32).into();
+			let period_begin = Slots::<T>::lease_period_index() + 1u32.into();
+			let offer_begin = period_begin + period_count;
+			Slots::<T>::force_lease(RawOrigin::Root.into(), para, leaser.clone(), amount, period_begin, period_count)?;
+			Slots::<T>::force_lease(RawOrigin::Root.into(), para, leaser.clone(), amount * 2u32.into(), offer_begin, period_count)?;
+			Slots::<T>::force_lease(RawOrigin::Root.into(), new_para, new_leaser.clone(), amount, period_begin, period_count)?;
+			Slots::<T>::offer_lease(RawOrigin::Signed(leaser.clone()).into(), para, new_para, offer_begin, period_count, amount)?;
+		}: _(RawOrigin::Signed(new_leaser.clone()), para, new_para)
+		verify {
+			assert_last_event::<T>(Event::<T>::LeaseTransferred(para, new_para, leaser, new_leaser, offer_begin, period_count).into());
+			assert!(!Leases::<T>::get(para).is_empty());
+		}
+
 		trigger_onboard {
 			// get a parachain into a bad state where they did not onboard
 			let (para, _) = register_a_parathread::<T>(1);
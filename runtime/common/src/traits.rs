@@ -55,6 +55,9 @@ pub trait Registrar {
 	/// Remove any lock on the para registration.
 	fn remove_lock(id: ParaId);
 
+	/// Return if the para registration is locked from being modified by the manager directly.
+	fn is_locked(id: ParaId) -> bool;
+
 	/// Register a Para ID under control of `who`. Registration may be be
 	/// delayed by session rotation.
 	fn register(
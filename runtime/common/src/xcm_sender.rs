@@ -16,33 +16,440 @@
 
 //! XCM sender for relay chain.
 
+use frame_support::traits::{Contains, Get};
 use parity_scale_codec::Encode;
+use primitives::v1::Id as ParaId;
 use runtime_parachains::{configuration, dmp};
-use sp_std::marker::PhantomData;
+use sp_std::{marker::PhantomData, prelude::*};
 use xcm::latest::prelude::*;
+use xcm_builder::TakeRevenue;
+use xcm_executor::traits::TransactAsset;
 
-/// XCM sender for relay chain. It only sends downward message.
-pub struct ChildParachainRouter<T, W>(PhantomData<(T, W)>);
+pub mod runtime_api;
 
-impl<T: configuration::Config + dmp::Config, W: xcm::WrapVersion> SendXcm
-	for ChildParachainRouter<T, W>
+/// The price of delivering downward messages to child parachains.
+pub trait PriceForParachainDelivery {
+	/// The fees for delivering the encoded `message` to `para`.
+	fn price_for_parachain_delivery(para: ParaId, message: &[u8]) -> MultiAssets;
+}
+
+/// Delivery is free.
+impl PriceForParachainDelivery for () {
+	fn price_for_parachain_delivery(_: ParaId, _: &[u8]) -> MultiAssets {
+		MultiAssets::new()
+	}
+}
+
+/// Prices delivery at `BaseFee` plus `ByteFee` for each byte of the message, paid in the asset
+/// `A`.
+///
+/// The price doubles with every quarter of the recipient's downward queue that is in use, see
+/// [`congestion_level`], so filling up a congested queue gets increasingly expensive.
+pub struct CongestionPrice<T, A, BaseFee, ByteFee>(PhantomData<(T, A, BaseFee, ByteFee)>);
+impl<T, A, BaseFee, ByteFee> PriceForParachainDelivery for CongestionPrice<T, A, BaseFee, ByteFee>
+where
+	T: dmp::Config,
+	A: Get<AssetId>,
+	BaseFee: Get<u128>,
+	ByteFee: Get<u128>,
 {
-	fn send_xcm(dest: MultiLocation, msg: Xcm<()>) -> SendResult {
+	fn price_for_parachain_delivery(para: ParaId, message: &[u8]) -> MultiAssets {
+		let fee =
+			BaseFee::get().saturating_add(ByteFee::get().saturating_mul(message.len() as u128));
+		let fee = fee.saturating_mul(1 << congestion_level::<T>(para));
+		(A::get(), fee).into()
+	}
+}
+
+/// The number of quarters of the downward queue of `para` that are in use, from 0 for a queue
+/// less than a quarter full up to 4 for a full one.
+pub fn congestion_level<T: dmp::Config>(para: ParaId) -> u32 {
+	let max_size = T::MaxDownwardQueueSize::get().max(1) as u64;
+	let total_size = <dmp::Pallet<T>>::dmq_total_size(para) as u64;
+	(total_size * 4 / max_size).min(4) as u32
+}
+
+/// Charges the fees for delivering a message to its sender.
+pub trait ChargeDeliveryFees {
+	/// Charge `fees` to `sender`, failing if it cannot pay them.
+	fn charge_delivery_fees(sender: &MultiLocation, fees: MultiAssets) -> Result<(), SendError>;
+}
+
+/// Nothing is charged.
+impl ChargeDeliveryFees for () {
+	fn charge_delivery_fees(_: &MultiLocation, _: MultiAssets) -> Result<(), SendError> {
+		Ok(())
+	}
+}
+
+/// Withdraws delivery fees from the sender with `AssetTransactor` and passes them on to
+/// `Revenue`. Senders matching `Exempt` don't pay any fees.
+///
+/// Either all of the fees are withdrawn or none of them.
+pub struct WithdrawDeliveryFees<AssetTransactor, Revenue, Exempt = LocalChainAndBodies>(
+	PhantomData<(AssetTransactor, Revenue, Exempt)>,
+);
+impl<AssetTransactor, Revenue, Exempt> ChargeDeliveryFees
+	for WithdrawDeliveryFees<AssetTransactor, Revenue, Exempt>
+where
+	AssetTransactor: TransactAsset,
+	Revenue: TakeRevenue,
+	Exempt: Contains<MultiLocation>,
+{
+	fn charge_delivery_fees(sender: &MultiLocation, fees: MultiAssets) -> Result<(), SendError> {
+		if Exempt::contains(sender) {
+			return Ok(())
+		}
+		let fees = fees.drain();
+		for (i, fee) in fees.iter().enumerate() {
+			if AssetTransactor::withdraw_asset(fee, sender).is_err() {
+				// Give back whatever was already withdrawn.
+				for fee in &fees[..i] {
+					let _ = AssetTransactor::deposit_asset(fee, sender);
+				}
+				return Err(SendError::Transport("cannot pay delivery fees"))
+			}
+		}
+		fees.into_iter().for_each(Revenue::take_revenue);
+		Ok(())
+	}
+}
+
+/// The chain itself and its collective bodies, which send messages on behalf of the chain.
+pub struct LocalChainAndBodies;
+impl Contains<MultiLocation> for LocalChainAndBodies {
+	fn contains(location: &MultiLocation) -> bool {
+		matches!(location, MultiLocation { parents: 0, interior: Here | X1(Plurality { .. }) })
+	}
+}
+
+/// XCM sender for relay chain. It only sends downward messages.
+///
+/// Messages are wrapped in the XCM version chosen by `W`. A message that doesn't fit into the
+/// downward queue of the recipient, either because it exceeds `max_downward_message_size` or
+/// because the queue is congested, is rejected with an error before anything is charged or
+/// enqueued. Otherwise, messages sent on behalf of an origin are charged the fees priced by `P`
+/// to that origin with `F`, while those the chain sends on its own behalf are free.
+pub struct ChildParachainRouter<T, W, P = (), F = ()>(PhantomData<(T, W, P, F)>);
+
+impl<T, W, P, F> ChildParachainRouter<T, W, P, F>
+where
+	T: configuration::Config + dmp::Config,
+	W: xcm::WrapVersion,
+	P: PriceForParachainDelivery,
+	F: ChargeDeliveryFees,
+{
+	fn send_downward(
+		dest: MultiLocation,
+		msg: Xcm<()>,
+		payer: Option<&MultiLocation>,
+	) -> SendResult {
 		match dest {
 			MultiLocation { parents: 0, interior: X1(Parachain(id)) } => {
 				// Downward message passing.
 				let versioned_xcm =
 					W::wrap_version(&dest, msg).map_err(|()| SendError::DestinationUnsupported)?;
+				let blob = versioned_xcm.encode();
 				let config = <configuration::Pallet<T>>::config();
-				<dmp::Pallet<T>>::queue_downward_message(
-					&config,
-					id.into(),
-					versioned_xcm.encode(),
-				)
-				.map_err(Into::<SendError>::into)?;
+				let para = ParaId::from(id);
+				<dmp::Pallet<T>>::can_queue_downward_message(&config, para, blob.len() as u32)
+					.map_err(Into::<SendError>::into)?;
+				if let Some(payer) = payer {
+					F::charge_delivery_fees(payer, P::price_for_parachain_delivery(para, &blob))?;
+				}
+				<dmp::Pallet<T>>::queue_downward_message(&config, para, blob)
+					.map_err(Into::<SendError>::into)?;
 				Ok(())
 			},
 			dest => Err(SendError::CannotReachDestination(dest, msg)),
 		}
 	}
 }
+
+impl<T, W, P, F> SendXcm for ChildParachainRouter<T, W, P, F>
+where
+	T: configuration::Config + dmp::Config,
+	W: xcm::WrapVersion,
+	P: PriceForParachainDelivery,
+	F: ChargeDeliveryFees,
+{
+	fn send_xcm(dest: MultiLocation, msg: Xcm<()>) -> SendResult {
+		Self::send_downward(dest, msg, None)
+	}
+
+	fn send_xcm_from(origin: &MultiLocation, dest: MultiLocation, msg: Xcm<()>) -> SendResult {
+		Self::send_downward(dest, msg, Some(origin))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::{assert_noop, assert_ok, parameter_types, traits::GenesisBuild};
+	use frame_system::limits;
+	use primitives::v1::{AccountId, Balance, BlockNumber, Header};
+	use runtime_parachains::shared;
+	use sp_core::H256;
+	use sp_io::TestExternalities;
+	use sp_runtime::traits::{BlakeTwo256, IdentityLookup};
+	use std::cell::RefCell;
+	use xcm::VersionedXcm;
+	use xcm_builder::{AccountId32Aliases, CurrencyAdapter, IsConcrete};
+
+	type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+	type Block = frame_system::mocking::MockBlock<Test>;
+
+	frame_support::construct_runtime!(
+		pub enum Test where
+			Block = Block,
+			NodeBlock = Block,
+			UncheckedExtrinsic = UncheckedExtrinsic,
+		{
+			System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+			Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+			Configuration: configuration::{Pallet, Call, Storage, Config<T>},
+			Dmp: dmp::{Pallet, Call, Storage},
+		}
+	);
+
+	parameter_types! {
+		pub const BlockHashCount: u32 = 250;
+		pub BlockWeights: limits::BlockWeights =
+			frame_system::limits::BlockWeights::simple_max(1024);
+	}
+
+	impl frame_system::Config for Test {
+		type BaseCallFilter = frame_support::traits::Everything;
+		type Origin = Origin;
+		type Call = Call;
+		type Index = u64;
+		type BlockNumber = BlockNumber;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = AccountId;
+		type Lookup = IdentityLookup<AccountId>;
+		type Header = Header;
+		type Event = Event;
+		type BlockHashCount = BlockHashCount;
+		type DbWeight = ();
+		type BlockWeights = BlockWeights;
+		type BlockLength = ();
+		type Version = ();
+		type PalletInfo = PalletInfo;
+		type AccountData = pallet_balances::AccountData<u128>;
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type SystemWeightInfo = ();
+		type SS58Prefix = ();
+		type OnSetCode = ();
+	}
+
+	parameter_types! {
+		pub const ExistentialDeposit: Balance = 1;
+	}
+
+	impl pallet_balances::Config for Test {
+		type Balance = u128;
+		type DustRemoval = ();
+		type Event = Event;
+		type ExistentialDeposit = ExistentialDeposit;
+		type AccountStore = System;
+		type MaxLocks = ();
+		type MaxReserves = ();
+		type ReserveIdentifier = [u8; 8];
+		type WeightInfo = ();
+	}
+
+	impl shared::Config for Test {}
+
+	impl configuration::Config for Test {
+		type WeightInfo = configuration::weights::WeightInfo<Test>;
+	}
+
+	parameter_types! {
+		pub const MaxDownwardQueueSize: u32 = 400;
+	}
+
+	impl dmp::Config for Test {
+		type MaxDownwardQueueSize = MaxDownwardQueueSize;
+	}
+
+	parameter_types! {
+		pub const AnyNetwork: NetworkId = NetworkId::Any;
+		pub NativeLocation: MultiLocation = MultiLocation::here();
+		pub NativeAsset: AssetId = Concrete(MultiLocation::here());
+		pub const BaseFee: u128 = 100;
+		pub const ByteFee: u128 = 1;
+	}
+
+	type LocalAssetTransactor = CurrencyAdapter<
+		Balances,
+		IsConcrete<NativeLocation>,
+		AccountId32Aliases<AnyNetwork, AccountId>,
+		AccountId,
+		(),
+	>;
+
+	thread_local! {
+		static REVENUE: RefCell<Vec<MultiAsset>> = RefCell::new(Vec::new());
+	}
+
+	pub struct RecordRevenue;
+	impl TakeRevenue for RecordRevenue {
+		fn take_revenue(revenue: MultiAsset) {
+			REVENUE.with(|r| r.borrow_mut().push(revenue));
+		}
+	}
+
+	type Price = CongestionPrice<Test, NativeAsset, BaseFee, ByteFee>;
+	type Router = ChildParachainRouter<
+		Test,
+		(),
+		Price,
+		WithdrawDeliveryFees<LocalAssetTransactor, RecordRevenue>,
+	>;
+
+	const ALICE: [u8; 32] = [1; 32];
+	const BOB: [u8; 32] = [2; 32];
+
+	fn new_test_ext() -> TestExternalities {
+		let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+		GenesisBuild::<Test>::assimilate_storage(
+			&configuration::GenesisConfig {
+				config: configuration::HostConfiguration {
+					max_downward_message_size: 100,
+					..Default::default()
+				},
+			},
+			&mut t,
+		)
+		.unwrap();
+
+		pallet_balances::GenesisConfig::<Test> {
+			balances: vec![(ALICE.into(), 10_000), (BOB.into(), 10)],
+		}
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+		t.into()
+	}
+
+	fn para() -> MultiLocation {
+		Parachain(2000).into()
+	}
+
+	fn account(id: [u8; 32]) -> MultiLocation {
+		AccountId32 { network: NetworkId::Any, id }.into()
+	}
+
+	/// A message carrying `len` bytes of call data.
+	fn message(len: usize) -> Xcm<()> {
+		Xcm(vec![Transact {
+			origin_type: OriginKind::SovereignAccount,
+			require_weight_at_most: 0,
+			call: vec![0u8; len].into(),
+		}])
+	}
+
+	fn delivery_fee(message: &Xcm<()>) -> u128 {
+		BaseFee::get() + VersionedXcm::from(message.clone()).encode().len() as u128
+	}
+
+	fn free_balance(id: [u8; 32]) -> u128 {
+		Balances::free_balance(&AccountId::from(id))
+	}
+
+	#[test]
+	fn delivery_is_charged_to_the_sending_origin() {
+		new_test_ext().execute_with(|| {
+			let fee = delivery_fee(&message(10));
+
+			assert_ok!(Router::send_xcm_from(&account(ALICE), para(), message(10)));
+			assert_eq!(free_balance(ALICE), 10_000 - fee);
+			assert_eq!(
+				REVENUE.with(|r| r.borrow().clone()),
+				vec![MultiAsset::from((NativeAsset::get(), fee))]
+			);
+			assert!(Dmp::dmq_total_size(2000.into()) > 0);
+
+			// The payer doesn't depend on the content of the message.
+			let spoofed =
+				Xcm(vec![DescendOrigin(X1(AccountId32 { network: NetworkId::Any, id: BOB }))]);
+			assert_ok!(Router::send_xcm_from(&account(ALICE), para(), spoofed.clone()));
+			assert_eq!(free_balance(ALICE), 10_000 - fee - delivery_fee(&spoofed));
+			assert_eq!(free_balance(BOB), 10);
+		});
+	}
+
+	#[test]
+	fn messages_of_the_chain_are_delivered_for_free() {
+		new_test_ext().execute_with(|| {
+			let council =
+				MultiLocation::from(Plurality { id: BodyId::Executive, part: BodyPart::Voice });
+			for origin in vec![MultiLocation::here(), council] {
+				let total_size = Dmp::dmq_total_size(2000.into());
+				assert_ok!(Router::send_xcm_from(&origin, para(), message(10)));
+				assert!(Dmp::dmq_total_size(2000.into()) > total_size);
+			}
+
+			let total_size = Dmp::dmq_total_size(2000.into());
+			assert_ok!(Router::send_xcm(para(), message(10)));
+			assert!(Dmp::dmq_total_size(2000.into()) > total_size);
+			assert!(REVENUE.with(|r| r.borrow().is_empty()));
+		});
+	}
+
+	#[test]
+	fn undeliverable_messages_are_rejected_before_charging() {
+		new_test_ext().execute_with(|| {
+			// Bob cannot pay the fees.
+			assert_noop!(
+				Router::send_xcm_from(&account(BOB), para(), message(10)),
+				SendError::Transport("cannot pay delivery fees"),
+			);
+
+			// The message doesn't fit into the downward queue.
+			assert_noop!(
+				Router::send_xcm_from(&account(ALICE), para(), message(100)),
+				SendError::ExceedsMaxMessageSize,
+			);
+
+			// Only child parachains are reachable.
+			assert_eq!(
+				Router::send_xcm_from(&account(ALICE), MultiLocation::parent(), message(10)),
+				Err(SendError::CannotReachDestination(MultiLocation::parent(), message(10))),
+			);
+			assert_eq!(free_balance(ALICE), 10_000);
+			assert!(REVENUE.with(|r| r.borrow().is_empty()));
+		});
+	}
+
+	#[test]
+	fn price_grows_with_congestion() {
+		new_test_ext().execute_with(|| {
+			let para_id = ParaId::from(2000);
+			let config = Configuration::config();
+			let price = || Price::price_for_parachain_delivery(para_id, &[0u8; 10]);
+
+			assert_eq!(congestion_level::<Test>(para_id), 0);
+			assert_eq!(price(), MultiAssets::from((NativeAsset::get(), 110)));
+
+			// A quarter of the queue is in use.
+			assert!(Dmp::queue_downward_message(&config, para_id, vec![0u8; 100]).is_ok());
+			assert_eq!(congestion_level::<Test>(para_id), 1);
+			assert_eq!(price(), MultiAssets::from((NativeAsset::get(), 220)));
+
+			// The queue is full.
+			for _ in 0..3 {
+				assert!(Dmp::queue_downward_message(&config, para_id, vec![0u8; 100]).is_ok());
+			}
+			assert_eq!(congestion_level::<Test>(para_id), 4);
+			assert_eq!(price(), MultiAssets::from((NativeAsset::get(), 1760)));
+			assert_noop!(
+				Router::send_xcm_from(&account(ALICE), para(), message(10)),
+				SendError::Transport("downward message queue is full"),
+			);
+		});
+	}
+}
@@ -0,0 +1,35 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API for inspecting the congestion of the downward queues and the price of delivering
+//! messages to child parachains.
+
+use primitives::v1::Id as ParaId;
+use sp_std::prelude::*;
+use xcm::VersionedMultiAssets;
+
+sp_api::decl_runtime_apis! {
+	/// The API for inspecting the delivery of downward messages before sending them.
+	pub trait XcmSenderApi {
+		/// Get the number of quarters of the downward queue of the given para that are in use,
+		/// from 0 for a queue less than a quarter full up to 4 for a full one.
+		fn downward_congestion_level(recipient: ParaId) -> u32;
+
+		/// Get the fees for delivering the encoded, versioned XCM `message` to the given para on
+		/// behalf of an account.
+		fn downward_delivery_fees(recipient: ParaId, message: Vec<u8>) -> VersionedMultiAssets;
+	}
+}
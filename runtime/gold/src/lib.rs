@@ -20,18 +20,20 @@
 // `construct_runtime!` does a lot of recursion and requires us to increase the limit to 256.
 #![recursion_limit = "256"]
 
-use pallet_transaction_payment::CurrencyAdapter;
 use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
 use primitives::v1::{
 	AccountId, AccountIndex, Balance, BlockNumber, CandidateEvent, CommittedCandidateReceipt,
-	CoreState, GroupRotationInfo, Hash, Id as ParaId, InboundDownwardMessage, InboundHrmpMessage,
-	Moment, Nonce, OccupiedCoreAssumption, PersistedValidationData, SessionInfo, Signature,
-	ValidationCode, ValidationCodeHash, ValidatorId, ValidatorIndex,
+	CoreIndex, CoreState, GroupRotationInfo, Hash, HrmpParaState, Id as ParaId,
+	InboundDownwardMessage, InboundHrmpMessage, Moment, Nonce, OccupiedCoreAssumption,
+	PersistedValidationData, SessionInfo, Signature, ValidationCode, ValidationCodeHash,
+	ValidatorId, ValidatorIndex,
 };
 use runtime_common::{
-	auctions, claims, crowdloan, impls::DealWithFees, paras_registrar, slots, xcm_sender,
-	BlockHashCount, BlockLength, BlockWeights, CurrencyToVote, OffchainSolutionLengthLimit,
-	OffchainSolutionWeightLimit, RocksDbWeight, SlowAdjustingFeeUpdate, ToAuthor,
+	auctions, claims, crowdloan,
+	impls::{SplitFeesAdapter, SplitRevenue, TreasuryAndAuthor},
+	paras_registrar, slots, xcm_sender, BlockHashCount, BlockLength, BlockWeights, CurrencyToVote,
+	OffchainSolutionLengthLimit, OffchainSolutionWeightLimit, RocksDbWeight,
+	SlowAdjustingFeeUpdate,
 };
 use sp_core::u32_trait::{_1, _2, _3, _5};
 use sp_std::{collections::btree_map::BTreeMap, prelude::*};
@@ -80,7 +82,7 @@ use sp_version::RuntimeVersion;
 use static_assertions::const_assert;
 use xcm::latest::prelude::*;
 use xcm_builder::{
-	AccountId32Aliases, AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom,
+	Account32Hash, AccountId32Aliases, AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom,
 	BackingToPlurality, ChildParachainAsNative, ChildParachainConvertsVia,
 	ChildSystemParachainAsSuperuser, CurrencyAdapter as XcmCurrencyAdapter, FixedWeightBounds,
 	IsChildSystemParachain, IsConcrete, LocationInverter, SignedAccountId32AsNative,
@@ -118,13 +120,13 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("gold"),
 	impl_name: create_runtime_str!("parity-gold"),
 	authoring_version: 2,
-	spec_version: 9100,
+	spec_version: 9110,
 	impl_version: 0,
 	#[cfg(not(feature = "disable-runtime-api"))]
 	apis: RUNTIME_API_VERSIONS,
 	#[cfg(feature = "disable-runtime-api")]
 	apis: version::create_apis_vec![[]],
-	transaction_version: 5,
+	transaction_version: 6,
 };
 
 /// The BABE epoch configuration at genesis.
@@ -277,7 +279,7 @@ parameter_types! {
 }
 
 impl pallet_transaction_payment::Config for Runtime {
-	type OnChargeTransaction = CurrencyAdapter<Balances, DealWithFees<Self>>;
+	type OnChargeTransaction = SplitFeesAdapter<Self, TreasuryAndAuthor>;
 	type TransactionByteFee = TransactionByteFee;
 	type WeightToFee = WeightToFee;
 	type FeeMultiplierUpdate = SlowAdjustingFeeUpdate<Self>;
@@ -1132,7 +1134,13 @@ impl parachains_configuration::Config for Runtime {
 
 impl parachains_shared::Config for Runtime {}
 
-impl parachains_session_info::Config for Runtime {}
+parameter_types! {
+	pub const SessionInfoRetentionPeriod: SessionIndex = 24;
+}
+
+impl parachains_session_info::Config for Runtime {
+	type RetentionPeriod = SessionInfoRetentionPeriod;
+}
 
 impl parachains_inclusion::Config for Runtime {
 	type Event = Event;
@@ -1140,13 +1148,20 @@ impl parachains_inclusion::Config for Runtime {
 	type RewardValidators = parachains_reward_points::RewardValidatorsWithEraPoints<Runtime>;
 }
 
+parameter_types! {
+	pub const DowngradeGracePeriod: SessionIndex = 24;
+}
+
 impl parachains_paras::Config for Runtime {
 	type Origin = Origin;
 	type Event = Event;
+	type DowngradeGracePeriod = DowngradeGracePeriod;
 }
 
 parameter_types! {
 	pub const FirstMessageFactorPercent: u64 = 100;
+	pub const OverweightDepositPerByte: Balance = deposit(0, 1);
+	pub const OverweightExpiry: BlockNumber = 7 * DAYS;
 }
 
 impl parachains_ump::Config for Runtime {
@@ -1154,19 +1169,32 @@ impl parachains_ump::Config for Runtime {
 	type UmpSink = crate::parachains_ump::XcmSink<XcmExecutor<XcmConfig>, Runtime>;
 	type FirstMessageFactorPercent = FirstMessageFactorPercent;
 	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
+	type Currency = Balances;
+	type OverweightDepositPerByte = OverweightDepositPerByte;
+	type OverweightExpiry = OverweightExpiry;
+	type WeightInfo = weights::runtime_parachains_ump::WeightInfo<Runtime>;
 }
 
-impl parachains_dmp::Config for Runtime {}
+parameter_types! {
+	pub const MaxDownwardQueueSize: u32 = 16 * 1024 * 1024;
+}
+
+impl parachains_dmp::Config for Runtime {
+	type MaxDownwardQueueSize = MaxDownwardQueueSize;
+}
 
 impl parachains_hrmp::Config for Runtime {
 	type Event = Event;
 	type Origin = Origin;
 	type Currency = Balances;
+	type ChannelManager = MoreThanHalfCouncil;
 }
 
 impl parachains_paras_inherent::Config for Runtime {}
 
-impl parachains_scheduler::Config for Runtime {}
+impl parachains_scheduler::Config for Runtime {
+	type ClaimHandler = ();
+}
 
 impl parachains_initializer::Config for Runtime {
 	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
@@ -1218,15 +1246,25 @@ impl crowdloan::Config for Runtime {
 	type Registrar = Registrar;
 	type Auctioneer = Auctions;
 	type MaxMemoLength = MaxMemoLength;
+	type RemoteContributorOrigin = pallet_xcm::EnsureXcm<crowdloan::RemoteContributorLocations>;
+	type LocationToAccountId = CrowdloanContributorAccountOf;
+	type XcmRouter = XcmRouter;
 	type WeightInfo = weights::runtime_common_crowdloan::WeightInfo<Runtime>;
 }
 
+/// The local account of a crowdloan contributor in another consensus system. Parachains and local
+/// accounts use their usual account, any other location gets an account derived from its hash.
+pub type CrowdloanContributorAccountOf =
+	(SovereignAccountOf, Account32Hash<GoldNetwork, AccountId>);
+
 parameter_types! {
 	// The average auction is 7 days long, so this will be 70% for ending period.
 	// 5 Days = 72000 Blocks @ 6 sec per block
 	pub const EndingPeriod: BlockNumber = 5 * DAYS;
 	// ~ 1000 samples per day -> ~ 20 blocks per sample -> 2 minute samples
 	pub const SampleLength: BlockNumber = 2 * MINUTES;
+	// The chosen sample and outcome are retained for the last 4 auctions.
+	pub const AuctionHistoryDepth: u32 = 4;
 }
 
 type AuctionInitiate = EnsureOneOf<
@@ -1242,6 +1280,7 @@ impl auctions::Config for Runtime {
 	type EndingPeriod = EndingPeriod;
 	type SampleLength = SampleLength;
 	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
+	type HistoryDepth = AuctionHistoryDepth;
 	type InitiateOrigin = AuctionInitiate;
 	type WeightInfo = weights::runtime_common_auctions::WeightInfo<Runtime>;
 }
@@ -1296,6 +1335,9 @@ type LocalOriginConverter = (
 	SignedAccountId32AsNative<GoldNetwork, Origin>,
 	// A system child parachain, expressed as a Superuser, converts to the `Root` origin.
 	ChildSystemParachainAsSuperuser<ParaId, Origin>,
+	// Xcm origins can be represented natively under the Xcm pallet's Xcm origin. Only crowdloan
+	// contributions accept it, from `crowdloan::RemoteContributorLocations`.
+	pallet_xcm::XcmPassthrough<Origin>,
 );
 
 parameter_types! {
@@ -1306,11 +1348,52 @@ parameter_types! {
 	pub const MaxInstructions: u32 = 100;
 }
 
+parameter_types! {
+	/// The asset that delivery fees for downward messages are paid in.
+	pub const DeliveryFeeAsset: AssetId = Concrete(KsmLocation::get());
+	/// The fee for delivering any downward message.
+	pub const BaseDeliveryFee: u128 = 3 * CENTS;
+	/// The fee for delivering each byte of a downward message.
+	pub const DeliveryFeePerByte: u128 = 10 * MILLICENTS;
+	/// The weight of pricing the delivery by the size of the downward queue, withdrawing the fees
+	/// from the sender and depositing them with the treasury and the block author.
+	///
+	/// FIXME: `WithdrawDeliveryFees` has no benchmark yet. Until it does, this is one balance
+	/// transfer for the withdrawal and the treasury deposit, another for the author's share, and
+	/// the reads of the queue size and the author plus the write of the total issuance.
+	///
+	/// Sends made by the executor on behalf of a remote origin are charged the same fees but are
+	/// only weighed by `Weigher`, which already charges `BaseXcmWeight` for each instruction. That
+	/// is safe as long as it stays above this weight, which `tests::delivery_fee_weight_is_covered`
+	/// checks.
+	pub DeliveryFeeWeight: Weight =
+		<weights::pallet_balances::WeightInfo<Runtime> as pallet_balances::WeightInfo>::transfer()
+			.saturating_mul(2)
+			.saturating_add(RocksDbWeight::get().reads_writes(2, 1));
+}
+
+/// The price of delivering downward messages, which grows with the congestion of the queue.
+pub type DeliveryPrice =
+	xcm_sender::CongestionPrice<Runtime, DeliveryFeeAsset, BaseDeliveryFee, DeliveryFeePerByte>;
+
+/// Delivery fees are withdrawn from the sending origin and split like transaction fees. Messages
+/// sent on behalf of the chain, the council or a system parachain are free.
+pub type DeliveryFeeCharger = xcm_sender::WithdrawDeliveryFees<
+	LocalAssetTransactor,
+	SplitRevenue<Runtime, TreasuryAndAuthor, KsmLocation>,
+	(xcm_sender::LocalChainAndBodies, IsChildSystemParachain<ParaId>),
+>;
+
 /// The XCM router. When we want to send an XCM message, we use this type. It amalgamates all of our
 /// individual routers.
 pub type XcmRouter = (
 	// Only one router so far - use DMP to communicate with child parachains.
-	xcm_sender::ChildParachainRouter<Runtime, xcm::AlwaysRelease>,
+	xcm_sender::ChildParachainRouter<
+		Runtime,
+		xcm::AlwaysRelease,
+		DeliveryPrice,
+		DeliveryFeeCharger,
+	>,
 );
 
 parameter_types! {
@@ -1340,8 +1423,15 @@ impl xcm_executor::Config for XcmConfig {
 	type LocationInverter = LocationInverter<Ancestry>;
 	type Barrier = Barrier;
 	type Weigher = FixedWeightBounds<BaseXcmWeight, Call, MaxInstructions>;
-	// The weight trader piggybacks on the existing transaction-fee conversion logic.
-	type Trader = UsingComponents<WeightToFee, KsmLocation, AccountId, Balances, ToAuthor<Runtime>>;
+	// The weight trader piggybacks on the existing transaction-fee conversion logic, and splits
+	// the weight bought like transaction fees.
+	type Trader = UsingComponents<
+		WeightToFee,
+		KsmLocation,
+		AccountId,
+		Balances,
+		SplitRevenue<Runtime, TreasuryAndAuthor, KsmLocation>,
+	>;
 	type ResponseHandler = XcmPallet;
 	type AssetTrap = XcmPallet;
 	type AssetClaims = XcmPallet;
@@ -1382,6 +1472,7 @@ impl pallet_xcm::Config for Runtime {
 	type Call = Call;
 	const VERSION_DISCOVERY_QUEUE_SIZE: u32 = 100;
 	type AdvertisedXcmVersion = pallet_xcm::CurrentXcmVersion;
+	type DeliveryFeeWeight = DeliveryFeeWeight;
 }
 
 parameter_types! {
@@ -1811,6 +1902,96 @@ sp_api::impl_runtime_apis! {
 		fn validation_code_by_hash(hash: ValidationCodeHash) -> Option<ValidationCode> {
 			parachains_runtime_api_impl::validation_code_by_hash::<Runtime>(hash)
 		}
+
+		fn hrmp_state(para_id: ParaId) -> HrmpParaState<BlockNumber> {
+			parachains_runtime_api_impl::hrmp_state::<Runtime>(para_id)
+		}
+	}
+
+	impl parachains_configuration::runtime_api::ConfigurationApi<Block, BlockNumber> for Runtime {
+		fn pending_config(
+			session: SessionIndex,
+		) -> Option<parachains_configuration::HostConfiguration<BlockNumber>> {
+			parachains_runtime_api_impl::pending_config::<Runtime>(session)
+		}
+
+		fn pending_config_changes(
+			session: SessionIndex,
+		) -> Vec<parachains_configuration::HostConfigurationChange<BlockNumber>> {
+			parachains_runtime_api_impl::pending_config_changes::<Runtime>(session)
+		}
+	}
+
+	impl parachains_paras::runtime_api::ParasApi<Block> for Runtime {
+		fn upcoming_lifecycle_transitions(
+		) -> Vec<(ParaId, SessionIndex, parachains_paras::ParaLifecycle)> {
+			parachains_runtime_api_impl::upcoming_lifecycle_transitions::<Runtime>()
+		}
+	}
+
+	impl parachains_dmp::runtime_api::DmpApi<Block, BlockNumber> for Runtime {
+		fn dmq_length(recipient: ParaId) -> u32 {
+			parachains_runtime_api_impl::dmq_length::<Runtime>(recipient)
+		}
+
+		fn dmq_contents_page(
+			recipient: ParaId,
+			start: u32,
+			count: u32,
+		) -> Vec<InboundDownwardMessage<BlockNumber>> {
+			parachains_runtime_api_impl::dmq_contents_page::<Runtime>(recipient, start, count)
+		}
+	}
+
+	impl parachains_ump::runtime_api::UmpApi<Block, BlockNumber> for Runtime {
+		fn upward_queues() -> Vec<parachains_ump::UpwardQueueState> {
+			parachains_runtime_api_impl::upward_queues::<Runtime>()
+		}
+
+		fn overweight_messages() -> Vec<parachains_ump::OverweightMessage<BlockNumber>> {
+			parachains_runtime_api_impl::overweight_messages::<Runtime>()
+		}
+	}
+
+	impl parachains_inclusion::runtime_api::InclusionApi<Block, BlockNumber> for Runtime {
+		fn availability_timeouts(
+		) -> Vec<(CoreIndex, parachains_inclusion::AvailabilityTimeout<BlockNumber>)> {
+			parachains_runtime_api_impl::availability_timeouts::<Runtime>()
+		}
+	}
+
+	impl xcm_sender::runtime_api::XcmSenderApi<Block> for Runtime {
+		fn downward_congestion_level(recipient: ParaId) -> u32 {
+			xcm_sender::congestion_level::<Runtime>(recipient)
+		}
+
+		fn downward_delivery_fees(
+			recipient: ParaId,
+			message: Vec<u8>,
+		) -> xcm::VersionedMultiAssets {
+			use xcm_sender::PriceForParachainDelivery;
+			DeliveryPrice::price_for_parachain_delivery(recipient, &message).into()
+		}
+	}
+
+	impl crowdloan::runtime_api::CrowdloanApi<Block, AccountId, Balance> for Runtime {
+		fn contributor_positions(who: AccountId) -> Vec<crowdloan::CrowdloanPosition<Balance>> {
+			Crowdloan::contributor_positions(&who)
+		}
+	}
+
+	impl auctions::runtime_api::AuctionsApi<Block, AccountId, Balance, BlockNumber, Hash> for Runtime {
+		fn winning_samples(
+			auction_index: auctions::AuctionIndex,
+		) -> Vec<(BlockNumber, Vec<Option<(AccountId, ParaId, Balance)>>)> {
+			Auctions::winning_samples(auction_index)
+		}
+
+		fn auction_outcome(
+			auction_index: auctions::AuctionIndex,
+		) -> Option<auctions::AuctionOutcome<BlockNumber, Hash>> {
+			Auctions::auction_outcome(auction_index)
+		}
 	}
 
 	impl beefy_primitives::BeefyApi<Block> for Runtime {
@@ -2003,6 +2184,7 @@ sp_api::impl_runtime_apis! {
 			list_benchmark!(list, extra, runtime_common::slots, Slots);
 			list_benchmark!(list, extra, runtime_common::paras_registrar, Registrar);
 			list_benchmark!(list, extra, runtime_parachains::configuration, Configuration);
+			list_benchmark!(list, extra, runtime_parachains::ump, Ump);
 			// Substrate
 			list_benchmark!(list, extra, pallet_bags_list, BagsList);
 			list_benchmark!(list, extra, pallet_balances, Balances);
@@ -2078,6 +2260,7 @@ sp_api::impl_runtime_apis! {
 			add_benchmark!(params, batches, runtime_common::slots, Slots);
 			add_benchmark!(params, batches, runtime_common::paras_registrar, Registrar);
 			add_benchmark!(params, batches, runtime_parachains::configuration, Configuration);
+			add_benchmark!(params, batches, runtime_parachains::ump, Ump);
 			// Substrate
 			add_benchmark!(params, batches, pallet_balances, Balances);
 			add_benchmark!(params, batches, pallet_bags_list, BagsList);
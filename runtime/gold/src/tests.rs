@@ -31,6 +31,13 @@ fn remove_keys_weight_is_sensible() {
 	assert!(max_weight * 2 < BlockWeights::get().max_block);
 }
 
+#[test]
+fn delivery_fee_weight_is_covered() {
+	// The executor weighs every sending instruction at `BaseXcmWeight` and adds nothing for the
+	// delivery fees it charges, so that must cover them.
+	assert!(DeliveryFeeWeight::get() <= BaseXcmWeight::get());
+}
+
 #[test]
 fn sample_size_is_sensible() {
 	use runtime_common::auctions::WeightInfo;
@@ -37,6 +37,7 @@
 #![allow(unused_imports)]
 
 use frame_support::{traits::Get, weights::Weight};
+use frame_system::WeightInfo as _;
 use sp_std::marker::PhantomData;
 
 /// Weight functions for `runtime_common::claims`.
@@ -67,4 +68,31 @@ impl<T: frame_system::Config> runtime_common::claims::WeightInfo for WeightInfo<
 			.saturating_add(T::DbWeight::get().reads(4 as Weight))
 			.saturating_add(T::DbWeight::get().writes(7 as Weight))
 	}
+	// FIXME: the claim root calls below have not been generated by the benchmark CLI yet and
+	// must be regenerated with the command above before release. `add_claim_root` and
+	// `remove_claim_root` touch no more storage than `mint_claim` plus the claim root read.
+	fn add_claim_root() -> Weight {
+		<Self as runtime_common::claims::WeightInfo>::mint_claim()
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+	}
+	fn remove_claim_root() -> Weight {
+		<Self as runtime_common::claims::WeightInfo>::mint_claim()
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+	}
+	// `claim` bounds the signature check, as Ethereum recovery costs more than ed25519 and
+	// sr25519 verification. Each proof node is one `blake2_256` of a 64 byte pair, bounded by
+	// the benchmarked `remark_with_event` of 64 bytes, which hashes its input too.
+	fn claim_from_root(p: u32, ) -> Weight {
+		<Self as runtime_common::claims::WeightInfo>::claim()
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(super::frame_system::WeightInfo::<T>::remark_with_event(64)
+				.saturating_mul(p as Weight))
+	}
+	fn claim_from_root_ed25519(p: u32, ) -> Weight {
+		<Self as runtime_common::claims::WeightInfo>::claim_from_root(p)
+	}
+	fn claim_from_root_sr25519(p: u32, ) -> Weight {
+		<Self as runtime_common::claims::WeightInfo>::claim_from_root(p)
+	}
 }
@@ -37,6 +37,7 @@
 #![allow(unused_imports)]
 
 use frame_support::{traits::Get, weights::Weight};
+use frame_system::WeightInfo as _;
 use sp_std::marker::PhantomData;
 
 /// Weight functions for `runtime_common::paras_registrar`.
@@ -67,4 +68,63 @@ impl<T: frame_system::Config> runtime_common::paras_registrar::WeightInfo for We
 			.saturating_add(T::DbWeight::get().reads(10 as Weight))
 			.saturating_add(T::DbWeight::get().writes(8 as Weight))
 	}
+	// FIXME: the code upload calls are not CLI output yet; regenerate them before release.
+	// Until then they are built from benchmarked entries only. `start_code_upload` reserves a
+	// deposit as `reserve` does and also reads the uploaded code.
+	fn start_code_upload() -> Weight {
+		<Self as runtime_common::paras_registrar::WeightInfo>::reserve()
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+	}
+	// Storing a chunk is charged per byte at the benchmarked `remark_with_event` rate, which
+	// hashes its input and so bounds copying the chunk.
+	fn upload_code_chunk(b: u32, ) -> Weight {
+		<Self as runtime_common::paras_registrar::WeightInfo>::reserve()
+			.saturating_add(super::frame_system::WeightInfo::<T>::remark_with_event(b))
+	}
+	// The code is read back from up to 64 chunks, hashed once here and once more by the paras
+	// pallet when retained, and written whole, so the per-byte rate is charged three times on
+	// top of the chunk reads, the prefix removal and the code and reference count writes.
+	fn complete_code_upload(c: u32, ) -> Weight {
+		<Self as runtime_common::paras_registrar::WeightInfo>::reserve()
+			.saturating_add(super::frame_system::WeightInfo::<T>::remark_with_event(c)
+				.saturating_mul(3 as Weight))
+			.saturating_add(super::frame_system::WeightInfo::<T>::kill_storage(64))
+			.saturating_add(T::DbWeight::get().reads(65 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Worst case: a partial upload of 64 chunks, removed as `kill_storage` removes keys.
+	fn remove_code() -> Weight {
+		<Self as runtime_common::paras_registrar::WeightInfo>::reserve()
+			.saturating_add(super::frame_system::WeightInfo::<T>::kill_storage(64))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Bounded by `register`, which also validates and stores a worst case code, plus reading
+	// the upload and the code back and writing the upload and the para's code hash.
+	fn register_with_code_hash() -> Weight {
+		<Self as runtime_common::paras_registrar::WeightInfo>::register()
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// FIXME: the role calls are not CLI output yet either. They are charged the benchmarked
+	// empty `remark` as a dispatch base plus the storage they access.
+	fn set_role() -> Weight {
+		super::frame_system::WeightInfo::<T>::remark(0)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// The new code is hashed and stored whole, so the per-byte `remark_with_event` rate is
+	// charged twice.
+	fn schedule_code_upgrade(b: u32, ) -> Weight {
+		super::frame_system::WeightInfo::<T>::remark(0)
+			.saturating_add(super::frame_system::WeightInfo::<T>::remark_with_event(b)
+				.saturating_mul(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	fn set_current_head() -> Weight {
+		super::frame_system::WeightInfo::<T>::remark(0)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
 }
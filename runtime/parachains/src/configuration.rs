@@ -33,6 +33,7 @@ pub mod weights;
 pub use pallet::*;
 
 pub mod migration;
+pub mod runtime_api;
 
 const LOG_TARGET: &str = "runtime::configuration";
 
@@ -233,31 +234,209 @@ impl<BlockNumber: Zero> HostConfiguration<BlockNumber> {
 	///
 	/// This function panics if any member is not set properly.
 	pub fn check_consistency(&self) {
+		if let Err(e) = self.check() {
+			panic!("Host configuration is inconsistent: {:?}", e)
+		}
+	}
+
+	/// Checks that this instance is consistent with the requirements on each individual member,
+	/// returning the first violation found.
+	pub fn check(&self) -> Result<(), InconsistentError> {
 		if self.group_rotation_frequency.is_zero() {
-			panic!("`group_rotation_frequency` must be non-zero!")
+			return Err(InconsistentError::ZeroGroupRotationFrequency)
 		}
 
 		if self.chain_availability_period.is_zero() {
-			panic!("`chain_availability_period` must be at least 1!")
+			return Err(InconsistentError::ZeroChainAvailabilityPeriod)
 		}
 
 		if self.thread_availability_period.is_zero() {
-			panic!("`thread_availability_period` must be at least 1!")
+			return Err(InconsistentError::ZeroThreadAvailabilityPeriod)
 		}
 
 		if self.no_show_slots.is_zero() {
-			panic!("`no_show_slots` must be at least 1!")
+			return Err(InconsistentError::ZeroNoShowSlots)
 		}
 
 		if self.max_code_size > MAX_CODE_SIZE {
-			panic!(
-				"`max_code_size` ({}) is bigger than allowed by the client ({})",
-				self.max_code_size, MAX_CODE_SIZE,
-			)
+			return Err(InconsistentError::MaxCodeSizeExceedHardLimit {
+				max_code_size: self.max_code_size,
+			})
 		}
 
 		if self.max_pov_size > MAX_POV_SIZE {
-			panic!("`max_pov_size` is bigger than allowed by the client")
+			return Err(InconsistentError::MaxPovSizeExceedHardLimit {
+				max_pov_size: self.max_pov_size,
+			})
+		}
+
+		Ok(())
+	}
+}
+
+/// The reason a [`HostConfiguration`] was found to be inconsistent.
+#[derive(Clone, Copy, PartialEq, Eq, sp_core::RuntimeDebug)]
+pub enum InconsistentError {
+	/// `group_rotation_frequency` is set to zero.
+	ZeroGroupRotationFrequency,
+	/// `chain_availability_period` is set to zero.
+	ZeroChainAvailabilityPeriod,
+	/// `thread_availability_period` is set to zero.
+	ZeroThreadAvailabilityPeriod,
+	/// `no_show_slots` is set to zero.
+	ZeroNoShowSlots,
+	/// `max_code_size` exceeds the hard limit of `MAX_CODE_SIZE`.
+	MaxCodeSizeExceedHardLimit { max_code_size: u32 },
+	/// `max_pov_size` exceeds the hard limit of `MAX_POV_SIZE`.
+	MaxPovSizeExceedHardLimit { max_pov_size: u32 },
+}
+
+/// A change of a single member of the [`HostConfiguration`].
+///
+/// A set of these is scheduled as one batch with `schedule_config_changes`.
+#[derive(Clone, Encode, Decode, PartialEq, Eq, sp_core::RuntimeDebug, scale_info::TypeInfo)]
+pub enum HostConfigurationChange<BlockNumber> {
+	/// Set [`HostConfiguration::max_code_size`].
+	MaxCodeSize(u32),
+	/// Set [`HostConfiguration::max_head_data_size`].
+	MaxHeadDataSize(u32),
+	/// Set [`HostConfiguration::max_upward_queue_count`].
+	MaxUpwardQueueCount(u32),
+	/// Set [`HostConfiguration::max_upward_queue_size`].
+	MaxUpwardQueueSize(u32),
+	/// Set [`HostConfiguration::max_upward_message_size`].
+	MaxUpwardMessageSize(u32),
+	/// Set [`HostConfiguration::max_upward_message_num_per_candidate`].
+	MaxUpwardMessageNumPerCandidate(u32),
+	/// Set [`HostConfiguration::hrmp_max_message_num_per_candidate`].
+	HrmpMaxMessageNumPerCandidate(u32),
+	/// Set [`HostConfiguration::validation_upgrade_frequency`].
+	ValidationUpgradeFrequency(BlockNumber),
+	/// Set [`HostConfiguration::validation_upgrade_delay`].
+	ValidationUpgradeDelay(BlockNumber),
+	/// Set [`HostConfiguration::max_pov_size`].
+	MaxPovSize(u32),
+	/// Set [`HostConfiguration::max_downward_message_size`].
+	MaxDownwardMessageSize(u32),
+	/// Set [`HostConfiguration::ump_service_total_weight`].
+	UmpServiceTotalWeight(Weight),
+	/// Set [`HostConfiguration::hrmp_max_parachain_outbound_channels`].
+	HrmpMaxParachainOutboundChannels(u32),
+	/// Set [`HostConfiguration::hrmp_max_parathread_outbound_channels`].
+	HrmpMaxParathreadOutboundChannels(u32),
+	/// Set [`HostConfiguration::hrmp_sender_deposit`].
+	HrmpSenderDeposit(Balance),
+	/// Set [`HostConfiguration::hrmp_recipient_deposit`].
+	HrmpRecipientDeposit(Balance),
+	/// Set [`HostConfiguration::hrmp_channel_max_capacity`].
+	HrmpChannelMaxCapacity(u32),
+	/// Set [`HostConfiguration::hrmp_channel_max_total_size`].
+	HrmpChannelMaxTotalSize(u32),
+	/// Set [`HostConfiguration::hrmp_max_parachain_inbound_channels`].
+	HrmpMaxParachainInboundChannels(u32),
+	/// Set [`HostConfiguration::hrmp_max_parathread_inbound_channels`].
+	HrmpMaxParathreadInboundChannels(u32),
+	/// Set [`HostConfiguration::hrmp_channel_max_message_size`].
+	HrmpChannelMaxMessageSize(u32),
+	/// Set [`HostConfiguration::code_retention_period`].
+	CodeRetentionPeriod(BlockNumber),
+	/// Set [`HostConfiguration::parathread_cores`].
+	ParathreadCores(u32),
+	/// Set [`HostConfiguration::parathread_retries`].
+	ParathreadRetries(u32),
+	/// Set [`HostConfiguration::group_rotation_frequency`].
+	GroupRotationFrequency(BlockNumber),
+	/// Set [`HostConfiguration::chain_availability_period`].
+	ChainAvailabilityPeriod(BlockNumber),
+	/// Set [`HostConfiguration::thread_availability_period`].
+	ThreadAvailabilityPeriod(BlockNumber),
+	/// Set [`HostConfiguration::scheduling_lookahead`].
+	SchedulingLookahead(u32),
+	/// Set [`HostConfiguration::max_validators_per_core`].
+	MaxValidatorsPerCore(Option<u32>),
+	/// Set [`HostConfiguration::max_validators`].
+	MaxValidators(Option<u32>),
+	/// Set [`HostConfiguration::dispute_period`].
+	DisputePeriod(SessionIndex),
+	/// Set [`HostConfiguration::dispute_post_conclusion_acceptance_period`].
+	DisputePostConclusionAcceptancePeriod(BlockNumber),
+	/// Set [`HostConfiguration::dispute_max_spam_slots`].
+	DisputeMaxSpamSlots(u32),
+	/// Set [`HostConfiguration::dispute_conclusion_by_time_out_period`].
+	DisputeConclusionByTimeOutPeriod(BlockNumber),
+	/// Set [`HostConfiguration::no_show_slots`].
+	NoShowSlots(u32),
+	/// Set [`HostConfiguration::n_delay_tranches`].
+	NDelayTranches(u32),
+	/// Set [`HostConfiguration::zeroth_delay_tranche_width`].
+	ZerothDelayTrancheWidth(u32),
+	/// Set [`HostConfiguration::needed_approvals`].
+	NeededApprovals(u32),
+	/// Set [`HostConfiguration::relay_vrf_modulo_samples`].
+	RelayVrfModuloSamples(u32),
+	/// Set [`HostConfiguration::ump_max_individual_weight`].
+	UmpMaxIndividualWeight(Weight),
+}
+
+impl<BlockNumber: Clone + PartialEq> HostConfigurationChange<BlockNumber> {
+	/// Apply this change to the given configuration, returning whether the configuration
+	/// was actually modified.
+	pub fn apply(&self, config: &mut HostConfiguration<BlockNumber>) -> bool {
+		fn set<V: Clone + PartialEq>(member: &mut V, new: &V) -> bool {
+			sp_std::mem::replace(member, new.clone()) != *new
+		}
+
+		match self {
+			Self::MaxCodeSize(new) => set(&mut config.max_code_size, new),
+			Self::MaxHeadDataSize(new) => set(&mut config.max_head_data_size, new),
+			Self::MaxUpwardQueueCount(new) => set(&mut config.max_upward_queue_count, new),
+			Self::MaxUpwardQueueSize(new) => set(&mut config.max_upward_queue_size, new),
+			Self::MaxUpwardMessageSize(new) => set(&mut config.max_upward_message_size, new),
+			Self::MaxUpwardMessageNumPerCandidate(new) =>
+				set(&mut config.max_upward_message_num_per_candidate, new),
+			Self::HrmpMaxMessageNumPerCandidate(new) =>
+				set(&mut config.hrmp_max_message_num_per_candidate, new),
+			Self::ValidationUpgradeFrequency(new) =>
+				set(&mut config.validation_upgrade_frequency, new),
+			Self::ValidationUpgradeDelay(new) => set(&mut config.validation_upgrade_delay, new),
+			Self::MaxPovSize(new) => set(&mut config.max_pov_size, new),
+			Self::MaxDownwardMessageSize(new) => set(&mut config.max_downward_message_size, new),
+			Self::UmpServiceTotalWeight(new) => set(&mut config.ump_service_total_weight, new),
+			Self::HrmpMaxParachainOutboundChannels(new) =>
+				set(&mut config.hrmp_max_parachain_outbound_channels, new),
+			Self::HrmpMaxParathreadOutboundChannels(new) =>
+				set(&mut config.hrmp_max_parathread_outbound_channels, new),
+			Self::HrmpSenderDeposit(new) => set(&mut config.hrmp_sender_deposit, new),
+			Self::HrmpRecipientDeposit(new) => set(&mut config.hrmp_recipient_deposit, new),
+			Self::HrmpChannelMaxCapacity(new) => set(&mut config.hrmp_channel_max_capacity, new),
+			Self::HrmpChannelMaxTotalSize(new) => set(&mut config.hrmp_channel_max_total_size, new),
+			Self::HrmpMaxParachainInboundChannels(new) =>
+				set(&mut config.hrmp_max_parachain_inbound_channels, new),
+			Self::HrmpMaxParathreadInboundChannels(new) =>
+				set(&mut config.hrmp_max_parathread_inbound_channels, new),
+			Self::HrmpChannelMaxMessageSize(new) =>
+				set(&mut config.hrmp_channel_max_message_size, new),
+			Self::CodeRetentionPeriod(new) => set(&mut config.code_retention_period, new),
+			Self::ParathreadCores(new) => set(&mut config.parathread_cores, new),
+			Self::ParathreadRetries(new) => set(&mut config.parathread_retries, new),
+			Self::GroupRotationFrequency(new) => set(&mut config.group_rotation_frequency, new),
+			Self::ChainAvailabilityPeriod(new) => set(&mut config.chain_availability_period, new),
+			Self::ThreadAvailabilityPeriod(new) => set(&mut config.thread_availability_period, new),
+			Self::SchedulingLookahead(new) => set(&mut config.scheduling_lookahead, new),
+			Self::MaxValidatorsPerCore(new) => set(&mut config.max_validators_per_core, new),
+			Self::MaxValidators(new) => set(&mut config.max_validators, new),
+			Self::DisputePeriod(new) => set(&mut config.dispute_period, new),
+			Self::DisputePostConclusionAcceptancePeriod(new) =>
+				set(&mut config.dispute_post_conclusion_acceptance_period, new),
+			Self::DisputeMaxSpamSlots(new) => set(&mut config.dispute_max_spam_slots, new),
+			Self::DisputeConclusionByTimeOutPeriod(new) =>
+				set(&mut config.dispute_conclusion_by_time_out_period, new),
+			Self::NoShowSlots(new) => set(&mut config.no_show_slots, new),
+			Self::NDelayTranches(new) => set(&mut config.n_delay_tranches, new),
+			Self::ZerothDelayTrancheWidth(new) => set(&mut config.zeroth_delay_tranche_width, new),
+			Self::NeededApprovals(new) => set(&mut config.needed_approvals, new),
+			Self::RelayVrfModuloSamples(new) => set(&mut config.relay_vrf_modulo_samples, new),
+			Self::UmpMaxIndividualWeight(new) => set(&mut config.ump_max_individual_weight, new),
 		}
 	}
 }
@@ -269,6 +448,7 @@ pub trait WeightInfo {
 	fn set_config_with_weight() -> Weight;
 	fn set_config_with_balance() -> Weight;
 	fn set_hrmp_open_request_ttl() -> Weight;
+	fn schedule_config_changes(c: u32) -> Weight;
 }
 
 #[frame_support::pallet]
@@ -290,6 +470,13 @@ pub mod pallet {
 	pub enum Error<T> {
 		/// The new value for a configuration parameter is invalid.
 		InvalidNewValue,
+		/// A batch of configuration changes must contain at least one change.
+		EmptyChangeSet,
+		/// Configuration changes cannot be scheduled for a session earlier than the next
+		/// scheduled session.
+		SessionTooEarly,
+		/// Applying the batch of changes would result in an inconsistent configuration.
+		InconsistentConfiguration,
 	}
 
 	/// The active configuration for the current session.
@@ -303,6 +490,17 @@ pub mod pallet {
 	pub(crate) type PendingConfig<T: Config> =
 		StorageMap<_, Twox64Concat, SessionIndex, HostConfiguration<T::BlockNumber>>;
 
+	/// The batches of changes scheduled via `schedule_config_changes`, by the session they take
+	/// effect in. These are already reflected in `PendingConfig` and are kept for introspection.
+	#[pallet::storage]
+	pub(crate) type PendingConfigChanges<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		SessionIndex,
+		Vec<HostConfigurationChange<T::BlockNumber>>,
+		ValueQuery,
+	>;
+
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
 		pub config: HostConfiguration<T::BlockNumber>,
@@ -794,6 +992,54 @@ pub mod pallet {
 			});
 			Ok(())
 		}
+
+		/// Schedule a batch of configuration changes to come into effect at the given session.
+		///
+		/// The changes are applied together on top of the configuration that would otherwise be
+		/// in force by `session` and the result is checked for consistency before anything is
+		/// stored, so the batch is either scheduled as a whole or rejected. Configurations
+		/// already scheduled for later sessions are updated as well, so that they do not revert
+		/// the batch.
+		#[pallet::weight(T::WeightInfo::schedule_config_changes(changes.len() as u32))]
+		pub fn schedule_config_changes(
+			origin: OriginFor<T>,
+			session: SessionIndex,
+			changes: Vec<HostConfigurationChange<T::BlockNumber>>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(!changes.is_empty(), Error::<T>::EmptyChangeSet);
+			ensure!(session >= Self::scheduled_session(), Error::<T>::SessionTooEarly);
+
+			let updated = Self::updated_configs(session, |config| {
+				changes.iter().fold(false, |changed, change| change.apply(config) | changed)
+			});
+
+			for (index, config) in &updated {
+				if let Err(e) = config.check() {
+					log::debug!(
+						target: LOG_TARGET,
+						"Rejecting configuration changes for session {}: inconsistent at session {}: {:?}",
+						session,
+						index,
+						e,
+					);
+					return Err(Error::<T>::InconsistentConfiguration.into())
+				}
+			}
+
+			if updated.is_empty() {
+				return Ok(())
+			}
+
+			for (index, config) in updated {
+				<Self as Store>::PendingConfig::insert(index, config);
+			}
+			<Self as Store>::PendingConfigChanges::mutate(session, |pending| {
+				pending.extend(changes)
+			});
+
+			Ok(())
+		}
 	}
 
 	#[pallet::hooks]
@@ -827,6 +1073,7 @@ impl<T: Config> Pallet<T> {
 		if let Some(pending) = <Self as Store>::PendingConfig::take(session_index) {
 			<Self as Store>::ActiveConfig::set(pending);
 		}
+		<Self as Store>::PendingConfigChanges::remove(session_index);
 	}
 
 	/// Return the session index that should be used for any future scheduled changes.
@@ -841,18 +1088,71 @@ impl<T: Config> Pallet<T> {
 		<Self as Store>::ActiveConfig::set(config);
 	}
 
+	/// Returns the configuration scheduled to come into effect at the given session, if any.
+	pub fn pending_config(session: SessionIndex) -> Option<HostConfiguration<T::BlockNumber>> {
+		<Self as Store>::PendingConfig::get(session)
+	}
+
+	/// Returns the batched changes scheduled to come into effect at the given session.
+	pub fn pending_config_changes(
+		session: SessionIndex,
+	) -> Vec<HostConfigurationChange<T::BlockNumber>> {
+		<Self as Store>::PendingConfigChanges::get(session)
+	}
+
+	/// Returns the configuration that will be in force at the given session, as far as is known
+	/// now.
+	fn config_at(session: SessionIndex) -> HostConfiguration<T::BlockNumber> {
+		<Self as Store>::PendingConfig::iter()
+			.filter(|(index, _)| *index <= session)
+			.max_by_key(|(index, _)| *index)
+			.map(|(_, config)| config)
+			.unwrap_or_else(Self::config)
+	}
+
+	/// Computes the configurations which would have to be stored in `PendingConfig` for the
+	/// given update to come into effect at `session`.
+	///
+	/// The update is applied to the configuration in force at `session` and to every
+	/// configuration pending for a later session. Batches scheduled for those later sessions are
+	/// re-applied on top, so that they retain precedence. Only the configurations which were
+	/// actually modified are returned.
+	fn updated_configs(
+		session: SessionIndex,
+		updater: impl Fn(&mut HostConfiguration<T::BlockNumber>) -> bool,
+	) -> Vec<(SessionIndex, HostConfiguration<T::BlockNumber>)> {
+		let mut updated = Vec::new();
+
+		let mut base = Self::config_at(session);
+		if updater(&mut base) {
+			updated.push((session, base));
+		}
+
+		let mut later: Vec<_> = <Self as Store>::PendingConfig::iter()
+			.filter(|(index, _)| *index > session)
+			.collect();
+		later.sort_by_key(|(index, _)| *index);
+
+		for (index, mut config) in later {
+			if updater(&mut config) {
+				for change in <Self as Store>::PendingConfigChanges::get(index) {
+					change.apply(&mut config);
+				}
+				updated.push((index, config));
+			}
+		}
+
+		updated
+	}
+
 	// NOTE: Explicitly tell rustc not to inline this because otherwise heuristics note the incoming
 	// closure making it's attractive to inline. However, in this case, we will end up with lots of
 	// duplicated code (making this function to show up in the top of heaviest functions) only for
 	// the sake of essentially avoiding an indirect call. Doesn't worth it.
 	#[inline(never)]
-	fn update_config_member(updater: impl FnOnce(&mut HostConfiguration<T::BlockNumber>) -> bool) {
-		let scheduled_session = Self::scheduled_session();
-		let pending = <Self as Store>::PendingConfig::get(scheduled_session);
-		let mut prev = pending.unwrap_or_else(Self::config);
-
-		if updater(&mut prev) {
-			<Self as Store>::PendingConfig::insert(scheduled_session, prev);
+	fn update_config_member(updater: impl Fn(&mut HostConfiguration<T::BlockNumber>) -> bool) {
+		for (index, config) in Self::updated_configs(Self::scheduled_session(), updater) {
+			<Self as Store>::PendingConfig::insert(index, config);
 		}
 	}
 }
@@ -860,9 +1160,9 @@ impl<T: Config> Pallet<T> {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::mock::{new_test_ext, Configuration, Origin};
+	use crate::mock::{new_test_ext, Configuration, Origin, Test};
 
-	use frame_support::assert_ok;
+	use frame_support::{assert_noop, assert_ok};
 
 	#[test]
 	fn config_changes_after_2_session_boundary() {
@@ -1123,6 +1423,111 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn config_changes_are_scheduled_as_a_batch() {
+		new_test_ext(Default::default()).execute_with(|| {
+			let old_config = Configuration::config();
+			let changes = vec![
+				HostConfigurationChange::HrmpChannelMaxCapacity(8),
+				HostConfigurationChange::HrmpChannelMaxTotalSize(8 * 1024),
+				HostConfigurationChange::HrmpChannelMaxMessageSize(1024),
+			];
+
+			assert_ok!(Configuration::schedule_config_changes(Origin::root(), 4, changes.clone()));
+
+			let expected = HostConfiguration {
+				hrmp_channel_max_capacity: 8,
+				hrmp_channel_max_total_size: 8 * 1024,
+				hrmp_channel_max_message_size: 1024,
+				..old_config.clone()
+			};
+			assert_eq!(Configuration::pending_config(4), Some(expected.clone()));
+			assert_eq!(Configuration::pending_config_changes(4), changes);
+			assert!(Configuration::pending_config(shared::SESSION_DELAY).is_none());
+
+			for session in 1..4 {
+				Configuration::initializer_on_new_session(&session);
+				assert_eq!(Configuration::config(), old_config);
+			}
+
+			Configuration::initializer_on_new_session(&4);
+			assert_eq!(Configuration::config(), expected);
+			assert!(Configuration::pending_config(4).is_none());
+			assert!(Configuration::pending_config_changes(4).is_empty());
+		});
+	}
+
+	#[test]
+	fn inconsistent_config_changes_are_rejected() {
+		new_test_ext(Default::default()).execute_with(|| {
+			assert_noop!(
+				Configuration::schedule_config_changes(
+					Origin::root(),
+					shared::SESSION_DELAY,
+					vec![
+						HostConfigurationChange::MaxCodeSize(1024),
+						HostConfigurationChange::GroupRotationFrequency(0),
+					],
+				),
+				Error::<Test>::InconsistentConfiguration,
+			);
+			assert_noop!(
+				Configuration::schedule_config_changes(
+					Origin::root(),
+					shared::SESSION_DELAY,
+					Vec::new()
+				),
+				Error::<Test>::EmptyChangeSet,
+			);
+			assert_noop!(
+				Configuration::schedule_config_changes(
+					Origin::root(),
+					shared::SESSION_DELAY - 1,
+					vec![HostConfigurationChange::MaxCodeSize(1024)],
+				),
+				Error::<Test>::SessionTooEarly,
+			);
+			assert!(Configuration::schedule_config_changes(
+				Origin::signed(1),
+				shared::SESSION_DELAY,
+				vec![HostConfigurationChange::MaxCodeSize(1024)],
+			)
+			.is_err());
+		});
+	}
+
+	#[test]
+	fn later_scheduled_configs_are_not_reverted() {
+		new_test_ext(Default::default()).execute_with(|| {
+			assert_ok!(Configuration::schedule_config_changes(
+				Origin::root(),
+				5,
+				vec![HostConfigurationChange::MaxCodeSize(100)],
+			));
+
+			// A single member update for an earlier session is carried over to the later one.
+			assert_ok!(Configuration::set_max_pov_size(Origin::root(), 1024));
+			let pending = Configuration::pending_config(5).unwrap();
+			assert_eq!(pending.max_code_size, 100);
+			assert_eq!(pending.max_pov_size, 1024);
+
+			// An earlier batch touching the same member doesn't override the later batch.
+			assert_ok!(Configuration::schedule_config_changes(
+				Origin::root(),
+				3,
+				vec![
+					HostConfigurationChange::MaxCodeSize(200),
+					HostConfigurationChange::MaxHeadDataSize(32),
+				],
+			));
+			assert_eq!(Configuration::pending_config(3).unwrap().max_code_size, 200);
+			let pending = Configuration::pending_config(5).unwrap();
+			assert_eq!(pending.max_code_size, 100);
+			assert_eq!(pending.max_head_data_size, 32);
+			assert_eq!(pending.max_pov_size, 1024);
+		});
+	}
+
 	#[test]
 	fn verify_externally_accessible() {
 		// This test verifies that the value can be accessed through the well known keys and the
@@ -19,14 +19,34 @@ use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, BenchmarkError,
 use frame_system::RawOrigin;
 use sp_runtime::traits::One;
 
+// Schedule a batch for the session after the scheduled one, so that setters also update the
+// configuration pending for it and re-apply the batch: their worst case.
+fn schedule_later_batch<T: Config>() -> Result<SessionIndex, &'static str> {
+	let session = crate::shared::Pallet::<T>::scheduled_session();
+	Pallet::<T>::schedule_config_changes(
+		RawOrigin::Root.into(),
+		session + 1,
+		vec![HostConfigurationChange::MaxHeadDataSize(100)],
+	)?;
+	Ok(session)
+}
+
 benchmarks! {
-	set_config_with_block_number {}: set_code_retention_period(RawOrigin::Root, One::one())
+	set_config_with_block_number {
+		schedule_later_batch::<T>()?;
+	}: set_code_retention_period(RawOrigin::Root, One::one())
 
-	set_config_with_u32 {}: set_max_code_size(RawOrigin::Root, 100)
+	set_config_with_u32 {
+		schedule_later_batch::<T>()?;
+	}: set_max_code_size(RawOrigin::Root, 100)
 
-	set_config_with_option_u32 {}: set_max_validators(RawOrigin::Root, Some(10))
+	set_config_with_option_u32 {
+		schedule_later_batch::<T>()?;
+	}: set_max_validators(RawOrigin::Root, Some(10))
 
-	set_config_with_weight {}: set_ump_service_total_weight(RawOrigin::Root, 3_000_000)
+	set_config_with_weight {
+		schedule_later_batch::<T>()?;
+	}: set_ump_service_total_weight(RawOrigin::Root, 3_000_000)
 
 	set_hrmp_open_request_ttl {}: {
 		Err(BenchmarkError::Override(
@@ -34,7 +54,21 @@ benchmarks! {
 		))?;
 	}
 
-	set_config_with_balance {}: set_hrmp_sender_deposit(RawOrigin::Root, 100_000_000_000)
+	set_config_with_balance {
+		schedule_later_batch::<T>()?;
+	}: set_hrmp_sender_deposit(RawOrigin::Root, 100_000_000_000)
+
+	// Worst case: a batch is already scheduled for a later session, so it is re-applied on top
+	// of the changes to the configuration pending for that session.
+	schedule_config_changes {
+		let c in 1 .. 40;
+		let changes = (0..c).map(|i| HostConfigurationChange::MaxCodeSize(100 + i)).collect::<Vec<_>>();
+		let session = schedule_later_batch::<T>()?;
+	}: _(RawOrigin::Root, session, changes)
+	verify {
+		let later = Pallet::<T>::pending_config(session + 1).unwrap();
+		assert_eq!(later.max_code_size, 100 + c - 1);
+	}
 }
 
 impl_benchmark_test_suite!(
@@ -0,0 +1,34 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API for inspecting configuration changes which are scheduled but not yet in effect.
+
+use super::{HostConfiguration, HostConfigurationChange};
+use parity_scale_codec::{Decode, Encode};
+use primitives::v1::SessionIndex;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	/// The API for querying the pending changes of the parachains host configuration.
+	pub trait ConfigurationApi<N: Encode + Decode> {
+		/// Get the configuration which will come into effect at the given session, if any change
+		/// is scheduled for it.
+		fn pending_config(session: SessionIndex) -> Option<HostConfiguration<N>>;
+
+		/// Get the batched changes scheduled to come into effect at the given session.
+		fn pending_config_changes(session: SessionIndex) -> Vec<HostConfigurationChange<N>>;
+	}
+}
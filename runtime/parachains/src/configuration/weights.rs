@@ -30,11 +30,15 @@
 #![allow(unused_imports)]
 
 use frame_support::{traits::Get, weights::Weight};
+use frame_system::WeightInfo as _;
 use sp_std::marker::PhantomData;
 
 /// Weight functions for `runtime_parachains::configuration`.
 pub struct WeightInfo<T>(PhantomData<T>);
 impl<T: frame_system::Config> super::WeightInfo for WeightInfo<T> {
+	// FIXME: the setters below were benchmarked before they updated the configurations pending
+	// for later sessions, and must be regenerated with the command above. Until then each is also
+	// charged for a later pending configuration: reading it and its batch, and writing it.
 	// Storage: ParasShared CurrentSessionIndex (r:1 w:0)
 	// Storage: Configuration PendingConfig (r:1 w:1)
 	// Storage: Configuration ActiveConfig (r:1 w:0)
@@ -42,6 +46,8 @@ impl<T: frame_system::Config> super::WeightInfo for WeightInfo<T> {
 		(16_730_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
 	// Storage: ParasShared CurrentSessionIndex (r:1 w:0)
 	// Storage: Configuration PendingConfig (r:1 w:1)
@@ -50,6 +56,8 @@ impl<T: frame_system::Config> super::WeightInfo for WeightInfo<T> {
 		(16_592_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
 	// Storage: ParasShared CurrentSessionIndex (r:1 w:0)
 	// Storage: Configuration PendingConfig (r:1 w:1)
@@ -58,6 +66,8 @@ impl<T: frame_system::Config> super::WeightInfo for WeightInfo<T> {
 		(16_419_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
 	// Storage: ParasShared CurrentSessionIndex (r:1 w:0)
 	// Storage: Configuration PendingConfig (r:1 w:1)
@@ -66,6 +76,8 @@ impl<T: frame_system::Config> super::WeightInfo for WeightInfo<T> {
 		(16_732_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
 	// Storage: Benchmark Override (r:0 w:0)
 	fn set_hrmp_open_request_ttl() -> Weight {
@@ -78,5 +90,17 @@ impl<T: frame_system::Config> super::WeightInfo for WeightInfo<T> {
 		(16_752_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// FIXME: not CLI output yet either. The setter's weight covers both configurations, plus
+	// reading and writing the batch. Applying each change to both and encoding it into the batch
+	// is charged as substrate's benchmarked `remark_with_event` of 64 bytes.
+	fn schedule_config_changes(c: u32, ) -> Weight {
+		<Self as super::WeightInfo>::set_config_with_u32()
+			.saturating_add(frame_system::weights::SubstrateWeight::<T>::remark_with_event(64)
+				.saturating_mul(c as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
 }
@@ -21,26 +21,62 @@ use crate::{
 use frame_support::pallet_prelude::*;
 use primitives::v1::{DownwardMessage, Hash, Id as ParaId, InboundDownwardMessage};
 use sp_runtime::traits::{BlakeTwo256, Hash as HashT, SaturatedConversion};
-use sp_std::{fmt, prelude::*};
+use sp_std::{cmp, fmt, prelude::*};
 use xcm::latest::SendError;
 
 pub use pallet::*;
 
+pub mod migration;
+pub mod runtime_api;
+
+/// The maximum number of messages stored within a single page of a downward message queue.
+pub const DMQ_PAGE_CAPACITY: u64 = 32;
+
 /// An error sending a downward message.
 #[cfg_attr(test, derive(Debug))]
 pub enum QueueDownwardMessageError {
 	/// The message being sent exceeds the configured max message size.
 	ExceedsMaxMessageSize,
+	/// Enqueueing the message would make the recipient's queue exceed its max total size.
+	ExceedsMaxQueueSize,
 }
 
 impl From<QueueDownwardMessageError> for SendError {
 	fn from(err: QueueDownwardMessageError) -> Self {
 		match err {
 			QueueDownwardMessageError::ExceedsMaxMessageSize => SendError::ExceedsMaxMessageSize,
+			QueueDownwardMessageError::ExceedsMaxQueueSize =>
+				SendError::Transport("downward message queue is full"),
 		}
 	}
 }
 
+/// The bookkeeping of a paged downward message queue.
+///
+/// Every message ever enqueued for a para gets a sequential index. The message with index `i` is
+/// stored in the page `i / DMQ_PAGE_CAPACITY`.
+#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct DownwardQueueState {
+	/// The index of the oldest pending message.
+	pub first: u64,
+	/// The index that will be assigned to the next enqueued message.
+	pub next: u64,
+	/// The total size in bytes of all pending messages.
+	pub total_size: u32,
+}
+
+impl DownwardQueueState {
+	/// The number of pending messages.
+	pub fn len(&self) -> u64 {
+		self.next.saturating_sub(self.first)
+	}
+
+	/// Returns `true` if there are no pending messages.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
 /// An error returned by [`check_processed_downward_messages`] that indicates an acceptance check
 /// didn't pass.
 pub enum ProcessedDownwardMessagesAcceptanceErr {
@@ -68,15 +104,25 @@ impl fmt::Debug for ProcessedDownwardMessagesAcceptanceErr {
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
+	use frame_system::pallet_prelude::*;
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(migration::STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config + configuration::Config {}
+	pub trait Config: frame_system::Config + configuration::Config {
+		/// The maximum total size in bytes of the messages pending in the downward message queue
+		/// of a single para.
+		#[pallet::constant]
+		type MaxDownwardQueueSize: Get<u32>;
+	}
 
 	/// The downward messages addressed for a certain para.
+	///
+	/// Deprecated in favour of `DownwardMessageQueuePages`. The contents are moved over by the
+	/// migration to storage version 1, so this is empty afterwards.
 	#[pallet::storage]
 	pub(crate) type DownwardMessageQueues<T: Config> = StorageMap<
 		_,
@@ -86,6 +132,25 @@ pub mod pallet {
 		ValueQuery,
 	>;
 
+	/// The pages of the downward message queue of a certain para, keyed by the page index.
+	///
+	/// Pages that only contain processed messages are removed.
+	#[pallet::storage]
+	pub(crate) type DownwardMessageQueuePages<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		ParaId,
+		Twox64Concat,
+		u64,
+		Vec<InboundDownwardMessage<T::BlockNumber>>,
+		ValueQuery,
+	>;
+
+	/// The bookkeeping of the downward message queue of a certain para.
+	#[pallet::storage]
+	pub(crate) type DownwardMessageQueueState<T: Config> =
+		StorageMap<_, Twox64Concat, ParaId, DownwardQueueState, ValueQuery>;
+
 	/// A mapping that stores the downward message queue MQC head for each para.
 	///
 	/// Each link in this chain has a form:
@@ -97,6 +162,13 @@ pub mod pallet {
 	pub(crate) type DownwardMessageQueueHeads<T: Config> =
 		StorageMap<_, Twox64Concat, ParaId, Hash, ValueQuery>;
 
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			migration::migrate_to_latest::<T>()
+		}
+	}
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {}
 }
@@ -129,13 +201,15 @@ impl<T: Config> Pallet<T> {
 
 	/// Remove all relevant storage items for an outgoing parachain.
 	fn clean_dmp_after_outgoing(outgoing_para: &ParaId) {
-		<Self as Store>::DownwardMessageQueues::remove(outgoing_para);
+		<Self as Store>::DownwardMessageQueueState::remove(outgoing_para);
+		let _ = <Self as Store>::DownwardMessageQueuePages::remove_prefix(outgoing_para, None);
 		<Self as Store>::DownwardMessageQueueHeads::remove(outgoing_para);
 	}
 
 	/// Enqueue a downward message to a specific recipient para.
 	///
-	/// When encoded, the message should not exceed the `config.max_downward_message_size`.
+	/// When encoded, the message should not exceed the `config.max_downward_message_size`, and
+	/// the total size of the recipient's queue should not exceed `T::MaxDownwardQueueSize`.
 	/// Otherwise, the message won't be sent and `Err` will be returned.
 	///
 	/// It is possible to send a downward message to a non-existent para. That, however, would lead
@@ -147,9 +221,9 @@ impl<T: Config> Pallet<T> {
 		msg: DownwardMessage,
 	) -> Result<(), QueueDownwardMessageError> {
 		let serialized_len = msg.len() as u32;
-		if serialized_len > config.max_downward_message_size {
-			return Err(QueueDownwardMessageError::ExceedsMaxMessageSize)
-		}
+		Self::can_queue_downward_message(config, para, serialized_len)?;
+
+		let mut state = <Self as Store>::DownwardMessageQueueState::get(para);
 
 		let inbound =
 			InboundDownwardMessage { msg, sent_at: <frame_system::Pallet<T>>::block_number() };
@@ -161,9 +235,34 @@ impl<T: Config> Pallet<T> {
 			*head = new_head;
 		});
 
-		<Self as Store>::DownwardMessageQueues::mutate(para, |v| {
-			v.push(inbound);
-		});
+		// appending doesn't require decoding the page.
+		<Self as Store>::DownwardMessageQueuePages::append(
+			para,
+			state.next / DMQ_PAGE_CAPACITY,
+			inbound,
+		);
+		state.next += 1;
+		state.total_size += serialized_len;
+		<Self as Store>::DownwardMessageQueueState::insert(para, state);
+
+		Ok(())
+	}
+
+	/// Checks whether a downward message of `serialized_len` bytes could be enqueued for the
+	/// given para right now, without enqueueing anything.
+	pub fn can_queue_downward_message(
+		config: &HostConfiguration<T::BlockNumber>,
+		para: ParaId,
+		serialized_len: u32,
+	) -> Result<(), QueueDownwardMessageError> {
+		if serialized_len > config.max_downward_message_size {
+			return Err(QueueDownwardMessageError::ExceedsMaxMessageSize)
+		}
+
+		let total_size = <Self as Store>::DownwardMessageQueueState::get(para).total_size;
+		if total_size.saturating_add(serialized_len) > T::MaxDownwardQueueSize::get() {
+			return Err(QueueDownwardMessageError::ExceedsMaxQueueSize)
+		}
 
 		Ok(())
 	}
@@ -190,17 +289,53 @@ impl<T: Config> Pallet<T> {
 
 	/// Prunes the specified number of messages from the downward message queue of the given para.
 	pub(crate) fn prune_dmq(para: ParaId, processed_downward_messages: u32) -> Weight {
-		<Self as Store>::DownwardMessageQueues::mutate(para, |q| {
-			let processed_downward_messages = processed_downward_messages as usize;
-			if processed_downward_messages > q.len() {
-				// reaching this branch is unexpected due to the constraint established by
-				// `check_processed_downward_messages`. But better be safe than sorry.
-				q.clear();
-			} else {
-				*q = q.split_off(processed_downward_messages);
+		let mut state = <Self as Store>::DownwardMessageQueueState::get(para);
+		if processed_downward_messages == 0 || state.is_empty() {
+			return T::DbWeight::get().reads(1)
+		}
+
+		// pruning more than `len` messages is unexpected due to the constraint established by
+		// `check_processed_downward_messages`. But better be safe than sorry.
+		let new_first = state.first + cmp::min(processed_downward_messages as u64, state.len());
+		let (mut reads, mut writes) = (1, 1);
+
+		if new_first == state.next {
+			// the queue is drained, so all its pages go and the indices start over.
+			let _ = <Self as Store>::DownwardMessageQueuePages::remove_prefix(para, None);
+			<Self as Store>::DownwardMessageQueueState::remove(para);
+			let pages = state.next.saturating_sub(1) / DMQ_PAGE_CAPACITY -
+				state.first / DMQ_PAGE_CAPACITY +
+				1;
+			return T::DbWeight::get().reads_writes(reads, writes + pages)
+		}
+
+		// walk the pages holding the processed messages in order to account for their sizes.
+		let mut page = state.first / DMQ_PAGE_CAPACITY;
+		while page * DMQ_PAGE_CAPACITY < new_first {
+			let page_start = page * DMQ_PAGE_CAPACITY;
+			let page_end = page_start + DMQ_PAGE_CAPACITY;
+			let skip = (cmp::max(state.first, page_start) - page_start) as usize;
+			let take = (cmp::min(new_first, page_end) - page_start) as usize - skip;
+
+			let messages = <Self as Store>::DownwardMessageQueuePages::get(para, page);
+			reads += 1;
+			let pruned_size = messages
+				.iter()
+				.skip(skip)
+				.take(take)
+				.fold(0u32, |acc, m| acc.saturating_add(m.msg.len() as u32));
+			state.total_size = state.total_size.saturating_sub(pruned_size);
+
+			if page_end <= new_first {
+				<Self as Store>::DownwardMessageQueuePages::remove(para, page);
+				writes += 1;
 			}
-		});
-		T::DbWeight::get().reads_writes(1, 1)
+			page += 1;
+		}
+
+		state.first = new_first;
+		<Self as Store>::DownwardMessageQueueState::insert(para, state);
+		T::DbWeight::get().reads_writes(reads, writes)
 	}
 
 	/// Returns the Head of Message Queue Chain for the given para or `None` if there is none
@@ -214,16 +349,53 @@ impl<T: Config> Pallet<T> {
 	///
 	/// Returns 0 if the para doesn't have an associated downward message queue.
 	pub(crate) fn dmq_length(para: ParaId) -> u32 {
-		<Self as Store>::DownwardMessageQueues::decode_len(&para)
-			.unwrap_or(0)
+		<Self as Store>::DownwardMessageQueueState::get(&para)
+			.len()
 			.saturated_into::<u32>()
 	}
 
+	/// Returns the total size in bytes of the pending downward messages addressed to the given
+	/// para.
+	pub fn dmq_total_size(para: ParaId) -> u32 {
+		<Self as Store>::DownwardMessageQueueState::get(&para).total_size
+	}
+
 	/// Returns the downward message queue contents for the given para.
 	///
 	/// The most recent messages are the latest in the vector.
 	pub(crate) fn dmq_contents(recipient: ParaId) -> Vec<InboundDownwardMessage<T::BlockNumber>> {
-		<Self as Store>::DownwardMessageQueues::get(&recipient)
+		Self::dmq_contents_page(recipient, 0, u32::MAX)
+	}
+
+	/// Returns at most `count` pending downward messages for the given para, starting with the
+	/// message at position `start` of the queue.
+	///
+	/// Position 0 is the oldest pending message. Only the pages holding the requested messages
+	/// are read.
+	pub(crate) fn dmq_contents_page(
+		recipient: ParaId,
+		start: u32,
+		count: u32,
+	) -> Vec<InboundDownwardMessage<T::BlockNumber>> {
+		let state = <Self as Store>::DownwardMessageQueueState::get(&recipient);
+		let begin = state.first.saturating_add(start as u64);
+		let end = cmp::min(begin.saturating_add(count as u64), state.next);
+
+		let mut messages = Vec::new();
+		let mut index = begin;
+		while index < end {
+			let page = index / DMQ_PAGE_CAPACITY;
+			let offset = index % DMQ_PAGE_CAPACITY;
+			let take = cmp::min(end - index, DMQ_PAGE_CAPACITY - offset);
+			messages.extend(
+				<Self as Store>::DownwardMessageQueuePages::get(&recipient, page)
+					.into_iter()
+					.skip(offset as usize)
+					.take(take as usize),
+			);
+			index += take;
+		}
+		messages
 	}
 }
 
@@ -288,9 +460,11 @@ mod tests {
 			let outgoing_paras = vec![a, b];
 			Dmp::initializer_on_new_session(&notification, &outgoing_paras);
 
-			assert!(<Dmp as Store>::DownwardMessageQueues::get(&a).is_empty());
-			assert!(<Dmp as Store>::DownwardMessageQueues::get(&b).is_empty());
-			assert!(!<Dmp as Store>::DownwardMessageQueues::get(&c).is_empty());
+			assert!(Dmp::dmq_contents(a).is_empty());
+			assert!(Dmp::dmq_contents(b).is_empty());
+			assert!(!Dmp::dmq_contents(c).is_empty());
+			assert_eq!(<Dmp as Store>::DownwardMessageQueuePages::iter_prefix(&a).count(), 0);
+			assert_eq!(<Dmp as Store>::DownwardMessageQueueState::get(&a), Default::default());
 		});
 	}
 
@@ -396,6 +570,75 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn queue_downward_message_respects_max_queue_size() {
+		let a = ParaId::from(1312);
+		let b = ParaId::from(228);
+
+		let mut genesis = default_genesis_config();
+		genesis.configuration.config.max_downward_message_size = 4096;
+
+		new_test_ext(genesis).execute_with(|| {
+			queue_downward_message(a, vec![0; 4000]).unwrap();
+			queue_downward_message(a, vec![0; 96]).unwrap();
+			assert_eq!(Dmp::dmq_total_size(a), 4096);
+
+			// the queue of `a` is full, but that doesn't affect other paras.
+			assert!(matches!(
+				queue_downward_message(a, vec![0]),
+				Err(QueueDownwardMessageError::ExceedsMaxQueueSize)
+			));
+			assert!(queue_downward_message(b, vec![0]).is_ok());
+
+			// processing messages makes room again.
+			Dmp::prune_dmq(a, 1);
+			assert_eq!(Dmp::dmq_total_size(a), 96);
+			assert!(queue_downward_message(a, vec![0]).is_ok());
+		});
+	}
+
+	#[test]
+	fn dmq_pages_work() {
+		let a = ParaId::from(1312);
+		let n = 2 * DMQ_PAGE_CAPACITY as u32 + 5;
+
+		new_test_ext(default_genesis_config()).execute_with(|| {
+			for i in 0..n {
+				queue_downward_message(a, vec![i as u8]).unwrap();
+			}
+			assert_eq!(<Dmp as Store>::DownwardMessageQueuePages::iter_prefix(&a).count(), 3);
+			assert_eq!(Dmp::dmq_length(a), n);
+			assert_eq!(Dmp::dmq_contents(a).len(), n as usize);
+
+			// a page spanning over a page boundary.
+			let msgs = |start, count| {
+				Dmp::dmq_contents_page(a, start, count)
+					.into_iter()
+					.map(|m| m.msg[0] as u32)
+					.collect::<Vec<_>>()
+			};
+			let boundary = DMQ_PAGE_CAPACITY as u32;
+			assert_eq!(msgs(boundary - 2, 4), (boundary - 2..boundary + 2).collect::<Vec<_>>());
+			// out of bounds requests are truncated.
+			assert_eq!(msgs(n - 1, 10), vec![n - 1]);
+			assert!(msgs(n, 10).is_empty());
+
+			// pruning past the first page removes it and shifts the positions.
+			Dmp::prune_dmq(a, boundary + 1);
+			assert_eq!(<Dmp as Store>::DownwardMessageQueuePages::iter_prefix(&a).count(), 2);
+			assert_eq!(Dmp::dmq_length(a), n - boundary - 1);
+			assert_eq!(Dmp::dmq_total_size(a), n - boundary - 1);
+			assert_eq!(msgs(0, 2), vec![boundary + 1, boundary + 2]);
+
+			// draining the queue removes all the pages and starts over.
+			Dmp::prune_dmq(a, n);
+			assert_eq!(<Dmp as Store>::DownwardMessageQueuePages::iter_prefix(&a).count(), 0);
+			assert_eq!(<Dmp as Store>::DownwardMessageQueueState::get(&a), Default::default());
+			queue_downward_message(a, vec![42]).unwrap();
+			assert_eq!(msgs(0, 1), vec![42]);
+		});
+	}
+
 	#[test]
 	fn verify_dmq_mqc_head_is_externally_accessible() {
 		use hex_literal::hex;
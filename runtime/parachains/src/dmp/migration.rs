@@ -0,0 +1,96 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module that is responsible for migration of storage.
+
+use crate::dmp::{self, Config, Pallet, Store, DMQ_PAGE_CAPACITY};
+use frame_support::{pallet_prelude::*, traits::StorageVersion, weights::Weight};
+use sp_std::prelude::*;
+
+/// The current storage version.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+/// Migrates the pallet storage to the most recent version, checking and setting the `StorageVersion`.
+pub fn migrate_to_latest<T: Config>() -> Weight {
+	let mut weight = 0;
+
+	if StorageVersion::get::<Pallet<T>>() == 0 {
+		weight += migrate_to_v1::<T>();
+		StorageVersion::new(1).put::<Pallet<T>>();
+	}
+
+	weight
+}
+
+/// Moves the downward message queues from v0, where each queue is a single storage value, to v1,
+/// where each queue is split into pages of `DMQ_PAGE_CAPACITY` messages.
+///
+/// NOTE: Only use this function if you know what you are doing. Default to using `migrate_to_latest`.
+pub fn migrate_to_v1<T: Config>() -> Weight {
+	let (mut reads, mut writes) = (0, 0);
+
+	for (para, queue) in <Pallet<T> as Store>::DownwardMessageQueues::drain() {
+		reads += 1;
+		writes += 2;
+
+		let mut state = dmp::DownwardQueueState::default();
+		for (page, messages) in queue.chunks(DMQ_PAGE_CAPACITY as usize).enumerate() {
+			state.next += messages.len() as u64;
+			state.total_size = messages
+				.iter()
+				.fold(state.total_size, |acc, m| acc.saturating_add(m.msg.len() as u32));
+			<Pallet<T> as Store>::DownwardMessageQueuePages::insert(
+				para,
+				page as u64,
+				messages.to_vec(),
+			);
+			writes += 1;
+		}
+		<Pallet<T> as Store>::DownwardMessageQueueState::insert(para, state);
+	}
+
+	T::DbWeight::get().reads_writes(reads, writes)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{new_test_ext, Dmp, MockGenesisConfig, Test};
+	use primitives::v1::{Id as ParaId, InboundDownwardMessage};
+
+	#[test]
+	fn test_migrate_to_v1() {
+		let a = ParaId::from(1000);
+		let b = ParaId::from(2000);
+		let queue: Vec<_> = (0..DMQ_PAGE_CAPACITY as u32 + 3)
+			.map(|i| InboundDownwardMessage { sent_at: i, msg: vec![i as u8; 2] })
+			.collect();
+
+		new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+			<Dmp as Store>::DownwardMessageQueues::insert(a, queue.clone());
+			<Dmp as Store>::DownwardMessageQueues::insert(b, vec![queue[0].clone()]);
+
+			migrate_to_v1::<Test>();
+
+			assert_eq!(<Dmp as Store>::DownwardMessageQueues::iter().count(), 0);
+			assert_eq!(<Dmp as Store>::DownwardMessageQueuePages::iter_prefix(&a).count(), 2);
+			assert_eq!(Dmp::dmq_contents(a), queue);
+			assert_eq!(Dmp::dmq_length(a), DMQ_PAGE_CAPACITY as u32 + 3);
+			assert_eq!(Dmp::dmq_total_size(a), 2 * (DMQ_PAGE_CAPACITY as u32 + 3));
+			assert_eq!(Dmp::dmq_contents(b), vec![queue[0].clone()]);
+		});
+	}
+}
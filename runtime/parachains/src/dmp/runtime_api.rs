@@ -0,0 +1,34 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API for reading the downward message queues page by page.
+
+use parity_scale_codec::{Decode, Encode};
+use primitives::v1::{Id as ParaId, InboundDownwardMessage};
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	/// The API for reading the downward message queues without loading them entirely.
+	pub trait DmpApi<N: Encode + Decode> {
+		/// Get the number of downward messages pending for the given para.
+		fn dmq_length(recipient: ParaId) -> u32;
+
+		/// Get at most `count` downward messages pending for the given para, starting with the
+		/// message at position `start` of the queue. Position 0 is the oldest pending message.
+		fn dmq_contents_page(recipient: ParaId, start: u32, count: u32)
+			-> Vec<InboundDownwardMessage<N>>;
+	}
+}
@@ -22,8 +22,8 @@ use frame_support::{pallet_prelude::*, traits::ReservableCurrency};
 use frame_system::pallet_prelude::*;
 use parity_scale_codec::{Decode, Encode};
 use primitives::v1::{
-	Balance, Hash, HrmpChannelId, Id as ParaId, InboundHrmpMessage, OutboundHrmpMessage,
-	SessionIndex,
+	Balance, Hash, HrmpChannelId, HrmpChannelState, HrmpOpenChannelRequestState, HrmpParaState,
+	Id as ParaId, InboundHrmpMessage, OutboundHrmpMessage, SessionIndex, LOWEST_USER_ID,
 };
 use scale_info::TypeInfo;
 use sp_runtime::traits::{AccountIdConversion, BlakeTwo256, Hash as HashT, UniqueSaturatedInto};
@@ -194,6 +194,9 @@ pub mod pallet {
 		/// pallet. Specifically, that means that the `Balance` of the `Currency` implementation should
 		/// be the same as `Balance` as used in the `Configuration`.
 		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Origin which is allowed to open channels between system parachains on their behalf.
+		type ChannelManager: EnsureOrigin<<Self as frame_system::Config>::Origin>;
 	}
 
 	#[pallet::event]
@@ -209,6 +212,9 @@ pub mod pallet {
 		OpenChannelAccepted(ParaId, ParaId),
 		/// HRMP channel closed. `[by_parachain, channel_id]`
 		ChannelClosed(ParaId, HrmpChannelId),
+		/// An HRMP channel between system parachains was opened by the channel manager.
+		/// `[sender, recipient]`
+		SystemChannelOpened(ParaId, ParaId),
 	}
 
 	#[pallet::error]
@@ -249,6 +255,8 @@ pub mod pallet {
 		OpenHrmpChannelDoesntExist,
 		/// Cannot cancel an HRMP open channel request because it is already confirmed.
 		OpenHrmpChannelAlreadyConfirmed,
+		/// Either end of the channel requested by the channel manager is not a system parachain.
+		OpenHrmpChannelNotSystem,
 	}
 
 	/// The set of pending HRMP open channel requests.
@@ -361,22 +369,30 @@ pub mod pallet {
 	///
 	/// 1. `max_capacity` and `max_message_size` should be within the limits set by the configuration pallet.
 	/// 2. `sender` and `recipient` must be valid paras.
+	///
+	/// Additionally, channels in both directions are opened between every pair of the system
+	/// parachains listed in `system_channel_mesh`, as if `establish_system_channel_mesh` was
+	/// called with them.
 	#[pallet::genesis_config]
 	pub struct GenesisConfig {
 		preopen_hrmp_channels: Vec<(ParaId, ParaId, u32, u32)>,
+		system_channel_mesh: Vec<ParaId>,
 	}
 
 	#[cfg(feature = "std")]
 	impl Default for GenesisConfig {
 		fn default() -> Self {
-			GenesisConfig { preopen_hrmp_channels: Default::default() }
+			GenesisConfig {
+				preopen_hrmp_channels: Default::default(),
+				system_channel_mesh: Default::default(),
+			}
 		}
 	}
 
 	#[pallet::genesis_build]
 	impl<T: Config> GenesisBuild<T> for GenesisConfig {
 		fn build(&self) {
-			initialize_storage::<T>(&self.preopen_hrmp_channels);
+			initialize_storage::<T>(&self.preopen_hrmp_channels, &self.system_channel_mesh);
 		}
 	}
 
@@ -491,11 +507,73 @@ pub mod pallet {
 			Self::deposit_event(Event::OpenChannelCanceled(origin, channel_id));
 			Ok(())
 		}
+
+		/// Open channels in both directions between two system parachains, with the maximum
+		/// capacity and message size allowed by the configuration.
+		///
+		/// The channels are opened immediately. No deposits are taken from either side and the
+		/// limits on the number of channels of a para do not apply. Directions which are already
+		/// open are left as they are, while pending requests made by the paras themselves are
+		/// enacted right away.
+		///
+		/// Origin must pass `ChannelManager`.
+		#[pallet::weight(0)]
+		pub fn establish_system_channel(
+			origin: OriginFor<T>,
+			a: ParaId,
+			b: ParaId,
+		) -> DispatchResult {
+			T::ChannelManager::ensure_origin(origin)?;
+			Self::open_system_channel_mesh(&[a, b])
+		}
+
+		/// Open channels in both directions between every pair of the given system parachains.
+		///
+		/// The same rules as for [`establish_system_channel`](Pallet::establish_system_channel)
+		/// apply to every channel of the mesh.
+		///
+		/// Origin must pass `ChannelManager`.
+		#[pallet::weight(0)]
+		pub fn establish_system_channel_mesh(
+			origin: OriginFor<T>,
+			paras: Vec<ParaId>,
+		) -> DispatchResult {
+			T::ChannelManager::ensure_origin(origin)?;
+			Self::open_system_channel_mesh(&paras)
+		}
+	}
+}
+
+/// A migration which opens a mesh of channels between the system parachains given by `Paras`
+/// upon a runtime upgrade.
+///
+/// Channels that already exist are left untouched, so the migration can be kept around for more
+/// than one upgrade.
+pub struct EstablishSystemChannelMesh<T, Paras>(sp_std::marker::PhantomData<(T, Paras)>);
+
+impl<T: Config, Paras: Get<Vec<ParaId>>> frame_support::traits::OnRuntimeUpgrade
+	for EstablishSystemChannelMesh<T, Paras>
+{
+	fn on_runtime_upgrade() -> Weight {
+		let paras = Paras::get();
+		if let Err(err) = <Pallet<T>>::open_system_channel_mesh(&paras) {
+			log::error!(
+				target: "runtime::hrmp",
+				"failed to establish the system channel mesh: {:?}",
+				err,
+			);
+		}
+
+		let channels = (paras.len() * paras.len().saturating_sub(1)) as Weight;
+		T::DbWeight::get().reads_writes(4 * channels + 1, 5 * channels + 1)
 	}
 }
 
 #[cfg(feature = "std")]
-fn initialize_storage<T: Config>(preopen_hrmp_channels: &[(ParaId, ParaId, u32, u32)]) {
+fn initialize_storage<T: Config>(
+	preopen_hrmp_channels: &[(ParaId, ParaId, u32, u32)],
+	system_channel_mesh: &[ParaId],
+) {
 	let host_config = configuration::Pallet::<T>::config();
 	for &(sender, recipient, max_capacity, max_message_size) in preopen_hrmp_channels {
 		if let Err(err) =
@@ -504,6 +582,9 @@ fn initialize_storage<T: Config>(preopen_hrmp_channels: &[(ParaId, ParaId, u32,
 			panic!("failed to initialize the genesis storage: {:?}", err);
 		}
 	}
+	if let Err(err) = <Pallet<T>>::open_system_channel_mesh(system_channel_mesh) {
+		panic!("failed to initialize the genesis storage: {:?}", err);
+	}
 	<Pallet<T>>::process_hrmp_open_channel_requests(&host_config);
 }
 
@@ -654,7 +735,7 @@ impl<T: Config> Pallet<T> {
 				if <paras::Pallet<T>>::is_valid_para(channel_id.sender) &&
 					<paras::Pallet<T>>::is_valid_para(channel_id.recipient)
 				{
-					<Self as Store>::HrmpChannels::insert(
+					Self::insert_channel(
 						&channel_id,
 						HrmpChannel {
 							sender_deposit: request.sender_deposit,
@@ -667,17 +748,6 @@ impl<T: Config> Pallet<T> {
 							mqc_head: None,
 						},
 					);
-
-					<Self as Store>::HrmpIngressChannelsIndex::mutate(&channel_id.recipient, |v| {
-						if let Err(i) = v.binary_search(&channel_id.sender) {
-							v.insert(i, channel_id.sender);
-						}
-					});
-					<Self as Store>::HrmpEgressChannelsIndex::mutate(&channel_id.sender, |v| {
-						if let Err(i) = v.binary_search(&channel_id.recipient) {
-							v.insert(i, channel_id.recipient);
-						}
-					});
 				}
 
 				Self::decrease_open_channel_request_count(channel_id.sender);
@@ -1125,6 +1195,93 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// Open channels in both directions between every pair of the given system parachains,
+	/// bypassing the deposits and the channel number limits.
+	///
+	/// Basically the same as
+	/// [`establish_system_channel_mesh`](Pallet::establish_system_channel_mesh) but intendend
+	/// for calling directly from other pallets or migrations rather than dispatched.
+	pub fn open_system_channel_mesh(paras: &[ParaId]) -> DispatchResult {
+		for &para in paras {
+			ensure!(para < LOWEST_USER_ID, Error::<T>::OpenHrmpChannelNotSystem);
+			ensure!(
+				<paras::Pallet<T>>::is_valid_para(para),
+				Error::<T>::OpenHrmpChannelInvalidRecipient,
+			);
+		}
+
+		let config = <configuration::Pallet<T>>::config();
+		for &sender in paras {
+			for &recipient in paras {
+				if sender != recipient {
+					Self::open_system_channel(&config, HrmpChannelId { sender, recipient });
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Open the given channel between two valid system parachains right away.
+	///
+	/// Existing channels are left untouched. A pending request made by the paras themselves is
+	/// enacted, keeping the deposits they have already put up.
+	fn open_system_channel(config: &HostConfiguration<T::BlockNumber>, channel_id: HrmpChannelId) {
+		if <Self as Store>::HrmpChannels::contains_key(&channel_id) {
+			return
+		}
+
+		let mut channel = HrmpChannel {
+			max_capacity: config.hrmp_channel_max_capacity,
+			max_total_size: config.hrmp_channel_max_total_size,
+			max_message_size: config.hrmp_channel_max_message_size,
+			msg_count: 0,
+			total_size: 0,
+			mqc_head: None,
+			sender_deposit: 0,
+			recipient_deposit: 0,
+		};
+
+		if let Some(request) = <Self as Store>::HrmpOpenChannelRequests::take(&channel_id) {
+			<Self as Store>::HrmpOpenChannelRequestsList::mutate(|open_req_channels| {
+				if let Some(pos) = open_req_channels.iter().position(|x| x == &channel_id) {
+					open_req_channels.swap_remove(pos);
+				}
+			});
+			Self::decrease_open_channel_request_count(channel_id.sender);
+
+			channel.max_capacity = request.max_capacity;
+			channel.max_total_size = request.max_total_size;
+			channel.max_message_size = request.max_message_size;
+			channel.sender_deposit = request.sender_deposit;
+			if request.confirmed {
+				// The request was confirmed within this session, so the recipient put up the
+				// deposit set in the current configuration.
+				channel.recipient_deposit = config.hrmp_recipient_deposit;
+				Self::decrease_accepted_channel_request_count(channel_id.recipient);
+			}
+		}
+
+		Self::insert_channel(&channel_id, channel);
+		Self::deposit_event(Event::SystemChannelOpened(channel_id.sender, channel_id.recipient));
+	}
+
+	/// Insert the given channel and register it in the ingress and egress indexes.
+	fn insert_channel(channel_id: &HrmpChannelId, channel: HrmpChannel) {
+		<Self as Store>::HrmpChannels::insert(channel_id, channel);
+
+		<Self as Store>::HrmpIngressChannelsIndex::mutate(&channel_id.recipient, |v| {
+			if let Err(i) = v.binary_search(&channel_id.sender) {
+				v.insert(i, channel_id.sender);
+			}
+		});
+		<Self as Store>::HrmpEgressChannelsIndex::mutate(&channel_id.sender, |v| {
+			if let Err(i) = v.binary_search(&channel_id.recipient) {
+				v.insert(i, channel_id.recipient);
+			}
+		});
+	}
+
 	fn cancel_open_request(origin: ParaId, channel_id: HrmpChannelId) -> DispatchResult {
 		// check if the origin is allowed to close the channel.
 		ensure!(channel_id.is_participant(origin), Error::<T>::CancelHrmpOpenChannelUnauthorized);
@@ -1236,6 +1393,70 @@ impl<T: Config> Pallet<T> {
 
 		inbound_hrmp_channels_contents
 	}
+
+	/// Returns the state of all channels and channel requests the given para is an end of,
+	/// along with its watermark.
+	pub(crate) fn hrmp_para_state(para: ParaId) -> HrmpParaState<T::BlockNumber> {
+		let channel_state = |id: HrmpChannelId| {
+			let channel = <Self as Store>::HrmpChannels::get(&id)?;
+			let message_sizes = <Self as Store>::HrmpChannelContents::get(&id)
+				.iter()
+				.map(|message| message.data.len() as u32)
+				.collect();
+
+			Some(HrmpChannelState {
+				sender: id.sender,
+				recipient: id.recipient,
+				max_capacity: channel.max_capacity,
+				max_total_size: channel.max_total_size,
+				max_message_size: channel.max_message_size,
+				msg_count: channel.msg_count,
+				total_size: channel.total_size,
+				mqc_head: channel.mqc_head,
+				message_sizes,
+			})
+		};
+
+		let inbound = <Self as Store>::HrmpIngressChannelsIndex::get(&para)
+			.into_iter()
+			.filter_map(|sender| channel_state(HrmpChannelId { sender, recipient: para }))
+			.collect();
+		let outbound = <Self as Store>::HrmpEgressChannelsIndex::get(&para)
+			.into_iter()
+			.filter_map(|recipient| channel_state(HrmpChannelId { sender: para, recipient }))
+			.collect();
+
+		let concerns_para = |id: &HrmpChannelId| id.sender == para || id.recipient == para;
+
+		let open_requests = <Self as Store>::HrmpOpenChannelRequestsList::get()
+			.into_iter()
+			.filter(concerns_para)
+			.filter_map(|id| {
+				let request = <Self as Store>::HrmpOpenChannelRequests::get(&id)?;
+				Some(HrmpOpenChannelRequestState {
+					sender: id.sender,
+					recipient: id.recipient,
+					confirmed: request.confirmed,
+					max_capacity: request.max_capacity,
+					max_total_size: request.max_total_size,
+					max_message_size: request.max_message_size,
+				})
+			})
+			.collect();
+		let close_requests = <Self as Store>::HrmpCloseChannelRequestsList::get()
+			.into_iter()
+			.filter(concerns_para)
+			.map(|id| (id.sender, id.recipient))
+			.collect();
+
+		HrmpParaState {
+			inbound,
+			outbound,
+			open_requests,
+			close_requests,
+			watermark: <Self as Store>::HrmpWatermarks::get(&para),
+		}
+	}
 }
 
 impl<T: Config> Pallet<T> {
@@ -1266,7 +1487,7 @@ impl<T: Config> Pallet<T> {
 mod tests {
 	use super::*;
 	use crate::mock::{
-		new_test_ext, Configuration, Event as MockEvent, Hrmp, MockGenesisConfig, Paras,
+		new_test_ext, Configuration, Event as MockEvent, Hrmp, MockGenesisConfig, Origin, Paras,
 		ParasShared, System, Test,
 	};
 	use frame_support::{assert_noop, assert_ok, traits::Currency as _};
@@ -1670,6 +1891,55 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn hrmp_para_state_reports_channels_and_requests() {
+		let para_a = 32.into();
+		let para_b = 64.into();
+		let para_c = 128.into();
+
+		let mut genesis = GenesisConfigBuilder::default();
+		genesis.hrmp_channel_max_message_size = 20;
+		genesis.hrmp_channel_max_total_size = 20;
+		new_test_ext(genesis.build()).execute_with(|| {
+			register_parachain(para_a);
+			register_parachain(para_b);
+			register_parachain(para_c);
+
+			run_to_block(5, Some(vec![4, 5]));
+			Hrmp::init_open_channel(para_a, para_b, 2, 20).unwrap();
+			Hrmp::accept_open_channel(para_b, para_a).unwrap();
+
+			run_to_block(6, Some(vec![6]));
+			Hrmp::init_open_channel(para_c, para_b, 2, 20).unwrap();
+			let msgs =
+				vec![OutboundHrmpMessage { recipient: para_b, data: b"knock knock".to_vec() }];
+			let _ = Hrmp::queue_outbound_hrmp(para_a, msgs);
+
+			run_to_block(7, None);
+			let _ = Hrmp::prune_hrmp(para_b, 5);
+
+			let state = Hrmp::hrmp_para_state(para_b);
+			assert!(state.outbound.is_empty());
+			assert_eq!(state.inbound.len(), 1);
+			let channel = &state.inbound[0];
+			assert_eq!((channel.sender, channel.recipient), (para_a, para_b));
+			assert_eq!((channel.msg_count, channel.total_size), (1, 11));
+			assert_eq!(channel.message_sizes, vec![11]);
+			assert!(channel.mqc_head.is_some());
+			assert_eq!(state.open_requests.len(), 1);
+			assert_eq!(state.open_requests[0].sender, para_c);
+			assert!(!state.open_requests[0].confirmed);
+			assert!(state.close_requests.is_empty());
+			assert_eq!(state.watermark, Some(5));
+
+			let state = Hrmp::hrmp_para_state(para_a);
+			assert!(state.inbound.is_empty());
+			assert_eq!(state.outbound.len(), 1);
+			assert!(state.open_requests.is_empty());
+			assert_eq!(state.watermark, None);
+		});
+	}
+
 	#[test]
 	fn hrmp_mqc_head_fixture() {
 		let para_a = 2000.into();
@@ -1882,6 +2152,110 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn establish_system_channel_mesh_works() {
+		let para_a = 1.into();
+		let para_b = 2.into();
+		let para_c = 3.into();
+		let para_d = 2000.into();
+		let para_a_origin: crate::Origin = 1.into();
+
+		new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+			register_parachain(para_a);
+			register_parachain(para_b);
+			register_parachain(para_c);
+			register_parachain(para_d);
+
+			run_to_block(5, Some(vec![4, 5]));
+			assert_noop!(
+				Hrmp::establish_system_channel_mesh(
+					para_a_origin.into(),
+					vec![para_a, para_b, para_c],
+				),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Hrmp::establish_system_channel(Origin::root(), para_a, para_d),
+				Error::<Test>::OpenHrmpChannelNotSystem,
+			);
+
+			assert_ok!(Hrmp::establish_system_channel_mesh(
+				Origin::root(),
+				vec![para_a, para_b, para_c],
+			));
+			assert_storage_consistency_exhaustive();
+
+			// The channels are opened right away, with the maximum parameters and without any
+			// deposits.
+			for &(sender, recipient) in &[
+				(para_a, para_b),
+				(para_a, para_c),
+				(para_b, para_a),
+				(para_b, para_c),
+				(para_c, para_a),
+				(para_c, para_b),
+			] {
+				let channel =
+					<Hrmp as Store>::HrmpChannels::get(&HrmpChannelId { sender, recipient })
+						.unwrap();
+				assert_eq!((channel.max_capacity, channel.max_message_size), (2, 8));
+				assert_eq!((channel.sender_deposit, channel.recipient_deposit), (0, 0));
+				assert!(System::events().iter().any(|record| record.event ==
+					MockEvent::Hrmp(Event::SystemChannelOpened(sender, recipient))));
+			}
+			for &para in &[para_a, para_b, para_c] {
+				assert_eq!(<Test as Config>::Currency::free_balance(&para.into_account()), 1000);
+			}
+
+			// Establishing the mesh again is a no-op.
+			assert_ok!(Hrmp::establish_system_channel(Origin::root(), para_a, para_b));
+			assert_eq!(
+				<Hrmp as Store>::HrmpEgressChannelsIndex::get(&para_a),
+				vec![para_b, para_c]
+			);
+			assert_storage_consistency_exhaustive();
+		});
+	}
+
+	#[test]
+	fn establish_system_channel_enacts_pending_requests() {
+		let para_a = 1.into();
+		let para_b = 2.into();
+
+		new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+			register_parachain_with_balance(para_a, 100);
+			register_parachain_with_balance(para_b, 100);
+
+			run_to_block(5, Some(vec![4, 5]));
+			Hrmp::init_open_channel(para_a, para_b, 1, 4).unwrap();
+			assert_eq!(<Test as Config>::Currency::free_balance(&para_a.into_account()), 0);
+
+			assert_ok!(Hrmp::establish_system_channel(Origin::root(), para_a, para_b));
+			assert!(channel_exists(para_a, para_b));
+			assert!(channel_exists(para_b, para_a));
+			assert!(<Hrmp as Store>::HrmpOpenChannelRequestsList::get().is_empty());
+			assert_storage_consistency_exhaustive();
+
+			// The request of A is enacted with its own parameters and deposit.
+			let channel = <Hrmp as Store>::HrmpChannels::get(&HrmpChannelId {
+				sender: para_a,
+				recipient: para_b,
+			})
+			.unwrap();
+			assert_eq!((channel.max_capacity, channel.max_message_size), (1, 4));
+			assert_eq!((channel.sender_deposit, channel.recipient_deposit), (100, 0));
+
+			// Closing the channel refunds the deposit of A.
+			Hrmp::close_channel(para_a, HrmpChannelId { sender: para_a, recipient: para_b })
+				.unwrap();
+			run_to_block(6, Some(vec![6]));
+			assert!(!channel_exists(para_a, para_b));
+			assert_eq!(<Test as Config>::Currency::free_balance(&para_a.into_account()), 100);
+			assert_eq!(<Test as Config>::Currency::free_balance(&para_b.into_account()), 100);
+			assert_storage_consistency_exhaustive();
+		});
+	}
+
 	#[test]
 	fn refund_deposit_on_normal_closure() {
 		let para_a = 32.into();
@@ -26,12 +26,13 @@ use parity_scale_codec::{Decode, Encode};
 use primitives::v1::{
 	AvailabilityBitfield, BackedCandidate, CandidateCommitments, CandidateDescriptor,
 	CandidateHash, CandidateReceipt, CommittedCandidateReceipt, CoreIndex, GroupIndex, HeadData,
-	Id as ParaId, SigningContext, UncheckedSignedAvailabilityBitfields, ValidatorIndex,
+	Id as ParaId, SessionIndex, SigningContext, UncheckedSignedAvailabilityBitfields,
+	ValidatorIndex,
 };
 use scale_info::TypeInfo;
 use sp_runtime::{
 	traits::{One, Saturating},
-	DispatchError,
+	DispatchError, RuntimeDebug,
 };
 use sp_std::prelude::*;
 
@@ -39,6 +40,8 @@ use crate::{configuration, disputes, dmp, hrmp, paras, scheduler::CoreAssignment
 
 pub use pallet::*;
 
+pub mod runtime_api;
+
 /// A bitfield signed by a validator indicating that it is keeping its piece of the erasure-coding
 /// for any backed candidates referred to by a `1` bit available.
 ///
@@ -100,6 +103,25 @@ impl<H, N> CandidatePendingAvailability<H, N> {
 	}
 }
 
+/// The record of a candidate which timed out while pending availability.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct AvailabilityTimeout<N> {
+	/// The para the candidate was backed for.
+	pub para_id: ParaId,
+	/// The hash of the candidate.
+	pub candidate_hash: CandidateHash,
+	/// The session the candidate timed out in. The validator indices refer to this session.
+	pub session: SessionIndex,
+	/// The block number of the relay-chain block the candidate was backed in.
+	pub backed_in_number: N,
+	/// The block number of the relay-chain block the candidate timed out in.
+	pub timed_out_at: N,
+	/// The number of availability bitfields which supported the candidate.
+	pub supporting_bitfields: u32,
+	/// The validators which didn't make their piece of the candidate available.
+	pub missing_validators: Vec<ValidatorIndex>,
+}
+
 /// A hook for applying validator rewards
 pub trait RewardValidators {
 	// Reward the validators with the given indices for issuing backing statements.
@@ -142,6 +164,9 @@ pub mod pallet {
 		CandidateIncluded(CandidateReceipt<T::Hash>, HeadData, CoreIndex, GroupIndex),
 		/// A candidate timed out. `[candidate, head_data]`
 		CandidateTimedOut(CandidateReceipt<T::Hash>, HeadData, CoreIndex),
+		/// A candidate occupying a core timed out for availability.
+		/// `[core, candidate_hash, supporting_bitfields, missing_validators]`
+		AvailabilityTimedOut(CoreIndex, CandidateHash, u32, Vec<ValidatorIndex>),
 	}
 
 	#[pallet::error]
@@ -214,6 +239,11 @@ pub mod pallet {
 	pub(crate) type PendingAvailabilityCommitments<T: Config> =
 		StorageMap<_, Twox64Concat, ParaId, CandidateCommitments>;
 
+	/// The last candidate which timed out for availability, by the core it occupied.
+	#[pallet::storage]
+	pub(crate) type AvailabilityTimeouts<T: Config> =
+		StorageMap<_, Twox64Concat, CoreIndex, AvailabilityTimeout<T::BlockNumber>>;
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {}
 }
@@ -230,14 +260,25 @@ impl<T: Config> Pallet<T> {
 	pub(crate) fn initializer_finalize() {}
 
 	/// Handle an incoming session change.
+	///
+	/// `n_cores` is the number of availability cores in the new session.
 	pub(crate) fn initializer_on_new_session(
 		_notification: &crate::initializer::SessionChangeNotification<T::BlockNumber>,
+		n_cores: u32,
 	) {
 		// unlike most drain methods, drained elements are not cleared on `Drop` of the iterator
 		// and require consumption.
 		for _ in <PendingAvailabilityCommitments<T>>::drain() {}
 		for _ in <PendingAvailability<T>>::drain() {}
 		for _ in <AvailabilityBitfields<T>>::drain() {}
+
+		// the timeouts of cores which no longer exist are stale.
+		let stale_cores: Vec<_> = <AvailabilityTimeouts<T>>::iter_keys()
+			.filter(|core| core.0 >= n_cores)
+			.collect();
+		for core in stale_cores {
+			<AvailabilityTimeouts<T>>::remove(core);
+		}
 	}
 
 	/// Process a set of incoming bitfields. Return a `vec` of cores freed by candidates
@@ -772,6 +813,8 @@ impl<T: Config> Pallet<T> {
 			let commitments = <PendingAvailabilityCommitments<T>>::take(&para_id);
 
 			if let (Some(pending), Some(commitments)) = (pending, commitments) {
+				Self::note_availability_timeout(para_id, &pending);
+
 				// defensive: this should always be true.
 				let candidate = CandidateReceipt {
 					descriptor: pending.descriptor,
@@ -789,6 +832,48 @@ impl<T: Config> Pallet<T> {
 		cleaned_up_cores
 	}
 
+	/// Record that the given candidate timed out for availability, along with the validators
+	/// which failed to make it available.
+	fn note_availability_timeout(
+		para_id: ParaId,
+		pending: &CandidatePendingAvailability<T::Hash, T::BlockNumber>,
+	) {
+		let supporting_bitfields = pending.availability_votes.count_ones() as u32;
+		let missing_validators: Vec<_> = pending
+			.availability_votes
+			.iter_zeros()
+			.map(|i| ValidatorIndex(i as _))
+			.collect();
+
+		Self::deposit_event(Event::<T>::AvailabilityTimedOut(
+			pending.core,
+			pending.hash,
+			supporting_bitfields,
+			missing_validators.clone(),
+		));
+
+		<AvailabilityTimeouts<T>>::insert(
+			pending.core,
+			AvailabilityTimeout {
+				para_id,
+				candidate_hash: pending.hash,
+				session: shared::Pallet::<T>::session_index(),
+				backed_in_number: pending.backed_in_number,
+				timed_out_at: frame_system::Pallet::<T>::block_number(),
+				supporting_bitfields,
+				missing_validators,
+			},
+		);
+	}
+
+	/// Returns the last candidate which timed out for availability on each core, ordered by
+	/// core index.
+	pub(crate) fn availability_timeouts() -> Vec<(CoreIndex, AvailabilityTimeout<T::BlockNumber>)> {
+		let mut timeouts: Vec<_> = <AvailabilityTimeouts<T>>::iter().collect();
+		timeouts.sort_by_key(|(core, _)| *core);
+		timeouts
+	}
+
 	/// Cleans up all paras pending availability that are in the given list of disputed candidates.
 	///
 	/// Returns a vector of cleaned-up core IDs.
@@ -952,7 +1037,7 @@ mod tests {
 		initializer::SessionChangeNotification,
 		mock::{
 			new_test_ext, Configuration, MockGenesisConfig, ParaInclusion, Paras, ParasShared,
-			System, Test,
+			Scheduler, System, Test,
 		},
 		paras::ParaGenesisArgs,
 		scheduler::AssignmentKind,
@@ -1113,7 +1198,10 @@ mod tests {
 					notification.validators.clone(),
 				);
 				Paras::initializer_on_new_session(&notification);
-				ParaInclusion::initializer_on_new_session(&notification);
+				ParaInclusion::initializer_on_new_session(
+					&notification,
+					Scheduler::availability_cores().len() as u32,
+				);
 			}
 
 			System::on_finalize(b);
@@ -1277,6 +1365,87 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn collect_pending_records_availability_timeouts() {
+		let chain_a = ParaId::from(1);
+		let chain_b = ParaId::from(2);
+		let thread_a = ParaId::from(3);
+
+		let paras = vec![(chain_a, true), (chain_b, true), (thread_a, false)];
+		new_test_ext(genesis_config(paras)).execute_with(|| {
+			let default_candidate = TestCandidateBuilder::default().build();
+			<PendingAvailability<Test>>::insert(
+				chain_a,
+				CandidatePendingAvailability {
+					core: CoreIndex::from(1),
+					hash: default_candidate.hash(),
+					descriptor: default_candidate.descriptor.clone(),
+					availability_votes: bitvec::bitvec![BitOrderLsb0, u8; 1, 0, 1, 0, 0],
+					relay_parent_number: 0,
+					backed_in_number: 2,
+					backers: default_backing_bitfield(),
+					backing_group: GroupIndex::from(0),
+				},
+			);
+			PendingAvailabilityCommitments::<Test>::insert(
+				chain_a,
+				default_candidate.commitments.clone(),
+			);
+
+			run_to_block(5, |_| None);
+			assert!(ParaInclusion::availability_timeouts().is_empty());
+
+			ParaInclusion::collect_pending(|core, _since| core == CoreIndex::from(1));
+
+			let expected = AvailabilityTimeout {
+				para_id: chain_a,
+				candidate_hash: default_candidate.hash(),
+				session: ParasShared::session_index(),
+				backed_in_number: 2,
+				timed_out_at: 5,
+				supporting_bitfields: 2,
+				missing_validators: vec![ValidatorIndex(1), ValidatorIndex(3), ValidatorIndex(4)],
+			};
+			assert_eq!(
+				ParaInclusion::availability_timeouts(),
+				vec![(CoreIndex::from(1), expected.clone())],
+			);
+
+			let event: <Test as frame_system::Config>::Event = Event::<Test>::AvailabilityTimedOut(
+				CoreIndex::from(1),
+				default_candidate.hash(),
+				2,
+				expected.missing_validators,
+			)
+			.into();
+			assert!(System::events().iter().any(|record| record.event == event));
+		});
+	}
+
+	#[test]
+	fn availability_timeouts_of_removed_cores_are_pruned() {
+		new_test_ext(Default::default()).execute_with(|| {
+			let timeout = AvailabilityTimeout {
+				para_id: ParaId::from(1),
+				candidate_hash: CandidateHash::default(),
+				session: 0,
+				backed_in_number: 1,
+				timed_out_at: 2,
+				supporting_bitfields: 0,
+				missing_validators: Vec::new(),
+			};
+			for core in 0..3 {
+				<AvailabilityTimeouts<Test>>::insert(CoreIndex::from(core), timeout.clone());
+			}
+
+			ParaInclusion::initializer_on_new_session(&Default::default(), 2);
+			assert_eq!(
+				ParaInclusion::availability_timeouts(),
+				vec![(CoreIndex::from(0), timeout.clone()), (CoreIndex::from(1), timeout)],
+			);
+		});
+	}
+
 	#[test]
 	fn bitfield_checks() {
 		let chain_a = ParaId::from(1);
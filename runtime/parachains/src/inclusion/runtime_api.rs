@@ -0,0 +1,31 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API for inspecting candidates which timed out for availability.
+
+use super::AvailabilityTimeout;
+use parity_scale_codec::{Decode, Encode};
+use primitives::v1::CoreIndex;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	/// The API for inspecting why candidates failed to become available.
+	pub trait InclusionApi<N: Encode + Decode> {
+		/// Get the last candidate which timed out for availability on each core, along with the
+		/// validators which failed to make it available.
+		fn availability_timeouts() -> Vec<(CoreIndex, AvailabilityTimeout<N>)>;
+	}
+}
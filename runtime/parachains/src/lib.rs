@@ -29,6 +29,7 @@ pub mod dmp;
 pub mod hrmp;
 pub mod inclusion;
 pub mod initializer;
+pub mod on_demand;
 pub mod origin;
 pub mod paras;
 pub mod paras_inherent;
@@ -71,3 +72,8 @@ pub fn schedule_parathread_upgrade<T: paras::Config>(id: ParaId) -> Result<(), (
 pub fn schedule_parachain_downgrade<T: paras::Config>(id: ParaId) -> Result<(), ()> {
 	paras::Pallet::<T>::schedule_parachain_downgrade(id).map_err(|_| ())
 }
+
+/// Schedule a parachain to be downgraded to a parathread once its grace period has passed.
+pub fn schedule_delayed_parachain_downgrade<T: paras::Config>(id: ParaId) -> Result<(), ()> {
+	paras::Pallet::<T>::schedule_delayed_parachain_downgrade(id).map_err(|_| ())
+}
@@ -17,8 +17,8 @@
 //! Mocks for all the traits.
 
 use crate::{
-	configuration, disputes, dmp, hrmp, inclusion, initializer, paras, paras_inherent, scheduler,
-	session_info, shared,
+	configuration, disputes, dmp, hrmp, inclusion, initializer, on_demand, paras, paras_inherent,
+	scheduler, session_info, shared,
 	ump::{self, MessageId, UmpSink},
 	ParaId,
 };
@@ -30,7 +30,10 @@ use primitives::v1::{
 };
 use sp_core::H256;
 use sp_io::TestExternalities;
-use sp_runtime::traits::{BlakeTwo256, IdentityLookup};
+use sp_runtime::{
+	traits::{BlakeTwo256, IdentityLookup},
+	Perbill,
+};
 use std::{cell::RefCell, collections::HashMap};
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -56,6 +59,7 @@ frame_support::construct_runtime!(
 		Hrmp: hrmp::{Pallet, Call, Storage, Event<T>},
 		SessionInfo: session_info::{Pallet, Storage},
 		Disputes: disputes::{Pallet, Storage, Event<T>},
+		OnDemand: on_demand::{Pallet, Call, Storage, Event<T>},
 	}
 );
 
@@ -120,15 +124,28 @@ impl crate::configuration::Config for Test {
 
 impl crate::shared::Config for Test {}
 
+parameter_types! {
+	pub static DowngradeGracePeriod: SessionIndex = 0;
+}
+
 impl crate::paras::Config for Test {
 	type Origin = Origin;
 	type Event = Event;
+	type DowngradeGracePeriod = DowngradeGracePeriod;
 }
 
-impl crate::dmp::Config for Test {}
+parameter_types! {
+	pub const MaxDownwardQueueSize: u32 = 4096;
+}
+
+impl crate::dmp::Config for Test {
+	type MaxDownwardQueueSize = MaxDownwardQueueSize;
+}
 
 parameter_types! {
 	pub const FirstMessageFactorPercent: u64 = 100;
+	pub const OverweightDepositPerByte: Balance = 1;
+	pub const OverweightExpiry: BlockNumber = 10;
 }
 
 impl crate::ump::Config for Test {
@@ -136,12 +153,17 @@ impl crate::ump::Config for Test {
 	type UmpSink = TestUmpSink;
 	type FirstMessageFactorPercent = FirstMessageFactorPercent;
 	type ExecuteOverweightOrigin = frame_system::EnsureRoot<AccountId>;
+	type Currency = pallet_balances::Pallet<Test>;
+	type OverweightDepositPerByte = OverweightDepositPerByte;
+	type OverweightExpiry = OverweightExpiry;
+	type WeightInfo = crate::ump::weights::WeightInfo<Test>;
 }
 
 impl crate::hrmp::Config for Test {
 	type Event = Event;
 	type Origin = Origin;
 	type Currency = pallet_balances::Pallet<Test>;
+	type ChannelManager = frame_system::EnsureRoot<AccountId>;
 }
 
 impl crate::disputes::Config for Test {
@@ -192,7 +214,25 @@ impl crate::disputes::PunishValidators for Test {
 	}
 }
 
-impl crate::scheduler::Config for Test {}
+impl crate::scheduler::Config for Test {
+	type ClaimHandler = OnDemand;
+}
+
+parameter_types! {
+	pub const OnDemandMinimumPrice: Balance = 100;
+	pub const OnDemandTargetUtilization: Perbill = Perbill::from_percent(50);
+	pub const OnDemandPriceAdjustment: Perbill = Perbill::from_percent(10);
+}
+
+impl crate::on_demand::Config for Test {
+	type Event = Event;
+	type Currency = pallet_balances::Pallet<Test>;
+	type OnRevenue = ();
+	type MinimumPrice = OnDemandMinimumPrice;
+	type TargetUtilization = OnDemandTargetUtilization;
+	type PriceAdjustment = OnDemandPriceAdjustment;
+	type WeightInfo = crate::on_demand::weights::WeightInfo<Test>;
+}
 
 impl crate::inclusion::Config for Test {
 	type Event = Event;
@@ -202,7 +242,13 @@ impl crate::inclusion::Config for Test {
 
 impl crate::paras_inherent::Config for Test {}
 
-impl crate::session_info::Config for Test {}
+parameter_types! {
+	pub static RetentionPeriod: SessionIndex = 0;
+}
+
+impl crate::session_info::Config for Test {
+	type RetentionPeriod = RetentionPeriod;
+}
 
 thread_local! {
 	pub static DISCOVERY_AUTHORITIES: RefCell<Vec<AuthorityDiscoveryId>> = RefCell::new(Vec::new());
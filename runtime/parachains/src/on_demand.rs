@@ -0,0 +1,427 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! On-demand parathread claims.
+//!
+//! This module sells one-off claims on the parathread cores to registered parathreads. An order
+//! reserves the current spot price from the buyer and feeds a claim into the scheduler's
+//! parathread queue. Once a candidate of the parathread is included, the reserved amount is
+//! collected; if the scheduler drops the claim without it being used, the amount is refunded.
+//!
+//! The spot price is adjusted every block according to how full the parathread claim queue is,
+//! rising while the queue is busier than the targeted utilization and falling back towards the
+//! minimum price while it is less busy.
+
+use crate::{
+	configuration, paras,
+	scheduler::{self, ParathreadClaimError, ParathreadClaimHandler},
+};
+use frame_support::{
+	pallet_prelude::*,
+	traits::{Currency, OnUnbalanced, ReservableCurrency},
+};
+use frame_system::pallet_prelude::*;
+use primitives::v1::{CollatorId, Id as ParaId, ParathreadClaim};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{Saturating, Zero},
+	PerThing, Perbill,
+};
+
+pub use pallet::*;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod weights;
+
+type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+	<T as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
+/// An order for a parathread claim which was placed but is not settled yet.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, sp_core::RuntimeDebug, TypeInfo)]
+pub struct Order<AccountId, Balance> {
+	/// The account which placed the order. The price is reserved from it.
+	pub who: AccountId,
+	/// The price paid for the claim.
+	pub price: Balance,
+}
+
+pub trait WeightInfo {
+	fn place_order() -> Weight;
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config:
+		frame_system::Config + configuration::Config + paras::Config + scheduler::Config
+	{
+		/// The outer event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The currency orders are paid in.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Handler for the payments of orders whose claims were used.
+		type OnRevenue: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// The lowest price a claim can be sold for.
+		#[pallet::constant]
+		type MinimumPrice: Get<BalanceOf<Self>>;
+
+		/// The utilization of the parathread claim queue at which the price stays the same.
+		#[pallet::constant]
+		type TargetUtilization: Get<Perbill>;
+
+		/// The largest relative change of the price within a single block, reached when the
+		/// queue is either full or empty.
+		#[pallet::constant]
+		type PriceAdjustment: Get<Perbill>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// An order for a parathread claim was placed. `[para_id, who, price]`
+		OrderPlaced(ParaId, T::AccountId, BalanceOf<T>),
+		/// The claim of an order was used and its price collected. `[para_id, who, price]`
+		OrderFulfilled(ParaId, T::AccountId, BalanceOf<T>),
+		/// The claim of an order was dropped unused and its price refunded. `[para_id, who, price]`
+		OrderRefunded(ParaId, T::AccountId, BalanceOf<T>),
+		/// The spot price of a claim changed. `[price]`
+		SpotPriceSet(BalanceOf<T>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The para is not a parathread.
+		NotParathread,
+		/// The parathread claim queue is full.
+		QueueFull,
+		/// The parathread already has a claim queued or occupying a core.
+		AlreadyClaimed,
+		/// The spot price is higher than the maximum amount the buyer is willing to pay.
+		SpotPriceHigherThanMaxAmount,
+		/// The buyer cannot afford the spot price.
+		InsufficientBalance,
+	}
+
+	/// The current price of a claim, if it was adjusted away from the minimum price.
+	#[pallet::storage]
+	pub(crate) type SpotPrice<T: Config> = StorageValue<_, BalanceOf<T>>;
+
+	/// Orders whose claims are still in the scheduler, by the parathread they were placed for.
+	///
+	/// The scheduler allows a single claim per parathread, thus there is at most one order for
+	/// each of them.
+	#[pallet::storage]
+	#[pallet::getter(fn order)]
+	pub(crate) type Orders<T: Config> =
+		StorageMap<_, Twox64Concat, ParaId, Order<T::AccountId, BalanceOf<T>>>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_now: T::BlockNumber) -> Weight {
+			let utilization = <scheduler::Pallet<T>>::parathread_queue_utilization();
+			let price = Self::spot_price();
+			let new_price = Self::adjust_price(price, utilization);
+
+			if new_price != price {
+				SpotPrice::<T>::put(new_price);
+				Self::deposit_event(Event::SpotPriceSet(new_price));
+				T::DbWeight::get().reads_writes(3, 2)
+			} else {
+				T::DbWeight::get().reads(3)
+			}
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Buy a claim for a single block of the given parathread, collated by `collator`.
+		///
+		/// The current spot price is reserved from the sender as long as the claim is in the
+		/// scheduler. It is collected once a candidate of the parathread is included and
+		/// refunded if the claim is dropped unused.
+		///
+		/// Fails if the spot price exceeds `max_amount`.
+		#[pallet::weight(T::WeightInfo::place_order())]
+		pub fn place_order(
+			origin: OriginFor<T>,
+			para_id: ParaId,
+			collator: CollatorId,
+			max_amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let price = Self::spot_price();
+			ensure!(price <= max_amount, Error::<T>::SpotPriceHigherThanMaxAmount);
+
+			T::Currency::reserve(&who, price).map_err(|_| Error::<T>::InsufficientBalance)?;
+
+			if let Err(e) =
+				<scheduler::Pallet<T>>::try_add_parathread_claim(ParathreadClaim(para_id, collator))
+			{
+				T::Currency::unreserve(&who, price);
+				return Err(match e {
+					ParathreadClaimError::NotParathread => Error::<T>::NotParathread,
+					ParathreadClaimError::QueueFull => Error::<T>::QueueFull,
+					ParathreadClaimError::AlreadyClaimed => Error::<T>::AlreadyClaimed,
+				}
+				.into())
+			}
+
+			Orders::<T>::insert(para_id, Order { who: who.clone(), price });
+			Self::deposit_event(Event::OrderPlaced(para_id, who, price));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The price an order placed now would pay.
+	pub fn spot_price() -> BalanceOf<T> {
+		SpotPrice::<T>::get().unwrap_or_else(T::MinimumPrice::get)
+	}
+
+	/// Compute the price for the next block given the current price and queue utilization.
+	fn adjust_price(price: BalanceOf<T>, utilization: Perbill) -> BalanceOf<T> {
+		let target = T::TargetUtilization::get();
+		let adjustment = T::PriceAdjustment::get();
+
+		let new_price = if utilization > target {
+			// how far the queue is from the target towards being full.
+			let pressure = Perbill::from_rational(
+				utilization.saturating_sub(target).deconstruct(),
+				target.left_from_one().deconstruct(),
+			);
+			price.saturating_add((adjustment * pressure) * price)
+		} else {
+			// how far the queue is from the target towards being empty.
+			let slack = if target.is_zero() {
+				Perbill::zero()
+			} else {
+				Perbill::from_rational(
+					target.saturating_sub(utilization).deconstruct(),
+					target.deconstruct(),
+				)
+			};
+			price.saturating_sub((adjustment * slack) * price)
+		};
+
+		new_price.max(T::MinimumPrice::get())
+	}
+}
+
+impl<T: Config> ParathreadClaimHandler for Pallet<T> {
+	fn claim_concluded(para: ParaId) {
+		if let Some(order) = Orders::<T>::take(para) {
+			let (imbalance, _) = T::Currency::slash_reserved(&order.who, order.price);
+			T::OnRevenue::on_unbalanced(imbalance);
+			Self::deposit_event(Event::OrderFulfilled(para, order.who, order.price));
+		}
+	}
+
+	fn claim_dropped(para: ParaId) {
+		if let Some(order) = Orders::<T>::take(para) {
+			let remaining = T::Currency::unreserve(&order.who, order.price);
+			let refunded = order.price.saturating_sub(remaining);
+			Self::deposit_event(Event::OrderRefunded(para, order.who, refunded));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use frame_support::{assert_noop, assert_ok};
+	use keyring::Sr25519Keyring;
+	use primitives::v1::{BlockNumber, ValidationCode};
+
+	use crate::{
+		configuration::HostConfiguration,
+		initializer::SessionChangeNotification,
+		mock::{
+			assert_last_event, new_test_ext, Balances, MockGenesisConfig, OnDemand, Origin,
+			Scheduler, System, Test,
+		},
+		paras::ParaGenesisArgs,
+	};
+
+	const BUYER: u64 = 1;
+
+	fn default_config() -> HostConfiguration<BlockNumber> {
+		HostConfiguration {
+			parathread_cores: 1,
+			scheduling_lookahead: 2,
+			parathread_retries: 1,
+			..Default::default()
+		}
+	}
+
+	fn para_genesis(parachain: bool) -> ParaGenesisArgs {
+		ParaGenesisArgs {
+			genesis_head: vec![1, 2, 3].into(),
+			validation_code: ValidationCode(vec![1, 2, 3]),
+			parachain,
+		}
+	}
+
+	fn genesis_config() -> MockGenesisConfig {
+		MockGenesisConfig {
+			configuration: crate::configuration::GenesisConfig {
+				config: default_config(),
+				..Default::default()
+			},
+			paras: crate::paras::GenesisConfig {
+				paras: vec![
+					(1.into(), para_genesis(false)),
+					(2.into(), para_genesis(false)),
+					(3.into(), para_genesis(false)),
+					(100.into(), para_genesis(true)),
+				],
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+
+	fn collator() -> CollatorId {
+		CollatorId::from(Sr25519Keyring::Alice.public())
+	}
+
+	fn place_order(para: u32) -> DispatchResult {
+		OnDemand::place_order(Origin::signed(BUYER), para.into(), collator(), 1_000)
+	}
+
+	#[test]
+	fn place_order_reserves_price_and_queues_claim() {
+		new_test_ext(genesis_config()).execute_with(|| {
+			System::set_block_number(1);
+			Balances::make_free_balance_be(&BUYER, 1_000);
+
+			assert_ok!(place_order(1));
+
+			assert_eq!(Balances::reserved_balance(&BUYER), 100);
+			assert_eq!(OnDemand::order(ParaId::from(1)), Some(Order { who: BUYER, price: 100 }));
+			assert_eq!(Scheduler::parathread_queue_utilization(), Perbill::from_percent(50));
+			assert_last_event(Event::<Test>::OrderPlaced(1.into(), BUYER, 100).into());
+		});
+	}
+
+	#[test]
+	fn failed_orders_are_not_charged() {
+		new_test_ext(genesis_config()).execute_with(|| {
+			Balances::make_free_balance_be(&BUYER, 1_000);
+
+			assert_noop!(place_order(100), Error::<Test>::NotParathread);
+			assert_noop!(
+				OnDemand::place_order(Origin::signed(BUYER), 1.into(), collator(), 99),
+				Error::<Test>::SpotPriceHigherThanMaxAmount,
+			);
+			assert_noop!(
+				OnDemand::place_order(Origin::signed(2), 1.into(), collator(), 1_000),
+				Error::<Test>::InsufficientBalance,
+			);
+
+			assert_ok!(place_order(1));
+			assert_noop!(place_order(1), Error::<Test>::AlreadyClaimed);
+
+			assert_ok!(place_order(2));
+			assert_noop!(place_order(3), Error::<Test>::QueueFull);
+
+			assert_eq!(Balances::reserved_balance(&BUYER), 200);
+		});
+	}
+
+	#[test]
+	fn orders_are_settled_with_their_claims() {
+		new_test_ext(genesis_config()).execute_with(|| {
+			System::set_block_number(1);
+			Balances::make_free_balance_be(&BUYER, 1_000);
+			let issuance = Balances::total_issuance();
+
+			assert_ok!(place_order(1));
+			assert_ok!(place_order(2));
+
+			// a used claim is paid for.
+			OnDemand::claim_concluded(1.into());
+			assert_eq!(Balances::reserved_balance(&BUYER), 100);
+			assert_eq!(Balances::free_balance(&BUYER), 800);
+			assert_eq!(Balances::total_issuance(), issuance - 100);
+			assert_last_event(Event::<Test>::OrderFulfilled(1.into(), BUYER, 100).into());
+
+			// claims dropped by the scheduler are refunded.
+			Scheduler::initializer_on_new_session(&SessionChangeNotification {
+				new_config: HostConfiguration { parathread_cores: 0, ..default_config() },
+				..Default::default()
+			});
+			assert_eq!(Balances::reserved_balance(&BUYER), 0);
+			assert_eq!(Balances::free_balance(&BUYER), 900);
+			assert!(OnDemand::order(ParaId::from(2)).is_none());
+			assert_last_event(Event::<Test>::OrderRefunded(2.into(), BUYER, 100).into());
+		});
+	}
+
+	#[test]
+	fn spot_price_follows_queue_utilization() {
+		new_test_ext(genesis_config()).execute_with(|| {
+			System::set_block_number(1);
+			Balances::make_free_balance_be(&BUYER, 1_000);
+
+			// an empty queue keeps the price at the minimum.
+			OnDemand::on_initialize(1);
+			assert_eq!(OnDemand::spot_price(), 100);
+
+			// a queue at the target utilization keeps the price.
+			assert_ok!(place_order(1));
+			OnDemand::on_initialize(2);
+			assert_eq!(OnDemand::spot_price(), 100);
+
+			// a full queue raises it.
+			assert_ok!(place_order(2));
+			OnDemand::on_initialize(3);
+			assert_eq!(OnDemand::spot_price(), 110);
+			assert_last_event(Event::<Test>::SpotPriceSet(110).into());
+			OnDemand::on_initialize(4);
+			assert_eq!(OnDemand::spot_price(), 121);
+
+			// and it decreases again, though not below the minimum, once the queue drains.
+			Scheduler::initializer_on_new_session(&SessionChangeNotification {
+				new_config: HostConfiguration { parathread_cores: 0, ..default_config() },
+				..Default::default()
+			});
+			OnDemand::on_initialize(5);
+			assert_eq!(OnDemand::spot_price(), 109);
+			OnDemand::on_initialize(6);
+			assert_eq!(OnDemand::spot_price(), 100);
+		});
+	}
+}
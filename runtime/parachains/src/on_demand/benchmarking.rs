@@ -0,0 +1,86 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+	configuration::ActiveConfig,
+	on_demand::*,
+	paras::{self, ParaGenesisArgs},
+	scheduler, shared,
+};
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_system::RawOrigin;
+use primitives::v1::{HeadData, ValidationCode};
+use sp_runtime::traits::TrailingZeroInput;
+
+/// The number of claims the parathread claim queue holds while benchmarking.
+const QUEUE_SIZE: u32 = 100;
+
+fn register_parathreads<T: Config>(count: u32) {
+	for id in 0..count {
+		paras::Pallet::<T>::schedule_para_initialize(
+			id.into(),
+			ParaGenesisArgs {
+				genesis_head: HeadData(vec![1]),
+				validation_code: ValidationCode(vec![1]),
+				parachain: false,
+			},
+		)
+		.unwrap();
+	}
+	shared::Pallet::<T>::set_session_index(shared::Pallet::<T>::scheduled_session());
+	paras::Pallet::<T>::test_on_new_session();
+}
+
+fn collator() -> CollatorId {
+	CollatorId::decode(&mut TrailingZeroInput::new(&[][..])).unwrap()
+}
+
+benchmarks! {
+	// Worst case: the queue is one claim short of being full.
+	place_order {
+		ActiveConfig::<T>::mutate(|config| {
+			config.parathread_cores = 1;
+			config.scheduling_lookahead = QUEUE_SIZE;
+		});
+		register_parathreads::<T>(QUEUE_SIZE);
+		for id in 1..QUEUE_SIZE {
+			scheduler::Pallet::<T>::try_add_parathread_claim(
+				ParathreadClaim(id.into(), collator()),
+			)
+			.unwrap();
+		}
+
+		let caller: T::AccountId = whitelisted_caller();
+		let price = Pallet::<T>::spot_price();
+		T::Currency::make_free_balance_be(
+			&caller,
+			price.saturating_mul(2u32.into()).saturating_add(T::Currency::minimum_balance()),
+		);
+	}: _(RawOrigin::Signed(caller.clone()), 0.into(), collator(), price)
+	verify {
+		assert_eq!(Pallet::<T>::order(ParaId::from(0)), Some(Order { who: caller, price }));
+		assert_eq!(
+			scheduler::Pallet::<T>::parathread_queue_utilization(),
+			Perbill::one(),
+		);
+	}
+}
+
+impl_benchmark_test_suite!(
+	Pallet,
+	crate::mock::new_test_ext(Default::default()),
+	crate::mock::Test
+);
@@ -0,0 +1,53 @@
+
+//! Weights for `runtime_parachains::on_demand`
+//!
+//! FIXME: not generated by the benchmark CLI yet, regenerate with the command below. Until then
+//! `place_order` is built from substrate's benchmarked weights: `transfer_keep_alive` bounds
+//! reserving the price, and scanning and re-encoding the claim queue of the benchmark, 100 claims
+//! of under 64 bytes, is charged as a `remark_with_event` of that size.
+
+// Command to run:
+// ./target/release/diamond
+// benchmark
+// --chain
+// titan-dev
+// --execution=wasm
+// --wasm-execution=compiled
+// --pallet
+// runtime_parachains::on_demand
+// --steps
+// 50
+// --repeat
+// 20
+// --raw
+// --extrinsic
+// *
+// --output
+// runtime/parachains/src/on_demand/weights.rs
+
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use frame_system::WeightInfo as _;
+use pallet_balances::WeightInfo as _;
+use sp_std::marker::PhantomData;
+
+/// Weight functions for `runtime_parachains::on_demand`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> super::WeightInfo for WeightInfo<T> {
+	// Storage: OnDemand SpotPrice (r:1 w:0)
+	// Storage: Paras ParaLifecycles (r:1 w:0)
+	// Storage: Configuration ActiveConfig (r:1 w:0)
+	// Storage: ParaScheduler ParathreadQueue (r:1 w:1)
+	// Storage: ParaScheduler ParathreadClaimIndex (r:1 w:1)
+	// Storage: OnDemand Orders (r:0 w:1)
+	fn place_order() -> Weight {
+		pallet_balances::weights::SubstrateWeight::<T>::transfer_keep_alive()
+			.saturating_add(frame_system::weights::SubstrateWeight::<T>::remark_with_event(6_400))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+}
@@ -43,6 +43,8 @@ pub use crate::Origin as ParachainOrigin;
 
 pub use pallet::*;
 
+pub mod runtime_api;
+
 // the two key times necessary to track for every code replacement.
 #[derive(Default, Encode, Decode, TypeInfo)]
 #[cfg_attr(test, derive(Debug, Clone, PartialEq))]
@@ -283,6 +285,13 @@ pub mod pallet {
 			+ Into<result::Result<Origin, <Self as Config>::Origin>>;
 
 		type Event: From<Event> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The number of sessions a parachain keeps its slot after a delayed downgrade to a
+		/// parathread was scheduled, e.g. because its lease ended.
+		///
+		/// The downgrade can be cancelled by upgrading the para again during this period.
+		#[pallet::constant]
+		type DowngradeGracePeriod: Get<SessionIndex>;
 	}
 
 	#[pallet::event]
@@ -298,6 +307,16 @@ pub mod pallet {
 		NewHeadNoted(ParaId),
 		/// A para has been queued to execute pending actions. `para_id`
 		ActionQueued(ParaId, SessionIndex),
+		/// A pending code upgrade of a Para has been aborted. `para_id`
+		CodeUpgradeAborted(ParaId),
+		/// A parachain is going to be downgraded to a parathread at the start of the given
+		/// session. `[para_id, session_index]`
+		ParachainDowngradeScheduled(ParaId, SessionIndex),
+		/// A scheduled downgrade of a parachain has been cancelled. `[para_id]`
+		ParachainDowngradeCancelled(ParaId),
+		/// A downgrading parachain is going to be upgraded back to a parachain once its
+		/// downgrade, which could no longer be cancelled, has taken effect. `[para_id]`
+		UpgradeAfterDowngradeScheduled(ParaId),
 	}
 
 	#[pallet::error]
@@ -312,6 +331,12 @@ pub mod pallet {
 		CannotUpgrade,
 		/// Para cannot be downgraded to a parathread.
 		CannotDowngrade,
+		/// Para has no pending code upgrade.
+		NoPendingCodeUpgrade,
+		/// Para already has a pending code upgrade.
+		CodeUpgradeAlreadyPending,
+		/// Para is restricted from upgrading its code, e.g. until its upgrade cooldown expires.
+		CodeUpgradeRestricted,
 	}
 
 	/// All parachains. Ordered ascending by `ParaId`. Parathreads are not included.
@@ -364,6 +389,9 @@ pub mod pallet {
 	/// The block number at which the planned code change is expected for a para.
 	/// The change will be applied after the first parablock for this ID included which executes
 	/// in the context of a relay chain block with a number >= `expected_at`.
+	///
+	/// NOTE that this field is used by parachains via merkle storage proofs, therefore changing
+	/// the format will require migration of parachains.
 	#[pallet::storage]
 	#[pallet::getter(fn future_code_upgrade_at)]
 	pub(super) type FutureCodeUpgrades<T: Config> =
@@ -383,12 +411,22 @@ pub mod pallet {
 	/// can switch its upgrade function. As soon as the parachain's block is included, the value
 	/// gets reset to `None`.
 	///
+	/// It is set to `Abort` when a pending upgrade is aborted, and reset to `None` as soon as a
+	/// block of the parachain built on top of a relay-parent that has observed the abort is
+	/// included, see [`AbortedUpgrades`].
+	///
 	/// NOTE that this field is used by parachains via merkle storage proofs, therefore changing
 	/// the format will require migration of parachains.
 	#[pallet::storage]
 	pub(super) type UpgradeGoAheadSignal<T: Config> =
 		StorageMap<_, Twox64Concat, ParaId, UpgradeGoAhead>;
 
+	/// The relay-chain block at which the pending upgrade of a para was aborted, as long as the
+	/// para hasn't included a block built on top of a relay-parent from this block or later.
+	#[pallet::storage]
+	pub(super) type AbortedUpgrades<T: Config> =
+		StorageMap<_, Twox64Concat, ParaId, T::BlockNumber>;
+
 	/// This is used by the relay-chain to communicate that there are restrictions for performing
 	/// an upgrade for this parachain.
 	///
@@ -423,6 +461,17 @@ pub mod pallet {
 	pub(super) type ActionsQueue<T: Config> =
 		StorageMap<_, Twox64Concat, SessionIndex, Vec<ParaId>, ValueQuery>;
 
+	/// The session at whose start a parachain which is `DowngradingParachain` is going to become a
+	/// parathread.
+	#[pallet::storage]
+	pub(super) type UpcomingDowngrades<T: Config> =
+		StorageMap<_, Twox64Concat, ParaId, SessionIndex>;
+
+	/// The downgrading parachains which are going to be scheduled for an upgrade back to a
+	/// parachain as soon as they become parathreads.
+	#[pallet::storage]
+	pub(super) type UpgradesAfterDowngrade<T: Config> = StorageMap<_, Twox64Concat, ParaId, ()>;
+
 	/// Upcoming paras instantiation arguments.
 	#[pallet::storage]
 	pub(super) type UpcomingParasGenesis<T: Config> =
@@ -536,6 +585,18 @@ pub mod pallet {
 			Ok(())
 		}
 
+		/// Abort the pending code upgrade of a para.
+		///
+		/// The para is signalled to abort the upgrade through `UpgradeGoAheadSignal`. The upgrade
+		/// cooldown is left untouched.
+		#[pallet::weight(0)]
+		pub fn force_abort_code_upgrade(origin: OriginFor<T>, para: ParaId) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::abort_code_upgrade(para)?;
+			Self::deposit_event(Event::CodeUpgradeAborted(para));
+			Ok(())
+		}
+
 		/// Note a new block head for para within the context of the current block.
 		#[pallet::weight(0)]
 		pub fn force_note_new_head(
@@ -652,7 +713,13 @@ impl<T: Config> Pallet<T> {
 					if let Ok(i) = parachains.binary_search(&para) {
 						parachains.remove(i);
 					}
+					<Self as Store>::UpcomingDowngrades::remove(&para);
 					ParaLifecycles::<T>::insert(&para, ParaLifecycle::Parathread);
+					if <Self as Store>::UpgradesAfterDowngrade::take(&para).is_some() {
+						// The para has just become a parathread, so this can't fail.
+						let res = Self::schedule_parathread_upgrade(para);
+						debug_assert!(res.is_ok());
+					}
 				},
 				// Offboard a parathread or parachain from the system
 				Some(ParaLifecycle::OffboardingParachain) |
@@ -662,8 +729,10 @@ impl<T: Config> Pallet<T> {
 					}
 
 					<Self as Store>::Heads::remove(&para);
+					<Self as Store>::UpgradesAfterDowngrade::remove(&para);
 					<Self as Store>::FutureCodeUpgrades::remove(&para);
 					<Self as Store>::UpgradeGoAheadSignal::remove(&para);
+					<Self as Store>::AbortedUpgrades::remove(&para);
 					<Self as Store>::UpgradeRestrictionSignal::remove(&para);
 					ParaLifecycles::<T>::remove(&para);
 					let removed_future_code_hash = <Self as Store>::FutureCodeHash::take(&para);
@@ -881,11 +950,21 @@ impl<T: Config> Pallet<T> {
 
 	/// Schedule a parathread to be upgraded to a parachain.
 	///
-	/// Will return error if `ParaLifecycle` is not `Parathread`.
+	/// If the para is a parachain with a pending downgrade whose session has not yet been
+	/// announced to the validators, the downgrade is cancelled instead and the para stays a
+	/// parachain. If the downgrade can no longer be cancelled, the upgrade is scheduled as soon
+	/// as the downgrade has taken effect.
+	///
+	/// Will return error if `ParaLifecycle` is neither `Parathread` nor such a
+	/// `DowngradingParachain`.
 	pub(crate) fn schedule_parathread_upgrade(id: ParaId) -> DispatchResult {
 		let scheduled_session = Self::scheduled_session();
 		let lifecycle = ParaLifecycles::<T>::get(&id).ok_or(Error::<T>::NotRegistered)?;
 
+		if lifecycle == ParaLifecycle::DowngradingParachain {
+			return Self::cancel_parachain_downgrade(id)
+		}
+
 		ensure!(lifecycle == ParaLifecycle::Parathread, Error::<T>::CannotUpgrade);
 
 		ParaLifecycles::<T>::insert(&id, ParaLifecycle::UpgradingParathread);
@@ -898,22 +977,71 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
-	/// Schedule a parachain to be downgraded to a parathread.
+	/// Schedule a parachain to be downgraded to a parathread at the start of the next scheduled
+	/// session.
 	///
-	/// Noop if `ParaLifecycle` is not `Parachain`.
+	/// Will return error if `ParaLifecycle` is not `Parachain`.
 	pub(crate) fn schedule_parachain_downgrade(id: ParaId) -> DispatchResult {
-		let scheduled_session = Self::scheduled_session();
+		Self::do_schedule_parachain_downgrade(id, Self::scheduled_session())
+	}
+
+	/// Schedule a parachain to be downgraded to a parathread after `T::DowngradeGracePeriod`
+	/// sessions on top of the next scheduled session.
+	///
+	/// Until then the para remains a parachain, and the downgrade can be cancelled by
+	/// [`Self::schedule_parathread_upgrade`]. If the para is a downgrading parachain which is
+	/// going to be upgraded back after its downgrade, that upgrade is cancelled instead.
+	///
+	/// Will return error if `ParaLifecycle` is not `Parachain`, or such a `DowngradingParachain`.
+	pub(crate) fn schedule_delayed_parachain_downgrade(id: ParaId) -> DispatchResult {
+		if <Self as Store>::UpgradesAfterDowngrade::take(&id).is_some() {
+			return Ok(())
+		}
+
+		let session = Self::scheduled_session().saturating_add(T::DowngradeGracePeriod::get());
+		Self::do_schedule_parachain_downgrade(id, session)
+	}
+
+	fn do_schedule_parachain_downgrade(id: ParaId, session: SessionIndex) -> DispatchResult {
 		let lifecycle = ParaLifecycles::<T>::get(&id).ok_or(Error::<T>::NotRegistered)?;
 
 		ensure!(lifecycle == ParaLifecycle::Parachain, Error::<T>::CannotDowngrade);
 
 		ParaLifecycles::<T>::insert(&id, ParaLifecycle::DowngradingParachain);
-		ActionsQueue::<T>::mutate(scheduled_session, |v| {
+		<Self as Store>::UpcomingDowngrades::insert(&id, session);
+		ActionsQueue::<T>::mutate(session, |v| {
 			if let Err(i) = v.binary_search(&id) {
 				v.insert(i, id);
 			}
 		});
 
+		Self::deposit_event(Event::ParachainDowngradeScheduled(id, session));
+		Ok(())
+	}
+
+	/// Cancel the pending downgrade of a parachain, leaving it a parachain.
+	///
+	/// If the downgrade takes effect at or before the next scheduled session, whose para set
+	/// might already be known, it can no longer be cancelled. The para is then upgraded back to a
+	/// parachain once the downgrade has taken effect.
+	fn cancel_parachain_downgrade(id: ParaId) -> DispatchResult {
+		let session =
+			<Self as Store>::UpcomingDowngrades::get(&id).ok_or(Error::<T>::CannotUpgrade)?;
+		if session <= Self::scheduled_session() {
+			<Self as Store>::UpgradesAfterDowngrade::insert(&id, ());
+			Self::deposit_event(Event::UpgradeAfterDowngradeScheduled(id));
+			return Ok(())
+		}
+
+		ParaLifecycles::<T>::insert(&id, ParaLifecycle::Parachain);
+		<Self as Store>::UpcomingDowngrades::remove(&id);
+		ActionsQueue::<T>::mutate(session, |v| {
+			if let Ok(i) = v.binary_search(&id) {
+				v.remove(i);
+			}
+		});
+
+		Self::deposit_event(Event::ParachainDowngradeCancelled(id));
 		Ok(())
 	}
 
@@ -968,6 +1096,48 @@ impl<T: Config> Pallet<T> {
 		})
 	}
 
+	/// Schedule a code upgrade of the given para in the context of the current block, as if the
+	/// para signalled it itself.
+	///
+	/// Will return error if the para already has a pending code upgrade or is restricted from
+	/// upgrading, e.g. because its upgrade cooldown hasn't expired yet.
+	pub fn schedule_code_upgrade_now(id: ParaId, new_code: ValidationCode) -> DispatchResult {
+		ensure!(
+			!<Self as Store>::FutureCodeUpgrades::contains_key(&id),
+			Error::<T>::CodeUpgradeAlreadyPending
+		);
+		ensure!(
+			!<Self as Store>::UpgradeRestrictionSignal::contains_key(&id),
+			Error::<T>::CodeUpgradeRestricted
+		);
+
+		let config = configuration::Pallet::<T>::config();
+		let now = <frame_system::Pallet<T>>::block_number();
+		Self::schedule_code_upgrade(id, new_code, now, &config);
+		Self::deposit_event(Event::CodeUpgradeScheduled(id));
+		Ok(())
+	}
+
+	/// Abort the pending code upgrade of the given para, signalling `UpgradeGoAhead::Abort` to
+	/// it.
+	///
+	/// Will return error if the para has no pending code upgrade.
+	pub(crate) fn abort_code_upgrade(id: ParaId) -> DispatchResult {
+		<Self as Store>::FutureCodeUpgrades::take(&id).ok_or(Error::<T>::NoPendingCodeUpgrade)?;
+
+		<Self as Store>::UpcomingUpgrades::mutate(|upcoming_upgrades| {
+			upcoming_upgrades.retain(|&(para, _)| para != id);
+		});
+		if let Some(future_code_hash) = FutureCodeHash::<T>::take(&id) {
+			Self::decrease_code_ref(&future_code_hash);
+		}
+		<Self as Store>::UpgradeGoAheadSignal::insert(&id, UpgradeGoAhead::Abort);
+		let now = <frame_system::Pallet<T>>::block_number();
+		<Self as Store>::AbortedUpgrades::insert(&id, now);
+
+		Ok(())
+	}
+
 	/// Note that a para has progressed to a new head, where the new head was executed in the context
 	/// of a relay-chain block with given number. This will apply pending code upgrades based
 	/// on the relay-parent block number provided.
@@ -982,6 +1152,7 @@ impl<T: Config> Pallet<T> {
 			if expected_at <= execution_context {
 				<Self as Store>::FutureCodeUpgrades::remove(&id);
 				<Self as Store>::UpgradeGoAheadSignal::remove(&id);
+				<Self as Store>::AbortedUpgrades::remove(&id);
 
 				// Both should always be `Some` in this case, since a code upgrade is scheduled.
 				let new_code_hash = FutureCodeHash::<T>::take(&id).unwrap_or_default();
@@ -997,12 +1168,22 @@ impl<T: Config> Pallet<T> {
 				let weight = Self::note_past_code(id, expected_at, now, prior_code_hash);
 
 				// add 1 to writes due to heads update.
-				weight + T::DbWeight::get().reads_writes(3, 1 + 3)
+				weight + T::DbWeight::get().reads_writes(3, 1 + 4)
 			} else {
 				T::DbWeight::get().reads_writes(1, 1 + 0)
 			}
+		} else if let Some(aborted_at) = <Self as Store>::AbortedUpgrades::get(&id) {
+			// the upgrade was aborted. The para has observed the abort signal only if the block
+			// was built on top of a relay-parent that has seen it.
+			if aborted_at <= execution_context {
+				<Self as Store>::AbortedUpgrades::remove(&id);
+				<Self as Store>::UpgradeGoAheadSignal::remove(&id);
+				T::DbWeight::get().reads_writes(2, 1 + 2)
+			} else {
+				T::DbWeight::get().reads_writes(2, 1 + 0)
+			}
 		} else {
-			T::DbWeight::get().reads_writes(1, 1)
+			T::DbWeight::get().reads_writes(2, 1)
 		}
 	}
 
@@ -1048,6 +1229,26 @@ impl<T: Config> Pallet<T> {
 		ParaLifecycles::<T>::get(&id)
 	}
 
+	/// Returns the lifecycle transitions which are going to take effect within the next
+	/// `T::DowngradeGracePeriod` sessions on top of the next scheduled session.
+	///
+	/// Each entry is the para, the session at whose start the transition happens, and the
+	/// transitional lifecycle state of the para. Ordered by session and then by para.
+	pub(crate) fn upcoming_lifecycle_transitions() -> Vec<(ParaId, SessionIndex, ParaLifecycle)> {
+		let current = shared::Pallet::<T>::session_index();
+		let last = Self::scheduled_session().saturating_add(T::DowngradeGracePeriod::get());
+
+		let mut transitions = Vec::new();
+		for session in current.saturating_add(1)..=last {
+			for para in ActionsQueue::<T>::get(session) {
+				if let Some(lifecycle) = ParaLifecycles::<T>::get(&para) {
+					transitions.push((para, session, lifecycle));
+				}
+			}
+		}
+		transitions
+	}
+
 	/// Returns whether the given ID refers to a valid para.
 	///
 	/// Paras that are onboarding or offboarding are not included.
@@ -1126,6 +1327,17 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
+	/// Store validation code on behalf of another pallet, which holds a reference on it until it
+	/// calls `release_code`. Paras using the same code share the stored copy.
+	pub fn retain_code(code: &ValidationCode) {
+		Self::increase_code_ref(&code.hash(), code);
+	}
+
+	/// Release a reference on validation code taken with `retain_code`.
+	pub fn release_code(code_hash: &ValidationCodeHash) {
+		Self::decrease_code_ref(code_hash);
+	}
+
 	/// Test function for triggering a new session in this pallet.
 	#[cfg(any(feature = "std", feature = "runtime-benchmarks", test))]
 	pub fn test_on_new_session() {
@@ -1139,12 +1351,15 @@ impl<T: Config> Pallet<T> {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use frame_support::assert_ok;
+	use frame_support::{assert_noop, assert_ok};
 	use primitives::v1::BlockNumber;
 
 	use crate::{
 		configuration::HostConfiguration,
-		mock::{new_test_ext, Configuration, MockGenesisConfig, Paras, ParasShared, System},
+		mock::{
+			assert_last_event, new_test_ext, Configuration, DowngradeGracePeriod,
+			MockGenesisConfig, Origin, Paras, ParasShared, System, Test,
+		},
 	};
 
 	fn run_to_block(to: BlockNumber, new_session: Option<Vec<BlockNumber>>) {
@@ -1554,6 +1769,93 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn code_upgrade_can_be_aborted() {
+		let validation_upgrade_delay = 5;
+		let validation_upgrade_frequency = 10;
+
+		let original_code = ValidationCode(vec![1, 2, 3]);
+		let paras = vec![(
+			0u32.into(),
+			ParaGenesisArgs {
+				parachain: true,
+				genesis_head: Default::default(),
+				validation_code: original_code.clone(),
+			},
+		)];
+
+		let genesis_config = MockGenesisConfig {
+			paras: GenesisConfig { paras, ..Default::default() },
+			configuration: crate::configuration::GenesisConfig {
+				config: HostConfiguration {
+					validation_upgrade_delay,
+					validation_upgrade_frequency,
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		};
+
+		new_test_ext(genesis_config).execute_with(|| {
+			let para_id = ParaId::from(0);
+			let new_code = ValidationCode(vec![4, 5, 6]);
+
+			run_to_block(2, None);
+			assert_noop!(
+				Paras::force_abort_code_upgrade(Origin::root(), para_id),
+				Error::<Test>::NoPendingCodeUpgrade,
+			);
+
+			Paras::schedule_code_upgrade(para_id, new_code.clone(), 1, &Configuration::config());
+			Paras::note_new_head(para_id, Default::default(), 1);
+			check_code_is_stored(&new_code);
+
+			assert_ok!(Paras::force_abort_code_upgrade(Origin::root(), para_id));
+			assert_last_event(Event::CodeUpgradeAborted(para_id).into());
+
+			// the upgrade is gone, but the cooldown still applies.
+			assert!(<Paras as Store>::FutureCodeUpgrades::get(&para_id).is_none());
+			assert!(<Paras as Store>::FutureCodeHash::get(&para_id).is_none());
+			assert!(<Paras as Store>::UpcomingUpgrades::get().is_empty());
+			assert_eq!(
+				<Paras as Store>::UpgradeGoAheadSignal::get(&para_id),
+				Some(UpgradeGoAhead::Abort)
+			);
+			assert_eq!(
+				<Paras as Store>::UpgradeRestrictionSignal::get(&para_id),
+				Some(UpgradeRestriction::Present)
+			);
+			check_code_is_not_stored(&new_code);
+			assert_eq!(<Paras as Store>::AbortedUpgrades::get(&para_id), Some(2));
+			assert_noop!(
+				Paras::schedule_code_upgrade_now(para_id, new_code.clone()),
+				Error::<Test>::CodeUpgradeRestricted,
+			);
+
+			// a parablock built on top of a relay-parent from before the abort hasn't observed
+			// the abort signal, so it is kept.
+			Paras::note_new_head(para_id, Default::default(), 1);
+			assert_eq!(
+				<Paras as Store>::UpgradeGoAheadSignal::get(&para_id),
+				Some(UpgradeGoAhead::Abort)
+			);
+
+			// the go-ahead signal is not raised when the upgrade would have been due.
+			run_to_block(1 + validation_upgrade_delay + 1, None);
+			assert_eq!(
+				<Paras as Store>::UpgradeGoAheadSignal::get(&para_id),
+				Some(UpgradeGoAhead::Abort)
+			);
+
+			// the next parablock observed the abort signal, so it is reset.
+			Paras::note_new_head(para_id, Default::default(), 1 + validation_upgrade_delay);
+			assert!(<Paras as Store>::UpgradeGoAheadSignal::get(&para_id).is_none());
+			assert!(<Paras as Store>::AbortedUpgrades::get(&para_id).is_none());
+			assert_eq!(Paras::current_code(&para_id), Some(original_code));
+		});
+	}
+
 	#[test]
 	fn code_upgrade_applied_after_delay_even_when_late() {
 		let code_retention_period = 10;
@@ -1907,6 +2209,142 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn delayed_parachain_downgrade_can_be_cancelled() {
+		let a = ParaId::from(100);
+		let b = ParaId::from(200);
+		let genesis_args = |code: u8| ParaGenesisArgs {
+			parachain: true,
+			genesis_head: Default::default(),
+			validation_code: vec![code].into(),
+		};
+		let genesis_config = MockGenesisConfig {
+			paras: GenesisConfig {
+				paras: vec![(a, genesis_args(1)), (b, genesis_args(2))],
+				..Default::default()
+			},
+			..Default::default()
+		};
+
+		new_test_ext(genesis_config).execute_with(|| {
+			DowngradeGracePeriod::set(&2);
+			run_to_block(1, None);
+
+			// session 0: the downgrades are scheduled for session 2 + 2.
+			assert_ok!(Paras::schedule_delayed_parachain_downgrade(a));
+			assert_last_event(Event::ParachainDowngradeScheduled(a, 4).into());
+			assert_ok!(Paras::schedule_delayed_parachain_downgrade(b));
+			assert_eq!(Paras::lifecycle(a), Some(ParaLifecycle::DowngradingParachain));
+			assert_eq!(
+				Paras::upcoming_lifecycle_transitions(),
+				vec![
+					(a, 4, ParaLifecycle::DowngradingParachain),
+					(b, 4, ParaLifecycle::DowngradingParachain),
+				],
+			);
+
+			// session 1: `a` renews before the downgrade session becomes the scheduled one.
+			run_to_block(2, Some(vec![2]));
+			assert_ok!(Paras::schedule_parathread_upgrade(a));
+			assert_last_event(Event::ParachainDowngradeCancelled(a).into());
+			assert_eq!(Paras::lifecycle(a), Some(ParaLifecycle::Parachain));
+			assert_eq!(
+				Paras::upcoming_lifecycle_transitions(),
+				vec![(b, 4, ParaLifecycle::DowngradingParachain)],
+			);
+
+			// session 2: the downgrade of `b` can no longer be cancelled, but it is still a
+			// parachain.
+			run_to_block(3, Some(vec![3]));
+			assert_eq!(Paras::parachains(), vec![a, b]);
+
+			// session 4: `b` is downgraded.
+			run_to_block(5, Some(vec![4, 5]));
+			assert_eq!(Paras::parachains(), vec![a]);
+			assert_eq!(Paras::lifecycle(a), Some(ParaLifecycle::Parachain));
+			assert_eq!(Paras::lifecycle(b), Some(ParaLifecycle::Parathread));
+			assert!(<Paras as Store>::UpcomingDowngrades::get(&b).is_none());
+			assert!(Paras::upcoming_lifecycle_transitions().is_empty());
+		});
+	}
+
+	#[test]
+	fn parachain_renewed_too_late_is_upgraded_after_its_downgrade() {
+		let a = ParaId::from(100);
+		let genesis_config = MockGenesisConfig {
+			paras: GenesisConfig {
+				paras: vec![(
+					a,
+					ParaGenesisArgs {
+						parachain: true,
+						genesis_head: Default::default(),
+						validation_code: vec![1].into(),
+					},
+				)],
+				..Default::default()
+			},
+			..Default::default()
+		};
+
+		new_test_ext(genesis_config).execute_with(|| {
+			run_to_block(1, None);
+
+			// session 0: the downgrade is scheduled for session 2, which can't be cancelled.
+			assert_ok!(Paras::schedule_delayed_parachain_downgrade(a));
+			assert_ok!(Paras::schedule_parathread_upgrade(a));
+			assert_last_event(Event::UpgradeAfterDowngradeScheduled(a).into());
+			assert_eq!(Paras::lifecycle(a), Some(ParaLifecycle::DowngradingParachain));
+
+			// session 2: `a` is downgraded, and the upgrade is scheduled for session 4.
+			run_to_block(3, Some(vec![2, 3]));
+			assert!(Paras::parachains().is_empty());
+			assert_eq!(Paras::lifecycle(a), Some(ParaLifecycle::UpgradingParathread));
+			assert!(<Paras as Store>::UpgradesAfterDowngrade::get(&a).is_none());
+			assert_eq!(
+				Paras::upcoming_lifecycle_transitions(),
+				vec![(a, 4, ParaLifecycle::UpgradingParathread)],
+			);
+
+			// session 4: `a` is a parachain again.
+			run_to_block(5, Some(vec![4, 5]));
+			assert_eq!(Paras::parachains(), vec![a]);
+			assert_eq!(Paras::lifecycle(a), Some(ParaLifecycle::Parachain));
+		});
+	}
+
+	#[test]
+	fn upgrade_after_downgrade_can_be_cancelled() {
+		let a = ParaId::from(100);
+		let genesis_config = MockGenesisConfig {
+			paras: GenesisConfig {
+				paras: vec![(
+					a,
+					ParaGenesisArgs {
+						parachain: true,
+						genesis_head: Default::default(),
+						validation_code: vec![1].into(),
+					},
+				)],
+				..Default::default()
+			},
+			..Default::default()
+		};
+
+		new_test_ext(genesis_config).execute_with(|| {
+			run_to_block(1, None);
+
+			assert_ok!(Paras::schedule_delayed_parachain_downgrade(a));
+			assert_ok!(Paras::schedule_parathread_upgrade(a));
+			// Losing the lease again only cancels the upgrade, the downgrade stands.
+			assert_ok!(Paras::schedule_delayed_parachain_downgrade(a));
+			assert!(<Paras as Store>::UpgradesAfterDowngrade::get(&a).is_none());
+
+			run_to_block(5, Some(vec![2, 3, 4, 5]));
+			assert!(Paras::parachains().is_empty());
+			assert_eq!(Paras::lifecycle(a), Some(ParaLifecycle::Parathread));
+		});
+	}
+
 	#[test]
 	fn code_hash_at_with_intermediate() {
 		let code_retention_period = 10;
@@ -2069,6 +2507,22 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn verify_future_code_upgrade_is_externally_accessible() {
+		use primitives::v1::well_known_keys;
+
+		let a = ParaId::from(2020);
+
+		new_test_ext(Default::default()).execute_with(|| {
+			assert!(sp_io::storage::get(&well_known_keys::future_code_upgrade_at(a)).is_none());
+			<Paras as Store>::FutureCodeUpgrades::insert(&a, 42);
+			assert_eq!(
+				sp_io::storage::get(&well_known_keys::future_code_upgrade_at(a)).unwrap(),
+				42u32.encode(),
+			);
+		});
+	}
+
 	#[test]
 	fn verify_upgrade_restriction_signal_is_externally_accessible() {
 		use primitives::v1::well_known_keys;
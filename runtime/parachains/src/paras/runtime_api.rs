@@ -0,0 +1,33 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API for inspecting the upcoming lifecycle transitions of paras.
+
+use super::ParaLifecycle;
+use primitives::v1::{Id as ParaId, SessionIndex};
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	/// The API for inspecting the lifecycle of paras.
+	pub trait ParasApi {
+		/// Get the lifecycle transitions of paras which take effect within the upcoming sessions.
+		///
+		/// Each entry is the para, the session at whose start the transition takes effect, and
+		/// the transitional lifecycle state of the para. This covers every downgrade which can
+		/// still be cancelled.
+		fn upcoming_lifecycle_transitions() -> Vec<(ParaId, SessionIndex, ParaLifecycle)>;
+	}
+}
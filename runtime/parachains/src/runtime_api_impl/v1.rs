@@ -18,11 +18,11 @@
 //! functions.
 
 use crate::{
-	configuration, dmp, hrmp, inclusion, initializer, paras, scheduler, session_info, shared,
+	configuration, dmp, hrmp, inclusion, initializer, paras, scheduler, session_info, shared, ump,
 };
 use primitives::v1::{
 	AuthorityDiscoveryId, CandidateEvent, CommittedCandidateReceipt, CoreIndex, CoreOccupied,
-	CoreState, GroupIndex, GroupRotationInfo, Id as ParaId, InboundDownwardMessage,
+	CoreState, GroupIndex, GroupRotationInfo, HrmpParaState, Id as ParaId, InboundDownwardMessage,
 	InboundHrmpMessage, OccupiedCore, OccupiedCoreAssumption, PersistedValidationData,
 	ScheduledCore, SessionIndex, SessionInfo, ValidationCode, ValidationCodeHash, ValidatorId,
 	ValidatorIndex,
@@ -292,13 +292,15 @@ where
 	<frame_system::Pallet<T>>::read_events_no_consensus()
 		.into_iter()
 		.filter_map(|record| extract_event(record.event))
-		.map(|event| match event {
+		.filter_map(|event| match event {
 			RawEvent::<T>::CandidateBacked(c, h, core, group) =>
-				CandidateEvent::CandidateBacked(c, h, core, group),
+				Some(CandidateEvent::CandidateBacked(c, h, core, group)),
 			RawEvent::<T>::CandidateIncluded(c, h, core, group) =>
-				CandidateEvent::CandidateIncluded(c, h, core, group),
+				Some(CandidateEvent::CandidateIncluded(c, h, core, group)),
 			RawEvent::<T>::CandidateTimedOut(c, h, core) =>
-				CandidateEvent::CandidateTimedOut(c, h, core),
+				Some(CandidateEvent::CandidateTimedOut(c, h, core)),
+			// the reasons of a time-out are exposed by the inclusion runtime API instead.
+			RawEvent::<T>::AvailabilityTimedOut(..) => None,
 			RawEvent::<T>::__Ignore(_, _) => unreachable!("__Ignore cannot be used"),
 		})
 		.collect()
@@ -316,6 +318,20 @@ pub fn dmq_contents<T: dmp::Config>(
 	<dmp::Pallet<T>>::dmq_contents(recipient)
 }
 
+/// Implementation for the `dmq_length` function of the DMP runtime API.
+pub fn dmq_length<T: dmp::Config>(recipient: ParaId) -> u32 {
+	<dmp::Pallet<T>>::dmq_length(recipient)
+}
+
+/// Implementation for the `dmq_contents_page` function of the DMP runtime API.
+pub fn dmq_contents_page<T: dmp::Config>(
+	recipient: ParaId,
+	start: u32,
+	count: u32,
+) -> Vec<InboundDownwardMessage<T::BlockNumber>> {
+	<dmp::Pallet<T>>::dmq_contents_page(recipient, start, count)
+}
+
 /// Implementation for the `inbound_hrmp_channels_contents` function of the runtime API.
 pub fn inbound_hrmp_channels_contents<T: hrmp::Config>(
 	recipient: ParaId,
@@ -329,3 +345,44 @@ pub fn validation_code_by_hash<T: paras::Config>(
 ) -> Option<ValidationCode> {
 	<paras::Pallet<T>>::code_by_hash(hash)
 }
+
+/// Implementation for the `hrmp_state` function of the runtime API.
+pub fn hrmp_state<T: hrmp::Config>(para_id: ParaId) -> HrmpParaState<T::BlockNumber> {
+	<hrmp::Pallet<T>>::hrmp_para_state(para_id)
+}
+
+/// Implementation for the `pending_config` function of the configuration runtime API.
+pub fn pending_config<T: configuration::Config>(
+	session: SessionIndex,
+) -> Option<configuration::HostConfiguration<T::BlockNumber>> {
+	<configuration::Pallet<T>>::pending_config(session)
+}
+
+/// Implementation for the `pending_config_changes` function of the configuration runtime API.
+pub fn pending_config_changes<T: configuration::Config>(
+	session: SessionIndex,
+) -> Vec<configuration::HostConfigurationChange<T::BlockNumber>> {
+	<configuration::Pallet<T>>::pending_config_changes(session)
+}
+
+/// Implementation for the `upcoming_lifecycle_transitions` function of the paras runtime API.
+pub fn upcoming_lifecycle_transitions<T: paras::Config>(
+) -> Vec<(ParaId, SessionIndex, paras::ParaLifecycle)> {
+	<paras::Pallet<T>>::upcoming_lifecycle_transitions()
+}
+
+/// Implementation for the `upward_queues` function of the UMP runtime API.
+pub fn upward_queues<T: ump::Config>() -> Vec<ump::UpwardQueueState> {
+	<ump::Pallet<T>>::upward_queues()
+}
+
+/// Implementation for the `overweight_messages` function of the UMP runtime API.
+pub fn overweight_messages<T: ump::Config>() -> Vec<ump::OverweightMessage<T::BlockNumber>> {
+	<ump::Pallet<T>>::overweight_messages()
+}
+
+/// Implementation for the `availability_timeouts` function of the inclusion runtime API.
+pub fn availability_timeouts<T: inclusion::Config>(
+) -> Vec<(CoreIndex, inclusion::AvailabilityTimeout<T::BlockNumber>)> {
+	<inclusion::Pallet<T>>::availability_timeouts()
+}
@@ -41,7 +41,10 @@ use primitives::v1::{
 	ParathreadClaim, ParathreadEntry, ScheduledCore, ValidatorIndex,
 };
 use scale_info::TypeInfo;
-use sp_runtime::traits::{One, Saturating};
+use sp_runtime::{
+	traits::{One, Saturating},
+	Perbill,
+};
 use sp_std::{convert::TryInto, prelude::*};
 
 use crate::{configuration, initializer::SessionChangeNotification, paras};
@@ -89,6 +92,32 @@ impl ParathreadClaimQueue {
 	}
 }
 
+/// Reasons a parathread claim could not be added to the queue.
+#[derive(Clone, Copy, PartialEq, Eq, sp_core::RuntimeDebug)]
+pub enum ParathreadClaimError {
+	/// The claim does not correspond to a live parathread.
+	NotParathread,
+	/// The queue of parathread claims is full.
+	QueueFull,
+	/// There already is a claim for the parathread in the queue or on a core.
+	AlreadyClaimed,
+}
+
+/// Something which is notified when a parathread claim leaves the scheduler.
+pub trait ParathreadClaimHandler {
+	/// A candidate of the claiming parathread was included and the claim has been used up.
+	fn claim_concluded(para: ParaId);
+
+	/// The claim was dropped without a candidate of the claiming parathread being included,
+	/// e.g. because it ran out of retries or the para is no longer a parathread.
+	fn claim_dropped(para: ParaId);
+}
+
+impl ParathreadClaimHandler for () {
+	fn claim_concluded(_: ParaId) {}
+	fn claim_dropped(_: ParaId) {}
+}
+
 /// Reasons a core might be freed
 pub enum FreedReason {
 	/// The core's work concluded and the parablock assigned to it is considered available.
@@ -152,7 +181,10 @@ pub mod pallet {
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config + configuration::Config + paras::Config {}
+	pub trait Config: frame_system::Config + configuration::Config + paras::Config {
+		/// Notified when parathread claims are concluded or dropped.
+		type ClaimHandler: ParathreadClaimHandler;
+	}
 
 	/// All the validator groups. One for each core. Indices are into `ActiveValidators` - not the
 	/// broader set of diamond validators, but instead just the subset used for parachains during
@@ -293,6 +325,9 @@ impl<T: Config> Pallet<T> {
 		ParathreadClaimIndex::<T>::mutate(|claim_index| {
 			// wipe all parathread metadata if no parathread cores are configured.
 			if config.parathread_cores == 0 {
+				for queued in &thread_queue.queue {
+					T::ClaimHandler::claim_dropped(queued.claim.claim.0);
+				}
 				thread_queue = ParathreadClaimQueue { queue: Vec::new(), next_core_offset: 0 };
 				claim_index.clear();
 				return
@@ -310,6 +345,8 @@ impl<T: Config> Pallet<T> {
 					if let Ok(i) = claim_index.binary_search(&claim_para) {
 						claim_index.remove(i);
 					}
+
+					T::ClaimHandler::claim_dropped(claim_para);
 				}
 
 				will_keep
@@ -337,8 +374,15 @@ impl<T: Config> Pallet<T> {
 	/// Fails if the claim does not correspond to any live parathread.
 	#[allow(unused)]
 	pub fn add_parathread_claim(claim: ParathreadClaim) {
+		let _ = Self::try_add_parathread_claim(claim);
+	}
+
+	/// Add a parathread claim to the queue, returning the reason if it could not be added.
+	///
+	/// See [`Pallet::add_parathread_claim`].
+	pub fn try_add_parathread_claim(claim: ParathreadClaim) -> Result<(), ParathreadClaimError> {
 		if !<paras::Pallet<T>>::is_parathread(claim.0) {
-			return
+			return Err(ParathreadClaimError::NotParathread)
 		}
 
 		let config = <configuration::Pallet<T>>::config();
@@ -346,7 +390,7 @@ impl<T: Config> Pallet<T> {
 
 		ParathreadQueue::<T>::mutate(|queue| {
 			if queue.queue.len() >= queue_max_size as usize {
-				return
+				return Err(ParathreadClaimError::QueueFull)
 			}
 
 			let para_id = claim.0;
@@ -361,14 +405,28 @@ impl<T: Config> Pallet<T> {
 				});
 
 			if competes_with_another {
-				return
+				return Err(ParathreadClaimError::AlreadyClaimed)
 			}
 
 			let entry = ParathreadEntry { claim, retries: 0 };
 			queue.enqueue_entry(entry, config.parathread_cores);
+			Ok(())
 		})
 	}
 
+	/// The share of the parathread claim queue which is currently in use.
+	///
+	/// A full queue is reported if there is no capacity for parathread claims at all.
+	pub fn parathread_queue_utilization() -> Perbill {
+		let config = <configuration::Pallet<T>>::config();
+		let queue_max_size = config.parathread_cores * config.scheduling_lookahead;
+		if queue_max_size == 0 {
+			return Perbill::one()
+		}
+
+		Perbill::from_rational(ParathreadQueue::<T>::get().queue.len() as u32, queue_max_size)
+	}
+
 	/// Schedule all unassigned cores, where possible. Provide a list of cores that should be considered
 	/// newly-freed along with the reason for them being freed. The list is assumed to be sorted in
 	/// ascending order by core index.
@@ -393,7 +451,8 @@ impl<T: Config> Pallet<T> {
 									if let Ok(i) = index.binary_search(&entry.claim.0) {
 										index.remove(i);
 									}
-								})
+								});
+								T::ClaimHandler::claim_concluded(entry.claim.0);
 							},
 							FreedReason::TimedOut => {
 								// If a parathread candidate times out, it's not the collator's fault,
@@ -728,17 +787,23 @@ impl<T: Config> Pallet<T> {
 		ParathreadQueue::<T>::mutate(|queue| {
 			for core_assignment in Scheduled::<T>::take() {
 				if let AssignmentKind::Parathread(collator, retries) = core_assignment.kind {
-					if !<paras::Pallet<T>>::is_parathread(core_assignment.para_id) {
-						continue
-					}
-
+					let para_id = core_assignment.para_id;
 					let entry = ParathreadEntry {
-						claim: ParathreadClaim(core_assignment.para_id, collator),
+						claim: ParathreadClaim(para_id, collator),
 						retries: retries + 1,
 					};
 
-					if entry.retries <= config.parathread_retries {
+					if <paras::Pallet<T>>::is_parathread(para_id) &&
+						entry.retries <= config.parathread_retries
+					{
 						queue.enqueue_entry(entry, config.parathread_cores);
+					} else {
+						ParathreadClaimIndex::<T>::mutate(|index| {
+							if let Ok(i) = index.binary_search(&para_id) {
+								index.remove(i);
+							}
+						});
+						T::ClaimHandler::claim_dropped(para_id);
 					}
 				}
 			}
@@ -1694,6 +1759,9 @@ mod tests {
 
 			run_to_block(2 + max_retries + 1, |_| None);
 			assert_eq!(Scheduler::scheduled().len(), 0);
+
+			// the threads are free to make new claims.
+			assert!(ParathreadClaimIndex::<Test>::get().is_empty());
 		});
 	}
 
@@ -46,6 +46,12 @@ pub mod pallet {
 		+ scheduler::Config
 		+ AuthorityDiscoveryConfig
 	{
+		/// The number of past sessions whose info is retained, on top of the current session.
+		///
+		/// The info is always retained for at least `config.dispute_period` sessions, so this
+		/// only has an effect when greater than that.
+		#[pallet::constant]
+		type RetentionPeriod: Get<SessionIndex>;
 	}
 
 	/// Assignment keys for the current session.
@@ -88,7 +94,7 @@ impl<T: Config> Pallet<T> {
 	) {
 		let config = <configuration::Pallet<T>>::config();
 
-		let dispute_period = config.dispute_period;
+		let retention_period = core::cmp::max(config.dispute_period, T::RetentionPeriod::get());
 
 		let validators = notification.validators.clone();
 		let discovery_keys = <T as AuthorityDiscoveryConfig>::authorities();
@@ -105,7 +111,7 @@ impl<T: Config> Pallet<T> {
 
 		let new_session_index = notification.session_index;
 		let old_earliest_stored_session = EarliestStoredSession::<T>::get();
-		let new_earliest_stored_session = new_session_index.saturating_sub(dispute_period);
+		let new_earliest_stored_session = new_session_index.saturating_sub(retention_period);
 		let new_earliest_stored_session =
 			core::cmp::max(new_earliest_stored_session, old_earliest_stored_session);
 		// remove all entries from `Sessions` from the previous value up to the new value
@@ -114,7 +120,7 @@ impl<T: Config> Pallet<T> {
 			for idx in old_earliest_stored_session..new_earliest_stored_session {
 				Sessions::<T>::remove(&idx);
 			}
-			// update `EarliestStoredSession` based on `config.dispute_period`
+			// update `EarliestStoredSession` based on the retention period
 			EarliestStoredSession::<T>::set(new_earliest_stored_session);
 		} else {
 			// just introduced on a live chain
@@ -176,8 +182,8 @@ mod tests {
 		configuration::HostConfiguration,
 		initializer::SessionChangeNotification,
 		mock::{
-			new_test_ext, Configuration, MockGenesisConfig, Origin, ParasShared, SessionInfo,
-			System, Test,
+			new_test_ext, Configuration, MockGenesisConfig, Origin, ParasShared, RetentionPeriod,
+			SessionInfo, System, Test,
 		},
 		util::take_active_subset,
 	};
@@ -303,6 +309,24 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn session_pruning_respects_retention_period() {
+		new_test_ext(genesis_config()).execute_with(|| {
+			RetentionPeriod::set(&6);
+
+			// the retention period exceeds the dispute period of 2.
+			run_to_block(100, session_changes);
+			assert_eq!(EarliestStoredSession::<Test>::get(), 10 - 6);
+			assert!(Sessions::<Test>::get(3).is_none());
+			assert!(Sessions::<Test>::get(4).is_some());
+
+			// the dispute period exceeds the retention period.
+			Configuration::set_dispute_period(Origin::root(), 8).unwrap();
+			run_to_block(200, session_changes);
+			assert_eq!(EarliestStoredSession::<Test>::get(), 20 - 8);
+		})
+	}
+
 	#[test]
 	fn session_info_is_based_on_config() {
 		new_test_ext(genesis_config()).execute_with(|| {
@@ -18,9 +18,15 @@ use crate::{
 	configuration::{self, HostConfiguration},
 	initializer,
 };
-use frame_support::{pallet_prelude::*, traits::EnsureOrigin};
+use frame_support::{
+	pallet_prelude::*,
+	traits::{EnsureOrigin, ReservableCurrency},
+};
 use frame_system::pallet_prelude::*;
-use primitives::v1::{Id as ParaId, UpwardMessage};
+use parity_scale_codec::{Decode, Encode};
+use primitives::v1::{Balance, Id as ParaId, UpwardMessage};
+use scale_info::TypeInfo;
+use sp_runtime::traits::{AccountIdConversion, UniqueSaturatedInto};
 use sp_std::{
 	collections::btree_map::BTreeMap, convert::TryFrom, fmt, marker::PhantomData, mem, prelude::*,
 };
@@ -28,6 +34,11 @@ use xcm::latest::Outcome;
 
 pub use pallet::*;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod runtime_api;
+pub mod weights;
+
 /// All upward messages coming from parachains will be funneled into an implementation of this trait.
 ///
 /// The message is opaque from the perspective of UMP. The message size can range from 0 to
@@ -74,6 +85,44 @@ pub type MessageId = [u8; 32];
 /// Index used to identify overweight messages.
 pub type OverweightIndex = u64;
 
+/// The bookkeeping of a message in the overweight queue.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct OverweightMessageMeta<BlockNumber> {
+	/// The weight the message required the last time its execution was attempted.
+	pub required_weight: Weight,
+	/// The block at which the message is dropped if it wasn't serviced until then.
+	pub expires_at: BlockNumber,
+	/// The deposit reserved from the sovereign account of the sending para.
+	pub deposit: Balance,
+}
+
+/// The state of the upward message queue of a para.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct UpwardQueueState {
+	/// The para which sent the messages.
+	pub para: ParaId,
+	/// The number of messages pending dispatch.
+	pub count: u32,
+	/// The total size in bytes of the messages pending dispatch.
+	pub total_size: u32,
+	/// The sizes in bytes of the messages pending dispatch, in the order they are dispatched.
+	pub message_sizes: Vec<u32>,
+}
+
+/// A message waiting in the overweight queue.
+#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct OverweightMessage<BlockNumber> {
+	/// The index of the message in the overweight queue.
+	pub index: OverweightIndex,
+	/// The para which sent the message.
+	pub para: ParaId,
+	/// The size of the message in bytes.
+	pub size: u32,
+	/// The bookkeeping of the message. `None` for messages stashed before the bookkeeping was
+	/// introduced, which neither expire nor hold a deposit.
+	pub meta: Option<OverweightMessageMeta<BlockNumber>>,
+}
+
 /// A specific implementation of a `UmpSink` where messages are in the XCM format
 /// and will be forwarded to the XCM Executor.
 pub struct XcmSink<XcmExecutor, Config>(PhantomData<(XcmExecutor, Config)>);
@@ -163,6 +212,10 @@ impl fmt::Debug for AcceptanceCheckErr {
 	}
 }
 
+pub trait WeightInfo {
+	fn purge_upward_queue(m: u32) -> Weight;
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -187,8 +240,27 @@ pub mod pallet {
 		/// Generally you'll want this to be a bit more - 150 or 200 would be good values.
 		type FirstMessageFactorPercent: Get<Weight>;
 
-		/// Origin which is allowed to execute overweight messages.
+		/// Origin which is allowed to execute overweight messages and to purge upward message
+		/// queues.
 		type ExecuteOverweightOrigin: EnsureOrigin<Self::Origin>;
+
+		/// The currency used to reserve the deposits for overweight messages.
+		///
+		/// The deposits are reserved from the sovereign account of the sending para.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// The deposit reserved per byte of a message moved to the overweight queue.
+		///
+		/// The deposit is returned as soon as the message leaves the overweight queue. If the
+		/// sending para cannot afford it, the message is stashed without a deposit.
+		type OverweightDepositPerByte: Get<Balance>;
+
+		/// The number of blocks after which an overweight message which was not serviced, neither
+		/// manually nor automatically in idle time, is dropped.
+		type OverweightExpiry: Get<Self::BlockNumber>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
 	}
 
 	#[pallet::event]
@@ -221,6 +293,14 @@ pub mod pallet {
 		///
 		/// \[ overweight_index, used \]
 		OverweightServiced(OverweightIndex, Weight),
+		/// An overweight message was not serviced before its expiry and has been dropped.
+		///
+		/// \[ overweight_index \]
+		OverweightExpired(OverweightIndex),
+		/// All the upward messages pending dispatch of a para were dropped.
+		///
+		/// \[ para, count \]
+		UpwardQueuePurged(ParaId, u32),
 	}
 
 	#[pallet::error]
@@ -229,6 +309,10 @@ pub mod pallet {
 		UnknownMessageIndex,
 		/// The amount of weight given is possibly not enough for executing the message.
 		WeightOverLimit,
+		/// The para has no upward messages pending dispatch.
+		NoPendingMessages,
+		/// The para has more upward messages pending dispatch than the given upper bound.
+		TooManyPendingMessages,
 	}
 
 	/// The messages waiting to be handled by the relay-chain originating from a certain parachain.
@@ -286,6 +370,29 @@ pub mod pallet {
 	#[pallet::storage]
 	pub type OverweightCount<T: Config> = StorageValue<_, OverweightIndex, ValueQuery>;
 
+	/// The bookkeeping of the messages in `Overweight`.
+	///
+	/// Messages stashed before the bookkeeping was introduced don't have an entry here.
+	#[pallet::storage]
+	pub type OverweightMeta<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		OverweightIndex,
+		OverweightMessageMeta<T::BlockNumber>,
+		OptionQuery,
+	>;
+
+	/// The index of the overweight message the next automatic retry in idle time starts with.
+	#[pallet::storage]
+	pub type NextOverweightRetry<T: Config> = StorageValue<_, OverweightIndex, ValueQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::retry_overweight(now, remaining_weight)
+		}
+	}
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// Service a single overweight upward message.
@@ -312,10 +419,43 @@ pub mod pallet {
 				Overweight::<T>::get(index).ok_or(Error::<T>::UnknownMessageIndex)?;
 			let used = T::UmpSink::process_upward_message(sender, &data[..], weight_limit)
 				.map_err(|_| Error::<T>::WeightOverLimit)?;
-			Overweight::<T>::remove(index);
+			Self::remove_overweight(index, sender);
 			Self::deposit_event(Event::OverweightServiced(index, used));
 			Ok(Some(used.saturating_add(1_000_000)).into())
 		}
+
+		/// Drop all the upward messages pending dispatch of a para.
+		///
+		/// This is meant for unblocking the dispatch of other paras when a queue is stuck. The
+		/// overweight messages of the para are left intact.
+		///
+		/// - `origin`: Must pass `ExecuteOverweightOrigin`.
+		/// - `para`: The para whose queue is purged.
+		/// - `max_count`: An upper bound on the number of messages pending dispatch of the para,
+		///   used to weigh the call. The weight of the messages actually purged is charged.
+		///
+		/// Errors:
+		/// - `NoPendingMessages`: The para has no upward messages pending dispatch.
+		/// - `TooManyPendingMessages`: The para has more than `max_count` messages pending
+		///   dispatch.
+		///
+		/// Events:
+		/// - `UpwardQueuePurged`: On success.
+		#[pallet::weight(T::WeightInfo::purge_upward_queue(*max_count))]
+		pub fn purge_upward_queue(
+			origin: OriginFor<T>,
+			para: ParaId,
+			max_count: u32,
+		) -> DispatchResultWithPostInfo {
+			T::ExecuteOverweightOrigin::ensure_origin(origin)?;
+
+			let (count, _) = <Self as Store>::RelayDispatchQueueSize::get(&para);
+			ensure!(count > 0, Error::<T>::NoPendingMessages);
+			ensure!(count <= max_count, Error::<T>::TooManyPendingMessages);
+			Self::clean_ump_after_outgoing(&para);
+			Self::deposit_event(Event::UpwardQueuePurged(para, count));
+			Ok(Some(T::WeightInfo::purge_upward_queue(count)).into())
+		}
 	}
 }
 
@@ -361,7 +501,7 @@ impl<T: Config> Pallet<T> {
 			}
 		});
 		<Self as Store>::NextDispatchRoundStartWith::mutate(|v| {
-			*v = v.filter(|p| p == outgoing_para)
+			*v = v.filter(|p| p != outgoing_para)
 		});
 	}
 
@@ -453,6 +593,10 @@ impl<T: Config> Pallet<T> {
 	}
 
 	/// Devote some time into dispatching pending upward messages.
+	///
+	/// The weight is shared fairly between the paras: a para doesn't get to dispatch another
+	/// message once it used its share of the weight in this block, unless all the other paras
+	/// used theirs as well. In that case the remaining weight is shared anew.
 	pub(crate) fn process_pending_upward_messages() -> Weight {
 		let mut weight_used = 0;
 
@@ -460,6 +604,11 @@ impl<T: Config> Pallet<T> {
 		let mut cursor = NeedsDispatchCursor::new::<T>();
 		let mut queue_cache = QueueCache::new();
 
+		let mut fair_share = Self::fair_share(config.ump_service_total_weight, cursor.len());
+		let mut para_weight_used = BTreeMap::<ParaId, Weight>::new();
+		// The number of paras skipped in a row for having used their share.
+		let mut skipped = 0;
+
 		while let Some(dispatchee) = cursor.peek() {
 			if weight_used >= config.ump_service_total_weight {
 				// Then check whether we've reached or overshoot the
@@ -468,6 +617,22 @@ impl<T: Config> Pallet<T> {
 				// if so - bail.
 				break
 			}
+
+			if para_weight_used.get(&dispatchee).map_or(false, |used| *used >= fair_share) {
+				skipped += 1;
+				if skipped < cursor.len() {
+					cursor.advance();
+					continue
+				}
+
+				// every para used its share - split what's left of the weight among them.
+				fair_share = fair_share.saturating_add(Self::fair_share(
+					config.ump_service_total_weight - weight_used,
+					cursor.len(),
+				));
+			}
+			skipped = 0;
+
 			let max_weight = if weight_used == 0 {
 				// we increase the amount of weight that we're allowed to use on the first message to try to prevent
 				// the possibility of blockage of the queue.
@@ -483,6 +648,7 @@ impl<T: Config> Pallet<T> {
 				match T::UmpSink::process_upward_message(dispatchee, upward_message, max_weight) {
 					Ok(used) => {
 						weight_used += used;
+						*para_weight_used.entry(dispatchee).or_default() += used;
 						let _ = queue_cache.consume_front::<T>(dispatchee);
 					},
 					Err((id, required)) => {
@@ -494,7 +660,8 @@ impl<T: Config> Pallet<T> {
 								if we get into this branch then `peek_front` returned `Some`;\
 								thus `upward_message` cannot be `None`; qed",
 							);
-							let index = Self::stash_overweight(dispatchee, upward_message);
+							let index =
+								Self::stash_overweight(dispatchee, upward_message, required);
 							Self::deposit_event(Event::OverweightEnqueued(
 								dispatchee, id, index, required,
 							));
@@ -522,18 +689,150 @@ impl<T: Config> Pallet<T> {
 		weight_used
 	}
 
+	/// The share of `weight` each of `paras` gets. Never zero, so that dispatching makes progress.
+	fn fair_share(weight: Weight, paras: usize) -> Weight {
+		(weight / paras.max(1) as Weight).max(1)
+	}
+
 	/// Puts a given upward message into the list of overweight messages allowing it to be executed
 	/// later.
-	fn stash_overweight(sender: ParaId, upward_message: Vec<u8>) -> OverweightIndex {
+	///
+	/// A deposit for the message is reserved from the sovereign account of the sender, if it can
+	/// afford it.
+	fn stash_overweight(
+		sender: ParaId,
+		upward_message: Vec<u8>,
+		required_weight: Weight,
+	) -> OverweightIndex {
 		let index = <Self as Store>::OverweightCount::mutate(|count| {
 			let index = *count;
 			*count += 1;
 			index
 		});
 
+		let deposit =
+			T::OverweightDepositPerByte::get().saturating_mul(upward_message.len() as Balance);
+		let deposit =
+			match T::Currency::reserve(&sender.into_account(), deposit.unique_saturated_into()) {
+				Ok(()) => deposit,
+				Err(_) => 0,
+			};
+		let expires_at = <frame_system::Pallet<T>>::block_number() + T::OverweightExpiry::get();
+
 		<Self as Store>::Overweight::insert(index, (sender, upward_message));
+		<Self as Store>::OverweightMeta::insert(
+			index,
+			OverweightMessageMeta { required_weight, expires_at, deposit },
+		);
 		index
 	}
+
+	/// Removes the given message from the list of overweight messages, returning the deposit of
+	/// the sender.
+	fn remove_overweight(index: OverweightIndex, sender: ParaId) {
+		<Self as Store>::Overweight::remove(index);
+		if let Some(meta) = <Self as Store>::OverweightMeta::take(index) {
+			T::Currency::unreserve(&sender.into_account(), meta.deposit.unique_saturated_into());
+		}
+	}
+
+	/// Retry executing the overweight messages with the given amount of weight, dropping the ones
+	/// which expired.
+	///
+	/// Every message is considered at most once per call. The retry picks up after the last
+	/// message considered by the previous call.
+	fn retry_overweight(now: T::BlockNumber, limit: Weight) -> Weight {
+		let db_weight = T::DbWeight::get();
+		// reading the retry cursor and the overweight count and writing back the cursor.
+		let mut weight_used = db_weight.reads_writes(2, 1);
+		if weight_used > limit {
+			return 0
+		}
+
+		let count = <Self as Store>::OverweightCount::get();
+		if count == 0 {
+			return weight_used
+		}
+
+		let mut index = <Self as Store>::NextOverweightRetry::get() % count;
+		for _ in 0..count {
+			// reading a message along with its bookkeeping and removing both.
+			let consider_weight = db_weight.reads_writes(2, 2);
+			if weight_used.saturating_add(consider_weight) > limit {
+				break
+			}
+			weight_used += consider_weight;
+
+			let current = index;
+			index = (index + 1) % count;
+
+			let (sender, data) = match <Self as Store>::Overweight::get(current) {
+				Some(message) => message,
+				None => continue,
+			};
+			let meta = <Self as Store>::OverweightMeta::get(current);
+
+			if meta.as_ref().map_or(false, |meta| meta.expires_at <= now) {
+				Self::remove_overweight(current, sender);
+				Self::deposit_event(Event::OverweightExpired(current));
+				continue
+			}
+
+			let available = limit - weight_used;
+			if meta.as_ref().map_or(false, |meta| meta.required_weight > available) {
+				// no point in trying, it won't fit.
+				continue
+			}
+
+			match T::UmpSink::process_upward_message(sender, &data[..], available) {
+				Ok(used) => {
+					weight_used += used;
+					Self::remove_overweight(current, sender);
+					Self::deposit_event(Event::OverweightServiced(current, used));
+				},
+				Err((_, required_weight)) =>
+					if let Some(meta) = meta {
+						<Self as Store>::OverweightMeta::insert(
+							current,
+							OverweightMessageMeta { required_weight, ..meta },
+						);
+					},
+			}
+		}
+
+		<Self as Store>::NextOverweightRetry::put(index);
+		weight_used
+	}
+
+	/// Returns the state of the upward message queues of all paras which have messages pending
+	/// dispatch.
+	pub(crate) fn upward_queues() -> Vec<UpwardQueueState> {
+		<Self as Store>::NeedsDispatch::get()
+			.into_iter()
+			.map(|para| {
+				let (count, total_size) = <Self as Store>::RelayDispatchQueueSize::get(&para);
+				let message_sizes = <Self as Store>::RelayDispatchQueues::get(&para)
+					.iter()
+					.map(|message| message.len() as u32)
+					.collect();
+				UpwardQueueState { para, count, total_size, message_sizes }
+			})
+			.collect()
+	}
+
+	/// Returns the messages waiting in the overweight queue, ordered by their index.
+	pub(crate) fn overweight_messages() -> Vec<OverweightMessage<T::BlockNumber>> {
+		let mut messages = <Self as Store>::Overweight::iter()
+			.map(|(index, (para, data))| OverweightMessage {
+				index,
+				para,
+				size: data.len() as u32,
+				meta: <Self as Store>::OverweightMeta::get(index),
+			})
+			.collect::<Vec<_>>();
+		messages.sort_by_key(|message| message.index);
+		messages
+	}
 }
 
 /// To avoid constant fetching, deserializing and serialization the queues are cached.
@@ -669,6 +968,11 @@ impl NeedsDispatchCursor {
 		Self { needs_dispatch, index: initial_index }
 	}
 
+	/// Returns the number of items the cursor iterates over.
+	fn len(&self) -> usize {
+		self.needs_dispatch.len()
+	}
+
 	/// Returns the item the cursor points to.
 	fn peek(&self) -> Option<ParaId> {
 		self.needs_dispatch.get(self.index).cloned()
@@ -711,7 +1015,11 @@ pub(crate) mod tests {
 		assert_last_event, new_test_ext, take_processed, Configuration, MockGenesisConfig, Origin,
 		System, Test, Ump,
 	};
-	use frame_support::{assert_noop, assert_ok, weights::Weight};
+	use frame_support::{
+		assert_noop, assert_ok,
+		traits::{Currency as _, Hooks},
+		weights::Weight,
+	};
 	use std::collections::HashSet;
 
 	struct GenesisConfigBuilder {
@@ -1041,4 +1349,202 @@ pub(crate) mod tests {
 			);
 		});
 	}
+
+	#[test]
+	fn dispatch_shares_weight_fairly() {
+		let a = ParaId::from(128);
+		let b = ParaId::from(228);
+
+		let a_msg_1 = (300u32, "a_msg_1").encode();
+		let a_msg_2 = (300u32, "a_msg_2").encode();
+		let b_msg_1 = (50u32, "b_msg_1").encode();
+		let b_msg_2 = (50u32, "b_msg_2").encode();
+		let b_msg_3 = (50u32, "b_msg_3").encode();
+
+		new_test_ext(
+			GenesisConfigBuilder {
+				ump_service_total_weight: 600,
+				ump_max_individual_weight: 300,
+				..Default::default()
+			}
+			.build(),
+		)
+		.execute_with(|| {
+			queue_upward_msg(a, a_msg_1.clone());
+			queue_upward_msg(a, a_msg_2.clone());
+			queue_upward_msg(b, b_msg_1.clone());
+			queue_upward_msg(b, b_msg_2.clone());
+			queue_upward_msg(b, b_msg_3.clone());
+
+			// `a` used up its share of 300 with its first message, so `b` gets to dispatch all
+			// of its messages before `a` gets another go at the remaining weight, which is not
+			// enough for its second message.
+			Ump::process_pending_upward_messages();
+			assert_eq!(
+				take_processed(),
+				vec![(a, a_msg_1), (b, b_msg_1), (b, b_msg_2), (b, b_msg_3)],
+			);
+			assert_storage_consistency_exhaustive();
+
+			Ump::process_pending_upward_messages();
+			assert_eq!(take_processed(), vec![(a, a_msg_2)]);
+			assert_storage_consistency_exhaustive();
+		});
+	}
+
+	#[test]
+	fn purge_upward_queue_works() {
+		let a = ParaId::from(128);
+		let b = ParaId::from(228);
+
+		let a_msg = (900u32, "a_msg").encode();
+		let b_msg = (100u32, "b_msg").encode();
+
+		new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+			System::set_block_number(1);
+
+			queue_upward_msg(a, a_msg.clone());
+			queue_upward_msg(a, a_msg.clone());
+			queue_upward_msg(b, b_msg.clone());
+			assert_eq!(
+				Ump::upward_queues(),
+				vec![
+					UpwardQueueState {
+						para: a,
+						count: 2,
+						total_size: 2 * a_msg.len() as u32,
+						message_sizes: vec![a_msg.len() as u32; 2],
+					},
+					UpwardQueueState {
+						para: b,
+						count: 1,
+						total_size: b_msg.len() as u32,
+						message_sizes: vec![b_msg.len() as u32],
+					},
+				],
+			);
+
+			assert_noop!(
+				Ump::purge_upward_queue(Origin::signed(1), a, 2),
+				DispatchError::BadOrigin
+			);
+			assert_noop!(
+				Ump::purge_upward_queue(Origin::root(), a, 1),
+				Error::<Test>::TooManyPendingMessages,
+			);
+			assert_ok!(Ump::purge_upward_queue(Origin::root(), a, 2));
+			assert_last_event(Event::UpwardQueuePurged(a, 2).into());
+			assert_eq!(
+				Ump::upward_queues().into_iter().map(|q| q.para).collect::<Vec<_>>(),
+				vec![b]
+			);
+			assert_storage_consistency_exhaustive();
+
+			assert_noop!(
+				Ump::purge_upward_queue(Origin::root(), a, 2),
+				Error::<Test>::NoPendingMessages,
+			);
+
+			Ump::process_pending_upward_messages();
+			assert_eq!(take_processed(), vec![(b, b_msg)]);
+		});
+	}
+
+	#[test]
+	fn overweight_messages_are_retried_in_idle_time() {
+		let para_a = ParaId::from(2021);
+
+		let a_msg_1 = (600u32, "a_msg_1").encode();
+		let a_msg_2 = (500u32, "a_msg_2").encode();
+
+		new_test_ext(
+			GenesisConfigBuilder {
+				ump_service_total_weight: 900,
+				ump_max_individual_weight: 300,
+				..Default::default()
+			}
+			.build(),
+		)
+		.execute_with(|| {
+			System::set_block_number(1);
+			let sovereign = para_a.into_account();
+			<Test as Config>::Currency::make_free_balance_be(&sovereign, 1000);
+
+			queue_upward_msg(para_a, a_msg_1.clone());
+			queue_upward_msg(para_a, a_msg_2.clone());
+			Ump::process_pending_upward_messages();
+			assert_eq!(take_processed(), vec![(para_a, a_msg_1)]);
+			assert_eq!(
+				Ump::overweight_messages(),
+				vec![OverweightMessage {
+					index: 0,
+					para: para_a,
+					size: a_msg_2.len() as u32,
+					meta: Some(OverweightMessageMeta {
+						required_weight: 500,
+						expires_at: 11,
+						deposit: a_msg_2.len() as Balance,
+					}),
+				}],
+			);
+			assert_eq!(
+				<Test as Config>::Currency::reserved_balance(&sovereign),
+				a_msg_2.len() as Balance,
+			);
+
+			// Not enough weight is left in this block.
+			Ump::on_idle(1, 499);
+			assert_eq!(take_processed(), vec![]);
+			assert!(<Ump as Store>::Overweight::get(0).is_some());
+
+			// But there is in the next one.
+			Ump::on_idle(2, 500);
+			assert_eq!(take_processed(), vec![(para_a, a_msg_2)]);
+			assert_last_event(Event::OverweightServiced(0, 500).into());
+			assert!(Ump::overweight_messages().is_empty());
+			assert_eq!(<Test as Config>::Currency::reserved_balance(&sovereign), 0);
+		});
+	}
+
+	#[test]
+	fn overweight_messages_expire() {
+		let para_a = ParaId::from(2021);
+
+		let a_msg_1 = (600u32, "a_msg_1").encode();
+		let a_msg_2 = (500u32, "a_msg_2").encode();
+
+		new_test_ext(
+			GenesisConfigBuilder {
+				ump_service_total_weight: 900,
+				ump_max_individual_weight: 300,
+				..Default::default()
+			}
+			.build(),
+		)
+		.execute_with(|| {
+			System::set_block_number(1);
+			let sovereign = para_a.into_account();
+			<Test as Config>::Currency::make_free_balance_be(&sovereign, 1000);
+
+			queue_upward_msg(para_a, a_msg_1.clone());
+			queue_upward_msg(para_a, a_msg_2.clone());
+			Ump::process_pending_upward_messages();
+			assert!(<Ump as Store>::Overweight::get(0).is_some());
+
+			Ump::on_idle(10, 0);
+			assert!(<Ump as Store>::Overweight::get(0).is_some());
+
+			// The message is dropped once it expires, even if there is no weight to execute it.
+			Ump::on_idle(11, 0);
+			assert_eq!(take_processed(), vec![(para_a, a_msg_1)]);
+			assert_last_event(Event::OverweightExpired(0).into());
+			assert!(Ump::overweight_messages().is_empty());
+			assert_eq!(<Test as Config>::Currency::reserved_balance(&sovereign), 0);
+
+			assert_noop!(
+				Ump::service_overweight(Origin::root(), 0, 1000),
+				Error::<Test>::UnknownMessageIndex
+			);
+		});
+	}
 }
@@ -0,0 +1,41 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::ump::*;
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
+use frame_system::RawOrigin;
+
+/// The largest number of messages purged from a queue while benchmarking.
+const MAX_PURGED_MESSAGES: u32 = 1_000;
+
+benchmarks! {
+	purge_upward_queue {
+		let m in 1 .. MAX_PURGED_MESSAGES;
+		let para = ParaId::from(1000);
+		let upward_messages = (0..m).map(|i| i.encode()).collect::<Vec<_>>();
+		Pallet::<T>::receive_upward_messages(para, upward_messages);
+	}: _(RawOrigin::Root, para, m)
+	verify {
+		assert_eq!(<Pallet<T> as Store>::RelayDispatchQueueSize::get(&para), (0, 0));
+		assert!(<Pallet<T> as Store>::NeedsDispatch::get().is_empty());
+	}
+}
+
+impl_benchmark_test_suite!(
+	Pallet,
+	crate::mock::new_test_ext(Default::default()),
+	crate::mock::Test
+);
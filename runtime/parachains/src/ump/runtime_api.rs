@@ -0,0 +1,32 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API for inspecting the upward message queues and the overweight messages.
+
+use super::{OverweightMessage, UpwardQueueState};
+use parity_scale_codec::{Decode, Encode};
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	/// The API for inspecting the state of the upward message passing.
+	pub trait UmpApi<N: Encode + Decode> {
+		/// Get the upward message queues of all paras which have messages pending dispatch.
+		fn upward_queues() -> Vec<UpwardQueueState>;
+
+		/// Get the messages waiting in the overweight queue, ordered by their index.
+		fn overweight_messages() -> Vec<OverweightMessage<N>>;
+	}
+}
@@ -0,0 +1,49 @@
+
+//! Weights for `runtime_parachains::ump`
+//!
+//! FIXME: not generated by the benchmark CLI yet, regenerate with the command below. Until then
+//! the base is substrate's benchmarked empty `remark`, and each purged message is charged as one
+//! key removed by `kill_storage`.
+
+// Command to run:
+// ./target/release/diamond
+// benchmark
+// --chain
+// titan-dev
+// --execution=wasm
+// --wasm-execution=compiled
+// --pallet
+// runtime_parachains::ump
+// --steps
+// 50
+// --repeat
+// 20
+// --raw
+// --extrinsic
+// *
+// --output
+// runtime/parachains/src/ump/weights.rs
+
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use frame_system::WeightInfo as _;
+use sp_std::marker::PhantomData;
+
+/// Weight functions for `runtime_parachains::ump`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> super::WeightInfo for WeightInfo<T> {
+	// Storage: Ump RelayDispatchQueueSize (r:1 w:1)
+	// Storage: Ump RelayDispatchQueues (r:0 w:1)
+	// Storage: Ump NeedsDispatch (r:1 w:1)
+	// Storage: Ump NextDispatchRoundStartWith (r:1 w:1)
+	fn purge_upward_queue(m: u32, ) -> Weight {
+		frame_system::weights::SubstrateWeight::<T>::remark(0)
+			.saturating_add(frame_system::weights::SubstrateWeight::<T>::kill_storage(m))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+}
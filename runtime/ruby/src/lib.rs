@@ -25,9 +25,10 @@ use pallet_transaction_payment::CurrencyAdapter;
 use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
 use primitives::v1::{
 	AccountId, AccountIndex, Balance, BlockNumber, CandidateEvent, CommittedCandidateReceipt,
-	CoreState, GroupRotationInfo, Hash, Id as ParaId, InboundDownwardMessage, InboundHrmpMessage,
-	Moment, Nonce, OccupiedCoreAssumption, PersistedValidationData, SessionInfo, Signature,
-	ValidationCode, ValidationCodeHash, ValidatorId, ValidatorIndex,
+	CoreIndex, CoreState, GroupRotationInfo, Hash, HrmpParaState, Id as ParaId,
+	InboundDownwardMessage, InboundHrmpMessage, Moment, Nonce, OccupiedCoreAssumption,
+	PersistedValidationData, SessionInfo, Signature, ValidationCode, ValidationCodeHash,
+	ValidatorId, ValidatorIndex,
 };
 use runtime_common::{
 	auctions, crowdloan, impls::ToAuthor, paras_registrar, paras_sudo_wrapper, slots, xcm_sender,
@@ -47,7 +48,7 @@ use runtime_parachains::{
 
 use xcm::latest::prelude::*;
 use xcm_builder::{
-	AccountId32Aliases, AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom,
+	Account32Hash, AccountId32Aliases, AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom,
 	ChildParachainAsNative, ChildParachainConvertsVia, ChildSystemParachainAsSuperuser,
 	CurrencyAdapter as XcmCurrencyAdapter, FixedWeightBounds, IsChildSystemParachain, IsConcrete,
 	LocationInverter, SignedAccountId32AsNative, SignedToAccountId32, SovereignSignedViaLocation,
@@ -116,13 +117,13 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("ruby"),
 	impl_name: create_runtime_str!("parity-ruby"),
 	authoring_version: 2,
-	spec_version: 9100,
+	spec_version: 9110,
 	impl_version: 0,
 	#[cfg(not(feature = "disable-runtime-api"))]
 	apis: RUNTIME_API_VERSIONS,
 	#[cfg(feature = "disable-runtime-api")]
 	apis: version::create_apis_vec![[]],
-	transaction_version: 5,
+	transaction_version: 6,
 };
 
 /// The BABE epoch configuration at genesis.
@@ -801,7 +802,13 @@ impl parachains_configuration::Config for Runtime {
 
 impl parachains_shared::Config for Runtime {}
 
-impl parachains_session_info::Config for Runtime {}
+parameter_types! {
+	pub const SessionInfoRetentionPeriod: SessionIndex = 24;
+}
+
+impl parachains_session_info::Config for Runtime {
+	type RetentionPeriod = SessionInfoRetentionPeriod;
+}
 
 impl parachains_inclusion::Config for Runtime {
 	type Event = Event;
@@ -809,13 +816,20 @@ impl parachains_inclusion::Config for Runtime {
 	type RewardValidators = parachains_reward_points::RewardValidatorsWithEraPoints<Runtime>;
 }
 
+parameter_types! {
+	pub const DowngradeGracePeriod: SessionIndex = 24;
+}
+
 impl parachains_paras::Config for Runtime {
 	type Origin = Origin;
 	type Event = Event;
+	type DowngradeGracePeriod = DowngradeGracePeriod;
 }
 
 parameter_types! {
 	pub const FirstMessageFactorPercent: u64 = 100;
+	pub const OverweightDepositPerByte: Balance = deposit(0, 1);
+	pub const OverweightExpiry: BlockNumber = 7 * DAYS;
 }
 
 impl parachains_ump::Config for Runtime {
@@ -823,19 +837,32 @@ impl parachains_ump::Config for Runtime {
 	type UmpSink = crate::parachains_ump::XcmSink<XcmExecutor<XcmConfig>, Runtime>;
 	type FirstMessageFactorPercent = FirstMessageFactorPercent;
 	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
+	type Currency = Balances;
+	type OverweightDepositPerByte = OverweightDepositPerByte;
+	type OverweightExpiry = OverweightExpiry;
+	type WeightInfo = weights::runtime_parachains_ump::WeightInfo<Runtime>;
+}
+
+parameter_types! {
+	pub const MaxDownwardQueueSize: u32 = 16 * 1024 * 1024;
 }
 
-impl parachains_dmp::Config for Runtime {}
+impl parachains_dmp::Config for Runtime {
+	type MaxDownwardQueueSize = MaxDownwardQueueSize;
+}
 
 impl parachains_hrmp::Config for Runtime {
 	type Event = Event;
 	type Origin = Origin;
 	type Currency = Balances;
+	type ChannelManager = EnsureRoot<AccountId>;
 }
 
 impl parachains_paras_inherent::Config for Runtime {}
 
-impl parachains_scheduler::Config for Runtime {}
+impl parachains_scheduler::Config for Runtime {
+	type ClaimHandler = ();
+}
 
 impl parachains_initializer::Config for Runtime {
 	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
@@ -889,15 +916,24 @@ impl crowdloan::Config for Runtime {
 	type Registrar = Registrar;
 	type Auctioneer = Auctions;
 	type MaxMemoLength = MaxMemoLength;
+	type RemoteContributorOrigin = pallet_xcm::EnsureXcm<crowdloan::RemoteContributorLocations>;
+	type LocationToAccountId = CrowdloanContributorAccountOf;
+	type XcmRouter = XcmRouter;
 	type WeightInfo = weights::runtime_common_crowdloan::WeightInfo<Runtime>;
 }
 
+/// The local account of a crowdloan contributor in another consensus system. Parachains and local
+/// accounts use their usual account, any other location gets an account derived from its hash.
+pub type CrowdloanContributorAccountOf = (LocationConverter, Account32Hash<RubyNetwork, AccountId>);
+
 parameter_types! {
 	// The average auction is 7 days long, so this will be 70% for ending period.
 	// 5 Days = 72000 Blocks @ 6 sec per block
 	pub const EndingPeriod: BlockNumber = 5 * DAYS;
 	// ~ 1000 samples per day -> ~ 20 blocks per sample -> 2 minute samples
 	pub const SampleLength: BlockNumber = 2 * MINUTES;
+	// The chosen sample and outcome are retained for the last 4 auctions.
+	pub const AuctionHistoryDepth: u32 = 4;
 }
 
 impl auctions::Config for Runtime {
@@ -907,6 +943,7 @@ impl auctions::Config for Runtime {
 	type EndingPeriod = EndingPeriod;
 	type SampleLength = SampleLength;
 	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
+	type HistoryDepth = AuctionHistoryDepth;
 	type InitiateOrigin = EnsureRoot<AccountId>;
 	type WeightInfo = weights::runtime_common_auctions::WeightInfo<Runtime>;
 }
@@ -939,6 +976,7 @@ type LocalOriginConverter = (
 	ChildParachainAsNative<parachains_origin::Origin, Origin>,
 	SignedAccountId32AsNative<RubyNetwork, Origin>,
 	ChildSystemParachainAsSuperuser<ParaId, Origin>,
+	pallet_xcm::XcmPassthrough<Origin>,
 );
 
 parameter_types! {
@@ -1011,6 +1049,8 @@ impl pallet_xcm::Config for Runtime {
 	type Call = Call;
 	const VERSION_DISCOVERY_QUEUE_SIZE: u32 = 100;
 	type AdvertisedXcmVersion = pallet_xcm::CurrentXcmVersion;
+	// Delivering messages to child parachains is free.
+	type DeliveryFeeWeight = ();
 }
 
 construct_runtime! {
@@ -1274,6 +1314,93 @@ sp_api::impl_runtime_apis! {
 		fn validation_code_by_hash(hash: ValidationCodeHash) -> Option<ValidationCode> {
 			parachains_runtime_api_impl::validation_code_by_hash::<Runtime>(hash)
 		}
+
+		fn hrmp_state(para_id: ParaId) -> HrmpParaState<BlockNumber> {
+			parachains_runtime_api_impl::hrmp_state::<Runtime>(para_id)
+		}
+	}
+
+	impl parachains_configuration::runtime_api::ConfigurationApi<Block, BlockNumber> for Runtime {
+		fn pending_config(
+			session: SessionIndex,
+		) -> Option<parachains_configuration::HostConfiguration<BlockNumber>> {
+			parachains_runtime_api_impl::pending_config::<Runtime>(session)
+		}
+
+		fn pending_config_changes(
+			session: SessionIndex,
+		) -> Vec<parachains_configuration::HostConfigurationChange<BlockNumber>> {
+			parachains_runtime_api_impl::pending_config_changes::<Runtime>(session)
+		}
+	}
+
+	impl parachains_paras::runtime_api::ParasApi<Block> for Runtime {
+		fn upcoming_lifecycle_transitions(
+		) -> Vec<(ParaId, SessionIndex, parachains_paras::ParaLifecycle)> {
+			parachains_runtime_api_impl::upcoming_lifecycle_transitions::<Runtime>()
+		}
+	}
+
+	impl parachains_dmp::runtime_api::DmpApi<Block, BlockNumber> for Runtime {
+		fn dmq_length(recipient: ParaId) -> u32 {
+			parachains_runtime_api_impl::dmq_length::<Runtime>(recipient)
+		}
+
+		fn dmq_contents_page(
+			recipient: ParaId,
+			start: u32,
+			count: u32,
+		) -> Vec<InboundDownwardMessage<BlockNumber>> {
+			parachains_runtime_api_impl::dmq_contents_page::<Runtime>(recipient, start, count)
+		}
+	}
+
+	impl parachains_ump::runtime_api::UmpApi<Block, BlockNumber> for Runtime {
+		fn upward_queues() -> Vec<parachains_ump::UpwardQueueState> {
+			parachains_runtime_api_impl::upward_queues::<Runtime>()
+		}
+
+		fn overweight_messages() -> Vec<parachains_ump::OverweightMessage<BlockNumber>> {
+			parachains_runtime_api_impl::overweight_messages::<Runtime>()
+		}
+	}
+
+	impl parachains_inclusion::runtime_api::InclusionApi<Block, BlockNumber> for Runtime {
+		fn availability_timeouts(
+		) -> Vec<(CoreIndex, parachains_inclusion::AvailabilityTimeout<BlockNumber>)> {
+			parachains_runtime_api_impl::availability_timeouts::<Runtime>()
+		}
+	}
+
+	impl xcm_sender::runtime_api::XcmSenderApi<Block> for Runtime {
+		fn downward_congestion_level(recipient: ParaId) -> u32 {
+			xcm_sender::congestion_level::<Runtime>(recipient)
+		}
+
+		fn downward_delivery_fees(_: ParaId, _: Vec<u8>) -> xcm::VersionedMultiAssets {
+			// Delivering messages to child parachains is free.
+			MultiAssets::new().into()
+		}
+	}
+
+	impl crowdloan::runtime_api::CrowdloanApi<Block, AccountId, Balance> for Runtime {
+		fn contributor_positions(who: AccountId) -> Vec<crowdloan::CrowdloanPosition<Balance>> {
+			Crowdloan::contributor_positions(&who)
+		}
+	}
+
+	impl auctions::runtime_api::AuctionsApi<Block, AccountId, Balance, BlockNumber, Hash> for Runtime {
+		fn winning_samples(
+			auction_index: auctions::AuctionIndex,
+		) -> Vec<(BlockNumber, Vec<Option<(AccountId, ParaId, Balance)>>)> {
+			Auctions::winning_samples(auction_index)
+		}
+
+		fn auction_outcome(
+			auction_index: auctions::AuctionIndex,
+		) -> Option<auctions::AuctionOutcome<BlockNumber, Hash>> {
+			Auctions::auction_outcome(auction_index)
+		}
 	}
 
 	impl beefy_primitives::BeefyApi<Block> for Runtime {
@@ -1465,6 +1592,7 @@ sp_api::impl_runtime_apis! {
 			list_benchmark!(list, extra, runtime_common::paras_registrar, Registrar);
 			list_benchmark!(list, extra, runtime_common::slots, Slots);
 			list_benchmark!(list, extra, runtime_parachains::configuration, Configuration);
+			list_benchmark!(list, extra, runtime_parachains::ump, Ump);
 			// Substrate
 			list_benchmark!(list, extra, pallet_bags_list, BagsList);
 			list_benchmark!(list, extra, pallet_balances, Balances);
@@ -1531,6 +1659,7 @@ sp_api::impl_runtime_apis! {
 			add_benchmark!(params, batches, runtime_common::paras_registrar, Registrar);
 			add_benchmark!(params, batches, runtime_common::slots, Slots);
 			add_benchmark!(params, batches, runtime_parachains::configuration, Configuration);
+			add_benchmark!(params, batches, runtime_parachains::ump, Ump);
 			// Substrate
 			add_benchmark!(params, batches, pallet_bags_list, BagsList);
 			add_benchmark!(params, batches, pallet_balances, Balances);
@@ -37,6 +37,8 @@
 #![allow(unused_imports)]
 
 use frame_support::{traits::Get, weights::Weight};
+use frame_system::WeightInfo as _;
+use pallet_balances::WeightInfo as _;
 use sp_std::marker::PhantomData;
 
 /// Weight functions for `runtime_common::crowdloan`.
@@ -52,19 +54,38 @@ impl<T: frame_system::Config> runtime_common::crowdloan::WeightInfo for WeightIn
 			.saturating_add(T::DbWeight::get().reads(7 as Weight))
 			.saturating_add(T::DbWeight::get().writes(4 as Weight))
 	}
+	// FIXME: not CLI output yet, regenerate with the command above. `contribute` plus hashing
+	// the location into its local account, and writing the location into the child trie.
+	fn contribute_from_location() -> Weight {
+		<Self as runtime_common::crowdloan::WeightInfo>::contribute()
+			.saturating_add(super::frame_system::WeightInfo::<T>::remark_with_event(128))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// FIXME: `withdraw` and `refund` are benchmarked with the refund routed back over XCM now, so
+	// the numbers below predate it and must be regenerated with the command above. Until then each
+	// routed refund adds the benchmarked `transfer` to the sovereign account of the para, hashing
+	// a message of under 128 bytes into the downward queue, and the location and queue storage.
 	fn withdraw() -> Weight {
-		(106_591_000 as Weight)
+		super::pallet_balances::WeightInfo::<T>::transfer()
+			.saturating_add(super::frame_system::WeightInfo::<T>::remark_with_event(128))
+			.saturating_add(106_591_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(4 as Weight))
 			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
 	}
 	fn refund(k: u32) -> Weight {
-		(0 as Weight)
+		super::pallet_balances::WeightInfo::<T>::transfer()
+			.saturating_add(super::frame_system::WeightInfo::<T>::remark_with_event(128))
+			.saturating_mul(k as Weight)
 			// Standard Error: 28_000
 			.saturating_add((44_792_000 as Weight).saturating_mul(k as Weight))
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(k as Weight)))
 			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(k as Weight)))
+			.saturating_add(T::DbWeight::get().reads((5 as Weight).saturating_mul(k as Weight)))
+			.saturating_add(T::DbWeight::get().writes((4 as Weight).saturating_mul(k as Weight)))
 	}
 	fn dissolve() -> Weight {
 		(61_912_000 as Weight)
@@ -95,4 +116,37 @@ impl<T: frame_system::Config> runtime_common::crowdloan::WeightInfo for WeightIn
 			.saturating_add(T::DbWeight::get().writes(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
 	}
+	// FIXME: `set_auto_rollover`, `on_idle` and `roll_over` are not CLI output yet and must be
+	// regenerated with the command above. Until then they are charged the benchmarked empty
+	// `remark` as a base. Disabling is the worst case of `set_auto_rollover`: it takes the
+	// rollover and the pending refund, and releases the refund.
+	fn set_auto_rollover() -> Weight {
+		super::frame_system::WeightInfo::<T>::remark(0)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// The idle cursor is read and written once. Decoding each fund and deriving its account is
+	// charged as a 64 byte `remark_with_event`, and the fund reads its free balance, its lease,
+	// the auction and its pending rollovers.
+	fn on_idle(n: u32, ) -> Weight {
+		super::frame_system::WeightInfo::<T>::remark(0)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(
+				super::frame_system::WeightInfo::<T>::remark_with_event(64)
+					.saturating_mul(n as Weight),
+			)
+			.saturating_add(T::DbWeight::get().reads((5 as Weight).saturating_mul(n as Weight)))
+	}
+	// Rolling over releases the pending refund and contributes it without a signature, which the
+	// benchmarked `contribute` bounds as it verifies one.
+	fn roll_over(r: u32, ) -> Weight {
+		<Self as runtime_common::crowdloan::WeightInfo>::on_idle(1)
+			.saturating_add(
+				<Self as runtime_common::crowdloan::WeightInfo>::contribute()
+					.saturating_mul(r as Weight),
+			)
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(r as Weight)))
+	}
 }
@@ -39,11 +39,15 @@
 #![allow(unused_imports)]
 
 use frame_support::{traits::Get, weights::Weight};
+use frame_system::WeightInfo as _;
 use sp_std::marker::PhantomData;
 
 /// Weight functions for `runtime_parachains::configuration`.
 pub struct WeightInfo<T>(PhantomData<T>);
 impl<T: frame_system::Config> runtime_parachains::configuration::WeightInfo for WeightInfo<T> {
+	// FIXME: the setters below were benchmarked before they updated the configurations pending
+	// for later sessions, and must be regenerated with the command above. Until then each is also
+	// charged for a later pending configuration: reading it and its batch, and writing it.
 	// Storage: ParasShared CurrentSessionIndex (r:1 w:0)
 	// Storage: Configuration PendingConfig (r:1 w:1)
 	// Storage: Configuration ActiveConfig (r:1 w:0)
@@ -51,6 +55,8 @@ impl<T: frame_system::Config> runtime_parachains::configuration::WeightInfo for
 		(12_795_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
 	// Storage: ParasShared CurrentSessionIndex (r:1 w:0)
 	// Storage: Configuration PendingConfig (r:1 w:1)
@@ -59,6 +65,8 @@ impl<T: frame_system::Config> runtime_parachains::configuration::WeightInfo for
 		(12_758_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
 	// Storage: ParasShared CurrentSessionIndex (r:1 w:0)
 	// Storage: Configuration PendingConfig (r:1 w:1)
@@ -67,6 +75,8 @@ impl<T: frame_system::Config> runtime_parachains::configuration::WeightInfo for
 		(12_861_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
 	// Storage: ParasShared CurrentSessionIndex (r:1 w:0)
 	// Storage: Configuration PendingConfig (r:1 w:1)
@@ -75,6 +85,8 @@ impl<T: frame_system::Config> runtime_parachains::configuration::WeightInfo for
 		(12_854_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
 	// Storage: Benchmark Override (r:0 w:0)
 	fn set_hrmp_open_request_ttl() -> Weight {
@@ -87,5 +99,17 @@ impl<T: frame_system::Config> runtime_parachains::configuration::WeightInfo for
 		(12_838_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// FIXME: not CLI output yet either. The setter's weight covers both configurations, plus
+	// reading and writing the batch. Applying each change to both and encoding it into the batch
+	// is charged as this runtime's benchmarked `remark_with_event` of 64 bytes.
+	fn schedule_config_changes(c: u32, ) -> Weight {
+		<Self as runtime_parachains::configuration::WeightInfo>::set_config_with_u32()
+			.saturating_add(super::frame_system::WeightInfo::<T>::remark_with_event(64)
+				.saturating_mul(c as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
 }
@@ -0,0 +1,58 @@
+// Copyright 2017-2021 Parity Technologies (UK) Ltd.
+// This file is part of diamond.
+
+// diamond is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// diamond is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with diamond.  If not, see <http://www.gnu.org/licenses/>.
+//! Weights for `runtime_parachains::ump`
+//!
+//! FIXME: not generated by the benchmark CLI yet, regenerate with the command below. Until then
+//! the base is this runtime's benchmarked empty `remark`, and each purged message is charged as
+//! one key removed by `kill_storage`.
+
+// Command to run:
+// target/release/diamond
+// benchmark
+// --chain=ruby-dev
+// --steps=50
+// --repeat=20
+// --pallet=runtime_parachains::ump
+// --extrinsic=*
+// --execution=wasm
+// --wasm-execution=compiled
+// --heap-pages=4096
+// --header=./file_header.txt
+// --output=./runtime/ruby/src/weights/runtime_parachains_ump.rs
+
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use frame_system::WeightInfo as _;
+use sp_std::marker::PhantomData;
+
+/// Weight functions for `runtime_parachains::ump`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> runtime_parachains::ump::WeightInfo for WeightInfo<T> {
+	// Storage: Ump RelayDispatchQueueSize (r:1 w:1)
+	// Storage: Ump RelayDispatchQueues (r:0 w:1)
+	// Storage: Ump NeedsDispatch (r:1 w:1)
+	// Storage: Ump NextDispatchRoundStartWith (r:1 w:1)
+	fn purge_upward_queue(m: u32, ) -> Weight {
+		super::frame_system::WeightInfo::<T>::remark(0)
+			.saturating_add(super::frame_system::WeightInfo::<T>::kill_storage(m))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+}
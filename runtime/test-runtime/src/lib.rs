@@ -46,13 +46,14 @@ use pallet_transaction_payment::{FeeDetails, RuntimeDispatchInfo};
 use diamond_runtime_parachains::reward_points::RewardValidatorsWithEraPoints;
 use primitives::v1::{
 	AccountId, AccountIndex, Balance, BlockNumber, CandidateEvent, CommittedCandidateReceipt,
-	CoreState, GroupRotationInfo, Hash as HashT, Id as ParaId, InboundDownwardMessage,
-	InboundHrmpMessage, Moment, Nonce, OccupiedCoreAssumption, PersistedValidationData,
-	SessionInfo as SessionInfoData, Signature, ValidationCode, ValidationCodeHash, ValidatorId,
-	ValidatorIndex,
+	CoreIndex, CoreState, GroupRotationInfo, Hash as HashT, HrmpParaState, Id as ParaId,
+	InboundDownwardMessage, InboundHrmpMessage, Moment, Nonce, OccupiedCoreAssumption,
+	PersistedValidationData, SessionIndex, SessionInfo as SessionInfoData, Signature,
+	ValidationCode, ValidationCodeHash, ValidatorId, ValidatorIndex,
 };
 use runtime_common::{
-	claims, paras_sudo_wrapper, BlockHashCount, BlockLength, BlockWeights, SlowAdjustingFeeUpdate,
+	claims, paras_sudo_wrapper, purchase, BlockHashCount, BlockLength, BlockWeights,
+	SlowAdjustingFeeUpdate,
 };
 use sp_core::OpaqueMetadata;
 use sp_runtime::{
@@ -64,7 +65,7 @@ use sp_runtime::{
 		SaturatedConversion, StaticLookup, Verify,
 	},
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, KeyTypeId, Perbill,
+	ApplyExtrinsicResult, KeyTypeId, Perbill, Permill,
 };
 use sp_staking::SessionIndex;
 #[cfg(any(feature = "std", test))]
@@ -94,10 +95,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("diamond-test-runtime"),
 	impl_name: create_runtime_str!("parity-diamond-test-runtime"),
 	authoring_version: 2,
-	spec_version: 1056,
+	spec_version: 1057,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 /// The BABE epoch configuration at genesis.
@@ -449,6 +450,23 @@ impl pallet_vesting::Config for Runtime {
 	const MAX_VESTING_SCHEDULES: u32 = 28;
 }
 
+parameter_types! {
+	pub const MaxStatementLength: u32 = 1_000;
+	pub const UnlockedProportion: Permill = Permill::zero();
+	pub const MaxUnlocked: Balance = 0;
+}
+
+impl purchase::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type VestingSchedule = Vesting;
+	type ValidityOrigin = frame_system::EnsureRoot<AccountId>;
+	type ConfigurationOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxStatementLength = MaxStatementLength;
+	type UnlockedProportion = UnlockedProportion;
+	type MaxUnlocked = MaxUnlocked;
+}
+
 impl pallet_sudo::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
@@ -479,17 +497,36 @@ impl parachains_initializer::Config for Runtime {
 	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
 }
 
-impl parachains_session_info::Config for Runtime {}
+parameter_types! {
+	pub const SessionInfoRetentionPeriod: SessionIndex = 0;
+}
+
+impl parachains_session_info::Config for Runtime {
+	type RetentionPeriod = SessionInfoRetentionPeriod;
+}
+
+parameter_types! {
+	pub const DowngradeGracePeriod: SessionIndex = 0;
+}
 
 impl parachains_paras::Config for Runtime {
 	type Origin = Origin;
 	type Event = Event;
+	type DowngradeGracePeriod = DowngradeGracePeriod;
+}
+
+parameter_types! {
+	pub const MaxDownwardQueueSize: u32 = 1024 * 1024;
 }
 
-impl parachains_dmp::Config for Runtime {}
+impl parachains_dmp::Config for Runtime {
+	type MaxDownwardQueueSize = MaxDownwardQueueSize;
+}
 
 parameter_types! {
 	pub const FirstMessageFactorPercent: u64 = 100;
+	pub const OverweightDepositPerByte: Balance = MILLICENTS;
+	pub const OverweightExpiry: BlockNumber = DAYS;
 }
 
 impl parachains_ump::Config for Runtime {
@@ -497,6 +534,10 @@ impl parachains_ump::Config for Runtime {
 	type UmpSink = ();
 	type FirstMessageFactorPercent = FirstMessageFactorPercent;
 	type ExecuteOverweightOrigin = frame_system::EnsureRoot<AccountId>;
+	type Currency = Balances;
+	type OverweightDepositPerByte = OverweightDepositPerByte;
+	type OverweightExpiry = OverweightExpiry;
+	type WeightInfo = parachains_ump::weights::WeightInfo<Runtime>;
 }
 
 parameter_types! {
@@ -524,15 +565,19 @@ impl pallet_xcm::Config for Runtime {
 	type Call = Call;
 	const VERSION_DISCOVERY_QUEUE_SIZE: u32 = 100;
 	type AdvertisedXcmVersion = pallet_xcm::CurrentXcmVersion;
+	type DeliveryFeeWeight = ();
 }
 
 impl parachains_hrmp::Config for Runtime {
 	type Event = Event;
 	type Origin = Origin;
 	type Currency = Balances;
+	type ChannelManager = frame_system::EnsureRoot<AccountId>;
 }
 
-impl parachains_scheduler::Config for Runtime {}
+impl parachains_scheduler::Config for Runtime {
+	type ClaimHandler = ();
+}
 
 impl paras_sudo_wrapper::Config for Runtime {}
 
@@ -654,6 +699,9 @@ construct_runtime! {
 		// Vesting. Usable initially, but removed once all vesting is finished.
 		Vesting: pallet_vesting::{Pallet, Call, Storage, Event<T>, Config<T>},
 
+		// DOT purchase. Usable initially.
+		Purchase: purchase::{Pallet, Call, Storage, Event<T>},
+
 		// Parachains runtime modules
 		Configuration: parachains_configuration::{Pallet, Call, Storage, Config<T>},
 		ParaInclusion: parachains_inclusion::{Pallet, Call, Storage, Event<T>},
@@ -840,6 +888,62 @@ sp_api::impl_runtime_apis! {
 		fn validation_code_by_hash(hash: ValidationCodeHash) -> Option<ValidationCode> {
 			runtime_impl::validation_code_by_hash::<Runtime>(hash)
 		}
+
+		fn hrmp_state(para_id: ParaId) -> HrmpParaState<BlockNumber> {
+			runtime_impl::hrmp_state::<Runtime>(para_id)
+		}
+	}
+
+	impl parachains_configuration::runtime_api::ConfigurationApi<Block, BlockNumber> for Runtime {
+		fn pending_config(
+			session: SessionIndex,
+		) -> Option<parachains_configuration::HostConfiguration<BlockNumber>> {
+			runtime_impl::pending_config::<Runtime>(session)
+		}
+
+		fn pending_config_changes(
+			session: SessionIndex,
+		) -> Vec<parachains_configuration::HostConfigurationChange<BlockNumber>> {
+			runtime_impl::pending_config_changes::<Runtime>(session)
+		}
+	}
+
+	impl parachains_paras::runtime_api::ParasApi<Block> for Runtime {
+		fn upcoming_lifecycle_transitions(
+		) -> Vec<(ParaId, SessionIndex, parachains_paras::ParaLifecycle)> {
+			runtime_impl::upcoming_lifecycle_transitions::<Runtime>()
+		}
+	}
+
+	impl parachains_dmp::runtime_api::DmpApi<Block, BlockNumber> for Runtime {
+		fn dmq_length(recipient: ParaId) -> u32 {
+			runtime_impl::dmq_length::<Runtime>(recipient)
+		}
+
+		fn dmq_contents_page(
+			recipient: ParaId,
+			start: u32,
+			count: u32,
+		) -> Vec<InboundDownwardMessage<BlockNumber>> {
+			runtime_impl::dmq_contents_page::<Runtime>(recipient, start, count)
+		}
+	}
+
+	impl parachains_ump::runtime_api::UmpApi<Block, BlockNumber> for Runtime {
+		fn upward_queues() -> Vec<parachains_ump::UpwardQueueState> {
+			runtime_impl::upward_queues::<Runtime>()
+		}
+
+		fn overweight_messages() -> Vec<parachains_ump::OverweightMessage<BlockNumber>> {
+			runtime_impl::overweight_messages::<Runtime>()
+		}
+	}
+
+	impl parachains_inclusion::runtime_api::InclusionApi<Block, BlockNumber> for Runtime {
+		fn availability_timeouts(
+		) -> Vec<(CoreIndex, parachains_inclusion::AvailabilityTimeout<BlockNumber>)> {
+			runtime_impl::availability_timeouts::<Runtime>()
+		}
 	}
 
 	impl beefy_primitives::BeefyApi<Block> for Runtime {
@@ -957,6 +1061,14 @@ sp_api::impl_runtime_apis! {
 		}
 	}
 
+	impl purchase::runtime_api::PurchaseApi<Block, AccountId, Balance, BlockNumber> for Runtime {
+		fn purchase_status(
+			who: AccountId,
+		) -> Option<purchase::PurchaseStatus<Balance, BlockNumber>> {
+			Purchase::purchase_status(&who)
+		}
+	}
+
 	impl frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Nonce> for Runtime {
 		fn account_nonce(account: AccountId) -> Nonce {
 			System::account_nonce(account)
@@ -37,9 +37,10 @@ use pallet_transaction_payment::{CurrencyAdapter, FeeDetails, RuntimeDispatchInf
 use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
 use primitives::v1::{
 	AccountId, AccountIndex, Balance, BlockNumber, CandidateEvent, CommittedCandidateReceipt,
-	CoreState, GroupRotationInfo, Hash, Id, InboundDownwardMessage, InboundHrmpMessage, Moment,
-	Nonce, OccupiedCoreAssumption, PersistedValidationData, SessionInfo as SessionInfoData,
-	Signature, ValidationCode, ValidationCodeHash, ValidatorId, ValidatorIndex,
+	CoreIndex, CoreState, GroupRotationInfo, Hash, HrmpParaState, Id, InboundDownwardMessage,
+	InboundHrmpMessage, Moment, Nonce, OccupiedCoreAssumption, PersistedValidationData,
+	SessionInfo as SessionInfoData, Signature, ValidationCode, ValidationCodeHash, ValidatorId,
+	ValidatorIndex,
 };
 use runtime_common::{
 	auctions, crowdloan, impls::ToAuthor, paras_registrar, paras_sudo_wrapper, slots, xcm_sender,
@@ -66,7 +67,8 @@ use sp_version::RuntimeVersion;
 use runtime_parachains::{
 	configuration as parachains_configuration, disputes as parachains_disputes,
 	dmp as parachains_dmp, hrmp as parachains_hrmp, inclusion as parachains_inclusion,
-	initializer as parachains_initializer, origin as parachains_origin, paras as parachains_paras,
+	initializer as parachains_initializer, on_demand as parachains_on_demand,
+	origin as parachains_origin, paras as parachains_paras,
 	paras_inherent as parachains_paras_inherent, scheduler as parachains_scheduler,
 	session_info as parachains_session_info, shared as parachains_shared, ump as parachains_ump,
 };
@@ -83,10 +85,10 @@ use constants::{currency::*, fee::*, time::*};
 use frame_support::traits::InstanceFilter;
 use xcm::latest::prelude::*;
 use xcm_builder::{
-	AccountId32Aliases, BackingToPlurality, ChildParachainAsNative, ChildParachainConvertsVia,
-	ChildSystemParachainAsSuperuser, CurrencyAdapter as XcmCurrencyAdapter, FixedWeightBounds,
-	IsConcrete, LocationInverter, SignedAccountId32AsNative, SignedToAccountId32,
-	SovereignSignedViaLocation, UsingComponents,
+	Account32Hash, AccountId32Aliases, BackingToPlurality, ChildParachainAsNative,
+	ChildParachainConvertsVia, ChildSystemParachainAsSuperuser,
+	CurrencyAdapter as XcmCurrencyAdapter, FixedWeightBounds, IsConcrete, LocationInverter,
+	SignedAccountId32AsNative, SignedToAccountId32, SovereignSignedViaLocation, UsingComponents,
 };
 use xcm_executor::XcmExecutor;
 
@@ -104,13 +106,13 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("titan"),
 	impl_name: create_runtime_str!("parity-titan-v1.8"),
 	authoring_version: 0,
-	spec_version: 9103,
+	spec_version: 9104,
 	impl_version: 0,
 	#[cfg(not(feature = "disable-runtime-api"))]
 	apis: RUNTIME_API_VERSIONS,
 	#[cfg(feature = "disable-runtime-api")]
 	apis: sp_version::create_apis_vec![[]],
-	transaction_version: 0,
+	transaction_version: 1,
 };
 
 /// The BABE epoch configuration at genesis.
@@ -253,6 +255,9 @@ construct_runtime! {
 
 		// Pallet for sending XCM.
 		XcmPallet: pallet_xcm::{Pallet, Call, Storage, Event<T>, Origin} = 99,
+
+		// On-demand parathread claims.
+		OnDemand: parachains_on_demand::{Pallet, Call, Storage, Event<T>} = 100,
 	}
 }
 
@@ -579,9 +584,14 @@ impl parachains_inclusion::Config for Runtime {
 	type RewardValidators = RewardValidators;
 }
 
+parameter_types! {
+	pub const DowngradeGracePeriod: SessionIndex = 24;
+}
+
 impl parachains_paras::Config for Runtime {
 	type Origin = Origin;
 	type Event = Event;
+	type DowngradeGracePeriod = DowngradeGracePeriod;
 }
 
 parameter_types! {
@@ -612,6 +622,7 @@ type LocalOriginConverter = (
 	ChildParachainAsNative<parachains_origin::Origin, Origin>,
 	SignedAccountId32AsNative<TitanNetwork, Origin>,
 	ChildSystemParachainAsSuperuser<ParaId, Origin>,
+	pallet_xcm::XcmPassthrough<Origin>,
 );
 
 parameter_types! {
@@ -709,12 +720,22 @@ impl pallet_xcm::Config for Runtime {
 	type Call = Call;
 	const VERSION_DISCOVERY_QUEUE_SIZE: u32 = 100;
 	type AdvertisedXcmVersion = pallet_xcm::CurrentXcmVersion;
+	// Delivering messages to child parachains is free.
+	type DeliveryFeeWeight = ();
 }
 
-impl parachains_session_info::Config for Runtime {}
+parameter_types! {
+	pub const SessionInfoRetentionPeriod: SessionIndex = 24;
+}
+
+impl parachains_session_info::Config for Runtime {
+	type RetentionPeriod = SessionInfoRetentionPeriod;
+}
 
 parameter_types! {
 	pub const FirstMessageFactorPercent: u64 = 100;
+	pub const OverweightDepositPerByte: Balance = deposit(0, 1);
+	pub const OverweightExpiry: BlockNumber = 7 * DAYS;
 }
 
 impl parachains_ump::Config for Runtime {
@@ -722,19 +743,48 @@ impl parachains_ump::Config for Runtime {
 	type UmpSink = crate::parachains_ump::XcmSink<XcmExecutor<XcmConfig>, Runtime>;
 	type FirstMessageFactorPercent = FirstMessageFactorPercent;
 	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
+	type Currency = Balances;
+	type OverweightDepositPerByte = OverweightDepositPerByte;
+	type OverweightExpiry = OverweightExpiry;
+	type WeightInfo = parachains_ump::weights::WeightInfo<Runtime>;
 }
 
-impl parachains_dmp::Config for Runtime {}
+parameter_types! {
+	pub const MaxDownwardQueueSize: u32 = 16 * 1024 * 1024;
+}
+
+impl parachains_dmp::Config for Runtime {
+	type MaxDownwardQueueSize = MaxDownwardQueueSize;
+}
 
 impl parachains_hrmp::Config for Runtime {
 	type Event = Event;
 	type Origin = Origin;
 	type Currency = Balances;
+	type ChannelManager = EnsureRoot<AccountId>;
 }
 
 impl parachains_paras_inherent::Config for Runtime {}
 
-impl parachains_scheduler::Config for Runtime {}
+impl parachains_scheduler::Config for Runtime {
+	type ClaimHandler = OnDemand;
+}
+
+parameter_types! {
+	pub const OnDemandMinimumPrice: Balance = 1 * CENTS;
+	pub const OnDemandTargetUtilization: Perbill = Perbill::from_percent(25);
+	pub const OnDemandPriceAdjustment: Perbill = Perbill::from_percent(5);
+}
+
+impl parachains_on_demand::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type OnRevenue = ();
+	type MinimumPrice = OnDemandMinimumPrice;
+	type TargetUtilization = OnDemandTargetUtilization;
+	type PriceAdjustment = OnDemandPriceAdjustment;
+	type WeightInfo = parachains_on_demand::weights::WeightInfo<Runtime>;
+}
 
 impl parachains_initializer::Config for Runtime {
 	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
@@ -962,6 +1012,7 @@ impl Randomness<Hash, BlockNumber> for ParentHashRandomness {
 parameter_types! {
 	pub const EndingPeriod: BlockNumber = 1 * HOURS;
 	pub const SampleLength: BlockNumber = 1;
+	pub const AuctionHistoryDepth: u32 = 4;
 }
 
 impl auctions::Config for Runtime {
@@ -971,6 +1022,7 @@ impl auctions::Config for Runtime {
 	type EndingPeriod = EndingPeriod;
 	type SampleLength = SampleLength;
 	type Randomness = ParentHashRandomness;
+	type HistoryDepth = AuctionHistoryDepth;
 	type InitiateOrigin = EnsureRoot<AccountId>;
 	type WeightInfo = auctions::TestWeightInfo;
 }
@@ -1005,9 +1057,17 @@ impl crowdloan::Config for Runtime {
 	type Registrar = Registrar;
 	type Auctioneer = Auctions;
 	type MaxMemoLength = MaxMemoLength;
+	type RemoteContributorOrigin = pallet_xcm::EnsureXcm<crowdloan::RemoteContributorLocations>;
+	type LocationToAccountId = CrowdloanContributorAccountOf;
+	type XcmRouter = XcmRouter;
 	type WeightInfo = crowdloan::TestWeightInfo;
 }
 
+/// The local account of a crowdloan contributor in another consensus system. Parachains and local
+/// accounts use their usual account, any other location gets an account derived from its hash.
+pub type CrowdloanContributorAccountOf =
+	(SovereignAccountOf, Account32Hash<TitanNetwork, AccountId>);
+
 impl pallet_sudo::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
@@ -1252,6 +1312,93 @@ sp_api::impl_runtime_apis! {
 		fn validation_code_by_hash(hash: ValidationCodeHash) -> Option<ValidationCode> {
 			runtime_api_impl::validation_code_by_hash::<Runtime>(hash)
 		}
+
+		fn hrmp_state(para_id: Id) -> HrmpParaState<BlockNumber> {
+			runtime_api_impl::hrmp_state::<Runtime>(para_id)
+		}
+	}
+
+	impl parachains_configuration::runtime_api::ConfigurationApi<Block, BlockNumber> for Runtime {
+		fn pending_config(
+			session: SessionIndex,
+		) -> Option<parachains_configuration::HostConfiguration<BlockNumber>> {
+			runtime_api_impl::pending_config::<Runtime>(session)
+		}
+
+		fn pending_config_changes(
+			session: SessionIndex,
+		) -> Vec<parachains_configuration::HostConfigurationChange<BlockNumber>> {
+			runtime_api_impl::pending_config_changes::<Runtime>(session)
+		}
+	}
+
+	impl parachains_paras::runtime_api::ParasApi<Block> for Runtime {
+		fn upcoming_lifecycle_transitions(
+		) -> Vec<(Id, SessionIndex, parachains_paras::ParaLifecycle)> {
+			runtime_api_impl::upcoming_lifecycle_transitions::<Runtime>()
+		}
+	}
+
+	impl parachains_dmp::runtime_api::DmpApi<Block, BlockNumber> for Runtime {
+		fn dmq_length(recipient: Id) -> u32 {
+			runtime_api_impl::dmq_length::<Runtime>(recipient)
+		}
+
+		fn dmq_contents_page(
+			recipient: Id,
+			start: u32,
+			count: u32,
+		) -> Vec<InboundDownwardMessage<BlockNumber>> {
+			runtime_api_impl::dmq_contents_page::<Runtime>(recipient, start, count)
+		}
+	}
+
+	impl parachains_ump::runtime_api::UmpApi<Block, BlockNumber> for Runtime {
+		fn upward_queues() -> Vec<parachains_ump::UpwardQueueState> {
+			runtime_api_impl::upward_queues::<Runtime>()
+		}
+
+		fn overweight_messages() -> Vec<parachains_ump::OverweightMessage<BlockNumber>> {
+			runtime_api_impl::overweight_messages::<Runtime>()
+		}
+	}
+
+	impl parachains_inclusion::runtime_api::InclusionApi<Block, BlockNumber> for Runtime {
+		fn availability_timeouts(
+		) -> Vec<(CoreIndex, parachains_inclusion::AvailabilityTimeout<BlockNumber>)> {
+			runtime_api_impl::availability_timeouts::<Runtime>()
+		}
+	}
+
+	impl xcm_sender::runtime_api::XcmSenderApi<Block> for Runtime {
+		fn downward_congestion_level(recipient: ParaId) -> u32 {
+			xcm_sender::congestion_level::<Runtime>(recipient)
+		}
+
+		fn downward_delivery_fees(_: ParaId, _: Vec<u8>) -> xcm::VersionedMultiAssets {
+			// Delivering messages to child parachains is free.
+			MultiAssets::new().into()
+		}
+	}
+
+	impl crowdloan::runtime_api::CrowdloanApi<Block, AccountId, Balance> for Runtime {
+		fn contributor_positions(who: AccountId) -> Vec<crowdloan::CrowdloanPosition<Balance>> {
+			Crowdloan::contributor_positions(&who)
+		}
+	}
+
+	impl auctions::runtime_api::AuctionsApi<Block, AccountId, Balance, BlockNumber, Hash> for Runtime {
+		fn winning_samples(
+			auction_index: auctions::AuctionIndex,
+		) -> Vec<(BlockNumber, Vec<Option<(AccountId, Id, Balance)>>)> {
+			Auctions::winning_samples(auction_index)
+		}
+
+		fn auction_outcome(
+			auction_index: auctions::AuctionIndex,
+		) -> Option<auctions::AuctionOutcome<BlockNumber, Hash>> {
+			Auctions::auction_outcome(auction_index)
+		}
 	}
 
 	impl fg_primitives::GrandpaApi<Block> for Runtime {
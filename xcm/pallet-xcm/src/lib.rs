@@ -123,6 +123,10 @@ pub mod pallet {
 		/// The latest supported version that we advertise. Generally just set it to
 		/// `pallet_xcm::CurrentXcmVersion`.
 		type AdvertisedXcmVersion: Get<XcmVersion>;
+
+		/// The weight of charging an origin for delivering a message that is sent on its behalf.
+		/// Generally the weight of withdrawing the delivery fees from its account.
+		type DeliveryFeeWeight: Get<Weight>;
 	}
 
 	/// The maximum number of distinct assets allowed to be transferred in a single helper extrinsic.
@@ -451,7 +455,7 @@ pub mod pallet {
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		#[pallet::weight(100_000_000)]
+		#[pallet::weight(T::DeliveryFeeWeight::get().saturating_add(100_000_000))]
 		pub fn send(
 			origin: OriginFor<T>,
 			dest: Box<VersionedMultiLocation>,
@@ -494,7 +498,9 @@ pub mod pallet {
 						WithdrawAsset(assets),
 						InitiateTeleport { assets: Wild(All), dest, xcm: Xcm(vec![]) },
 					]);
-					T::Weigher::weight(&mut message).map_or(Weight::max_value(), |w| 100_000_000 + w)
+					T::Weigher::weight(&mut message).map_or(Weight::max_value(), |w| {
+						100_000_000 + w + T::DeliveryFeeWeight::get()
+					})
 				},
 				_ => Weight::max_value(),
 			}
@@ -566,7 +572,9 @@ pub mod pallet {
 					let mut message = Xcm(vec![
 						TransferReserveAsset { assets, dest, xcm: Xcm(vec![]) }
 					]);
-					T::Weigher::weight(&mut message).map_or(Weight::max_value(), |w| 100_000_000 + w)
+					T::Weigher::weight(&mut message).map_or(Weight::max_value(), |w| {
+						100_000_000 + w + T::DeliveryFeeWeight::get()
+					})
 				},
 				_ => Weight::max_value(),
 			}
@@ -624,7 +632,14 @@ pub mod pallet {
 		///
 		/// NOTE: A successful return to this does *not* imply that the `msg` was executed successfully
 		/// to completion; only that *some* of it was executed.
-		#[pallet::weight(max_weight.saturating_add(100_000_000u64))]
+		#[pallet::weight({
+			let maybe_message: Result<Xcm<<T as SysConfig>::Call>, ()> =
+				(*message.clone()).try_into();
+			let sends = maybe_message.map_or(0, |message| sending_instructions(&message));
+			max_weight
+				.saturating_add(100_000_000u64)
+				.saturating_add(T::DeliveryFeeWeight::get().saturating_mul(sends))
+		})]
 		pub fn execute(
 			origin: OriginFor<T>,
 			message: Box<VersionedXcm<<T as SysConfig>::Call>>,
@@ -898,16 +913,19 @@ pub mod pallet {
 
 		/// Relay an XCM `message` from a given `interior` location in this context to a given `dest`
 		/// location. A null `dest` is not handled.
+		///
+		/// The message is sent on behalf of `interior`.
 		pub fn send_xcm(
 			interior: Junctions,
 			dest: MultiLocation,
 			mut message: Xcm<()>,
 		) -> Result<(), SendError> {
+			let origin = MultiLocation::from(interior.clone());
 			if interior != Junctions::Here {
 				message.0.insert(0, DescendOrigin(interior))
 			};
 			log::trace!(target: "xcm::send_xcm", "dest: {:?}, message: {:?}", &dest, &message);
-			T::XcmRouter::send_xcm(dest, message)
+			T::XcmRouter::send_xcm_from(&origin, dest, message)
 		}
 
 		pub fn check_account() -> T::AccountId {
@@ -1274,6 +1292,25 @@ pub mod pallet {
 	}
 }
 
+/// The number of instructions in `message`, including its error handler and appendix, which
+/// send a message on behalf of the origin when executed.
+fn sending_instructions<Call>(message: &Xcm<Call>) -> u64 {
+	message
+		.0
+		.iter()
+		.map(|instruction| match instruction {
+			TransferReserveAsset { .. } |
+			ReportError { .. } |
+			DepositReserveAsset { .. } |
+			InitiateReserveWithdraw { .. } |
+			InitiateTeleport { .. } |
+			QueryHolding { .. } => 1,
+			SetErrorHandler(xcm) | SetAppendix(xcm) => sending_instructions(xcm),
+			_ => 0,
+		})
+		.sum()
+}
+
 /// Ensure that the origin `o` represents an XCM (`Transact`) origin.
 ///
 /// Returns `Ok` with the location of the XCM sender or an `Err` otherwise.
@@ -286,6 +286,7 @@ impl pallet_xcm::Config for Test {
 	type Call = Call;
 	const VERSION_DISCOVERY_QUEUE_SIZE: u32 = 100;
 	type AdvertisedXcmVersion = AdvertisedXcmVersion;
+	type DeliveryFeeWeight = ();
 }
 
 impl origin::Config for Test {}
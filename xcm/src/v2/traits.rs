@@ -299,6 +299,20 @@ pub trait SendXcm {
 	/// return `CannotReachDestination`. Any other error will cause the tuple implementation to exit early without
 	/// trying other type fields.
 	fn send_xcm(destination: MultiLocation, message: Xcm<()>) -> SendResult;
+
+	/// Send an XCM `message` to a given `destination` on behalf of `origin`, a location within this
+	/// chain's context that is accountable for the message, e.g. for paying its delivery fees.
+	///
+	/// `send_xcm` is used for messages that the chain sends on its own behalf. By default, there is no
+	/// difference between both.
+	fn send_xcm_from(
+		origin: &MultiLocation,
+		destination: MultiLocation,
+		message: Xcm<()>,
+	) -> SendResult {
+		let _ = origin;
+		Self::send_xcm(destination, message)
+	}
 }
 
 #[impl_trait_for_tuples::impl_for_tuples(30)]
@@ -313,6 +327,21 @@ impl SendXcm for Tuple {
 		)* );
 		Err(SendError::CannotReachDestination(destination, message))
 	}
+
+	fn send_xcm_from(
+		origin: &MultiLocation,
+		destination: MultiLocation,
+		message: Xcm<()>,
+	) -> SendResult {
+		for_tuples!( #(
+			// we shadow `destination` and `message` in each expansion for the next one.
+			let (destination, message) = match Tuple::send_xcm_from(origin, destination, message) {
+				Err(SendError::CannotReachDestination(d, m)) => (d, m),
+				o @ _ => return o,
+			};
+		)* );
+		Err(SendError::CannotReachDestination(destination, message))
+	}
 }
 
 /// The info needed to weight an XCM.
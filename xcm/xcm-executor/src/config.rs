@@ -30,6 +30,8 @@ pub trait Config {
 	type Call: Parameter + Dispatchable<PostInfo = PostDispatchInfo> + GetDispatchInfo;
 
 	/// How to send an onward XCM message.
+	///
+	/// Messages are sent on behalf of the original origin of the message being executed.
 	type XcmSender: SendXcm;
 
 	/// How to withdraw and deposit an asset.
@@ -271,7 +271,8 @@ impl<Config: config::Config> XcmExecutor<Config> {
 				assets.reanchor(&inv_dest)?;
 				let mut message = vec![ReserveAssetDeposited(assets), ClearOrigin];
 				message.extend(xcm.0.into_iter());
-				Config::XcmSender::send_xcm(dest, Xcm(message)).map_err(Into::into)
+				Config::XcmSender::send_xcm_from(&self.original_origin, dest, Xcm(message))
+					.map_err(Into::into)
 			},
 			ReceiveTeleportedAsset(assets) => {
 				let origin = self.origin.as_ref().ok_or(XcmError::BadOrigin)?;
@@ -348,7 +349,7 @@ impl<Config: config::Config> XcmExecutor<Config> {
 					Some(e) => Err(e),
 				});
 				let message = QueryResponse { query_id, response, max_weight };
-				Config::XcmSender::send_xcm(dest, Xcm(vec![message]))?;
+				Config::XcmSender::send_xcm_from(&self.original_origin, dest, Xcm(vec![message]))?;
 				Ok(())
 			},
 			DepositAsset { assets, max_assets, beneficiary } => {
@@ -366,13 +367,15 @@ impl<Config: config::Config> XcmExecutor<Config> {
 				let assets = Self::reanchored(deposited, &dest)?;
 				let mut message = vec![ReserveAssetDeposited(assets), ClearOrigin];
 				message.extend(xcm.0.into_iter());
-				Config::XcmSender::send_xcm(dest, Xcm(message)).map_err(Into::into)
+				Config::XcmSender::send_xcm_from(&self.original_origin, dest, Xcm(message))
+					.map_err(Into::into)
 			},
 			InitiateReserveWithdraw { assets, reserve, xcm } => {
 				let assets = Self::reanchored(self.holding.saturating_take(assets), &reserve)?;
 				let mut message = vec![WithdrawAsset(assets), ClearOrigin];
 				message.extend(xcm.0.into_iter());
-				Config::XcmSender::send_xcm(reserve, Xcm(message)).map_err(Into::into)
+				Config::XcmSender::send_xcm_from(&self.original_origin, reserve, Xcm(message))
+					.map_err(Into::into)
 			},
 			InitiateTeleport { assets, dest, xcm } => {
 				// We must do this first in order to resolve wildcards.
@@ -383,14 +386,20 @@ impl<Config: config::Config> XcmExecutor<Config> {
 				let assets = Self::reanchored(assets, &dest)?;
 				let mut message = vec![ReceiveTeleportedAsset(assets), ClearOrigin];
 				message.extend(xcm.0.into_iter());
-				Config::XcmSender::send_xcm(dest, Xcm(message)).map_err(Into::into)
+				Config::XcmSender::send_xcm_from(&self.original_origin, dest, Xcm(message))
+					.map_err(Into::into)
 			},
 			QueryHolding { query_id, dest, assets, max_response_weight } => {
 				let assets = Self::reanchored(self.holding.min(&assets), &dest)?;
 				let max_weight = max_response_weight;
 				let response = Response::Assets(assets);
 				let instruction = QueryResponse { query_id, response, max_weight };
-				Config::XcmSender::send_xcm(dest, Xcm(vec![instruction])).map_err(Into::into)
+				Config::XcmSender::send_xcm_from(
+					&self.original_origin,
+					dest,
+					Xcm(vec![instruction]),
+				)
+				.map_err(Into::into)
 			},
 			BuyExecution { fees, weight_limit } => {
 				// There is no need to buy any weight is `weight_limit` is `Unlimited` since it